@@ -0,0 +1,70 @@
+//! Measures the cost of arming and cancelling a large number of timeouts, along
+//! with the number of SQEs submitted to the ring to do so.
+//!
+//! Timeouts shorter than a second are batched on the runtime's timer wheel,
+//! while longer ones are submitted to the reactor one by one, each costing an
+//! SQE to arm and another one to cancel.
+//!
+//! The SQEs are counted from the tail of the submission queue, as reported in
+//! `/proc/self/fdinfo` for the ring since Linux 5.14.
+use osiris::spawn;
+use osiris::task::yield_now;
+use osiris::time::{timeout, Duration};
+use std::fs;
+use std::io::Result;
+use std::time::Instant;
+
+const N: usize = 100_000;
+
+/// Returns the tail of the submission queue of the runtime's ring, which
+/// is advanced for every SQE pushed.
+fn sq_tail() -> Option<u32> {
+    for entry in fs::read_dir("/proc/self/fd").ok()? {
+        let entry = entry.ok()?;
+        let link = fs::read_link(entry.path()).unwrap_or_default();
+        if !link.to_string_lossy().contains("io_uring") {
+            continue;
+        }
+        let path = format!("/proc/self/fdinfo/{}", entry.file_name().to_string_lossy());
+        let info = fs::read_to_string(path).ok()?;
+        let line = info.lines().find(|line| line.starts_with("SqTail:"))?;
+        return line["SqTail:".len()..].trim().parse().ok();
+    }
+    None
+}
+
+async fn bench(dur: Duration) -> (Duration, Option<u32>) {
+    let tail = sq_tail();
+    let start = Instant::now();
+    let mut handles = Vec::with_capacity(N);
+    for _ in 0..N {
+        handles.push(spawn(timeout(dur, std::future::pending::<()>())));
+    }
+    // let every timeout get armed before cancelling them.
+    yield_now().await;
+    drop(handles);
+    yield_now().await;
+    let elapsed = start.elapsed();
+    // let the cancellations be submitted.
+    yield_now().await;
+    let sqes = tail
+        .zip(sq_tail())
+        .map(|(start, end)| end.wrapping_sub(start));
+    (elapsed, sqes)
+}
+
+fn report(name: &str, (elapsed, sqes): (Duration, Option<u32>)) {
+    match sqes {
+        Some(sqes) => println!("{N} {name}: {elapsed:?}, {sqes} SQEs"),
+        None => println!("{N} {name}: {elapsed:?}, SQEs unavailable"),
+    }
+}
+
+#[osiris::main]
+async fn main() -> Result<()> {
+    let wheel = bench(Duration::from_millis(500)).await;
+    let reactor = bench(Duration::from_secs(5)).await;
+    report("short timeouts (timer wheel)", wheel);
+    report("long timeouts (one SQE each)", reactor);
+    Ok(())
+}
@@ -9,33 +9,62 @@ use syn::{parse_macro_input, parse_quote, Block, Error, Expr, ItemFn, Result, Re
 #[proc_macro_attribute]
 pub fn main(args: TokenStream, input: TokenStream) -> TokenStream {
     let AsyncMain { item } = parse_macro_input!(input);
-    let Args { scale, restart } = parse_macro_input!(args);
-    let item = transform(item, scale, restart);
+    let args = parse_macro_input!(args);
+    let item = transform(item, args, false);
     quote!(#item).into()
 }
 
 #[proc_macro_attribute]
-pub fn test(_: TokenStream, input: TokenStream) -> TokenStream {
+pub fn test(args: TokenStream, input: TokenStream) -> TokenStream {
     let item = parse_macro_input!(input);
-    let item = transform(item, parse_quote!(1), parse_quote!(false));
+    let args: Args = parse_macro_input!(args);
+    // scaled tests report their outcome through an exit code,
+    // since each replica has an output of its own.
+    let scaled = args.scale.is_some() || args.restart.is_some();
+    let item = transform(item, args, !scaled);
     quote!(#[test] #item).into()
 }
 
-fn transform(mut item: ItemFn, scale: Expr, restart: Expr) -> ItemFn {
+/// Turns the async function into a sync one that runs the runtime. The function
+/// returns an `ExitCode`, except for unscaled tests, which keep their return type
+/// so the test harness can report returned errors.
+fn transform(mut item: ItemFn, args: Args, test: bool) -> ItemFn {
     let block = item.block.clone();
+    let Args {
+        scale,
+        restart,
+        event_interval,
+        queue_depth,
+        config,
+    } = args;
+    let scale = scale.unwrap_or(parse_quote!(1));
+    let restart = restart.unwrap_or(parse_quote!(false));
+    let event_interval = event_interval.map(|value| quote!(event_interval: #value,));
+    let queue_depth = queue_depth.map(|value| quote!(queue_entries: #value,));
+    let config = config.unwrap_or(parse_quote!(osiris::_priv::default_config));
 
     let ty = match item.sig.output {
         ReturnType::Default => parse_quote!(()),
         ReturnType::Type(_, ty) => ty,
     };
 
-    let new_block: Block = parse_quote!({
-        osiris::_priv::run(#scale, #restart, || -> std::io::Result<#ty> {
-            osiris::block_on(async { #block })
-        })
+    let main = quote!(|config| -> std::io::Result<#ty> {
+        let config = osiris::runtime::Config {
+            #event_interval
+            #queue_depth
+            ..config
+        };
+        config.build()?.block_on(async { #block })
     });
+
+    let new_block: Block = if test {
+        item.sig.output = parse_quote!(-> #ty);
+        parse_quote!({ osiris::_priv::run_test(#config, #main) })
+    } else {
+        item.sig.output = parse_quote!(-> std::process::ExitCode);
+        parse_quote!({ osiris::_priv::run(#scale, #restart, #config, #main) })
+    };
     let _ = replace(&mut item.block, Box::new(new_block));
-    item.sig.output = parse_quote!(-> std::process::ExitCode);
     item.sig.asyncness = None;
     item
 }
@@ -44,9 +73,13 @@ struct AsyncMain {
     item: ItemFn,
 }
 
+#[derive(Default)]
 struct Args {
-    scale: Expr,
-    restart: Expr,
+    scale: Option<Expr>,
+    restart: Option<Expr>,
+    event_interval: Option<Expr>,
+    queue_depth: Option<Expr>,
+    config: Option<Expr>,
 }
 
 impl Parse for AsyncMain {
@@ -67,44 +100,38 @@ impl Parse for AsyncMain {
 
 impl Parse for Args {
     fn parse(input: ParseStream) -> Result<Self> {
-        if input.is_empty() {
-            return Ok(Args {
-                scale: parse_quote!(1),
-                restart: parse_quote!(false),
-            });
-        }
-
-        let mut scale = None;
-        let mut restart = None;
+        let mut args = Args::default();
 
         while !input.is_empty() {
-            if scale.is_some() || restart.is_some() {
-                let _: Token![,] = input.parse()?;
-            }
-
             let ident: Ident = input.parse()?;
             let _: Token![=] = input.parse()?;
             let value: Expr = input.parse()?;
 
-            if ident == "scale" && scale.is_none() {
-                scale = Some(value);
-            } else if ident == "restart" && restart.is_none() {
-                restart = Some(value);
-            } else if ident != "scale" && ident != "restart" {
-                return Err(Error::new(
-                    ident.span(),
-                    "unsupported argument. Supported arguments are: \"scale\" and \"restart\".",
-                ));
-            } else {
+            let arg = match &*ident.to_string() {
+                "scale" => &mut args.scale,
+                "restart" => &mut args.restart,
+                "event_interval" => &mut args.event_interval,
+                "queue_depth" => &mut args.queue_depth,
+                "config" => &mut args.config,
+                _ => {
+                    return Err(Error::new(
+                        ident.span(),
+                        "unsupported argument. Supported arguments are: \"scale\", \"restart\", \"event_interval\", \"queue_depth\" and \"config\".",
+                    ))
+                }
+            };
+            if arg.is_some() {
                 return Err(Error::new(
                     ident.span(),
                     format!("repeated argument \"{ident}\"."),
                 ));
             }
-        }
-        let scale = scale.unwrap_or(parse_quote!(1));
-        let restart = restart.unwrap_or(parse_quote!(false));
+            *arg = Some(value);
 
-        Ok(Args { scale, restart })
+            if !input.is_empty() {
+                let _: Token![,] = input.parse()?;
+            }
+        }
+        Ok(args)
     }
 }
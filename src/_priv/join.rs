@@ -23,6 +23,11 @@ use std::task::{Context, Poll, Waker};
 ///
 /// [`try_join!`]: crate::try_join
 ///
+/// # Cancellation
+/// If the future returned by `join!` is dropped before it completes, for example
+/// because it timed out, its branches are dropped in the order they were passed to
+/// the macro. Dropping a branch cancels any I/O operation it was waiting on.
+///
 /// # Implementation notes
 /// This `join!` macro implementation has two advantages over other alternatives:
 /// 1. It does not poll spuriously (i.e. it doesn't poll branches that weren't woken).
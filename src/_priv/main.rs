@@ -1,6 +1,15 @@
+use crate::runtime::Config;
+use core_affinity::CoreId;
 use std::io;
 use std::panic::UnwindSafe;
 use std::process::{ExitCode, Termination};
+use std::thread::sleep;
+use std::time::Duration;
+
+/// The delay before the first restart of a panicked replica.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+/// The upper bound for the delay between restarts.
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
 
 mod sealed {
     pub trait Sealed {}
@@ -30,64 +39,144 @@ impl IntoScale for usize {
     }
 }
 
-pub fn run<T>(scale: impl IntoScale, restart: bool, main: fn() -> io::Result<T>) -> ExitCode
+/// The maximum number of times panicked replicas are restarted.
+/// `true` restarts them indefinitely, while `false` never restarts them.
+pub trait IntoRestart: sealed::Sealed {
+    fn max_restarts(self) -> usize;
+}
+
+impl IntoRestart for bool {
+    fn max_restarts(self) -> usize {
+        if self {
+            usize::MAX
+        } else {
+            0
+        }
+    }
+}
+
+impl IntoRestart for usize {
+    fn max_restarts(self) -> usize {
+        self
+    }
+}
+
+/// Returns how long to wait before the `restarts`-th restart,
+/// doubling the delay for each restart up to [`MAX_BACKOFF`].
+fn backoff(restarts: usize) -> Duration {
+    let exp = restarts.saturating_sub(1).min(16) as u32;
+    INITIAL_BACKOFF.saturating_mul(1 << exp).min(MAX_BACKOFF)
+}
+
+/// The default runtime configuration used by the `main` and `test` macros.
+pub fn default_config() -> io::Result<Config> {
+    Ok(Config::default())
+}
+
+pub fn run<T>(
+    scale: impl IntoScale,
+    restart: impl IntoRestart,
+    config: fn() -> io::Result<Config>,
+    main: fn(Config) -> io::Result<T>,
+) -> ExitCode
 where
     T: Termination,
 {
+    let main = move || main(config()?);
     let scale = scale.scale();
-    if scale == 1 && !restart {
+    let max_restarts = restart.max_restarts();
+    if scale == 1 && max_restarts == 0 {
         main().unwrap().report()
     } else if scale == 1 {
-        no_scale_restart(main)
-    } else if !restart {
+        no_scale_restart(max_restarts, main)
+    } else if max_restarts == 0 {
         scaled_no_restart(scale, main)
     } else {
-        scaled_and_restart(scale, || main().report())
+        scaled_and_restart(scale, max_restarts, || main().report())
     }
 }
 
-fn no_scale_restart<T: Termination>(main: fn() -> io::Result<T>) -> ExitCode {
+/// Runs the body of a test. Unlike [`run`], the output is returned as is,
+/// so the test harness can report it.
+pub fn run_test<T>(config: fn() -> io::Result<Config>, main: fn(Config) -> io::Result<T>) -> T
+where
+    T: Termination,
+{
+    let config = config().expect("osiris: failed to create the runtime configuration");
+    main(config).expect("osiris: failed to run the runtime")
+}
+
+fn no_scale_restart<T, F>(max_restarts: usize, main: F) -> ExitCode
+where
+    T: Termination,
+    F: Fn() -> io::Result<T> + UnwindSafe + Copy,
+{
+    let mut restarts = 0;
     loop {
         match std::panic::catch_unwind(main) {
             Ok(ok) => return ok.unwrap().report(),
+            Err(_) if restarts == max_restarts => {
+                eprintln!("osiris: thread panicked after {restarts} restarts, exiting");
+                return ExitCode::FAILURE;
+            }
             Err(_) => {
-                eprintln!("osiris: restarting thread");
+                restarts += 1;
+                sleep(backoff(restarts));
+                eprintln!("osiris: restarting thread (restart #{restarts})");
                 continue;
             }
         }
     }
 }
 
-fn scaled_no_restart<T: Termination>(scale: usize, main: fn() -> io::Result<T>) -> ExitCode {
+fn scaled_no_restart<T, F>(scale: usize, main: F) -> ExitCode
+where
+    T: Termination,
+    F: Fn() -> io::Result<T> + Send + Sync + Copy,
+{
     let cores = &core_affinity::get_core_ids().unwrap_or_default();
-    let n = cores.len().max(1);
     std::thread::scope(|s| {
-        for thread in 0..scale {
-            s.spawn(move || {
-                let core_id = cores.get(thread % n);
-                if let Some(core_id) = core_id {
-                    core_affinity::set_for_current(*core_id);
-                }
-                main().unwrap().report();
-            });
+        let replicas: Vec<_> = (0..scale)
+            .map(|thread| {
+                s.spawn(move || {
+                    let core_id = core_for(cores, thread);
+                    if let Some(core_id) = core_id {
+                        core_affinity::set_for_current(*core_id);
+                    }
+                    main().unwrap().report()
+                })
+            })
+            .collect();
+        let codes: Vec<_> = replicas
+            .into_iter()
+            .map(|r| r.join().unwrap_or(ExitCode::FAILURE))
+            .collect();
+        if codes.iter().all(|code| *code == ExitCode::SUCCESS) {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
         }
-    });
-    ExitCode::SUCCESS
+    })
+}
+
+/// Returns the core a replica should be pinned to, distributing
+/// replicas round robin. Returns `None` if no cores are available.
+fn core_for(cores: &[CoreId], thread: usize) -> Option<&CoreId> {
+    cores.get(thread.checked_rem(cores.len())?)
 }
 
 fn scaled_and_restart(
     scale: usize,
+    max_restarts: usize,
     main: impl Fn() -> ExitCode + Copy + Clone + Sync + Send + UnwindSafe,
 ) -> ExitCode {
     let cores = &core_affinity::get_core_ids().unwrap_or_default();
     std::thread::scope(|s| {
         let (tx, rx) = std::sync::mpsc::channel();
 
-        let n = cores.len().min(1);
-
         for thread in 0..scale {
             let tx = tx.clone();
-            let core_id = cores.get(thread % n);
+            let core_id = core_for(cores, thread);
             s.spawn(move || {
                 if let Some(core_id) = core_id {
                     core_affinity::set_for_current(*core_id);
@@ -97,6 +186,7 @@ fn scaled_and_restart(
         }
 
         let mut exit_count = 0;
+        let mut restarts = 0;
 
         while exit_count < scale {
             let Ok((thread, res)) = rx.recv() else {
@@ -106,12 +196,19 @@ fn scaled_and_restart(
                 exit_count += 1;
                 continue;
             };
+            if restarts == max_restarts {
+                eprintln!("osiris: thread #{thread} panicked after {restarts} restarts, exiting");
+                // the remaining replicas may never exit, so we can't wait for them.
+                std::process::exit(1);
+            }
+            restarts += 1;
+            sleep(backoff(restarts));
             // we restart the panicked dead replica
             let tx = tx.clone();
-            let core_id = cores.get(thread % n);
+            let core_id = core_for(cores, thread);
 
             s.spawn(move || {
-                eprintln!("osiris: restarting thread #{thread}");
+                eprintln!("osiris: restarting thread #{thread} (restart #{restarts})");
                 if let Some(core_id) = core_id {
                     core_affinity::set_for_current(*core_id);
                 }
@@ -121,3 +218,19 @@ fn scaled_and_restart(
         ExitCode::SUCCESS
     })
 }
+
+#[test]
+fn core_for_round_robin() {
+    let cores: Vec<_> = (0..3).map(|id| CoreId { id }).collect();
+    let ids: Vec<_> = (0..7).map(|t| core_for(&cores, t).unwrap().id).collect();
+    assert_eq!(ids, [0, 1, 2, 0, 1, 2, 0]);
+    assert!(core_for(&[], 5).is_none());
+}
+
+#[test]
+fn backoff_is_exponential_and_bounded() {
+    assert_eq!(backoff(1), INITIAL_BACKOFF);
+    assert_eq!(backoff(2), INITIAL_BACKOFF * 2);
+    assert_eq!(backoff(4), INITIAL_BACKOFF * 8);
+    assert_eq!(backoff(usize::MAX), MAX_BACKOFF);
+}
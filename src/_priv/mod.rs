@@ -5,7 +5,7 @@
 pub use join::Join;
 pub(crate) use join_waker::cast;
 pub use join_waker::JoinWaker;
-pub use main::run;
+pub use main::{default_config, run, run_test};
 pub use try_join::TryJoin;
 
 mod join;
@@ -21,6 +21,12 @@ use super::{cast, JoinWaker};
 /// multiplexed on the current task. The `try_join!` macro returns when **all**
 /// branches return with `Ok` or when the **first** branch returns with `Err`.
 ///
+/// # Cancellation
+/// When a branch returns with `Err`, the remaining branches are dropped right away,
+/// before `try_join!` returns. Branches are dropped in the order they were passed
+/// to the macro, along with the outputs of the branches that had already completed.
+/// Dropping a branch cancels any I/O operation it was waiting on, so the operations
+/// of the losing branches don't run to completion in the background.
 ///
 /// # Implementation notes
 /// This `try_join!` macro implementation has two advantages over other alternative
@@ -158,7 +164,11 @@ macro_rules! implement_future_for_tuple {
 
                         match ready {
                             Ok(val) => *cell = ControlFlow::Break(val),
-                            Err(err) => return Poll::Ready(Err(err))
+                            Err(err) => {
+                                // the remaining branches are cancelled in order
+                                join.cells = None;
+                                return Poll::Ready(Err(err));
+                            }
                         }
                     }
                 )*
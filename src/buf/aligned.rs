@@ -0,0 +1,235 @@
+use std::alloc::{self, Layout};
+use std::fmt::Debug;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+use crate::buf::{IoBuf, IoBufMut};
+use crate::fs::{File, StatxMask};
+
+/// A fixed capacity buffer whose memory is aligned to a power of two.
+///
+/// Files opened with `O_DIRECT` bypass the page cache, so the kernel transfers data
+/// directly from and to the memory of the buffer. This requires the address of the
+/// buffer, as well as the length and the offset of each read and write, to be aligned
+/// to the block size of the device, otherwise the operation fails with `EINVAL`.
+/// `AlignedVec` takes care of the alignment of the memory, while the lengths and offsets
+/// are the responsibility of the caller. The alignment required by a file can be queried
+/// with [`Metadata::dio_mem_align`](crate::fs::Metadata::dio_mem_align).
+///
+/// Unlike a `Vec`, the buffer never reallocates, so its capacity is fixed on creation.
+///
+/// # Examples
+/// ```no_run
+/// use osiris::buf::AlignedVec;
+/// use osiris::fs::OpenOptions;
+///
+/// #[osiris::main]
+/// async fn main() -> std::io::Result<()> {
+///     let mut file = OpenOptions::new()
+///         .read(true)
+///         .custom_flags(libc::O_DIRECT)
+///         .open("data.db")
+///         .await?;
+///     let buf = AlignedVec::for_file(&file, 4096).await?;
+///     let (n, buf) = file.read_at(buf, 0).await;
+///     println!("read {:?}", &buf[..n?]);
+///     Ok(())
+/// }
+/// ```
+pub struct AlignedVec {
+    ptr: NonNull<u8>,
+    len: usize,
+    layout: Layout,
+}
+
+impl AlignedVec {
+    /// Creates an empty buffer with the given capacity, aligned to the page size.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> AlignedVec {
+        AlignedVec::with_capacity_aligned(capacity, page_size())
+    }
+
+    /// Creates an empty buffer with the given capacity and alignment.
+    ///
+    /// # Panics
+    /// If `align` is not a power of two, or if the capacity overflows when
+    /// rounded up to a multiple of `align`.
+    #[must_use]
+    pub fn with_capacity_aligned(capacity: usize, align: usize) -> AlignedVec {
+        let layout = Layout::from_size_align(capacity, align)
+            .expect("the alignment must be a power of two.");
+        let ptr = if capacity == 0 {
+            // a dangling pointer that still satisfies the alignment
+            NonNull::new(align as *mut u8).unwrap()
+        } else {
+            let ptr = unsafe { alloc::alloc(layout) };
+            NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+        };
+        AlignedVec {
+            ptr,
+            len: 0,
+            layout,
+        }
+    }
+
+    /// Creates an empty buffer with the given capacity, aligned as required for direct
+    /// I/O on `file`. The page size is used if the alignment cannot be determined.
+    ///
+    /// # Errors
+    /// This function errors if the metadata of the file cannot be queried.
+    pub async fn for_file(file: &File, capacity: usize) -> std::io::Result<AlignedVec> {
+        let metadata = file.metadata_with(StatxMask::DIOALIGN).await?;
+        let align = metadata
+            .dio_mem_align()
+            .map_or_else(page_size, |a| a as usize);
+        Ok(AlignedVec::with_capacity_aligned(capacity, align))
+    }
+
+    /// Returns the number of initialized bytes in the buffer.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the buffer contains no bytes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the capacity of the buffer.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.layout.size()
+    }
+
+    /// Returns the alignment of the buffer.
+    #[must_use]
+    pub fn alignment(&self) -> usize {
+        self.layout.align()
+    }
+
+    /// Clears the buffer, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Shortens the buffer to `len` bytes. It has no effect if `len` is
+    /// greater than the current length.
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    /// Appends the bytes to the buffer.
+    ///
+    /// # Panics
+    /// If the bytes don't fit in the remaining capacity.
+    pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+        assert!(
+            bytes.len() <= self.capacity() - self.len,
+            "the bytes exceed the capacity of the buffer."
+        );
+        unsafe {
+            let end = self.ptr.as_ptr().add(self.len);
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), end, bytes.len());
+        }
+        self.len += bytes.len();
+    }
+
+    /// Resizes the buffer to `len` bytes, filling the new bytes with `value`.
+    ///
+    /// # Panics
+    /// If `len` is greater than the capacity.
+    pub fn resize(&mut self, len: usize, value: u8) {
+        assert!(
+            len <= self.capacity(),
+            "the length exceeds the capacity of the buffer."
+        );
+        if len > self.len {
+            unsafe {
+                let end = self.ptr.as_ptr().add(self.len);
+                std::ptr::write_bytes(end, value, len - self.len);
+            }
+        }
+        self.len = len;
+    }
+}
+
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+impl Drop for AlignedVec {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) };
+        }
+    }
+}
+
+impl Deref for AlignedVec {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for AlignedVec {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Debug for AlignedVec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlignedVec")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity())
+            .field("alignment", &self.alignment())
+            .finish()
+    }
+}
+
+// Safety: the memory is owned by the buffer, and it is never reallocated.
+unsafe impl IoBuf for AlignedVec {
+    fn stable_ptr(&self) -> *const u8 {
+        self.ptr.as_ptr()
+    }
+
+    fn bytes_init(&self) -> usize {
+        self.len
+    }
+
+    fn bytes_total(&self) -> usize {
+        self.capacity()
+    }
+}
+
+// Safety: the memory is owned by the buffer, and it is never reallocated.
+unsafe impl IoBufMut for AlignedVec {
+    fn stable_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    unsafe fn set_init(&mut self, pos: usize) {
+        debug_assert!(
+            pos <= self.bytes_total(),
+            "initialized past the end of the buffer"
+        );
+        self.len = self.len.max(pos);
+    }
+}
+
+#[test]
+fn aligned_allocation() {
+    let mut buf = AlignedVec::with_capacity_aligned(4096, 512);
+    assert_eq!(buf.as_ptr() as usize % 512, 0);
+    assert_eq!(buf.capacity(), 4096);
+    buf.extend_from_slice(b"hello");
+    buf.resize(512, 0);
+    assert_eq!(&buf[..5], b"hello");
+    assert_eq!(buf.len(), 512);
+    let empty = AlignedVec::with_capacity(0);
+    assert_eq!(empty.as_ptr() as usize % empty.alignment(), 0);
+    assert!(empty.is_empty());
+}
@@ -88,6 +88,43 @@ pub unsafe trait IoBuf: Unpin + 'static {
 
         Slice::new(self, begin, end)
     }
+
+    /// Splits the buffer into two non-overlapping views at `mid`, where the
+    /// first one contains `[0, mid)` and the second one `[mid, bytes_total)`.
+    ///
+    /// Both halves share ownership of the buffer through an [`Rc`], so they
+    /// can be handed to separate operations. The original buffer can be
+    /// recovered with [`Rc::try_unwrap`] once the other half is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid` is greater than the number of initialized bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    /// use osiris::buf::IoBuf;
+    ///
+    /// let buf = b"\x05hello".to_vec();
+    /// let (prefix, payload) = buf.split_owned(1);
+    ///
+    /// assert_eq!(&prefix[..], b"\x05");
+    /// assert_eq!(&payload[..], b"hello");
+    ///
+    /// drop(prefix);
+    /// let buf = Rc::try_unwrap(payload.into_inner()).unwrap();
+    /// assert_eq!(buf, b"\x05hello");
+    /// ```
+    fn split_owned(self, mid: usize) -> (Slice<Rc<Self>>, Slice<Rc<Self>>)
+    where
+        Self: Sized,
+    {
+        assert!(mid <= self.bytes_init());
+        let end = self.bytes_total();
+        let buf = Rc::new(self);
+        (Slice::new(buf.clone(), 0, mid), Slice::new(buf, mid, end))
+    }
 }
 // Safety: Vec<u8> allocates memory which is stable.
 unsafe impl IoBuf for Vec<u8> {
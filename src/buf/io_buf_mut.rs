@@ -1,4 +1,4 @@
-use crate::buf::IoBuf;
+use crate::buf::{IoBuf, Slice};
 
 /// A mutable`io-uring` compatible buffer.
 ///
@@ -31,6 +31,33 @@ pub unsafe trait IoBufMut: IoBuf {
     /// The caller must ensure that all bytes starting at `stable_mut_ptr()` up
     /// to `pos` are initialized and owned by the buffer.
     unsafe fn set_init(&mut self, pos: usize);
+
+    /// Returns a view of the buffer limited to its first `n` bytes, without
+    /// reallocating. If `n` is greater than `bytes_total`, the whole buffer is used.
+    ///
+    /// Unlike [`slice`](IoBuf::slice), this method never panics, making it useful for
+    /// reusing a large buffer for small reads. The original buffer, with its
+    /// capacity intact, can be recovered with [`Slice::into_inner`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use osiris::buf::{IoBuf, IoBufMut};
+    ///
+    /// let buf = Vec::with_capacity(64 * 1024);
+    /// let limited = buf.limit(512);
+    /// assert_eq!(limited.bytes_total(), 512);
+    ///
+    /// let buf = limited.into_inner();
+    /// assert_eq!(buf.capacity(), 64 * 1024);
+    /// ```
+    fn limit(self, n: usize) -> Slice<Self>
+    where
+        Self: Sized,
+    {
+        let end = n.min(self.bytes_total());
+        Slice::new(self, 0, end)
+    }
 }
 
 // Safety: Vec<u8> is always an allocated buffer, which is stable
@@ -40,6 +67,10 @@ unsafe impl IoBufMut for Vec<u8> {
     }
 
     unsafe fn set_init(&mut self, init_len: usize) {
+        debug_assert!(
+            init_len <= self.capacity(),
+            "initialized past the end of the buffer"
+        );
         if self.len() < init_len {
             // Safety: the invariants must be upheld by the caller
             unsafe { self.set_len(init_len) }
@@ -1,11 +1,15 @@
 #![allow(clippy::module_name_repetitions)]
+pub use aligned::AlignedVec;
 pub use io_buf::IoBuf;
 pub use io_buf_mut::IoBufMut;
 pub use slice::Slice;
+pub use uninit::Uninit;
 
+mod aligned;
 mod io_buf;
 mod io_buf_mut;
 mod slice;
+mod uninit;
 
 pub(crate) fn deref(buf: &impl IoBuf) -> &[u8] {
     // Safety: the `IoBuf` trait is marked as unsafe and is expected to be
@@ -161,6 +161,10 @@ unsafe impl<T: IoBufMut> IoBufMut for Slice<T> {
     }
 
     unsafe fn set_init(&mut self, pos: usize) {
+        debug_assert!(
+            pos <= self.bytes_total(),
+            "initialized past the end of the slice"
+        );
         // Safety: invariants are upheld by the caller
         unsafe { self.buf.set_init(self.begin + pos) }
     }
@@ -0,0 +1,113 @@
+use std::fmt::{self, Debug};
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ops;
+
+use crate::buf::{IoBuf, IoBufMut};
+
+/// A fixed size buffer backed by uninitialized memory.
+///
+/// Allocating an `Uninit` buffer does not zero its memory, which can be a
+/// measurable cost for large buffers. The buffer starts with no initialized
+/// bytes, and the only way to initialize them is to pass the buffer to a read
+/// operation, which marks the bytes written by the kernel as initialized through
+/// [`IoBufMut::set_init`]. Only the initialized prefix of the buffer can be
+/// accessed, through its `Deref` implementation.
+///
+/// # Examples
+///
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::buf::{IoBuf, Uninit};
+/// use osiris::fs::File;
+///
+/// let mut file = File::open("Cargo.toml").await?;
+/// let buf = Uninit::new(64 * 1024);
+/// assert_eq!(buf.bytes_init(), 0);
+///
+/// let (n, buf) = file.read_at(buf, 0).await;
+/// assert_eq!(buf.len(), n?);
+/// # std::io::Result::Ok(()) }).unwrap();
+/// ```
+pub struct Uninit {
+    buf: Box<[MaybeUninit<u8>]>,
+    init: usize,
+}
+
+impl Uninit {
+    /// Allocates a new buffer of `len` bytes, without initializing them.
+    #[must_use]
+    pub fn new(len: usize) -> Uninit {
+        let mut buf = Vec::with_capacity(len);
+        // Safety: `MaybeUninit` does not require initialization.
+        unsafe { buf.set_len(len) };
+        Uninit {
+            buf: buf.into_boxed_slice(),
+            init: 0,
+        }
+    }
+
+    /// Converts the buffer into a vector containing its initialized bytes,
+    /// without copying them. The capacity of the vector is the size of the buffer.
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        let mut this = ManuallyDrop::new(self);
+        let capacity = this.buf.len();
+        let ptr = this.buf.as_mut_ptr().cast::<u8>();
+        // Safety: the allocation came from a `Vec` with the same capacity, `u8` and
+        // `MaybeUninit<u8>` have the same layout, and the first `init` bytes are initialized.
+        unsafe { Vec::from_raw_parts(ptr, this.init, capacity) }
+    }
+}
+
+// Safety: the buffer is heap allocated, so it is stable
+unsafe impl IoBuf for Uninit {
+    fn stable_ptr(&self) -> *const u8 {
+        self.buf.as_ptr().cast()
+    }
+
+    fn bytes_init(&self) -> usize {
+        self.init
+    }
+
+    fn bytes_total(&self) -> usize {
+        self.buf.len()
+    }
+}
+
+// Safety: the buffer is heap allocated, so it is stable
+unsafe impl IoBufMut for Uninit {
+    fn stable_mut_ptr(&mut self) -> *mut u8 {
+        self.buf.as_mut_ptr().cast()
+    }
+
+    unsafe fn set_init(&mut self, pos: usize) {
+        debug_assert!(
+            pos <= self.bytes_total(),
+            "initialized past the end of the buffer"
+        );
+        self.init = self.init.max(pos);
+    }
+}
+
+impl ops::Deref for Uninit {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        super::deref(self)
+    }
+}
+
+impl ops::DerefMut for Uninit {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        super::deref_mut(self)
+    }
+}
+
+impl Debug for Uninit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Uninit")
+            .field("init", &&**self)
+            .field("len", &self.buf.len())
+            .finish()
+    }
+}
@@ -1,7 +1,9 @@
-use super::cstr;
+use super::{cstr, File, Metadata, OpenOptions, StatxMask};
 use crate::reactor::op::{self, unlink_at};
+use std::fmt::Debug;
 use std::io::Result;
-use std::path::PathBuf;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd};
+use std::path::{Path, PathBuf};
 
 /// Creates a new, empty directory at the provided path
 ///
@@ -25,7 +27,7 @@ use std::path::PathBuf;
 /// ```
 pub async fn create_dir(path: impl Into<PathBuf>) -> Result<()> {
     let path = cstr(path)?;
-    op::mkdir_at(path).await
+    op::mkdir_at(libc::AT_FDCWD, path).await
 }
 
 /// Removes an empty directory.
@@ -57,5 +59,169 @@ pub async fn create_dir(path: impl Into<PathBuf>) -> Result<()> {
 /// ```
 pub async fn remove_dir(path: impl Into<PathBuf>) -> Result<()> {
     let path = cstr(path)?;
-    unlink_at(path, libc::AT_REMOVEDIR).await
+    unlink_at(libc::AT_FDCWD, path, libc::AT_REMOVEDIR).await
+}
+
+/// A handle to an open directory, used to operate on the paths relative to it.
+///
+/// The `*_at` methods resolve relative paths starting from the directory itself,
+/// instead of the current working directory, by passing its file descriptor to the
+/// `*at` family of system calls. Since the directory stays open, renaming or replacing
+/// any of its parents does not change the files the operations refer to, which makes
+/// recursive traversals free of the races that affect operating on full paths.
+/// Absolute paths ignore the directory.
+///
+/// Combined with [`ResolveFlags::BENEATH`](crate::fs::ResolveFlags::BENEATH), it can
+/// be used to confine the files opened to a directory.
+///
+/// # Examples
+///
+/// ```no_run
+/// # osiris::block_on(async {
+/// use osiris::fs::{Dir, OpenOptions};
+///
+/// let dir = Dir::open("/var/lib/app").await?;
+/// let file = dir
+///     .open_at("data.db", OpenOptions::new().read(true).write(true).create(true))
+///     .await?;
+/// let metadata = dir.metadata_at("data.db").await?;
+/// dir.remove_file_at("data.db.lock").await?;
+/// # std::io::Result::Ok(()) }).unwrap();
+/// ```
+pub struct Dir {
+    file: File,
+}
+
+impl Dir {
+    /// Opens the directory at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `path` does not exist, if it is not
+    /// a directory, or if the user lacks permissions to open it.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Dir> {
+        open_dir(libc::AT_FDCWD, path.as_ref()).await
+    }
+
+    /// Opens a file at `path`, relative to the directory, with the options specified
+    /// by `options`. See [`OpenOptions::open`] for more details.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as [`OpenOptions::open`].
+    pub async fn open_at(&self, path: impl AsRef<Path>, options: &OpenOptions) -> Result<File> {
+        options.open_at(self.file.fd, path.as_ref()).await
+    }
+
+    /// Opens the directory at `path`, relative to the directory.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as [`Dir::open`].
+    pub async fn open_dir_at(&self, path: impl AsRef<Path>) -> Result<Dir> {
+        open_dir(self.file.fd, path.as_ref()).await
+    }
+
+    /// Creates a new, empty directory at `path`, relative to the directory.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as [`create_dir`].
+    pub async fn create_dir_at(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = cstr(path.as_ref())?;
+        op::mkdir_at(self.file.fd, path).await
+    }
+
+    /// Queries the metadata of the file at `path`, relative to the directory,
+    /// following symlinks. See [`metadata`](crate::fs::metadata).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `path` does not exist, or if
+    /// the user lacks permissions to query it.
+    pub async fn metadata_at(&self, path: impl AsRef<Path>) -> Result<Metadata> {
+        self.statx_at(path.as_ref(), 0).await
+    }
+
+    /// Queries the metadata of the file at `path`, relative to the directory,
+    /// without following symlinks. See [`symlink_metadata`](crate::fs::symlink_metadata).
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as [`Dir::metadata_at`].
+    pub async fn symlink_metadata_at(&self, path: impl AsRef<Path>) -> Result<Metadata> {
+        self.statx_at(path.as_ref(), libc::AT_SYMLINK_NOFOLLOW)
+            .await
+    }
+
+    /// Removes the file at `path`, relative to the directory.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as [`remove_file`](crate::fs::remove_file).
+    pub async fn remove_file_at(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = cstr(path.as_ref())?;
+        op::unlink_at(self.file.fd, path, 0).await
+    }
+
+    /// Removes the empty directory at `path`, relative to the directory.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the same errors as [`remove_dir`].
+    pub async fn remove_dir_at(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = cstr(path.as_ref())?;
+        op::unlink_at(self.file.fd, path, libc::AT_REMOVEDIR).await
+    }
+
+    /// Queries the metadata of the directory itself.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the metadata cannot be queried.
+    pub async fn metadata(&self) -> Result<Metadata> {
+        self.file.metadata().await
+    }
+
+    /// Closes the directory. Calling this method is recommended
+    /// over letting the value be dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file descriptor
+    /// cannot be closed.
+    pub async fn close(self) -> Result<()> {
+        self.file.close().await
+    }
+
+    async fn statx_at(&self, path: &Path, flags: i32) -> Result<Metadata> {
+        let path = cstr(path)?;
+        let statx = op::statx(self.file.fd, Some(path), flags, StatxMask::ALL.bits()).await?;
+        Ok(Metadata { statx })
+    }
+}
+
+async fn open_dir(dirfd: i32, path: &Path) -> Result<Dir> {
+    let path = cstr(path)?;
+    let flags = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC;
+    let fd = op::open_at(dirfd, path, flags, 0).await?;
+    Ok(Dir { file: File { fd } })
+}
+
+impl AsRawFd for Dir {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+impl AsFd for Dir {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
+
+impl Debug for Dir {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Dir").field("fd", &self.file.fd).finish()
+    }
 }
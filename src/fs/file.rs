@@ -2,15 +2,17 @@
 
 use crate::buf::{IoBuf, IoBufMut};
 use crate::detach;
-use crate::fs::Metadata;
+use crate::fs::{Metadata, Mmap, MmapMut, StatxMask};
 use crate::reactor::op;
 use crate::runtime::current;
+use crate::task::spawn_blocking;
 use crate::utils::futures::{catch_unwind, not_thread_safe};
+use crate::utils::syscall;
 
 use libc::AT_FDCWD;
-use std::io::{self, Error, Result};
+use std::io::{self, Error, Result, SeekFrom};
 use std::mem::{forget, MaybeUninit};
-use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd};
 use std::path::Path;
 
 use super::{cstr, OpenOptions};
@@ -41,13 +43,75 @@ pub struct File {
     pub(crate) fd: i32,
 }
 
+/// The expected access pattern for a region of a file, passed to [`File::advise`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Advice {
+    /// No particular pattern is expected. This is the default.
+    #[default]
+    Normal,
+    /// The data is expected to be read from lower to higher offsets, so the kernel
+    /// may read ahead more aggressively.
+    Sequential,
+    /// The data is expected to be accessed in random order, so read ahead is disabled.
+    Random,
+    /// The data will be accessed soon, so the kernel may start reading it into the
+    /// page cache.
+    WillNeed,
+    /// The data will not be accessed soon, so the kernel may drop it from the page cache.
+    DontNeed,
+}
+
+impl From<Advice> for i32 {
+    fn from(advice: Advice) -> i32 {
+        #[cfg(target_os = "linux")]
+        match advice {
+            Advice::Normal => libc::POSIX_FADV_NORMAL,
+            Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+            Advice::Random => libc::POSIX_FADV_RANDOM,
+            Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+            Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            advice as i32
+        }
+    }
+}
+
+/// Files opened with `O_DIRECT` fail with `EINVAL` when the length or the offset of
+/// a transfer is not a multiple of the logical block size, which is at least 512 bytes.
+/// Debug builds check it upfront, since the error is otherwise hard to trace back.
+#[allow(unused_variables)]
+fn debug_assert_direct_io(fd: i32, len: usize, pos: u64) {
+    #[cfg(all(debug_assertions, target_os = "linux"))]
+    {
+        let Ok(flags) = syscall!(fcntl, fd, libc::F_GETFL) else {
+            return;
+        };
+        if flags & libc::O_DIRECT != 0 {
+            assert!(
+                len.is_multiple_of(512) && pos.is_multiple_of(512),
+                "files opened with O_DIRECT require block aligned lengths and offsets."
+            );
+        }
+    }
+}
+
+/// Converts an offset into the signed offset taken by the operations, where `-1` stands
+/// for the file position. Files can't be larger than `i64::MAX` bytes, so larger offsets
+/// are rejected rather than being truncated or mistaken for the file position.
+fn offset(pos: u64) -> Result<i64> {
+    i64::try_from(pos)
+        .map_err(|_| Error::new(io::ErrorKind::InvalidInput, "file offset is too large"))
+}
+
 impl Drop for File {
     fn drop(&mut self) {
         let Some(rt) = current() else {
             unsafe { std::fs::File::from_raw_fd(self.as_raw_fd()) };
             return;
         };
-        rt.detach(op::close(self.fd));
+        rt.detach_cleanup(op::close(self.fd));
     }
 }
 
@@ -78,6 +142,9 @@ impl File {
     /// This function will create a file if it does not exist,
     /// and will truncate it if it does.
     ///
+    /// New files are created with the permissions `0o666 & !umask`, see
+    /// [`OpenOptions::mode`].
+    ///
     /// Depending on the platform, this function may fail if the
     /// full directory path does not exist.
     ///
@@ -196,6 +263,9 @@ impl File {
     /// the entire write may not succeed, or the write may also generate an
     /// error. The bytes will be written starting at the specified offset.
     ///
+    /// If the file was opened with `O_DIRECT`, the buffer, its length and the offset
+    /// must be aligned to the block size of the device. See [`AlignedVec`](crate::buf::AlignedVec).
+    ///
     /// # Return
     ///
     /// The method returns the operation result and the same buffer value passed
@@ -207,7 +277,9 @@ impl File {
     ///
     /// Each call to `write` may generate an I/O error indicating that the
     /// operation could not be completed. If an error is returned then no bytes
-    /// in the buffer were written to this writer.
+    /// in the buffer were written to this writer. An error of kind
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) is returned if `pos` is
+    /// larger than `i64::MAX`, which is the maximum size of a file.
     ///
     /// It is **not** considered an error if the entire buffer could not be
     /// written to this writer.
@@ -232,8 +304,65 @@ impl File {
     /// ```
     ///
     /// [`Ok(n)`]: Ok
-    pub async fn write_at<T: IoBuf>(&mut self, buf: T, pos: usize) -> (Result<usize>, T) {
-        op::write_at(self.fd, buf, pos as _).await
+    pub async fn write_at<T: IoBuf>(&mut self, buf: T, pos: u64) -> (Result<usize>, T) {
+        let pos = match offset(pos) {
+            Ok(pos) => pos,
+            Err(err) => return (Err(err), buf),
+        };
+        debug_assert_direct_io(self.fd, buf.bytes_init(), pos as u64);
+        op::write_at(self.fd, buf, pos).await
+    }
+
+    /// Attempts to write an entire buffer into this file at the specified offset.
+    ///
+    /// This method will continuously call [`write_at`] until there is no more data
+    /// to be written or an error is returned, advancing the offset by the number of
+    /// bytes written each time. The file position is not used nor updated. This
+    /// method will not return until the entire buffer has been successfully written
+    /// or an error has occurred.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the first error that [`write_at`] returns. If a
+    /// write returns `0`, an error of kind [`ErrorKind::WriteZero`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::File;
+    ///
+    /// let file = File::create("foo.txt").await?;
+    ///
+    /// // Writes the whole byte string, starting at offset 4.
+    /// file.write_all_at(b"some bytes", 4).await.0?;
+    ///
+    /// // Close the file
+    /// file.close().await?;
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    ///
+    /// [`write_at`]: File::write_at
+    /// [`ErrorKind::WriteZero`]: io::ErrorKind::WriteZero
+    pub async fn write_all_at<B: IoBuf>(&self, mut buf: B, pos: u64) -> (Result<()>, B) {
+        let mut n = 0;
+        while n < buf.bytes_init() {
+            let pos = match offset(pos.saturating_add(n as u64)) {
+                Ok(pos) => pos,
+                Err(err) => return (Err(err), buf),
+            };
+            let (written, slice) = op::write_at(self.fd, buf.slice(n..), pos).await;
+            buf = slice.into_inner();
+            match written {
+                Ok(0) => {
+                    let err = Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer");
+                    return (Err(err), buf);
+                }
+                Ok(written) => n += written,
+                Err(err) => return (Err(err), buf),
+            }
+        }
+        (Ok(()), buf)
     }
 
     /// Write a buffer into this file at file's position, returning how
@@ -291,6 +420,9 @@ impl File {
     /// Read some bytes at the specified offset from the file into the specified
     /// buffer, returning how many bytes were read.
     ///
+    /// If the file was opened with `O_DIRECT`, the buffer, its capacity and the offset
+    /// must be aligned to the block size of the device. See [`AlignedVec`](crate::buf::AlignedVec).
+    ///
     /// # Return
     ///
     /// The method returns the operation result and the same buffer value passed
@@ -309,7 +441,9 @@ impl File {
     /// # Errors
     ///
     /// If this function encounters any form of I/O or other error, an error
-    /// variant will be returned. The buffer is returned on error.
+    /// variant will be returned. The buffer is returned on error. An error of kind
+    /// [`InvalidInput`](io::ErrorKind::InvalidInput) is returned if `pos` is
+    /// larger than `i64::MAX`, which is the maximum size of a file.
     ///
     /// # Examples
     ///
@@ -330,8 +464,13 @@ impl File {
     /// f.close().await?;
     /// # std::io::Result::Ok(()) }).unwrap();
     /// ```
-    pub async fn read_at<T: IoBufMut>(&mut self, buf: T, pos: usize) -> (Result<usize>, T) {
-        let (res, mut buf) = op::read_at(self.fd, buf, pos as _).await;
+    pub async fn read_at<T: IoBufMut>(&mut self, buf: T, pos: u64) -> (Result<usize>, T) {
+        let pos = match offset(pos) {
+            Ok(pos) => pos,
+            Err(err) => return (Err(err), buf),
+        };
+        debug_assert_direct_io(self.fd, buf.bytes_total(), pos as u64);
+        let (res, mut buf) = op::read_at(self.fd, buf, pos).await;
         match res {
             Ok(len) => {
                 // Safety: initilialized by io-uring
@@ -342,6 +481,59 @@ impl File {
         }
     }
 
+    /// Reads the exact number of bytes required to fill `buf` from the specified offset.
+    ///
+    /// This method will continuously call [`read_at`] until the buffer is full,
+    /// advancing the offset by the number of bytes read each time. The file position
+    /// is not used nor updated. On success, the whole buffer is initialized.
+    ///
+    /// # Errors
+    ///
+    /// If the end of the file is reached before the buffer is filled, an error of kind
+    /// [`ErrorKind::UnexpectedEof`] is returned. Otherwise, this function returns the
+    /// first error that [`read_at`] returns. In both cases, the contents of the buffer
+    /// are unspecified.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::File;
+    ///
+    /// let f = File::open("foo.txt").await?;
+    ///
+    /// // Read exactly 10 bytes, starting at offset 512
+    /// let (res, buffer) = f.read_exact_at(Vec::with_capacity(10), 512).await;
+    /// res?;
+    ///
+    /// println!("The bytes: {:?}", buffer);
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    ///
+    /// [`read_at`]: File::read_at
+    /// [`ErrorKind::UnexpectedEof`]: io::ErrorKind::UnexpectedEof
+    pub async fn read_exact_at<B: IoBufMut>(&self, mut buf: B, pos: u64) -> (Result<()>, B) {
+        let mut n = 0;
+        while n < buf.bytes_total() {
+            let pos = match offset(pos.saturating_add(n as u64)) {
+                Ok(pos) => pos,
+                Err(err) => return (Err(err), buf),
+            };
+            let (read, slice) = op::read_at(self.fd, buf.slice(n..), pos).await;
+            buf = slice.into_inner();
+            match read {
+                Ok(0) => {
+                    let err =
+                        Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer");
+                    return (Err(err), buf);
+                }
+                Ok(read) => n += read,
+                Err(err) => return (Err(err), buf),
+            }
+        }
+        (Ok(()), buf)
+    }
+
     /// Read some bytes using the file position from the file into the specified
     /// buffer, returning how many bytes were read.
     ///
@@ -399,6 +591,107 @@ impl File {
         }
     }
 
+    /// Read some bytes at the specified offset from the file into multiple
+    /// buffers, returning how many bytes were read in total.
+    ///
+    /// The buffers are filled in order, each one up to its capacity, and the
+    /// length of each buffer is set to the number of bytes that landed in it.
+    /// If fewer bytes than the combined capacity were read, the later buffers
+    /// are left empty. This is useful to read a fixed-size header and a
+    /// variable body with a single operation.
+    ///
+    /// # Errors
+    ///
+    /// If this function encounters any form of I/O or other error, an error
+    /// variant will be returned. The buffers are returned on error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::File;
+    ///
+    /// let mut f = File::open("foo.txt").await?;
+    /// let bufs = vec![Vec::with_capacity(4), Vec::with_capacity(1024)];
+    ///
+    /// let (res, bufs) = f.read_vectored_at(bufs, 0).await;
+    /// let n = res?;
+    ///
+    /// println!("header: {:?}, body: {:?}", bufs[0], bufs[1]);
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    pub async fn read_vectored_at(
+        &mut self,
+        bufs: Vec<Vec<u8>>,
+        pos: u64,
+    ) -> (Result<usize>, Vec<Vec<u8>>) {
+        let pos = match offset(pos) {
+            Ok(pos) => pos,
+            Err(err) => return (Err(err), bufs),
+        };
+        op::read_vectored_at(self.fd, bufs, pos).await
+    }
+
+    /// Read some bytes using the file position from the file into multiple
+    /// buffers, returning how many bytes were read in total.
+    ///
+    /// This function will use (and advance) the file position. See
+    /// [`File::read_vectored_at`] for more details.
+    pub async fn read_vectored(&mut self, bufs: Vec<Vec<u8>>) -> (Result<usize>, Vec<Vec<u8>>) {
+        op::read_vectored_at(self.fd, bufs, -1).await
+    }
+
+    /// Seeks to an offset, in bytes, in the file, returning the new position from
+    /// the start of the file. This position is used by the methods that don't take
+    /// an explicit offset, like [`read`](File::read) and [`write`](File::write).
+    ///
+    /// A seek beyond the end of the file is allowed. In this case, subsequent
+    /// writes will extend the file.
+    ///
+    /// # Errors
+    /// Seeking to a negative offset is considered an error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::File;
+    /// use std::io::SeekFrom;
+    ///
+    /// let mut f = File::open("foo.txt").await?;
+    /// // read the last 5 bytes of the file
+    /// f.seek(SeekFrom::End(-5)).await?;
+    /// let (res, buf) = f.read(vec![0; 5]).await;
+    /// let n = res?;
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    pub async fn seek(&self, pos: SeekFrom) -> Result<u64> {
+        let (offset, whence) = match pos {
+            SeekFrom::Start(offset) => (offset.try_into().ok(), libc::SEEK_SET),
+            SeekFrom::Current(offset) => (Some(offset), libc::SEEK_CUR),
+            SeekFrom::End(offset) => {
+                let len = self.metadata().await?.len() as u64;
+                let offset = len.checked_add_signed(offset);
+                (offset.and_then(|o| o.try_into().ok()), libc::SEEK_SET)
+            }
+        };
+        let Some(offset) = offset else {
+            let msg = "invalid seek to a negative or overflowing position";
+            return Err(Error::new(io::ErrorKind::InvalidInput, msg));
+        };
+        let fd = self.fd;
+        // io-uring doesn't support seeking, but lseek shouldn't block for long.
+        let offset = spawn_blocking(move || syscall!(lseek, fd, offset, whence)).await?;
+        Ok(offset as u64)
+    }
+
+    /// Returns the current seek position from the start of the file.
+    ///
+    /// This is equivalent to `self.seek(SeekFrom::Current(0))`.
+    pub async fn stream_position(&self) -> Result<u64> {
+        self.seek(SeekFrom::Current(0)).await
+    }
+
     /// Attempts to sync all OS-internal metadata to disk.
     ///
     /// This function will attempt to ensure that all in-memory data reaches the
@@ -464,6 +757,107 @@ impl File {
         Ok(())
     }
 
+    /// Declares the expected access pattern for `len` bytes of the file starting at
+    /// `offset`, so the kernel can tune its read ahead and caching. A `len` of zero
+    /// extends the region to the end of the file.
+    ///
+    /// The advice is only a hint, and it is ignored on platforms without
+    /// `posix_fadvise`, such as macOS.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::{Advice, File};
+    ///
+    /// let f = File::open("foo.txt").await?;
+    /// f.advise(0, 0, Advice::Sequential).await?;
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    pub async fn advise(&self, offset: u64, len: u64, advice: Advice) -> Result<()> {
+        let (offset, len) = (self::offset(offset)?, self::offset(len)?);
+        op::fadvise(self.fd, offset, len, advice.into()).await
+    }
+
+    /// Maps `len` bytes of the file starting at `offset` into memory. The file must be
+    /// opened for reading.
+    ///
+    /// Accessing the map bypasses the async I/O path, and its page faults block the
+    /// thread, stalling the runtime. See [`Mmap`] for when this pays off.
+    ///
+    /// # Safety
+    ///
+    /// The map hands out a shared reference to the mapped bytes, so the file must not
+    /// be modified while it is mapped, by this process or any other. If the file is
+    /// truncated, accessing the pages past its end raises `SIGBUS`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::File;
+    ///
+    /// let f = File::open("foo.txt").await?;
+    /// let len = f.metadata().await?.len();
+    /// // Safety: the file is not modified while it is mapped
+    /// let map = unsafe { f.memory_map(0, len as usize) }.await?;
+    /// assert_eq!(map.len() as u64, len);
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    pub async unsafe fn memory_map(&self, offset: u64, len: usize) -> Result<Mmap> {
+        Mmap::new(self.fd, offset, len).await
+    }
+
+    /// Maps `len` bytes of the file starting at `offset` into writable memory. The file
+    /// must be opened for reading and writing, and it must be at least `offset + len`
+    /// bytes long, see [`set_len`](File::set_len).
+    ///
+    /// # Safety
+    ///
+    /// The map hands out a mutable reference to the mapped bytes, so the file must not
+    /// be modified through other means while it is mapped, by this process or any other.
+    /// If the file is truncated, accessing the pages past its end raises `SIGBUS`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::OpenOptions;
+    ///
+    /// let mut f = OpenOptions::new().read(true).write(true).create(true).open("foo.txt").await?;
+    /// f.set_len(5).await?;
+    /// // Safety: the file is not modified while it is mapped
+    /// let mut map = unsafe { f.memory_map_mut(0, 5) }.await?;
+    /// map.copy_from_slice(b"hello");
+    /// map.flush().await?;
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    pub async unsafe fn memory_map_mut(&self, offset: u64, len: usize) -> Result<MmapMut> {
+        MmapMut::new(self.fd, offset, len).await
+    }
+
+    /// Truncates or extends the underlying file, updating the size of this file to
+    /// become `size`. If the file is extended, the new bytes read as zeros.
+    ///
+    /// The file must be opened for writing.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::File;
+    ///
+    /// let mut f = File::create("foo.txt").await?;
+    /// f.set_len(10).await?;
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    pub async fn set_len(&mut self, size: u64) -> Result<()> {
+        let (fd, size) = (self.fd, offset(size)?);
+        // io-uring only supports ftruncate since 6.9.
+        spawn_blocking(move || syscall!(ftruncate, fd, size)).await?;
+        Ok(())
+    }
+
     /// Queries metadata about the underlying file.
     ///
     /// # Examples
@@ -478,7 +872,27 @@ impl File {
     /// # std::io::Result::Ok(()) }).unwrap();
     /// ```
     pub async fn metadata(&self) -> Result<Metadata> {
-        let statx = op::statx(self.fd, None, 0).await?;
+        self.metadata_with(StatxMask::ALL).await
+    }
+
+    /// Queries metadata about the underlying file, requesting only the fields in `mask`.
+    ///
+    /// The values of the fields that were not requested are unspecified. See
+    /// [`StatxMask`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::{File, StatxMask};
+    ///
+    /// let f = File::open("foo.txt").await?;
+    /// let metadata = f.metadata_with(StatxMask::SIZE | StatxMask::MTIME).await?;
+    /// println!("{} bytes, modified at {:?}", metadata.len(), metadata.modified()?);
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    pub async fn metadata_with(&self, mask: StatxMask) -> Result<Metadata> {
+        let statx = op::statx(self.fd, None, 0, mask.bits()).await?;
         Ok(Metadata { statx })
     }
     /// Destructures `File` into a [`std::fs::File`].
@@ -553,7 +967,7 @@ impl From<File> for std::fs::File {
 /// Non generic remove file
 async fn _remove_file(path: &Path) -> Result<()> {
     let path = cstr(path)?;
-    op::unlink_at(path, 0).await?;
+    op::unlink_at(AT_FDCWD, path, 0).await?;
     Ok(())
 }
 
@@ -571,6 +985,13 @@ impl AsRawFd for File {
     }
 }
 
+impl AsFd for File {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // Safety: the file descriptor is open for as long as the file is alive
+        unsafe { BorrowedFd::borrow_raw(self.fd) }
+    }
+}
+
 impl FromRawFd for File {
     unsafe fn from_raw_fd(fd: std::os::fd::RawFd) -> Self {
         File { fd }
@@ -2,9 +2,10 @@
 
 use super::cstr;
 use crate::reactor::op;
-use crate::utils::{statx, statx_timestamp};
+use crate::utils::{self, statx, statx_timestamp};
 use libc::{mode_t, AT_SYMLINK_NOFOLLOW, S_IFDIR, S_IFLNK, S_IFMT, S_IFREG};
 use std::io::{self, Error, Result};
+use std::ops::{BitOr, BitOrAssign};
 use std::path::Path;
 use std::time::{Duration, SystemTime};
 
@@ -78,10 +79,83 @@ pub async fn symlink_metadata(path: impl AsRef<Path>) -> Result<Metadata> {
 
 async fn _metadata(path: &Path, flags: i32) -> std::io::Result<Metadata> {
     let path = cstr(path)?;
-    let statx = op::statx(libc::AT_FDCWD, Some(path), flags).await?;
+    let statx = op::statx(libc::AT_FDCWD, Some(path), flags, StatxMask::ALL.0).await?;
     Ok(Metadata { statx })
 }
 
+/// The set of fields requested when querying the metadata of a file
+/// with [`File::metadata_with`](crate::fs::File::metadata_with).
+///
+/// Some filesystems, such as network filesystems, need extra work to compute
+/// some of the fields, so requesting only the required ones can be noticeably
+/// faster. The values of the fields that were not requested are unspecified,
+/// though the kernel may fill them anyway if they are cheap to retrieve.
+/// This corresponds to the `mask` argument of
+/// [`statx(2)`](https://man7.org/linux/man-pages/man2/statx.2.html), and is
+/// ignored on platforms without `statx`. Masks can be combined with the `|` operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatxMask(u32);
+
+impl StatxMask {
+    /// The file type, used by [`Metadata::file_type`].
+    pub const TYPE: StatxMask = StatxMask(utils::STATX_TYPE);
+    /// The file permissions.
+    pub const MODE: StatxMask = StatxMask(utils::STATX_MODE);
+    /// The number of hard links, returned by [`Metadata::nlink`].
+    pub const NLINK: StatxMask = StatxMask(utils::STATX_NLINK);
+    /// The owner's user id, returned by [`Metadata::uid`].
+    pub const UID: StatxMask = StatxMask(utils::STATX_UID);
+    /// The owner's group id, returned by [`Metadata::gid`].
+    pub const GID: StatxMask = StatxMask(utils::STATX_GID);
+    /// The last access time, returned by [`Metadata::accessed`].
+    pub const ATIME: StatxMask = StatxMask(utils::STATX_ATIME);
+    /// The last modification time, returned by [`Metadata::modified`].
+    pub const MTIME: StatxMask = StatxMask(utils::STATX_MTIME);
+    /// The last status change time.
+    pub const CTIME: StatxMask = StatxMask(utils::STATX_CTIME);
+    /// The inode number, returned by [`Metadata::ino`].
+    pub const INO: StatxMask = StatxMask(utils::STATX_INO);
+    /// The file size, returned by [`Metadata::len`].
+    pub const SIZE: StatxMask = StatxMask(utils::STATX_SIZE);
+    /// The number of allocated blocks, returned by [`Metadata::blocks`].
+    pub const BLOCKS: StatxMask = StatxMask(utils::STATX_BLOCKS);
+    /// All the fields of `stat`.
+    pub const BASIC_STATS: StatxMask = StatxMask(utils::STATX_BASIC_STATS);
+    /// The creation time.
+    pub const BTIME: StatxMask = StatxMask(utils::STATX_BTIME);
+    /// All the fields, used by [`File::metadata`](crate::fs::File::metadata).
+    pub const ALL: StatxMask = StatxMask(utils::STATX_ALL);
+    /// The alignment required for direct I/O, returned by [`Metadata::dio_mem_align`]
+    /// and [`Metadata::dio_offset_align`]. It is not part of [`ALL`](Self::ALL),
+    /// and it requires Linux 6.1 or later.
+    pub const DIOALIGN: StatxMask = StatxMask(utils::STATX_DIOALIGN);
+
+    /// Returns the raw value of the mask.
+    #[must_use]
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns whether all the fields in `other` are requested.
+    #[must_use]
+    pub const fn contains(self, other: StatxMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for StatxMask {
+    type Output = StatxMask;
+    fn bitor(self, rhs: StatxMask) -> StatxMask {
+        StatxMask(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for StatxMask {
+    fn bitor_assign(&mut self, rhs: StatxMask) {
+        self.0 |= rhs.0;
+    }
+}
+
 /// Metadata information about a file.
 ///
 /// This structure is returned from the [`metadata`] function
@@ -284,6 +358,164 @@ impl Metadata {
     pub fn len(&self) -> usize {
         self.statx.stx_size as usize
     }
+
+    /// Returns the ID of the device containing the file.
+    ///
+    /// Together with [`ino`](Metadata::ino), it uniquely identifies a file
+    /// in the system.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs;
+    ///
+    /// let a = fs::metadata("foo.txt").await?;
+    /// let b = fs::metadata("bar.txt").await?;
+    /// let same_file = (a.dev(), a.ino()) == (b.dev(), b.ino());
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    #[must_use]
+    pub fn dev(&self) -> u64 {
+        libc::makedev(self.statx.stx_dev_major, self.statx.stx_dev_minor) as u64
+    }
+
+    /// Returns the inode number.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs;
+    ///
+    /// let metadata = fs::metadata("foo.txt").await?;
+    /// let inode = metadata.ino();
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    #[must_use]
+    pub fn ino(&self) -> u64 {
+        self.statx.stx_ino
+    }
+
+    /// Returns the number of hard links pointing to this file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs;
+    ///
+    /// let metadata = fs::metadata("foo.txt").await?;
+    /// let nb_hard_links = metadata.nlink();
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    #[must_use]
+    pub fn nlink(&self) -> u64 {
+        self.statx.stx_nlink.into()
+    }
+
+    /// Returns the user ID of the owner of this file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs;
+    ///
+    /// let metadata = fs::metadata("foo.txt").await?;
+    /// let user_id = metadata.uid();
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    #[must_use]
+    pub fn uid(&self) -> u32 {
+        self.statx.stx_uid
+    }
+
+    /// Returns the group ID of the owner of this file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs;
+    ///
+    /// let metadata = fs::metadata("foo.txt").await?;
+    /// let group_id = metadata.gid();
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    #[must_use]
+    pub fn gid(&self) -> u32 {
+        self.statx.stx_gid
+    }
+
+    /// Returns the block size for filesystem I/O.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs;
+    ///
+    /// let metadata = fs::metadata("foo.txt").await?;
+    /// let block_size = metadata.blksize();
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    #[must_use]
+    pub fn blksize(&self) -> u64 {
+        self.statx.stx_blksize.into()
+    }
+
+    /// Returns the number of blocks allocated to the file, in 512-byte units.
+    ///
+    /// Please note that this may be smaller than `len() / 512` if the file has holes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs;
+    ///
+    /// let metadata = fs::metadata("foo.txt").await?;
+    /// let blocks = metadata.blocks();
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    #[must_use]
+    pub fn blocks(&self) -> u64 {
+        self.statx.stx_blocks
+    }
+
+    /// Returns the alignment in bytes required for the memory of the buffers used
+    /// in direct I/O, that is, on files opened with `O_DIRECT`. Returns `None` if
+    /// [`StatxMask::DIOALIGN`] was not requested, if the kernel does not report it,
+    /// or if the file does not support direct I/O.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::{File, StatxMask};
+    ///
+    /// let f = File::open("foo.txt").await?;
+    /// let metadata = f.metadata_with(StatxMask::DIOALIGN).await?;
+    /// let align = metadata.dio_mem_align().unwrap_or(4096);
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    #[must_use]
+    pub fn dio_mem_align(&self) -> Option<u32> {
+        self.dio_align(self.statx.stx_dio_mem_align)
+    }
+
+    /// Returns the alignment in bytes required for the offsets and lengths of direct I/O.
+    /// Returns `None` under the same conditions as [`dio_mem_align`](Self::dio_mem_align).
+    #[must_use]
+    pub fn dio_offset_align(&self) -> Option<u32> {
+        self.dio_align(self.statx.stx_dio_offset_align)
+    }
+
+    fn dio_align(&self, align: u32) -> Option<u32> {
+        let reported = self.statx.stx_mask & utils::STATX_DIOALIGN != 0;
+        (reported && align != 0).then_some(align)
+    }
 }
 
 impl FileType {
@@ -386,6 +618,69 @@ impl FileType {
     pub fn is_fifo(&self) -> bool {
         (self.0 as mode_t & libc::S_IFIFO) == libc::S_IFIFO
     }
+
+    /// Returns `true` if this file type is a socket.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use osiris::fs;
+    /// use std::io;
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> io::Result<()> {
+    ///     let meta = fs::metadata("unix.socket").await?;
+    ///     let file_type = meta.file_type();
+    ///     assert!(file_type.is_socket());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn is_socket(&self) -> bool {
+        (self.0 as mode_t & S_IFMT) == libc::S_IFSOCK
+    }
+
+    /// Returns `true` if this file type is a block device.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use osiris::fs;
+    /// use std::io;
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> io::Result<()> {
+    ///     let meta = fs::metadata("/dev/sda").await?;
+    ///     let file_type = meta.file_type();
+    ///     assert!(file_type.is_block_device());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn is_block_device(&self) -> bool {
+        (self.0 as mode_t & S_IFMT) == libc::S_IFBLK
+    }
+
+    /// Returns `true` if this file type is a char device.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use osiris::fs;
+    /// use std::io;
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> io::Result<()> {
+    ///     let meta = fs::metadata("/dev/null").await?;
+    ///     let file_type = meta.file_type();
+    ///     assert!(file_type.is_char_device());
+    ///     Ok(())
+    /// }
+    /// ```
+    #[must_use]
+    pub fn is_char_device(&self) -> bool {
+        (self.0 as mode_t & S_IFMT) == libc::S_IFCHR
+    }
 }
 
 fn system_time(time: statx_timestamp) -> SystemTime {
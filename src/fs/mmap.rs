@@ -0,0 +1,162 @@
+use std::io::{self, Error, Result};
+use std::ops::{Deref, DerefMut};
+use std::ptr::null_mut;
+use std::slice;
+
+use crate::task::spawn_blocking;
+use crate::utils::syscall;
+
+use super::Advice;
+
+/// A read-only memory map of a file, created with [`File::memory_map`](super::File::memory_map).
+///
+/// The map dereferences to the mapped bytes, and it is unmapped when dropped. Reading
+/// from it bypasses the async I/O path: the pages are loaded from disk on first access,
+/// and these page faults block the thread, stalling every task of the runtime until
+/// they are served. Memory maps pay off for read-mostly data that is accessed in
+/// random order and tends to stay in the page cache, such as indexes. Consider
+/// calling [`advise`](Mmap::advise) with [`Advice::WillNeed`] to load the pages ahead.
+///
+/// The file must not be modified while it is mapped, including by other processes,
+/// see the safety section of [`File::memory_map`](super::File::memory_map).
+#[derive(Debug)]
+pub struct Mmap {
+    inner: MmapInner,
+}
+
+/// A writable memory map of a file, created with [`File::memory_map_mut`](super::File::memory_map_mut).
+///
+/// Writes to the map are carried to the file, and they are visible to other maps of
+/// the same file. They are written back by the kernel eventually, or when the map is
+/// [`flush`](MmapMut::flush)ed. Like [`Mmap`], page faults block the thread.
+#[derive(Debug)]
+pub struct MmapMut {
+    inner: MmapInner,
+}
+
+#[derive(Debug)]
+struct MmapInner {
+    /// The start of the mapping, which is page aligned.
+    ptr: *mut u8,
+    /// The distance from the start of the mapping to the requested offset.
+    align: usize,
+    len: usize,
+}
+
+// Safety: the map is owned memory, like a `Vec<u8>`.
+unsafe impl Send for MmapInner {}
+// Safety: the map is only mutated through `&mut` references.
+unsafe impl Sync for MmapInner {}
+
+impl MmapInner {
+    async fn new(fd: i32, offset: u64, len: usize, prot: i32) -> Result<MmapInner> {
+        if len == 0 {
+            let msg = "cannot memory map an empty region";
+            return Err(Error::new(io::ErrorKind::InvalidInput, msg));
+        }
+        // mmap requires the offset to be a multiple of the page size.
+        let page = syscall!(sysconf, libc::_SC_PAGESIZE)? as u64;
+        let align = (offset % page) as usize;
+        let overflow = || Error::new(io::ErrorKind::InvalidInput, "memory map is too large");
+        let offset = i64::try_from(offset - align as u64).map_err(|_| overflow())?;
+        let map_len = len.checked_add(align).ok_or_else(overflow)?;
+        let ptr = spawn_blocking(move || {
+            // Safety: the kernel picks the address, so no existing memory is replaced
+            let ptr =
+                unsafe { libc::mmap(null_mut(), map_len, prot, libc::MAP_SHARED, fd, offset) };
+            if ptr == libc::MAP_FAILED {
+                return Err(Error::last_os_error());
+            }
+            Ok(ptr as usize)
+        })
+        .await?;
+        Ok(MmapInner {
+            ptr: ptr as *mut u8,
+            align,
+            len,
+        })
+    }
+
+    fn as_ptr(&self) -> *mut u8 {
+        // Safety: the offset is within the mapping
+        unsafe { self.ptr.add(self.align) }
+    }
+
+    fn advise(&self, advice: Advice) -> Result<()> {
+        let advice = match advice {
+            Advice::Normal => libc::MADV_NORMAL,
+            Advice::Sequential => libc::MADV_SEQUENTIAL,
+            Advice::Random => libc::MADV_RANDOM,
+            Advice::WillNeed => libc::MADV_WILLNEED,
+            Advice::DontNeed => libc::MADV_DONTNEED,
+        };
+        let ptr = self.ptr.cast();
+        syscall!(madvise, ptr, self.len + self.align, advice)?;
+        Ok(())
+    }
+}
+
+impl Drop for MmapInner {
+    fn drop(&mut self) {
+        // Safety: the mapping is not referenced after the map is dropped.
+        unsafe { libc::munmap(self.ptr.cast(), self.len + self.align) };
+    }
+}
+
+impl Mmap {
+    pub(crate) async fn new(fd: i32, offset: u64, len: usize) -> Result<Mmap> {
+        let inner = MmapInner::new(fd, offset, len, libc::PROT_READ).await?;
+        Ok(Mmap { inner })
+    }
+
+    /// Declares the expected access pattern for the map with `madvise`, so the kernel
+    /// can tune its read ahead. The advice is only a hint.
+    pub fn advise(&self, advice: Advice) -> Result<()> {
+        self.inner.advise(advice)
+    }
+}
+
+impl MmapMut {
+    pub(crate) async fn new(fd: i32, offset: u64, len: usize) -> Result<MmapMut> {
+        let prot = libc::PROT_READ | libc::PROT_WRITE;
+        let inner = MmapInner::new(fd, offset, len, prot).await?;
+        Ok(MmapMut { inner })
+    }
+
+    /// Declares the expected access pattern for the map with `madvise`, so the kernel
+    /// can tune its read ahead. The advice is only a hint.
+    pub fn advise(&self, advice: Advice) -> Result<()> {
+        self.inner.advise(advice)
+    }
+
+    /// Writes the modified pages of the map back to the file, waiting for the writes
+    /// to complete.
+    pub async fn flush(&self) -> Result<()> {
+        let (ptr, len) = (self.inner.ptr as usize, self.inner.len + self.inner.align);
+        spawn_blocking(move || syscall!(msync, ptr as *mut _, len, libc::MS_SYNC)).await?;
+        Ok(())
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        // Safety: the mapping is readable and lives as long as the map
+        unsafe { slice::from_raw_parts(self.inner.as_ptr(), self.inner.len) }
+    }
+}
+
+impl Deref for MmapMut {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        // Safety: the mapping is readable and lives as long as the map
+        unsafe { slice::from_raw_parts(self.inner.as_ptr(), self.inner.len) }
+    }
+}
+
+impl DerefMut for MmapMut {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // Safety: the mapping is writable and lives as long as the map
+        unsafe { slice::from_raw_parts_mut(self.inner.as_ptr(), self.inner.len) }
+    }
+}
@@ -14,19 +14,26 @@ use std::io::Result;
 use std::os::unix::prelude::OsStringExt;
 use std::path::PathBuf;
 
-pub use dir::{create_dir, remove_dir};
-pub use file::{remove_file, File};
-pub use metadata::{metadata, symlink_metadata, FileType, Metadata};
+pub use dir::{create_dir, remove_dir, Dir};
+pub use file::{remove_file, Advice, File};
+pub use metadata::{metadata, symlink_metadata, FileType, Metadata, StatxMask};
+pub use mmap::{Mmap, MmapMut};
 pub use open_options::OpenOptions;
+#[cfg(target_os = "linux")]
+pub use open_options::ResolveFlags;
 pub use read::{read, read_to_string};
-pub use symlink::symlink;
+pub use stdio::{stderr, stdin, stdout, Stderr, Stdin, Stdout};
+pub use symlink::{canonicalize, read_link, symlink};
 
 mod dir;
 mod file;
 mod metadata;
+mod mmap;
 mod open_options;
 mod read;
+mod stdio;
 mod symlink;
+pub mod watch;
 
 pub(crate) fn cstr(path: impl Into<PathBuf>) -> Result<CString> {
     let path: PathBuf = path.into();
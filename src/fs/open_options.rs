@@ -3,6 +3,8 @@
 use crate::fs::File;
 use crate::reactor::op;
 use std::io::{self, Error, Result};
+#[cfg(target_os = "linux")]
+use std::ops::{BitOr, BitOrAssign};
 use std::path::Path;
 
 /// Options and flags which can be used to configure how a file is opened.
@@ -55,6 +57,70 @@ pub struct OpenOptions {
     create_new: bool,
     #[cfg(unix)]
     pub(crate) mode: libc::mode_t,
+    custom_flags: libc::c_int,
+    #[cfg(target_os = "linux")]
+    resolve: ResolveFlags,
+}
+
+/// Flags restricting how the path of a file is resolved when it is opened.
+///
+/// They are set with [`OpenOptions::resolve_flags`], and correspond to the
+/// `RESOLVE_*` flags of [`openat2(2)`](https://man7.org/linux/man-pages/man2/openat2.2.html).
+/// Since the restrictions are enforced by the kernel during path resolution, they are
+/// not subject to the races that affect checking a path in userspace before opening it.
+/// Flags can be combined with the `|` operator.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ResolveFlags(u64);
+
+#[cfg(target_os = "linux")]
+impl ResolveFlags {
+    /// Disallow traversal of mount points.
+    pub const NO_XDEV: ResolveFlags = ResolveFlags(libc::RESOLVE_NO_XDEV);
+    /// Disallow the resolution of magic links, such as those in `/proc/[pid]/fd`.
+    pub const NO_MAGICLINKS: ResolveFlags = ResolveFlags(libc::RESOLVE_NO_MAGICLINKS);
+    /// Disallow the resolution of symbolic links, including magic links.
+    pub const NO_SYMLINKS: ResolveFlags = ResolveFlags(libc::RESOLVE_NO_SYMLINKS);
+    /// Do not permit the path to escape the directory it is relative to, either
+    /// through `..` components, absolute paths, or symbolic links. For
+    /// [`OpenOptions::open`], this is the current working directory.
+    pub const BENEATH: ResolveFlags = ResolveFlags(libc::RESOLVE_BENEATH);
+    /// Treat the directory the path is relative to as the root directory, so
+    /// `..` components and absolute symbolic links are scoped to it, like `chroot(2)`.
+    pub const IN_ROOT: ResolveFlags = ResolveFlags(libc::RESOLVE_IN_ROOT);
+
+    /// Returns an empty set of flags, which places no restrictions on path resolution.
+    #[must_use]
+    pub const fn empty() -> ResolveFlags {
+        ResolveFlags(0)
+    }
+
+    /// Returns the raw value of the flags.
+    #[must_use]
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    /// Returns whether all the flags in `other` are set.
+    #[must_use]
+    pub const fn contains(self, other: ResolveFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl BitOr for ResolveFlags {
+    type Output = ResolveFlags;
+    fn bitor(self, rhs: ResolveFlags) -> ResolveFlags {
+        ResolveFlags(self.0 | rhs.0)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl BitOrAssign for ResolveFlags {
+    fn bitor_assign(&mut self, rhs: ResolveFlags) {
+        self.0 |= rhs.0;
+    }
 }
 
 impl OpenOptions {
@@ -84,6 +150,9 @@ impl OpenOptions {
             create_new: false,
             #[cfg(unix)]
             mode: 0o666,
+            custom_flags: 0,
+            #[cfg(target_os = "linux")]
+            resolve: ResolveFlags::empty(),
         }
     }
 
@@ -243,6 +312,88 @@ impl OpenOptions {
         self
     }
 
+    /// Sets the permissions a new file is created with, `0o666` by default, as
+    /// with [`std::fs::OpenOptions`].
+    ///
+    /// The permissions the file ends up with are `mode & !umask`, since the kernel
+    /// clears the bits set in the process umask, which is usually `0o022`. Files
+    /// created with the default mode are then readable by everyone, but writable only
+    /// by their owner. The mode has no effect on files that already exist.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::OpenOptions;
+    ///
+    /// // only the owner can read or write the file
+    /// let file = OpenOptions::new()
+    ///     .write(true)
+    ///     .create(true)
+    ///     .mode(0o600)
+    ///     .open("foo.txt")
+    ///     .await?;
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    #[cfg(unix)]
+    pub fn mode(&mut self, mode: u32) -> &mut OpenOptions {
+        self.mode = mode as libc::mode_t;
+        self
+    }
+
+    /// Passes custom flags to the `flags` argument of `open(2)`, such as `O_DIRECT`
+    /// or `O_NOATIME`. The bits that define the access mode are masked out, so they
+    /// must be set with [`read`](Self::read), [`write`](Self::write) and [`append`](Self::append).
+    ///
+    /// Files opened with `O_DIRECT` require the buffers, lengths and offsets of their
+    /// reads and writes to be aligned, otherwise they fail with `EINVAL`. The
+    /// [`AlignedVec`](crate::buf::AlignedVec) buffer can be used to satisfy these requirements.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::OpenOptions;
+    ///
+    /// let file = OpenOptions::new()
+    ///     .read(true)
+    ///     .custom_flags(libc::O_DIRECT)
+    ///     .open("foo.txt")
+    ///     .await?;
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    pub fn custom_flags(&mut self, flags: libc::c_int) -> &mut OpenOptions {
+        self.custom_flags = flags;
+        self
+    }
+
+    /// Restricts how the path is resolved when the file is opened.
+    ///
+    /// When any flag is set, the file is opened with `openat2(2)`, which requires
+    /// Linux 5.6 or later. Paths are resolved relative to the current working
+    /// directory. For example, [`ResolveFlags::BENEATH`] can be used to serve
+    /// files from a directory without risking path traversal attacks, since
+    /// paths escaping the directory fail with an `EXDEV` error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # osiris::block_on(async {
+    /// use osiris::fs::{OpenOptions, ResolveFlags};
+    ///
+    /// let file = OpenOptions::new()
+    ///     .read(true)
+    ///     .resolve_flags(ResolveFlags::BENEATH | ResolveFlags::NO_SYMLINKS)
+    ///     .open("static/index.html")
+    ///     .await?;
+    /// # std::io::Result::Ok(()) }).unwrap();
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn resolve_flags(&mut self, flags: ResolveFlags) -> &mut OpenOptions {
+        self.resolve = flags;
+        self
+    }
+
     /// Opens a file at `path` with the options specified by `self`.
     ///
     /// # Errors
@@ -285,14 +436,29 @@ impl OpenOptions {
     /// [`Other`]: io::ErrorKind::Other
     /// [`PermissionDenied`]: io::ErrorKind::PermissionDenied
     pub async fn open(&self, path: impl AsRef<Path>) -> Result<File> {
-        self._open(path.as_ref()).await
+        self.open_at(libc::AT_FDCWD, path.as_ref()).await
     }
 
-    async fn _open(&self, path: &Path) -> Result<File> {
+    /// Opens a file at `path` relative to the directory `dirfd`.
+    pub(crate) async fn open_at(&self, dirfd: i32, path: &Path) -> Result<File> {
         use crate::fs::cstr;
         let path = cstr(path)?;
-        let flags = libc::O_CLOEXEC | self.access_mode()? | self.creation_mode()?;
-        let fd = op::open_at(path, flags, self.mode).await?;
+        let flags = libc::O_CLOEXEC
+            | self.access_mode()?
+            | self.creation_mode()?
+            | (self.custom_flags & !libc::O_ACCMODE);
+        #[cfg(target_os = "linux")]
+        if self.resolve != ResolveFlags::empty() {
+            // openat2 rejects a mode unless a file may be created
+            let mode = if flags & libc::O_CREAT != 0 {
+                self.mode
+            } else {
+                0
+            };
+            let fd = op::openat2(dirfd, path, flags, mode, self.resolve.bits()).await?;
+            return Ok(File { fd });
+        }
+        let fd = op::open_at(dirfd, path, flags, self.mode).await?;
         Ok(File { fd })
     }
 
@@ -0,0 +1,175 @@
+use std::fmt::{self, Debug};
+use std::io::{Error, ErrorKind, Result};
+use std::os::fd::{AsRawFd, RawFd};
+
+use crate::buf::{IoBuf, IoBufMut};
+use crate::reactor::op;
+
+/// A handle to the standard input stream of a process.
+///
+/// Created by the [`stdin`] function. Dropping the handle does not
+/// close the standard input of the process.
+pub struct Stdin {
+    fd: RawFd,
+}
+
+/// A handle to the standard output stream of a process.
+///
+/// Created by the [`stdout`] function. Dropping the handle does not
+/// close the standard output of the process.
+///
+/// Note that writes are not buffered, and that they are not synchronized with
+/// the [`println!`] macro, which writes to a buffer of its own.
+pub struct Stdout {
+    fd: RawFd,
+}
+
+/// A handle to the standard error stream of a process.
+///
+/// Created by the [`stderr`] function. Dropping the handle does not
+/// close the standard error of the process.
+pub struct Stderr {
+    fd: RawFd,
+}
+
+/// Constructs a new handle to the standard input of the current process.
+///
+/// # Examples
+/// ```no_run
+/// # osiris::block_on(async {
+/// use osiris::fs::stdin;
+///
+/// let (n, buf) = stdin().read(vec![0; 1024]).await;
+/// println!("read {} bytes", n?);
+/// # std::io::Result::Ok(()) }).unwrap();
+/// ```
+pub fn stdin() -> Stdin {
+    Stdin {
+        fd: libc::STDIN_FILENO,
+    }
+}
+
+/// Constructs a new handle to the standard output of the current process.
+///
+/// # Examples
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::fs::stdout;
+///
+/// stdout().write_all("hello world\n").await.0?;
+/// # std::io::Result::Ok(()) }).unwrap();
+/// ```
+pub fn stdout() -> Stdout {
+    Stdout {
+        fd: libc::STDOUT_FILENO,
+    }
+}
+
+/// Constructs a new handle to the standard error of the current process.
+///
+/// # Examples
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::fs::stderr;
+///
+/// stderr().write_all("something went wrong\n").await.0?;
+/// # std::io::Result::Ok(()) }).unwrap();
+/// ```
+pub fn stderr() -> Stderr {
+    Stderr {
+        fd: libc::STDERR_FILENO,
+    }
+}
+
+impl Stdin {
+    /// Read some data from the standard input into the buffer, returning the original
+    /// buffer and quantity of data read. A return value of 0 bytes read means the
+    /// end of the input was reached.
+    pub async fn read<B: IoBufMut>(&mut self, buf: B) -> (Result<usize>, B) {
+        op::read_blocking_fd(self.fd, buf, -1).await
+    }
+}
+
+macro_rules! impl_write {
+    ($name: ident, $stream: literal) => {
+        impl $name {
+            #[doc = concat!("Write some data to the standard ", $stream, " from the buffer, returning")]
+            /// the original buffer and quantity of data written.
+            pub async fn write<B: IoBuf>(&mut self, buf: B) -> (Result<usize>, B) {
+                op::write_blocking_fd(self.fd, buf, -1).await
+            }
+
+            #[doc = concat!("Attempts to write an entire buffer to the standard ", $stream, ".")]
+            ///
+            /// This method will continuously call [`write`] until there is no more data to be
+            /// written or an error is returned. This method will not return until the entire
+            /// buffer has been successfully written or an error has occurred.
+            ///
+            /// # Errors
+            ///
+            /// This function will return the first error that [`write`] returns.
+            ///
+            /// [`write`]: Self::write
+            pub async fn write_all<B: IoBuf>(&mut self, mut buf: B) -> (Result<()>, B) {
+                let mut n = 0;
+                while n < buf.bytes_init() {
+                    let (written, buf_) = self.write(buf.slice(n..)).await;
+                    buf = buf_.into_inner();
+                    match written {
+                        Ok(0) => {
+                            let err =
+                                Error::new(ErrorKind::WriteZero, "failed to write whole buffer");
+                            return (Err(err), buf);
+                        }
+                        Ok(written) => n += written,
+                        Err(err) => return (Err(err), buf),
+                    }
+                }
+                (Ok(()), buf)
+            }
+        }
+    };
+}
+
+impl_write!(Stdout, "output");
+impl_write!(Stderr, "error");
+
+macro_rules! impl_fd {
+    ($name: ident) => {
+        impl AsRawFd for $name {
+            fn as_raw_fd(&self) -> RawFd {
+                self.fd
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("fd", &self.fd)
+                    .finish()
+            }
+        }
+    };
+}
+
+impl_fd!(Stdin);
+impl_fd!(Stdout);
+impl_fd!(Stderr);
+
+#[test]
+fn stdio_is_not_closed_on_drop() {
+    crate::block_on(async {
+        let mut out = stdout();
+        out.write_all(b"hello from stdout\n".to_vec())
+            .await
+            .0
+            .unwrap();
+        drop(out);
+        drop(stdin());
+        stderr().write_all("hello from stderr\n").await.0.unwrap();
+        for fd in 0..3 {
+            assert!(crate::utils::syscall!(fcntl, fd, libc::F_GETFD).is_ok());
+        }
+    })
+    .unwrap();
+}
@@ -1,7 +1,11 @@
+use std::ffi::OsString;
 use std::io::Result;
-use std::path::PathBuf;
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
 
 use crate::reactor::op;
+use crate::task::spawn_blocking;
+use crate::utils::syscall;
 
 use super::cstr;
 
@@ -10,3 +14,73 @@ pub async fn symlink(original: impl Into<PathBuf>, link: impl Into<PathBuf>) ->
     let link = cstr(link)?;
     op::symlink(original, link).await
 }
+
+/// Reads a symbolic link, returning the file that the link points to.
+///
+/// # Errors
+///
+/// This function will return an error in the following situations, but is not
+/// limited to just these cases:
+///
+/// * `path` is not a symbolic link.
+/// * `path` does not exist.
+///
+/// # Examples
+///
+/// ```no_run
+/// # osiris::block_on(async {
+/// let path = osiris::fs::read_link("a.txt").await?;
+/// # std::io::Result::Ok(()) }).unwrap();
+/// ```
+pub async fn read_link<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = cstr(path.as_ref())?;
+    // io-uring doesn't support readlinkat, so we do it in the thread pool
+    spawn_blocking(move || {
+        let mut buf = Vec::<u8>::with_capacity(libc::PATH_MAX as usize);
+        loop {
+            let ptr = buf.as_mut_ptr().cast();
+            let len = syscall!(
+                readlinkat,
+                libc::AT_FDCWD,
+                path.as_ptr(),
+                ptr,
+                buf.capacity()
+            )?;
+            let len = len as usize;
+            // the link may have been truncated, so we retry with a bigger buffer
+            if len == buf.capacity() {
+                buf.reserve(buf.capacity());
+                continue;
+            }
+            // Safety: initialized by readlinkat
+            unsafe { buf.set_len(len) };
+            return Ok(PathBuf::from(OsString::from_vec(buf)));
+        }
+    })
+    .await
+}
+
+/// Returns the canonical, absolute form of a path with all intermediate
+/// components normalized and symbolic links resolved.
+///
+/// # Errors
+///
+/// This function will return an error in the following situations, but is not
+/// limited to just these cases:
+///
+/// * `path` does not exist.
+/// * A non-final component in path is not a directory.
+/// * Too many symbolic links were encountered while resolving the path.
+///
+/// # Examples
+///
+/// ```no_run
+/// # osiris::block_on(async {
+/// let path = osiris::fs::canonicalize("../a/../foo.txt").await?;
+/// # std::io::Result::Ok(()) }).unwrap();
+/// ```
+pub async fn canonicalize<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = path.as_ref().to_owned();
+    // realpath performs multiple blocking system calls, so we run it in the thread pool
+    spawn_blocking(move || std::fs::canonicalize(path)).await
+}
@@ -0,0 +1,419 @@
+//! Filesystem change notifications.
+//!
+//! A [`Watcher`] reports the files created, modified and deleted under the paths
+//! it watches. On Linux it is backed by
+//! [`inotify(7)`](https://man7.org/linux/man-pages/man7/inotify.7.html), and on
+//! BSD systems by the `EVFILT_VNODE` filter of `kqueue(2)`. In both cases, the
+//! notification file descriptor is polled through the reactor with a [`PollFd`],
+//! so waiting for events doesn't block the runtime.
+//!
+//! # Platform differences
+//! inotify reports the events of the entries of a watched directory, so the
+//! [`Event::path`] of an event is the path of the entry that changed. kqueue
+//! only reports events on the watched path itself, so changes to the entries of
+//! a watched directory are reported as a [`EventKind::Modify`] of the directory.
+//!
+//! # Examples
+//! ```no_run
+//! use osiris::fs::watch::{WatchMask, Watcher};
+//!
+//! #[osiris::main]
+//! async fn main() -> std::io::Result<()> {
+//!     let mut events = Watcher::new()?.watch("/tmp", WatchMask::ALL)?;
+//!     loop {
+//!         let event = events.next().await?;
+//!         println!("{:?}: {}", event.kind, event.path.display());
+//!     }
+//! }
+//! ```
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+use std::io::{ErrorKind, Result};
+use std::ops::{BitOr, BitOrAssign};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::path::{Path, PathBuf};
+
+use crate::io::PollFd;
+use crate::utils::syscall;
+
+/// The kind of changes a [`Watcher`] is notified of.
+///
+/// Masks can be combined with the `|` operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchMask(u32);
+
+#[cfg(target_os = "linux")]
+impl WatchMask {
+    /// An entry was created in a watched directory, or moved into it.
+    pub const CREATE: WatchMask = WatchMask(libc::IN_CREATE | libc::IN_MOVED_TO);
+    /// A watched file, or an entry in a watched directory, was written to.
+    pub const MODIFY: WatchMask = WatchMask(libc::IN_MODIFY);
+    /// A watched path, or an entry in a watched directory, was deleted or moved away.
+    pub const DELETE: WatchMask = WatchMask(
+        libc::IN_DELETE | libc::IN_MOVED_FROM | libc::IN_DELETE_SELF | libc::IN_MOVE_SELF,
+    );
+}
+
+#[cfg(kqueue)]
+impl WatchMask {
+    /// The link count of a watched path changed, which happens when a subdirectory
+    /// is created in a watched directory.
+    pub const CREATE: WatchMask = WatchMask(libc::NOTE_LINK);
+    /// A watched path was written to. For a directory, this means one of its entries
+    /// was created, deleted or renamed.
+    pub const MODIFY: WatchMask = WatchMask(libc::NOTE_WRITE | libc::NOTE_EXTEND);
+    /// A watched path was deleted or renamed.
+    pub const DELETE: WatchMask = WatchMask(libc::NOTE_DELETE | libc::NOTE_RENAME);
+}
+
+impl WatchMask {
+    /// All the kinds of changes.
+    pub const ALL: WatchMask = WatchMask(Self::CREATE.0 | Self::MODIFY.0 | Self::DELETE.0);
+
+    /// Returns the raw value of the mask.
+    #[must_use]
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns whether all the kinds of changes in `other` are set.
+    #[must_use]
+    pub const fn contains(self, other: WatchMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for WatchMask {
+    type Output = WatchMask;
+    fn bitor(self, rhs: WatchMask) -> WatchMask {
+        WatchMask(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for WatchMask {
+    fn bitor_assign(&mut self, rhs: WatchMask) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// The kind of change reported by an [`Event`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EventKind {
+    /// The path was created. See [`WatchMask::CREATE`].
+    Create,
+    /// The path was modified. See [`WatchMask::MODIFY`].
+    Modify,
+    /// The path was deleted. See [`WatchMask::DELETE`].
+    Delete,
+}
+
+/// A change to a watched path, returned by [`WatchStream::next`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    /// The path that changed. It is the watched path joined with the name of
+    /// the entry that changed when the event refers to an entry of a directory.
+    pub path: PathBuf,
+    /// The kind of change.
+    pub kind: EventKind,
+}
+
+/// A set of watched paths, backed by an inotify or kqueue file descriptor.
+///
+/// A `Watcher` is turned into a [`WatchStream`] with [`Watcher::watch`],
+/// which is used to receive the events.
+pub struct Watcher {
+    fd: OwnedFd,
+    /// the watched paths by watch descriptor, or by file descriptor on kqueue
+    #[cfg(target_os = "linux")]
+    paths: HashMap<i32, PathBuf>,
+    #[cfg(kqueue)]
+    paths: HashMap<RawFd, (OwnedFd, PathBuf)>,
+    /// the buffer the packed `inotify_event` records are read into
+    #[cfg(target_os = "linux")]
+    buf: Vec<u8>,
+}
+
+/// The stream of events of a [`Watcher`].
+pub struct WatchStream {
+    watcher: Watcher,
+    events: VecDeque<Event>,
+}
+
+impl Watcher {
+    /// Creates a new watcher, without any watched paths.
+    ///
+    /// # Errors
+    /// This function errors if the inotify or kqueue instance cannot be created,
+    /// for example because the per-user limit of instances was reached.
+    #[cfg(target_os = "linux")]
+    pub fn new() -> Result<Watcher> {
+        let fd = syscall!(inotify_init1, libc::IN_NONBLOCK | libc::IN_CLOEXEC)?;
+        Ok(Watcher {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+            paths: HashMap::new(),
+            buf: Vec::with_capacity(4096),
+        })
+    }
+
+    /// Creates a new watcher, without any watched paths.
+    ///
+    /// # Errors
+    /// This function errors if the inotify or kqueue instance cannot be created,
+    /// for example because the per-user limit of instances was reached.
+    #[cfg(kqueue)]
+    pub fn new() -> Result<Watcher> {
+        let fd = syscall!(kqueue,)?;
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        syscall!(fcntl, fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC)?;
+        Ok(Watcher {
+            fd,
+            paths: HashMap::new(),
+        })
+    }
+
+    /// Starts watching `path` for the changes in `mask`, and returns the stream of events.
+    /// More paths can be watched with [`WatchStream::watch`].
+    ///
+    /// # Errors
+    /// This function errors if `path` does not exist, if it cannot be accessed,
+    /// or if the limit of watched paths was reached.
+    pub fn watch(mut self, path: impl AsRef<Path>, mask: WatchMask) -> Result<WatchStream> {
+        self.add(path.as_ref(), mask)?;
+        Ok(WatchStream {
+            watcher: self,
+            events: VecDeque::new(),
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    fn add(&mut self, path: &Path, mask: WatchMask) -> Result<()> {
+        let cpath = super::cstr(path)?;
+        let wd = syscall!(
+            inotify_add_watch,
+            self.fd.as_raw_fd(),
+            cpath.as_ptr(),
+            mask.0
+        )?;
+        self.paths.insert(wd, path.to_path_buf());
+        Ok(())
+    }
+
+    #[cfg(kqueue)]
+    fn add(&mut self, path: &Path, mask: WatchMask) -> Result<()> {
+        let cpath = super::cstr(path)?;
+        let fd = syscall!(open, cpath.as_ptr(), libc::O_RDONLY | libc::O_CLOEXEC)?;
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let mut event: libc::kevent = unsafe { std::mem::zeroed() };
+        event.ident = fd.as_raw_fd() as _;
+        event.filter = libc::EVFILT_VNODE as _;
+        event.flags = (libc::EV_ADD | libc::EV_CLEAR) as _;
+        event.fflags = mask.0 as _;
+        let timeout = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        let kq = self.fd.as_raw_fd();
+        syscall!(kevent, kq, &event, 1, std::ptr::null_mut(), 0, &timeout)?;
+        self.paths.insert(fd.as_raw_fd(), (fd, path.to_path_buf()));
+        Ok(())
+    }
+
+    /// Reads the pending events without blocking, and appends them to `events`.
+    #[cfg(target_os = "linux")]
+    fn read(&mut self, events: &mut VecDeque<Event>) -> Result<()> {
+        let fd = self.fd.as_raw_fd();
+        let buf = &mut self.buf;
+        let len = syscall!(read, fd, buf.as_mut_ptr().cast(), buf.capacity())?;
+        unsafe { buf.set_len(len as usize) };
+        let header = std::mem::size_of::<libc::inotify_event>();
+        let mut offset = 0;
+        while offset + header <= buf.len() {
+            // the records are packed, so they may be misaligned.
+            let event: libc::inotify_event =
+                unsafe { std::ptr::read_unaligned(buf[offset..].as_ptr().cast()) };
+            let name = &buf[offset + header..offset + header + event.len as usize];
+            offset += header + event.len as usize;
+
+            if event.mask & libc::IN_Q_OVERFLOW != 0 {
+                buf.clear();
+                return Err(std::io::Error::other(
+                    "the inotify event queue overflowed, some events were lost",
+                ));
+            }
+            if event.mask & libc::IN_IGNORED != 0 {
+                // the watch was removed, because the path was deleted or unmounted.
+                self.paths.remove(&event.wd);
+                continue;
+            }
+            let Some(path) = self.paths.get(&event.wd) else {
+                continue;
+            };
+            let Some(kind) = inotify_kind(event.mask) else {
+                continue;
+            };
+            // the name is padded with null bytes.
+            let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+            let path = if end == 0 {
+                path.clone()
+            } else {
+                use std::os::unix::ffi::OsStrExt;
+                path.join(std::ffi::OsStr::from_bytes(&name[..end]))
+            };
+            events.push_back(Event { path, kind });
+        }
+        buf.clear();
+        Ok(())
+    }
+
+    /// Reads the pending events without blocking, and appends them to `events`.
+    #[cfg(kqueue)]
+    fn read(&mut self, events: &mut VecDeque<Event>) -> Result<()> {
+        const LEN: usize = 32;
+        let mut ready: [libc::kevent; LEN] = unsafe { std::mem::zeroed() };
+        let timeout = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        let kq = self.fd.as_raw_fd();
+        let n = syscall!(
+            kevent,
+            kq,
+            std::ptr::null(),
+            0,
+            ready.as_mut_ptr(),
+            LEN as _,
+            &timeout
+        )?;
+        if n == 0 {
+            return Err(ErrorKind::WouldBlock.into());
+        }
+        for event in &ready[..n as usize] {
+            let fd = event.ident as RawFd;
+            let Some((_, path)) = self.paths.get(&fd) else {
+                continue;
+            };
+            let fflags = event.fflags as u32;
+            for kind in [EventKind::Create, EventKind::Modify, EventKind::Delete] {
+                if fflags & kind_mask(kind).0 != 0 {
+                    events.push_back(Event {
+                        path: path.clone(),
+                        kind,
+                    });
+                }
+            }
+            if fflags & WatchMask::DELETE.0 != 0 {
+                // the path no longer refers to the file, so there is nothing left to watch.
+                self.paths.remove(&fd);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn inotify_kind(mask: u32) -> Option<EventKind> {
+    [EventKind::Create, EventKind::Modify, EventKind::Delete]
+        .into_iter()
+        .find(|&kind| mask & kind_mask(kind).0 != 0)
+}
+
+fn kind_mask(kind: EventKind) -> WatchMask {
+    match kind {
+        EventKind::Create => WatchMask::CREATE,
+        EventKind::Modify => WatchMask::MODIFY,
+        EventKind::Delete => WatchMask::DELETE,
+    }
+}
+
+impl WatchStream {
+    /// Starts watching another path for the changes in `mask`.
+    ///
+    /// # Errors
+    /// This function errors if `path` does not exist, if it cannot be accessed,
+    /// or if the limit of watched paths was reached.
+    pub fn watch(&mut self, path: impl AsRef<Path>, mask: WatchMask) -> Result<()> {
+        self.watcher.add(path.as_ref(), mask)
+    }
+
+    /// Waits for the next event.
+    ///
+    /// # Errors
+    /// This function errors if the notification file descriptor cannot be polled or read.
+    /// On Linux, it also errors if the kernel's event queue overflowed, in which case
+    /// some events were lost, but the stream can still be used to receive newer events.
+    pub async fn next(&mut self) -> Result<Event> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(event);
+            }
+            let fd = PollFd::new(self.watcher.fd.as_raw_fd());
+            fd.readable().await?;
+            match self.watcher.read(&mut self.events) {
+                Err(err) if err.kind() == ErrorKind::WouldBlock => continue,
+                res => res?,
+            }
+        }
+    }
+}
+
+impl AsRawFd for Watcher {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl AsRawFd for WatchStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.watcher.as_raw_fd()
+    }
+}
+
+impl Debug for Watcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watcher").field("fd", &self.fd).finish()
+    }
+}
+
+impl Debug for WatchStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WatchStream")
+            .field("fd", &self.watcher.fd)
+            .finish()
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn watch_dir() {
+    crate::block_on(async {
+        let dir = std::env::temp_dir().join(format!("osiris-watch-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).unwrap();
+        let mut events = Watcher::new()
+            .unwrap()
+            .watch(&dir, WatchMask::CREATE | WatchMask::DELETE)
+            .unwrap();
+        let file = dir.join("file");
+        crate::fs::File::create(&file).await.unwrap();
+        crate::fs::remove_file(&file).await.unwrap();
+        let event = events.next().await.unwrap();
+        assert_eq!(event.kind, EventKind::Create);
+        assert_eq!(event.path, file);
+        let event = events.next().await.unwrap();
+        assert_eq!(event.kind, EventKind::Delete);
+        assert_eq!(event.path, file);
+
+        events.watch(&file, WatchMask::ALL).unwrap_err();
+        std::fs::remove_dir(&dir).unwrap();
+        let event = events.next().await.unwrap();
+        assert_eq!(
+            event,
+            Event {
+                path: dir.clone(),
+                kind: EventKind::Delete
+            }
+        );
+    })
+    .unwrap();
+}
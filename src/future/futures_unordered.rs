@@ -0,0 +1,164 @@
+use std::fmt::{self, Debug};
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use slab::Slab;
+
+use super::ready::{indices, ReadySet};
+
+/// A set of futures which may complete in any order.
+///
+/// The futures are driven concurrently on the current task, and their outputs are
+/// yielded by [`next`](FuturesUnordered::next) as they complete. Futures can be pushed
+/// at any moment, including while other futures are still running. Unlike
+/// [`spawn`](crate::spawn), the futures are not required to be `'static`.
+///
+/// Only the futures that were woken are polled again, so the cost of a wakeup does not
+/// grow with the number of futures in the set.
+///
+/// # Examples
+///
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::future::FuturesUnordered;
+/// use osiris::time::{sleep, Duration};
+///
+/// let mut futures = FuturesUnordered::new();
+/// for ms in [30, 10, 20] {
+///     futures.push(async move {
+///         sleep(Duration::from_millis(ms)).await;
+///         ms
+///     });
+/// }
+///
+/// let mut outputs = vec![];
+/// while let Some(ms) = futures.next().await {
+///     outputs.push(ms);
+/// }
+/// assert_eq!(outputs, [10, 20, 30]);
+/// # }).unwrap();
+/// ```
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct FuturesUnordered<F> {
+    futures: Slab<Pin<Box<F>>>,
+    ready: ReadySet,
+}
+
+impl<F: Future> FuturesUnordered<F> {
+    /// Constructs a new, empty `FuturesUnordered`.
+    pub fn new() -> FuturesUnordered<F> {
+        FuturesUnordered {
+            futures: Slab::new(),
+            ready: ReadySet::new(),
+        }
+    }
+
+    /// Pushes a future into the set. The future will be first polled on the
+    /// next call to [`next`](FuturesUnordered::next).
+    pub fn push(&mut self, future: F) {
+        let index = self.futures.insert(Box::pin(future));
+        self.ready.insert(index);
+    }
+
+    /// Returns the number of futures in the set.
+    pub fn len(&self) -> usize {
+        self.futures.len()
+    }
+
+    /// Returns `true` if the set contains no futures.
+    pub fn is_empty(&self) -> bool {
+        self.futures.is_empty()
+    }
+
+    /// Waits for any of the futures in the set to complete, and returns its output.
+    /// Returns `None` if the set is empty.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. If it is dropped before completing, no output
+    /// is lost, and the futures remain in the set.
+    pub async fn next(&mut self) -> Option<F::Output> {
+        poll_fn(|cx| self.poll_next(cx)).await
+    }
+
+    /// Polls the futures that were woken, returning the output of the first one that
+    /// completes, or `Poll::Ready(None)` if the set is empty.
+    pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<F::Output>> {
+        if self.futures.is_empty() {
+            return Poll::Ready(None);
+        }
+        self.ready.register(cx.waker());
+        for page in 0..self.ready.pages() {
+            let bits = self.ready.take(page);
+            let mut woken = indices(page, bits);
+            while let Some(index) = woken.next() {
+                let Some(future) = self.futures.get_mut(index) else {
+                    // stale wakeup of a completed future
+                    continue;
+                };
+                let cx = &mut Context::from_waker(self.ready.waker(index));
+                if let Poll::Ready(output) = future.as_mut().poll(cx) {
+                    self.futures.remove(index);
+                    // the remaining futures of this page were
+                    // woken but not polled yet.
+                    let rest = woken.fold(0, |bits, index| bits | 1 << (index % 64));
+                    self.ready.restore(page, rest);
+                    return Poll::Ready(Some(output));
+                }
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl<F: Future> Default for FuturesUnordered<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Future> FromIterator<F> for FuturesUnordered<F> {
+    fn from_iter<I: IntoIterator<Item = F>>(iter: I) -> Self {
+        let mut futures = FuturesUnordered::new();
+        iter.into_iter().for_each(|future| futures.push(future));
+        futures
+    }
+}
+
+impl<F> Debug for FuturesUnordered<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FuturesUnordered")
+            .field("len", &self.futures.len())
+            .finish()
+    }
+}
+
+#[test]
+fn only_woken_futures_are_polled() {
+    use crate::task::yield_now;
+    use std::cell::Cell;
+
+    crate::block_on(async {
+        let polls = Cell::new(0);
+        let mut futures: FuturesUnordered<_> = (0..200)
+            .map(|i| {
+                let polls = &polls;
+                async move {
+                    polls.set(polls.get() + 1);
+                    if i == 150 {
+                        yield_now().await;
+                        polls.set(polls.get() + 1);
+                    } else {
+                        std::future::pending::<()>().await;
+                    }
+                    i
+                }
+            })
+            .collect();
+        assert_eq!(futures.next().await, Some(150));
+        assert_eq!(polls.get(), 201);
+        assert_eq!(futures.len(), 199);
+    })
+    .unwrap();
+}
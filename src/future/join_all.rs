@@ -0,0 +1,129 @@
+use std::fmt::{self, Debug};
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use super::ready::{indices, ReadySet};
+use super::{maybe_done, MaybeDone};
+
+/// Future for the [`join_all`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct JoinAll<F: Future> {
+    // the slots are never moved once the future is created,
+    // since the boxed slice is never reallocated.
+    slots: Box<[MaybeDone<F>]>,
+    ready: ReadySet,
+    pending: usize,
+}
+
+/// Creates a future which represents a collection of the outputs of the futures given.
+///
+/// The returned future will drive execution for all of its underlying futures
+/// concurrently on the current task, collecting the results into a destination
+/// `Vec<T>` in the same order as they were provided. Unlike [`spawn`](crate::spawn),
+/// the futures are not required to be `'static`, and unlike [`join!`](crate::join!),
+/// the number of futures does not need to be known at compile time.
+///
+/// Like `join!`, only the futures that were woken are polled again, so the cost of
+/// a wakeup does not grow with the number of futures.
+///
+/// # Examples
+///
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::future::join_all;
+///
+/// async fn double(n: u32) -> u32 {
+///     n * 2
+/// }
+///
+/// let numbers = vec![1, 2, 3];
+/// let doubled = join_all(numbers.iter().map(|&n| double(n))).await;
+/// assert_eq!(doubled, [2, 4, 6]);
+/// # }).unwrap();
+/// ```
+pub fn join_all<I>(iter: I) -> JoinAll<<I::Item as IntoFuture>::IntoFuture>
+where
+    I: IntoIterator,
+    I::Item: IntoFuture,
+{
+    let slots: Box<[_]> = iter
+        .into_iter()
+        .map(|fut| maybe_done(fut.into_future()))
+        .collect();
+    let mut ready = ReadySet::new();
+    for index in 0..slots.len() {
+        ready.insert(index);
+    }
+    JoinAll {
+        pending: slots.len(),
+        slots,
+        ready,
+    }
+}
+
+impl<F: Future> Future for JoinAll<F> {
+    type Output = Vec<F::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let JoinAll {
+            slots,
+            ready,
+            pending,
+        } = self.get_mut();
+        ready.register(cx.waker());
+        for page in 0..ready.pages() {
+            for index in indices(page, ready.take(page)) {
+                let slot = &mut slots[index];
+                if !matches!(slot, MaybeDone::Future(_)) {
+                    continue;
+                }
+                // Safety: the slot is never moved, see the `slots` field.
+                let slot = unsafe { Pin::new_unchecked(slot) };
+                let cx = &mut Context::from_waker(ready.waker(index));
+                if slot.poll(cx).is_ready() {
+                    *pending -= 1;
+                }
+            }
+        }
+        if *pending != 0 {
+            return Poll::Pending;
+        }
+        let outputs = slots.iter_mut().map(|slot| {
+            // Safety: the slot is never moved, see the `slots` field.
+            let slot = unsafe { Pin::new_unchecked(slot) };
+            slot.take_output()
+                .expect("`JoinAll` polled after completion")
+        });
+        Poll::Ready(outputs.collect())
+    }
+}
+
+impl<F: Future> Debug for JoinAll<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinAll")
+            .field("len", &self.slots.len())
+            .field("pending", &self.pending)
+            .finish()
+    }
+}
+
+#[test]
+fn join_all_keeps_order() {
+    use crate::time::sleep;
+    use std::time::Duration;
+
+    crate::block_on(async {
+        let offset = 10;
+        let outputs = join_all((0..150u64).map(|i| async move {
+            sleep(Duration::from_millis(i % 7)).await;
+            i + offset
+        }))
+        .await;
+        assert_eq!(outputs, (10..160).collect::<Vec<_>>());
+        assert!(join_all(Vec::<std::future::Ready<()>>::new())
+            .await
+            .is_empty());
+    })
+    .unwrap();
+}
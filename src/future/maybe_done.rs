@@ -0,0 +1,88 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A future that may have completed, keeping its output until it is taken.
+///
+/// Created with the [`maybe_done`] function. Polling a `MaybeDone` polls the inner
+/// future, and once it completes, its output is stored so it can be retrieved later
+/// with [`take_output`](MaybeDone::take_output). This is the building block of
+/// combinators that await several futures, like [`join_all`](super::join_all).
+#[derive(Debug)]
+pub enum MaybeDone<F: Future> {
+    /// A not-yet-completed future.
+    Future(F),
+    /// The output of the completed future.
+    Done(F::Output),
+    /// The empty variant after the output has been taken
+    /// with [`take_output`](MaybeDone::take_output).
+    Gone,
+}
+
+/// Wraps a future into a [`MaybeDone`].
+///
+/// # Examples
+///
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::future::{maybe_done, pin};
+///
+/// let mut future = pin!(maybe_done(async { 5 }));
+/// future.as_mut().await;
+/// assert_eq!(future.as_mut().take_output(), Some(5));
+/// assert_eq!(future.as_mut().take_output(), None);
+/// # }).unwrap();
+/// ```
+pub fn maybe_done<F: Future>(future: F) -> MaybeDone<F> {
+    MaybeDone::Future(future)
+}
+
+impl<F: Future> MaybeDone<F> {
+    /// Returns whether the inner future has completed, and
+    /// its output has not been taken yet.
+    pub fn is_done(&self) -> bool {
+        matches!(self, MaybeDone::Done(_))
+    }
+
+    /// Returns a mutable reference to the output of the future, if it has completed
+    /// and the output has not been taken yet.
+    pub fn output_mut(self: Pin<&mut Self>) -> Option<&mut F::Output> {
+        // Safety: the output is not pinned.
+        match unsafe { self.get_unchecked_mut() } {
+            MaybeDone::Done(output) => Some(output),
+            _ => None,
+        }
+    }
+
+    /// Takes the output of the future, if it has completed and
+    /// the output has not been taken yet.
+    pub fn take_output(self: Pin<&mut Self>) -> Option<F::Output> {
+        // Safety: the `Future` variant is never moved out.
+        let this = unsafe { self.get_unchecked_mut() };
+        if !this.is_done() {
+            return None;
+        }
+        match std::mem::replace(this, MaybeDone::Gone) {
+            MaybeDone::Done(output) => Some(output),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<F: Future> Future for MaybeDone<F> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        // Safety: the future is pinned in place, and it is only
+        // dropped in place once it completes.
+        let this = unsafe { self.get_unchecked_mut() };
+        if let MaybeDone::Future(future) = this {
+            let future = unsafe { Pin::new_unchecked(future) };
+            let Poll::Ready(output) = future.poll(cx) else {
+                return Poll::Pending;
+            };
+            *this = MaybeDone::Done(output);
+        }
+        Poll::Ready(())
+    }
+}
@@ -0,0 +1,19 @@
+//! Asynchronous values, and utilities for awaiting them.
+//!
+//! This module re-exports the executor agnostic primitives of [`std::future`] and
+//! the [`pin!`] macro, so they can be found in a single place. It also provides
+//! utilities for awaiting a runtime sized number of futures on the current task.
+//! The [`join!`](crate::join!) macro can only await a number of futures known
+//! at compile time. [`join_all`] and [`FuturesUnordered`] drive a dynamic collection
+//! of futures concurrently without spawning them, so the futures may borrow from
+//! the current task.
+pub use futures_unordered::FuturesUnordered;
+pub use join_all::{join_all, JoinAll};
+pub use maybe_done::{maybe_done, MaybeDone};
+pub use std::future::{pending, poll_fn, ready, Future, IntoFuture, Pending, PollFn, Ready};
+pub use std::pin::pin;
+
+mod futures_unordered;
+mod join_all;
+mod maybe_done;
+mod ready;
@@ -0,0 +1,118 @@
+//! Tracks which futures of a dynamically sized collection have been woken.
+//!
+//! This is a generalization of the [`JoinWaker`](crate::_priv::JoinWaker) used by
+//! [`join!`](crate::join!) to a runtime sized number of futures. The wake flags are
+//! stored in pages of 64 bits, and each future gets a waker that sets its own bit,
+//! and then wakes the task polling the collection. This way, only the futures that
+//! were actually woken are polled again.
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Wake, Waker};
+
+const BITS: usize = u64::BITS as usize;
+
+pub(crate) struct ReadySet {
+    parent: Arc<Parent>,
+    pages: Vec<Arc<Page>>,
+    /// the waker of each slot, created lazily.
+    wakers: Vec<Waker>,
+}
+
+/// The waker of the task polling the collection.
+struct Parent(Mutex<Option<Waker>>);
+
+struct Page {
+    bits: AtomicU64,
+    parent: Arc<Parent>,
+}
+
+struct SlotWaker {
+    page: Arc<Page>,
+    mask: u64,
+}
+
+impl ReadySet {
+    pub fn new() -> ReadySet {
+        ReadySet {
+            parent: Arc::new(Parent(Mutex::new(None))),
+            pages: vec![],
+            wakers: vec![],
+        }
+    }
+
+    /// Sets the waker to be woken when any of the slots is woken.
+    pub fn register(&self, waker: &Waker) {
+        let mut parent = self.parent.0.lock().unwrap();
+        match &mut *parent {
+            Some(old) if old.will_wake(waker) => {}
+            _ => *parent = Some(waker.clone()),
+        }
+    }
+
+    /// Creates the waker of a slot if needed, and marks the slot
+    /// as woken so it gets polled for the first time.
+    pub fn insert(&mut self, index: usize) {
+        while self.wakers.len() <= index {
+            let i = self.wakers.len();
+            if i / BITS == self.pages.len() {
+                self.pages.push(Arc::new(Page {
+                    bits: AtomicU64::new(0),
+                    parent: self.parent.clone(),
+                }));
+            }
+            let waker = SlotWaker {
+                page: self.pages[i / BITS].clone(),
+                mask: 1 << (i % BITS),
+            };
+            self.wakers.push(Arc::new(waker).into());
+        }
+        self.pages[index / BITS]
+            .bits
+            .fetch_or(1 << (index % BITS), Ordering::Release);
+    }
+
+    /// Returns the waker of a slot previously passed to [`insert`](ReadySet::insert).
+    pub fn waker(&self, index: usize) -> &Waker {
+        &self.wakers[index]
+    }
+
+    /// Clears the woken slots of the page starting at `page * 64`, and returns them.
+    pub fn take(&self, page: usize) -> u64 {
+        self.pages[page].bits.swap(0, Ordering::Acquire)
+    }
+
+    /// Marks the slots of a page as woken again.
+    pub fn restore(&self, page: usize, bits: u64) {
+        self.pages[page].bits.fetch_or(bits, Ordering::Release);
+    }
+
+    /// The number of pages.
+    pub fn pages(&self) -> usize {
+        self.pages.len()
+    }
+}
+
+impl Wake for SlotWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.page.bits.fetch_or(self.mask, Ordering::Release);
+        if let Some(waker) = &*self.page.parent.0.lock().unwrap() {
+            waker.wake_by_ref();
+        }
+    }
+}
+
+/// Iterates over the indices of the set bits of `bits`, offset by `page * 64`.
+pub(crate) fn indices(page: usize, mut bits: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if bits == 0 {
+            return None;
+        }
+        let bit = bits.trailing_zeros() as usize;
+        bits &= bits - 1;
+        Some(page * BITS + bit)
+    })
+}
@@ -0,0 +1,47 @@
+//! Low level I/O primitives.
+//!
+//! ## Readiness
+//!
+//! [`PollFd`] waits for arbitrary file descriptors, such as the ones returned by C
+//! libraries, to become readable or writable. It is the escape hatch for the kinds of
+//! file descriptors osiris doesn't natively support.
+//!
+//! ## Zero-copy transfers
+//!
+//! On Linux, [`splice`] and [`tee`] move data between file descriptors inside the kernel,
+//! without copying it through userspace. They are useful for proxies, which forward
+//! the bytes of a socket to another socket, or for servers writing uploads to files.
+//!
+//! At least one end of a [`splice`], and both ends of a [`tee`], must be
+//! a pipe. To move data between two sockets, or between a socket and a file, the data
+//! must go through an intermediate [`pipe`](crate::net::pipe::pipe).
+//!
+//! ```no_run
+//! use osiris::io::splice;
+//! use osiris::net::{pipe::pipe, TcpStream};
+//!
+//! #[osiris::main]
+//! async fn main() -> std::io::Result<()> {
+//!     let upstream = TcpStream::connect("localhost:8080").await?;
+//!     let downstream = TcpStream::connect("localhost:8081").await?;
+//!     let (reader, writer) = pipe().await?;
+//!     loop {
+//!         let n = splice(&upstream, &writer, 64 * 1024).await?;
+//!         if n == 0 {
+//!             return Ok(());
+//!         }
+//!         let mut left = n;
+//!         while left > 0 {
+//!             left -= splice(&reader, &downstream, left).await?;
+//!         }
+//!     }
+//! }
+//! ```
+
+pub use poll_fd::PollFd;
+#[cfg(target_os = "linux")]
+pub use splice::{splice, tee};
+
+mod poll_fd;
+#[cfg(target_os = "linux")]
+mod splice;
@@ -0,0 +1,98 @@
+use std::fmt::Debug;
+use std::io::Result;
+use std::os::fd::{AsRawFd, RawFd};
+
+use crate::reactor::op;
+
+/// A file descriptor registered with the reactor to be notified of its readiness.
+///
+/// `PollFd` does not own the file descriptor, and it does not perform any I/O on it.
+/// Instead, it waits for the file descriptor to become ready so the caller can perform
+/// the I/O with a nonblocking system call. The file descriptor must be kept open for as
+/// long as the `PollFd` is in use, and it should be in nonblocking mode, so a spurious
+/// wakeup doesn't block the runtime.
+///
+/// # Examples
+/// ```
+/// use osiris::io::PollFd;
+/// use osiris::net::pipe::pipe;
+/// use std::os::fd::AsRawFd;
+///
+/// #[osiris::main]
+/// async fn main() -> std::io::Result<()> {
+///     let (reader, mut writer) = pipe().await?;
+///     let fd = PollFd::new(reader.as_raw_fd());
+///     writer.write_all("ready").await.0?;
+///     fd.readable().await?;
+///     Ok(())
+/// }
+/// ```
+pub struct PollFd {
+    fd: RawFd,
+}
+
+impl PollFd {
+    /// Creates a new `PollFd` for the given file descriptor.
+    #[must_use]
+    pub fn new(fd: RawFd) -> PollFd {
+        PollFd { fd }
+    }
+
+    /// Waits for the file descriptor to become readable. It resolves immediately if it
+    /// is already readable, and also when the peer hung up or an error is pending on it.
+    ///
+    /// # Errors
+    /// This function errors if the file descriptor is invalid, or if it cannot be polled.
+    pub async fn readable(&self) -> Result<()> {
+        op::wait_readable(self.fd).await
+    }
+
+    /// Waits for the file descriptor to become writable. It resolves immediately if it
+    /// is already writable, and also when the peer hung up or an error is pending on it.
+    ///
+    /// # Errors
+    /// This function errors if the file descriptor is invalid, or if it cannot be polled.
+    pub async fn writable(&self) -> Result<()> {
+        op::wait_writable(self.fd).await
+    }
+}
+
+impl AsRawFd for PollFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl Debug for PollFd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PollFd").field("fd", &self.fd).finish()
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn poll_fd_readiness() {
+    use crate::time::{sleep, Duration};
+    use crate::utils::syscall;
+
+    crate::block_on(async {
+        let mut fds = [-1; 2];
+        syscall!(pipe2, fds.as_mut_ptr(), libc::O_NONBLOCK).unwrap();
+        let [reader, writer] = fds;
+        let fd = PollFd::new(reader);
+        let write = crate::spawn(async move {
+            sleep(Duration::from_millis(10)).await;
+            syscall!(write, writer, b"x".as_ptr().cast(), 1).unwrap();
+        });
+        fd.readable().await.unwrap();
+        write.await;
+        PollFd::new(writer).writable().await.unwrap();
+        let mut buf = [0u8; 1];
+        let n = syscall!(read, reader, buf.as_mut_ptr().cast(), 1).unwrap();
+        assert_eq!((n, buf), (1, *b"x"));
+        for fd in fds {
+            syscall!(close, fd).unwrap();
+        }
+    })
+    .unwrap();
+}
@@ -0,0 +1,31 @@
+use std::io::Result;
+use std::os::fd::AsRawFd;
+
+use crate::reactor::op;
+
+/// Moves up to `len` bytes from `src` to `dst` without copying them through userspace,
+/// returning the number of bytes moved. A return value of zero signals that `src`
+/// reached the end of the stream.
+///
+/// Regular files are read and written from their current position, which is advanced
+/// by the number of bytes moved.
+///
+/// # Errors
+/// This function errors if neither `src` nor `dst` is a pipe, or if either file
+/// descriptor fails to be read or written.
+pub async fn splice(src: &impl AsRawFd, dst: &impl AsRawFd, len: usize) -> Result<usize> {
+    let len = len.min(u32::MAX as usize) as u32;
+    op::splice(src.as_raw_fd(), -1, dst.as_raw_fd(), -1, len).await
+}
+
+/// Copies up to `len` bytes from the pipe `src` to the pipe `dst` without consuming
+/// them, returning the number of bytes copied. The data can still be read from `src`
+/// afterwards, so it can be sent to two destinations without copying it through
+/// userspace.
+///
+/// # Errors
+/// This function errors if either `src` or `dst` is not a pipe.
+pub async fn tee(src: &impl AsRawFd, dst: &impl AsRawFd, len: usize) -> Result<usize> {
+    let len = len.min(u32::MAX as usize) as u32;
+    op::tee(src.as_raw_fd(), dst.as_raw_fd(), len).await
+}
@@ -26,6 +26,16 @@
 //!     // ...
 //! }
 //! ```
+//! Restarts are delayed with an exponential backoff, starting at 10 milliseconds and capped at
+//! 5 seconds. Setting `restart` to a number limits how many times panicked threads will be
+//! restarted, after which the process exits with a failure exit code.
+//! ```
+//! // give up after 10 restarts
+//! #[osiris::main(scale = true, restart = 10)]
+//! async fn main() {
+//!     // ...
+//! }
+//! ```
 //! If more control is needed over the number of threads, it can be specified explicitly
 //! ```no_run
 //! // this will spawn 4 threads and set the affinity of each
@@ -35,6 +45,33 @@
 //!     // ...
 //! }
 //! ```
+//! The runtime of each replica can be tuned with the `queue_depth` parameter, which sets the
+//! number of entries of the submission queue, and the `event_interval` parameter, which sets
+//! how many tasks are polled before polling the reactor for IO events. See [`Config`](runtime::Config)
+//! for more details.
+//! ```
+//! #[osiris::main(queue_depth = 512, event_interval = 64)]
+//! async fn main() {
+//!     // ...
+//! }
+//! ```
+//! For full control over the runtime, the `config` parameter takes the path of a function returning
+//! an `io::Result<Config>`, which is used to build the runtime of each replica.
+//! ```
+//! use osiris::runtime::{Config, Mode};
+//!
+//! fn config() -> std::io::Result<Config> {
+//!     Ok(Config {
+//!         mode: Mode::Polling { idle_timeout: 100 },
+//!         ..Config::default()
+//!     })
+//! }
+//!
+//! #[osiris::main(config = config)]
+//! async fn main() {
+//!     // ...
+//! }
+//! ```
 //! Note that scaling the application will create identical parallel replicas of the main task, which is useful for a
 //! concurrent server, but not as much for clients. This shouldn't be confused with how  work stealing runtimes work
 //! (e.g. [`tokio`](https://docs.rs/tokio/latest/tokio/)), that will spawn a pool of worker threads, but the main task will remain unique.
@@ -95,6 +132,8 @@
 //!     // task continues execution after being dropped.
 //! }
 //! ```
+//! A [`DetachHandle`](task::DetachHandle) that is kept around can still be used
+//! to [`abort`](task::DetachHandle::abort) the detached task.
 //! # Async I/O
 //! Osiris is a completion based async runtime, which means it has stricter requirements
 //! for what kinds of buffers can be used for I/O. Specifically, it cannot work with non-'static
@@ -188,7 +227,10 @@ pub mod _priv;
 pub mod buf;
 
 pub mod fs;
+pub mod future;
+pub mod io;
 pub mod net;
+pub mod process;
 mod reactor;
 pub mod runtime;
 pub mod sync;
@@ -0,0 +1,147 @@
+use std::io::Result;
+
+use super::socket::{Domain, Protocol, Socket, Type};
+use super::to_socket_addr::try_until_success;
+use super::{TcpListener, ToSocketAddrs};
+
+/// Options which can be used to configure how a [`TcpListener`] is bound.
+///
+/// [`TcpListener::bind`] is an alias for binding with the default options, which
+/// enable both `SO_REUSEADDR` and `SO_REUSEPORT`, and use a backlog of 8192 connections.
+///
+/// # Examples
+///
+/// Binding a listener for a low traffic service, that refuses to share its port
+/// with other sockets:
+///
+/// ```no_run
+/// # osiris::block_on(async {
+/// use osiris::net::BindOptions;
+///
+/// let listener = BindOptions::new()
+///     .reuseport(false)
+///     .backlog(128)
+///     .bind("127.0.0.1:8080")
+///     .await?;
+/// # std::io::Result::Ok(()) }).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct BindOptions {
+    reuseaddr: bool,
+    reuseport: bool,
+    backlog: u32,
+    #[cfg(target_os = "linux")]
+    device: Option<String>,
+    #[cfg(target_os = "linux")]
+    ip_transparent: bool,
+}
+
+impl BindOptions {
+    /// Creates a blank new set of options, with both `reuseaddr`
+    /// and `reuseport` enabled, and a backlog of 8192 connections.
+    pub fn new() -> BindOptions {
+        BindOptions {
+            reuseaddr: true,
+            reuseport: true,
+            backlog: 8192,
+            #[cfg(target_os = "linux")]
+            device: None,
+            #[cfg(target_os = "linux")]
+            ip_transparent: false,
+        }
+    }
+
+    /// Sets the `SO_REUSEADDR` option on the socket.
+    ///
+    /// This option allows binding to an address whose previous connections are
+    /// still in the `TIME_WAIT` state, which is the case for a while after a
+    /// server restarts. It does not allow two listeners to be bound to the same
+    /// address at the same time. Servers should generally leave it enabled.
+    pub fn reuseaddr(&mut self, reuseaddr: bool) -> &mut BindOptions {
+        self.reuseaddr = reuseaddr;
+        self
+    }
+
+    /// Sets the `SO_REUSEPORT` option on the socket.
+    ///
+    /// This option allows several listeners, possibly on different threads, to be
+    /// bound to the same address at the same time. Incoming connections are
+    /// distributed among them by the kernel, which is how each thread of a
+    /// [scaled](crate::main) program accepts connections on the same port. Note that
+    /// this also allows a different process of the same user to bind to the port.
+    pub fn reuseport(&mut self, reuseport: bool) -> &mut BindOptions {
+        self.reuseport = reuseport;
+        self
+    }
+
+    /// Sets the maximum number of pending connections, that is, connections
+    /// that have been established but not yet accepted.
+    ///
+    /// Once the backlog is full, new connection attempts are dropped or refused.
+    /// The value is passed to the `listen` syscall, which silently caps it to
+    /// `/proc/sys/net/core/somaxconn` on Linux.
+    pub fn backlog(&mut self, backlog: u32) -> &mut BindOptions {
+        self.backlog = backlog;
+        self
+    }
+
+    /// Sets the `SO_BINDTODEVICE` option on the socket, restricting the listener to
+    /// connections arriving on the network interface named `interface`, such as `"eth0"`.
+    /// Passing `None` leaves the listener unrestricted, which is the default.
+    ///
+    /// The option is applied before the socket is bound. Setting it requires the
+    /// `CAP_NET_RAW` capability, so [`bind`](BindOptions::bind) fails with an error
+    /// of kind [`PermissionDenied`](std::io::ErrorKind::PermissionDenied) for
+    /// unprivileged processes. This option is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn bind_device(&mut self, interface: Option<&str>) -> &mut BindOptions {
+        self.device = interface.map(String::from);
+        self
+    }
+
+    /// Sets the `IP_TRANSPARENT` option on the socket, which allows the listener to be
+    /// bound to a non-local address, and to accept connections redirected to it by a
+    /// `TPROXY` firewall rule, as transparent proxies do. Disabled by default.
+    ///
+    /// The option is applied before the socket is bound. Setting it requires the
+    /// `CAP_NET_ADMIN` capability, so [`bind`](BindOptions::bind) fails with an error
+    /// of kind [`PermissionDenied`](std::io::ErrorKind::PermissionDenied) for
+    /// unprivileged processes. This option is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn ip_transparent(&mut self, ip_transparent: bool) -> &mut BindOptions {
+        self.ip_transparent = ip_transparent;
+        self
+    }
+
+    /// Creates a new `TcpListener` bound to the specified address with the
+    /// options specified by `self`. See [`TcpListener::bind`] for more details.
+    ///
+    /// # Errors
+    /// This function errors if none of the addresses could be bound.
+    pub async fn bind<A: ToSocketAddrs>(&self, addr: A) -> Result<TcpListener> {
+        try_until_success(addr, |addr| async move {
+            let domain = Domain::from(addr);
+            let socket = Socket::new(domain, Type::STREAM, Protocol::TCP).await?;
+            socket.set_reuseaddr(self.reuseaddr)?;
+            socket.set_reuseport(self.reuseport)?;
+            #[cfg(target_os = "linux")]
+            if self.device.is_some() {
+                socket.set_bind_device(self.device.as_deref())?;
+            }
+            #[cfg(target_os = "linux")]
+            if self.ip_transparent {
+                socket.set_ip_transparent(true)?;
+            }
+            socket.bind(&addr)?;
+            socket.listen(self.backlog)?;
+            Ok(TcpListener { socket })
+        })
+        .await
+    }
+}
+
+impl Default for BindOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,199 @@
+//! Write buffering for sockets.
+//!
+//! Every write to a [`TcpStream`] is submitted to the reactor as a separate operation.
+//! A protocol emitting many small frames pays a submission per frame, so the
+//! [`BufferedStream`] coalesces them into a single write.
+use std::io::{Error, ErrorKind, Result};
+
+use crate::buf::{self, IoBuf, IoBufMut};
+
+use super::TcpStream;
+
+/// The default capacity of the write buffer, 8 KiB.
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+/// A [`TcpStream`] that buffers the data written to it, sending it with a single
+/// write once the buffer fills up, or when it is explicitly flushed.
+/// It is created with the [`TcpStream::buffered`] method.
+///
+/// Writes larger than the capacity of the buffer bypass it, after the buffered data
+/// is flushed. Reads are not buffered, and they do not flush the buffer, so the buffer
+/// should be flushed before waiting for the response to a request.
+///
+/// The buffered data is discarded if the stream is dropped, so it should be shut down
+/// with [`close`](BufferedStream::close), which flushes it first.
+///
+/// # Examples
+/// ```no_run
+/// use osiris::net::TcpStream;
+///
+/// #[osiris::main]
+/// async fn main() -> std::io::Result<()> {
+///     let mut stream = TcpStream::connect("127.0.0.1:8080").await?.buffered();
+///     for i in 0..100 {
+///         stream.write_all(format!("frame {i}\n")).await.0?;
+///     }
+///     stream.close().await
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BufferedStream {
+    stream: TcpStream,
+    buf: Vec<u8>,
+    /// Holds a copy of the buffered data while it is being flushed.
+    scratch: Vec<u8>,
+    capacity: usize,
+}
+
+impl TcpStream {
+    /// Wraps the stream in a [`BufferedStream`] with a write buffer of 8 KiB.
+    #[must_use]
+    pub fn buffered(self) -> BufferedStream {
+        BufferedStream::with_capacity(DEFAULT_CAPACITY, self)
+    }
+}
+
+impl BufferedStream {
+    /// Wraps the stream in a [`BufferedStream`] with a write buffer of the given capacity.
+    #[must_use]
+    pub fn with_capacity(capacity: usize, stream: TcpStream) -> BufferedStream {
+        BufferedStream {
+            stream,
+            buf: Vec::with_capacity(capacity),
+            scratch: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Writes the buffer into the stream, returning how many bytes were written.
+    /// The bytes are copied into the write buffer if they fit in it, in which case
+    /// the whole buffer is always written.
+    ///
+    /// # Errors
+    /// This function errors if the buffered data had to be flushed and the flush
+    /// failed, or if the buffer bypassed the write buffer and the write failed.
+    pub async fn write<B: IoBuf>(&mut self, buf: B) -> (Result<usize>, B) {
+        let len = buf.bytes_init();
+        if self.buf.len() + len > self.capacity {
+            if let Err(err) = self.flush().await {
+                return (Err(err), buf);
+            }
+        }
+        if len >= self.capacity {
+            return self.stream.write(buf).await;
+        }
+        self.buf.extend_from_slice(buf::deref(&buf));
+        (Ok(len), buf)
+    }
+
+    /// Attempts to write an entire buffer into the stream.
+    ///
+    /// # Errors
+    /// This function errors if the buffered data had to be flushed and the flush
+    /// failed, or if the buffer bypassed the write buffer and the write failed.
+    pub async fn write_all<B: IoBuf>(&mut self, buf: B) -> (Result<()>, B) {
+        let len = buf.bytes_init();
+        if self.buf.len() + len > self.capacity {
+            if let Err(err) = self.flush().await {
+                return (Err(err), buf);
+            }
+        }
+        if len >= self.capacity {
+            return self.stream.write_all(buf).await;
+        }
+        self.buf.extend_from_slice(buf::deref(&buf));
+        (Ok(()), buf)
+    }
+
+    /// Writes all the buffered data into the stream.
+    ///
+    /// # Errors
+    /// This function errors if the write fails. The data that could not be
+    /// written is kept in the buffer, so the flush can be retried.
+    ///
+    /// # Cancellation
+    /// The data is only removed from the buffer once it has been written, so
+    /// if the flush is cancelled, the data that was not written yet is kept in
+    /// the buffer and sent by the next flush.
+    pub async fn flush(&mut self) -> Result<()> {
+        // the in-flight write owns a copy of the data, so that the buffer
+        // still holds it if this future is dropped before the write completes.
+        let mut pending = std::mem::take(&mut self.scratch);
+        pending.clear();
+        pending.extend_from_slice(&self.buf);
+        let mut written = 0;
+        let mut res = Ok(());
+        while written < pending.len() {
+            let (n, slice) = self.stream.write(pending.slice(written..)).await;
+            pending = slice.into_inner();
+            match n {
+                Ok(0) => {
+                    let msg = "failed to write the buffered data";
+                    res = Err(Error::new(ErrorKind::WriteZero, msg));
+                    break;
+                }
+                Ok(n) => {
+                    written += n;
+                    self.buf.drain(..n);
+                }
+                Err(err) => {
+                    res = Err(err);
+                    break;
+                }
+            }
+        }
+        self.scratch = pending;
+        res
+    }
+
+    /// Read some data from the stream into the buffer. See [`TcpStream::read`].
+    /// The write buffer is not flushed.
+    pub async fn read<B: IoBufMut>(&mut self, buf: B) -> (Result<usize>, B) {
+        self.stream.read(buf).await
+    }
+
+    /// Flushes the buffered data and closes the stream. The stream is closed
+    /// even if the flush fails.
+    ///
+    /// # Errors
+    /// This function returns the error of the flush if it failed, and otherwise
+    /// the error of closing the file descriptor.
+    pub async fn close(mut self) -> Result<()> {
+        let flushed = self.flush().await;
+        let closed = self.stream.close().await;
+        flushed.and(closed)
+    }
+
+    /// Returns the data that is waiting to be flushed.
+    #[must_use]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Returns the capacity of the write buffer.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns a reference to the underlying stream.
+    #[must_use]
+    pub fn get_ref(&self) -> &TcpStream {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream. Writing to
+    /// it directly bypasses the data that is still buffered.
+    pub fn get_mut(&mut self) -> &mut TcpStream {
+        &mut self.stream
+    }
+
+    /// Flushes the buffered data and returns the underlying stream.
+    ///
+    /// # Errors
+    /// This function errors if the flush fails, in which case the stream is dropped.
+    pub async fn into_inner(mut self) -> Result<TcpStream> {
+        self.flush().await?;
+        Ok(self.stream)
+    }
+}
@@ -0,0 +1,270 @@
+//! Length delimited framing for byte streams.
+//!
+//! Many binary protocols prefix each message with its length. The [`LengthDelimitedCodec`]
+//! takes care of splitting a [`TcpStream`] into such frames, buffering partial reads across
+//! frame boundaries.
+use std::io::{Error, ErrorKind, Result};
+
+use crate::buf::IoBuf;
+
+use super::TcpStream;
+
+/// The default maximum frame length, 8 MiB.
+const DEFAULT_MAX_FRAME_LENGTH: usize = 8 * 1024 * 1024;
+
+/// The minimum amount of space reserved in the read buffer before each read.
+const READ_CHUNK: usize = 4096;
+
+/// Configuration for length delimited framing. Each frame is prefixed by a 2 or 4 byte
+/// unsigned integer holding the length of the frame, not including the prefix itself.
+///
+/// # Examples
+/// ```no_run
+/// use osiris::net::{LengthDelimitedCodec, TcpStream};
+///
+/// #[osiris::main]
+/// async fn main() -> std::io::Result<()> {
+///     let stream = TcpStream::connect("127.0.0.1:8080").await?;
+///     let mut framed = LengthDelimitedCodec::new()
+///         .length_field_length(2)
+///         .little_endian()
+///         .framed(stream);
+///
+///     framed.send_frame("ping").await?;
+///     while let Some(frame) = framed.next_frame().await? {
+///         println!("received {} bytes", frame.len());
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct LengthDelimitedCodec {
+    length_field_length: usize,
+    big_endian: bool,
+    max_frame_length: usize,
+}
+
+/// A [`TcpStream`] split into length delimited frames.
+/// It is created with the [`LengthDelimitedCodec::framed`] method.
+#[derive(Debug)]
+pub struct Framed {
+    stream: TcpStream,
+    codec: LengthDelimitedCodec,
+    /// bytes read from the stream that haven't been returned yet.
+    buf: Vec<u8>,
+    /// the position of the first unreturned byte in `buf`.
+    pos: usize,
+    /// the buffer handed to reads, so `buf` is never held by an in flight read.
+    scratch: Vec<u8>,
+}
+
+impl Default for LengthDelimitedCodec {
+    fn default() -> Self {
+        LengthDelimitedCodec::new()
+    }
+}
+
+impl LengthDelimitedCodec {
+    /// Creates a codec with a 4 byte big endian length prefix, and
+    /// a maximum frame length of 8 MiB.
+    #[must_use]
+    pub fn new() -> Self {
+        LengthDelimitedCodec {
+            length_field_length: 4,
+            big_endian: true,
+            max_frame_length: DEFAULT_MAX_FRAME_LENGTH,
+        }
+    }
+
+    /// Sets the width of the length prefix in bytes. It defaults to 4.
+    ///
+    /// # Panics
+    /// If `len` is not 2 or 4.
+    #[must_use]
+    pub fn length_field_length(mut self, len: usize) -> Self {
+        assert!(
+            len == 2 || len == 4,
+            "the length field must be either 2 or 4 bytes long."
+        );
+        self.length_field_length = len;
+        self
+    }
+
+    /// Encodes the length prefix as big endian. This is the default.
+    #[must_use]
+    pub fn big_endian(mut self) -> Self {
+        self.big_endian = true;
+        self
+    }
+
+    /// Encodes the length prefix as little endian.
+    #[must_use]
+    pub fn little_endian(mut self) -> Self {
+        self.big_endian = false;
+        self
+    }
+
+    /// Sets the maximum length of a frame. Receiving or sending a bigger frame
+    /// results in an error. It defaults to 8 MiB.
+    #[must_use]
+    pub fn max_frame_length(mut self, len: usize) -> Self {
+        self.max_frame_length = len;
+        self
+    }
+
+    /// Wraps the stream, splitting it into frames.
+    #[must_use]
+    pub fn framed(self, stream: TcpStream) -> Framed {
+        Framed {
+            stream,
+            codec: self,
+            buf: Vec::new(),
+            pos: 0,
+            scratch: Vec::new(),
+        }
+    }
+
+    fn decode_len(&self, prefix: &[u8]) -> usize {
+        match (prefix.len(), self.big_endian) {
+            (2, true) => u16::from_be_bytes([prefix[0], prefix[1]]) as usize,
+            (2, false) => u16::from_le_bytes([prefix[0], prefix[1]]) as usize,
+            (_, true) => u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize,
+            (_, false) => u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize,
+        }
+    }
+
+    fn encode_len(&self, len: usize) -> Result<Vec<u8>> {
+        let max = match self.length_field_length {
+            2 => u16::MAX as usize,
+            _ => u32::MAX as usize,
+        };
+        if len > self.max_frame_length.min(max) {
+            return Err(frame_too_large());
+        }
+        let prefix = match (self.length_field_length, self.big_endian) {
+            (2, true) => (len as u16).to_be_bytes().to_vec(),
+            (2, false) => (len as u16).to_le_bytes().to_vec(),
+            (_, true) => (len as u32).to_be_bytes().to_vec(),
+            (_, false) => (len as u32).to_le_bytes().to_vec(),
+        };
+        Ok(prefix)
+    }
+}
+
+impl Framed {
+    /// Receives the next frame from the stream. Returns `Ok(None)` if the stream
+    /// was closed at a frame boundary.
+    ///
+    /// # Cancellation
+    /// This method is not fully cancellation safe. The bytes already received are
+    /// kept when the returned future is dropped, but the read in flight is cancelled,
+    /// and if it completed in the meantime the bytes it read are lost, desyncing the
+    /// framing.
+    ///
+    /// # Errors
+    /// This function errors if the frame is larger than the maximum frame length,
+    /// if the stream was closed in the middle of a frame, or if the read fails.
+    pub async fn next_frame(&mut self) -> Result<Option<Vec<u8>>> {
+        loop {
+            if let Some(frame) = self.decode()? {
+                return Ok(Some(frame));
+            }
+            if self.fetch().await? == 0 {
+                if self.pos == self.buf.len() {
+                    return Ok(None);
+                }
+                let msg = "stream closed in the middle of a frame";
+                return Err(Error::new(ErrorKind::UnexpectedEof, msg));
+            }
+        }
+    }
+
+    /// Sends a frame, prefixed by its length.
+    ///
+    /// # Errors
+    /// This function errors if the frame is larger than the maximum frame length,
+    /// or if the write fails.
+    pub async fn send_frame<B: IoBuf>(&mut self, buf: B) -> Result<()> {
+        let prefix = self.codec.encode_len(buf.bytes_init())?;
+        self.stream.write_all(prefix).await.0?;
+        self.stream.write_all(buf).await.0
+    }
+
+    /// Returns a reference to the underlying stream.
+    #[must_use]
+    pub fn get_ref(&self) -> &TcpStream {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream. Reading from
+    /// it directly may corrupt the framing.
+    pub fn get_mut(&mut self) -> &mut TcpStream {
+        &mut self.stream
+    }
+
+    /// Returns the underlying stream. Any buffered bytes are discarded.
+    #[must_use]
+    pub fn into_inner(self) -> TcpStream {
+        self.stream
+    }
+
+    /// Attempts to take a whole frame from the read buffer.
+    fn decode(&mut self) -> Result<Option<Vec<u8>>> {
+        let width = self.codec.length_field_length;
+        let buf = &self.buf[self.pos..];
+        let Some(prefix) = buf.get(..width) else {
+            return Ok(None);
+        };
+        let len = self.codec.decode_len(prefix);
+        if len > self.codec.max_frame_length {
+            return Err(frame_too_large());
+        }
+        let Some(frame) = buf.get(width..width + len) else {
+            return Ok(None);
+        };
+        let frame = frame.to_vec();
+        self.pos += width + len;
+        Ok(Some(frame))
+    }
+
+    /// Reads more bytes from the stream into the read buffer.
+    async fn fetch(&mut self) -> Result<usize> {
+        // discard the frames that were already returned.
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+        // the bytes are read into a separate buffer, so the ones already received
+        // are kept if this future is dropped.
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.clear();
+        scratch.reserve(READ_CHUNK);
+        let (res, scratch) = self.stream.read(scratch).await;
+        self.buf.extend_from_slice(&scratch);
+        self.scratch = scratch;
+        res
+    }
+}
+
+fn frame_too_large() -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        "frame exceeds the maximum frame length",
+    )
+}
+
+#[test]
+fn length_prefix_round_trip() {
+    for codec in [
+        LengthDelimitedCodec::new(),
+        LengthDelimitedCodec::new().little_endian(),
+        LengthDelimitedCodec::new().length_field_length(2),
+        LengthDelimitedCodec::new()
+            .length_field_length(2)
+            .little_endian(),
+    ] {
+        let prefix = codec.encode_len(300).unwrap();
+        assert_eq!(prefix.len(), codec.length_field_length);
+        assert_eq!(codec.decode_len(&prefix), 300);
+    }
+    let codec = LengthDelimitedCodec::new().length_field_length(2);
+    assert!(codec.encode_len(u16::MAX as usize + 1).is_err());
+}
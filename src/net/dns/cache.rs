@@ -0,0 +1,57 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Negative results (i.e. names with no addresses) are cached for this long.
+const NEGATIVE_TTL: Duration = Duration::from_secs(5);
+
+/// Maps each hostname to its resolved addresses and the instant they expire.
+type Entries = HashMap<String, (Vec<IpAddr>, Instant)>;
+
+/// A per-runtime cache of DNS results, keyed by hostname.
+#[derive(Clone, Default)]
+pub(crate) struct DnsCache(Rc<RefCell<Entries>>);
+
+impl DnsCache {
+    /// Returns the cached addresses for `name`, evicting any expired entries.
+    pub fn get(&self, name: &str) -> Option<Vec<IpAddr>> {
+        let mut map = self.0.borrow_mut();
+        let now = Instant::now();
+        map.retain(|_, (_, expires)| *expires > now);
+        map.get(name).map(|(addrs, _)| addrs.clone())
+    }
+
+    /// Caches the addresses for `name`. The entry will expire after `ttl`
+    /// seconds, unless `addrs` is empty, in which case it expires after
+    /// a short fixed duration.
+    pub fn insert(&self, name: &str, addrs: Vec<IpAddr>, ttl: u32) {
+        let ttl = if addrs.is_empty() {
+            NEGATIVE_TTL
+        } else {
+            Duration::from_secs(ttl.into())
+        };
+        if ttl.is_zero() {
+            return;
+        }
+        let expires = Instant::now() + ttl;
+        self.0.borrow_mut().insert(name.into(), (addrs, expires));
+    }
+}
+
+#[test]
+fn cache_expires_entries() {
+    let cache = DnsCache::default();
+    let addrs = vec![IpAddr::from([127, 0, 0, 1])];
+
+    cache.insert("localhost", addrs.clone(), 60);
+    assert_eq!(cache.get("localhost"), Some(addrs.clone()));
+
+    cache.insert("nxdomain", vec![], 0);
+    assert_eq!(cache.get("nxdomain"), Some(vec![]));
+
+    cache.0.borrow_mut().get_mut("nxdomain").unwrap().1 = Instant::now();
+    assert_eq!(cache.get("nxdomain"), None);
+    assert!(cache.0.borrow().get("nxdomain").is_none());
+}
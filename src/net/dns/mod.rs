@@ -1,6 +1,10 @@
 use std::io::Result;
 use std::net::IpAddr;
 
+pub(crate) use cache::DnsCache;
+pub use unix::lookup_port;
+
+mod cache;
 mod unix;
 
 /// Preform a DNS lookup, retrieving the IP addresses and other necessary information.
@@ -10,7 +14,9 @@ pub async fn lookup(name: &str) -> Result<impl Iterator<Item = IpAddr>> {
         return Ok(Either::Left(Some(ip).into_iter()));
     }
 
-    Ok(Either::Right(unix::lookup(name).await?.into_iter()))
+    let cache = crate::runtime::current().and_then(|rt| rt.dns_cache);
+    let addrs = unix::lookup(name, cache.as_ref()).await?;
+    Ok(Either::Right(addrs.into_iter()))
 }
 
 enum Either<L, R> {
@@ -31,3 +37,12 @@ where
         }
     }
 }
+
+#[test]
+fn lookup_localhost() {
+    crate::block_on(async {
+        let ips: Vec<_> = lookup("localhost").await.unwrap().collect();
+        assert!(ips.iter().any(IpAddr::is_loopback), "{ips:?}");
+    })
+    .unwrap();
+}
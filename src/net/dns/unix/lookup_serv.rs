@@ -31,6 +31,16 @@ async fn lookup_serv(
     Ok(len)
 }
 
+/// Looks up the port of a well-known service, such as `"http"`, in `/etc/services`.
+pub async fn lookup_port(name: &str) -> Result<Option<u16>> {
+    let services = &mut [Service {
+        port: 0,
+        proto: Protocol::TCP,
+    }];
+    let len = lookup_serv(services, Some(name.as_bytes()), None).await?;
+    Ok(services[..len].first().map(|service| service.port))
+}
+
 fn parse_line(name: Option<&[u8]>, protocol: Option<Protocol>, line: &[u8]) -> Option<Service> {
     let line = remove_comment(line);
     let mut columns = line.split(is_whitespace).filter(|name| !name.is_empty());
@@ -81,6 +91,9 @@ fn lookup_services() {
             .await
             .unwrap();
         assert_eq!(len, 1);
+
+        assert_eq!(lookup_port("http").await.unwrap(), Some(80));
+        assert_eq!(lookup_port("not-a-service").await.unwrap(), None);
     })
     .unwrap();
 }
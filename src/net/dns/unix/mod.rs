@@ -1,3 +1,4 @@
+use super::cache::DnsCache;
 use crate::net::utils::{is_whitespace, lines, remove_comment};
 use resolv::ResolvConf;
 use std::io::Result;
@@ -7,7 +8,13 @@ use std::str::from_utf8;
 mod lookup_serv;
 mod resolv;
 mod search;
-pub async fn lookup(name: &str) -> Result<Vec<IpAddr>> {
+
+pub use lookup_serv::lookup_port;
+pub async fn lookup(name: &str, cache: Option<&DnsCache>) -> Result<Vec<IpAddr>> {
+    if let Some(addrs) = cache.and_then(|cache| cache.get(name)) {
+        return Ok(addrs);
+    }
+
     // We may be able to use the /etc/hosts resolver.
     let addr = from_hosts(name).await?;
     if let Some(addr) = addr {
@@ -15,7 +22,11 @@ pub async fn lookup(name: &str) -> Result<Vec<IpAddr>> {
     }
 
     let resolv = ResolvConf::load();
-    search::dns_search(name, &resolv).await
+    let answer = search::dns_search(name, &resolv).await?;
+    if let Some(cache) = cache {
+        cache.insert(name, answer.addrs.clone(), answer.ttl);
+    }
+    Ok(answer.addrs)
 }
 
 /// Try parsing the name from the "hosts" file.
@@ -54,7 +65,7 @@ fn resolve_conf_load_test() {
 #[test]
 fn lookup_test() {
     crate::block_on(async {
-        let ips = dbg!(lookup("www.wikipedia.org").await.unwrap());
+        let ips = dbg!(lookup("www.wikipedia.org", None).await.unwrap());
         assert!(!ips.is_empty(), "{ips:?}");
     })
     .unwrap();
@@ -64,7 +75,7 @@ fn lookup_test() {
 #[test]
 fn lookup_non_existent_test() {
     crate::block_on(async {
-        let ips = dbg!(lookup("www.non-existent-host.com").await.unwrap());
+        let ips = dbg!(lookup("www.non-existent-host.com", None).await.unwrap());
         assert!(dbg!(ips).is_empty());
     })
     .unwrap();
@@ -10,15 +10,39 @@ use dns_protocol::{Flags, Message, Question, ResourceRecord, ResourceType};
 
 use crate::buf::IoBuf;
 use crate::net::udp::UdpSocket;
-use crate::net::TcpStream;
+use crate::net::{LengthDelimitedCodec, TcpStream};
 use crate::spawn;
 use crate::task::yield_now;
 use crate::time::timeout;
 
 use super::resolv::ResolvConf;
 
+/// The addresses resolved for a name, along with the minimum
+/// TTL of the records they were parsed from.
+#[derive(Debug)]
+pub struct Answer {
+    pub addrs: Vec<IpAddr>,
+    pub ttl: u32,
+}
+
+impl Default for Answer {
+    fn default() -> Self {
+        Answer {
+            addrs: vec![],
+            ttl: u32::MAX,
+        }
+    }
+}
+
+impl Answer {
+    fn append(&mut self, other: &mut Answer) {
+        self.addrs.append(&mut other.addrs);
+        self.ttl = self.ttl.min(other.ttl);
+    }
+}
+
 /// Preform a DNS lookup, considering the search variable.
-pub async fn dns_search(mut name: &str, resolv: &ResolvConf) -> Result<Vec<IpAddr>> {
+pub async fn dns_search(mut name: &str, resolv: &ResolvConf) -> Result<Answer> {
     // See if we should just use global scope.
     let num_dots = memchr::Memchr::new(b'.', name.as_bytes()).count();
     let global_scope = num_dots >= resolv.ndots as usize || name.ends_with('.');
@@ -45,9 +69,9 @@ pub async fn dns_search(mut name: &str, resolv: &ResolvConf) -> Result<Vec<IpAdd
                 buffer.truncate(name_end);
                 buffer.push_str(domain);
 
-                if let Ok(addrs) = dns_lookup(&buffer, resolv).await {
-                    if !addrs.is_empty() {
-                        return Ok(addrs);
+                if let Ok(answer) = dns_lookup(&buffer, resolv).await {
+                    if !answer.addrs.is_empty() {
+                        return Ok(answer);
                     }
                 }
             }
@@ -59,11 +83,11 @@ pub async fn dns_search(mut name: &str, resolv: &ResolvConf) -> Result<Vec<IpAdd
 }
 
 /// Preform a manual lookup for the name.
-async fn dns_lookup(name: &str, resolv: &ResolvConf) -> Result<Vec<IpAddr>> {
+async fn dns_lookup(name: &str, resolv: &ResolvConf) -> Result<Answer> {
     match resolv.name_servers.len() {
         0 => {
             // No nameservers, so we can't do anything.
-            Ok(vec![])
+            Ok(Answer::default())
         }
         1 => {
             // Just poll the one nameserver.
@@ -84,7 +108,7 @@ async fn dns_lookup(name: &str, resolv: &ResolvConf) -> Result<Vec<IpAddr>> {
                     spawn(async move { query_name_and_nameserver(&name, ns, &resolv).await });
                 handles.push(handle);
             }
-            let mut result = Vec::with_capacity(resolv.name_servers.len());
+            let mut result = Answer::default();
             for handle in handles {
                 result.append(&mut handle.await?);
             }
@@ -99,15 +123,15 @@ async fn query_name_and_nameserver(
     name: &str,
     nameserver: IpAddr,
     resolv: &ResolvConf,
-) -> Result<Vec<IpAddr>> {
+) -> Result<Answer> {
     // Try to poll for an IPv4 address first.
-    let mut addrs =
+    let mut answer =
         query_question_and_nameserver(Question::new(name, ResourceType::A, 1), nameserver, resolv)
             .await?;
 
     // If we didn't get any addresses, try an IPv6 address.
-    if addrs.is_empty() {
-        addrs = query_question_and_nameserver(
+    if answer.addrs.is_empty() {
+        answer = query_question_and_nameserver(
             Question::new(name, ResourceType::AAAA, 1),
             nameserver,
             resolv,
@@ -115,7 +139,7 @@ async fn query_name_and_nameserver(
         .await?;
     }
 
-    Ok(addrs)
+    Ok(answer)
 }
 
 /// Poll for a DNS response on the given nameserver.
@@ -123,7 +147,7 @@ async fn query_question_and_nameserver(
     question: Question<'_>,
     nameserver: IpAddr,
     resolv: &ResolvConf,
-) -> Result<Vec<IpAddr>> {
+) -> Result<Answer> {
     // Create the DNS query.
     // I'd like to use two questions at once, but at least the DNS system I use just drops the packet.
     let id = fastrand::u16(..);
@@ -149,8 +173,8 @@ async fn query_question_and_nameserver(
 
     // The query may be too large, so we need to use TCP.
     if len <= 512 {
-        if let Some(addrs) = question_with_udp(id, buf.clone(), nameserver, resolv).await? {
-            return Ok(addrs);
+        if let Some(answer) = question_with_udp(id, buf.clone(), nameserver, resolv).await? {
+            return Ok(answer);
         }
     }
 
@@ -166,7 +190,7 @@ async fn question_with_udp(
     query: impl IoBuf + Clone,
     nameserver: IpAddr,
     resolv: &ResolvConf,
-) -> Result<Option<Vec<IpAddr>>> {
+) -> Result<Option<Answer>> {
     const RECORD_BUFSIZE: usize = 16;
 
     /// The result of waiting for a packet on a fixed timeout.
@@ -177,7 +201,7 @@ async fn question_with_udp(
         TimedOut,
     }
 
-    let mut addrs = vec![];
+    let mut answer = Answer::default();
 
     // Write the query to the nameserver address.
     let mut socket = UdpSocket::bind(("0.0.0.0", 0)).await?;
@@ -240,10 +264,10 @@ async fn question_with_udp(
         }
 
         // Parse the resulting answer.
-        parse_answers(&message, &mut addrs);
+        parse_answers(&message, &mut answer);
 
         // We got a response, so we're done.
-        return Ok(Some(addrs));
+        return Ok(Some(answer));
     }
 
     // We did not receive a response.
@@ -252,37 +276,27 @@ async fn question_with_udp(
 
 /// Query a nameserver for the given question, using the TCP protocol.
 #[cold]
-async fn question_with_tcp(id: u16, query: impl IoBuf, nameserver: IpAddr) -> Result<Vec<IpAddr>> {
+async fn question_with_tcp(id: u16, query: impl IoBuf, nameserver: IpAddr) -> Result<Answer> {
     const RECORD_BUFSIZE: usize = 16;
 
     if query.bytes_init() > u16::MAX as usize {
         return Err(Error::new(ErrorKind::Other, "query too large for TCP"));
     }
 
-    // Open the socket to the server.
-    let mut socket = TcpStream::connect((nameserver, 53)).await?;
-
-    // Write the length of the query.
-    let len_bytes = (query.bytes_init() as u16).to_be_bytes();
-    socket.write_all(len_bytes.to_vec()).await.0?;
+    // Open the socket to the server. Messages are prefixed by their length.
+    let socket = TcpStream::connect((nameserver, 53)).await?;
+    let mut socket = LengthDelimitedCodec::new()
+        .length_field_length(2)
+        .framed(socket);
 
     // Write the query.
-    socket.write_all(query).await.0?;
+    socket.send_frame(query).await?;
 
-    // Read the length of the response.
-    let len_bytes = vec![0; 2];
-    let (Ok(2), len_bytes) = socket.read(len_bytes).await else {
+    // Read the response.
+    let Some(buf) = socket.next_frame().await? else {
         return Err(Error::new(ErrorKind::Other, "received malformed response."));
     };
 
-    let len = u16::from_be_bytes([len_bytes[0], len_bytes[1]]) as usize;
-
-    let buf = vec![0; len];
-
-    let (n, buf) = socket.read(buf).await;
-
-    let buf = buf.slice(..n?);
-
     // Parse the response.
     let mut q_buf = [Question::default(); 1];
     let mut answers = [ResourceRecord::default(); RECORD_BUFSIZE];
@@ -290,7 +304,7 @@ async fn question_with_tcp(id: u16, query: impl IoBuf, nameserver: IpAddr) -> Re
     let mut additional = [ResourceRecord::default(); RECORD_BUFSIZE];
 
     let message = Message::read(
-        &buf[..len],
+        &buf,
         &mut q_buf,
         &mut answers,
         &mut authority,
@@ -303,27 +317,31 @@ async fn question_with_tcp(id: u16, query: impl IoBuf, nameserver: IpAddr) -> Re
     }
 
     // Parse the answers as address info.
-    let mut addrs = vec![];
-    parse_answers(&message, &mut addrs);
-    Ok(addrs)
+    let mut answer = Answer::default();
+    parse_answers(&message, &mut answer);
+    Ok(answer)
 }
 
-/// Append address information to the vector, given the DNS response.
-fn parse_answers(response: &Message<'_, '_>, addrs: &mut Vec<IpAddr>) {
-    addrs.extend(response.answers().iter().filter_map(|answer| {
-        let data = answer.data();
-
-        // Parse the data as an IP address.
-        match data.len() {
-            4 => {
-                let data: [u8; 4] = data.try_into().unwrap();
-                Some(IpAddr::V4(data.into()))
-            }
-            16 => {
-                let data: [u8; 16] = data.try_into().unwrap();
-                Some(IpAddr::V6(data.into()))
+/// Append address information to the answer, given the DNS response.
+fn parse_answers(response: &Message<'_, '_>, answer: &mut Answer) {
+    let ttl = &mut answer.ttl;
+    answer
+        .addrs
+        .extend(response.answers().iter().filter_map(|record| {
+            let data = record.data();
+            *ttl = (*ttl).min(record.ttl());
+
+            // Parse the data as an IP address.
+            match data.len() {
+                4 => {
+                    let data: [u8; 4] = data.try_into().unwrap();
+                    Some(IpAddr::V4(data.into()))
+                }
+                16 => {
+                    let data: [u8; 16] = data.try_into().unwrap();
+                    Some(IpAddr::V6(data.into()))
+                }
+                _ => None,
             }
-            _ => None,
-        }
-    }));
+        }));
 }
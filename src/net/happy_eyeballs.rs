@@ -0,0 +1,111 @@
+//! Implementation of the "Happy Eyeballs" connection algorithm ([RFC 8305]).
+//!
+//! Instead of trying each resolved address sequentially, connection attempts
+//! are started with a fixed stagger between them, alternating between address
+//! families. The first attempt to succeed is returned, and the remaining
+//! attempts are cancelled by dropping them, which closes their sockets.
+//!
+//! [RFC 8305]: https://datatracker.ietf.org/doc/html/rfc8305
+use std::future::{poll_fn, Future};
+use std::io::Result;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::Poll;
+use std::time::Duration;
+
+use super::socket::{Domain, Protocol, Socket, Type};
+use super::to_socket_addr::ToSocketAddrs;
+use super::utils::invalid_input;
+use crate::time::sleep;
+
+/// The delay recommended by RFC 8305 between connection attempts.
+pub(crate) const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+type Attempt = Pin<Box<dyn Future<Output = Result<Socket>>>>;
+
+/// Races connection attempts to every address yielded by `addr`, starting a new
+/// attempt every `stagger`, or as soon as the previous attempt fails.
+pub(crate) async fn connect<A: ToSocketAddrs>(addr: A, stagger: Duration) -> Result<Socket> {
+    let mut addrs = interleave(addr.to_socket_addrs().await?).into_iter();
+    let mut attempts: Vec<Attempt> = Vec::new();
+    let mut timer: Option<Pin<Box<dyn Future<Output = ()>>>> = None;
+    let mut error = None;
+
+    poll_fn(|cx| loop {
+        let mut start_next = attempts.is_empty();
+
+        let mut i = 0;
+        while i < attempts.len() {
+            match attempts[i].as_mut().poll(cx) {
+                Poll::Ready(Ok(socket)) => return Poll::Ready(Ok(socket)),
+                Poll::Ready(Err(err)) => {
+                    // the attempt failed, so there is no reason to
+                    // wait for the stagger to try the next address.
+                    drop(attempts.swap_remove(i));
+                    error = Some(err);
+                    start_next = true;
+                }
+                Poll::Pending => i += 1,
+            }
+        }
+
+        if let Some(delay) = timer.as_mut() {
+            start_next |= delay.as_mut().poll(cx).is_ready();
+        }
+
+        if !start_next {
+            return Poll::Pending;
+        }
+
+        let Some(addr) = addrs.next() else {
+            timer = None;
+            if attempts.is_empty() {
+                let err = error.take().unwrap_or_else(invalid_input);
+                return Poll::Ready(Err(err));
+            }
+            return Poll::Pending;
+        };
+        attempts.push(Box::pin(attempt(addr)));
+        timer = Some(Box::pin(sleep(stagger)));
+    })
+    .await
+}
+
+async fn attempt(addr: SocketAddr) -> Result<Socket> {
+    let domain = Domain::from(addr);
+    let socket = Socket::new(domain, Type::STREAM, Protocol::TCP).await?;
+    socket.connect(addr).await?;
+    Ok(socket)
+}
+
+/// Sorts the addresses so they alternate between address families, starting
+/// with the family of the first address, as described in section 4 of RFC 8305.
+fn interleave(addrs: impl Iterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+    let mut addrs = addrs.peekable();
+    let prefer_v6 = addrs.peek().is_some_and(|addr| addr.is_ipv6());
+    let (v6, v4): (Vec<_>, Vec<_>) = addrs.partition(SocketAddr::is_ipv6);
+    let (first, second) = if prefer_v6 { (v6, v4) } else { (v4, v6) };
+
+    let mut out = Vec::with_capacity(first.len() + second.len());
+    let mut first = first.into_iter();
+    let mut second = second.into_iter();
+    loop {
+        match (first.next(), second.next()) {
+            (None, None) => return out,
+            (a, b) => out.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+#[test]
+fn interleave_alternates_families() {
+    let v4: SocketAddr = "127.0.0.1:80".parse().unwrap();
+    let v6: SocketAddr = "[::1]:80".parse().unwrap();
+    let other_v4: SocketAddr = "127.0.0.2:80".parse().unwrap();
+
+    let addrs = interleave([v6, other_v4, v4].into_iter());
+    assert_eq!(addrs, [v6, other_v4, v4]);
+
+    let addrs = interleave([v4, other_v4, v6].into_iter());
+    assert_eq!(addrs, [v4, v6, other_v4]);
+}
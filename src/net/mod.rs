@@ -7,20 +7,32 @@
 //!
 //! * [`TcpListener`] and [`TcpStream`] provide functionality for communication over TCP
 //! * [`UdpSocket`] provides functionality for communication over UDP
+//! * [`BufferedStream`] coalesces many small writes to a [`TcpStream`] into a single one
+//! * [`LengthDelimitedCodec`] splits a [`TcpStream`] into length prefixed frames
+//! * [`pipe`](pipe::pipe) creates anonymous pipes for streaming bytes within a process or to child processes
+//! * [`tls`] layers TLS over a [`TcpStream`] using `rustls`, when the `tls` feature is enabled
 
-#[cfg(io_uring)]
-mod dns;
-pub(crate) mod pipe;
+mod bind_options;
+mod buffered;
+mod codec;
+pub(crate) mod dns;
+mod happy_eyeballs;
+pub mod pipe;
 mod socket;
 mod tcp_listener;
 mod tcp_stream;
+#[cfg(feature = "tls")]
+pub mod tls;
 mod to_socket_addr;
 
 mod udp;
 pub(crate) mod utils;
 
+pub use bind_options::BindOptions;
+pub use buffered::BufferedStream;
+pub use codec::{Framed, LengthDelimitedCodec};
 pub use std::net::{Shutdown, SocketAddr};
 pub use tcp_listener::TcpListener;
-pub use tcp_stream::TcpStream;
+pub use tcp_stream::{RecvMulti, TcpStream};
 pub use to_socket_addr::ToSocketAddrs;
 pub use udp::UdpSocket;
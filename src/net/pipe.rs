@@ -1,12 +1,20 @@
 #![allow(warnings)]
-use std::io::Error;
-use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+//! Anonymous pipes.
+//!
+//! A pipe is a unidirectional byte stream, with a reading end and a writing end.
+//! They are useful for in-process byte streaming, and for communicating with
+//! child processes through their standard input and output.
+use std::fmt::{self, Debug};
+use std::io::{Error, ErrorKind, Result};
+use std::mem::forget;
+use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
 
 // use io_uring::types::Fd;
 
 use crate::buf::{IoBuf, IoBufMut};
 
 use crate::reactor::op::{self, write_nonblock};
+use crate::runtime::current;
 use crate::utils::syscall;
 
 pub(crate) struct Sender {
@@ -16,37 +24,193 @@ pub(crate) struct Receiver {
     fd: OwnedFd,
 }
 
-pub(crate) fn pipe() -> Result<(Sender, Receiver), Error> {
+/// Creates a close-on-exec, nonblocking pipe, returning the reading and writing ends.
+fn pipe_fds() -> Result<(OwnedFd, OwnedFd)> {
     let mut fds = [-1, -1];
 
-    syscall!(pipe, &mut fds[0])?;
+    #[cfg(target_os = "linux")]
+    syscall!(pipe2, fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK)?;
 
-    let sender = Sender {
-        fd: unsafe { OwnedFd::from_raw_fd(fds[1]) },
-    };
+    #[cfg(not(target_os = "linux"))]
+    syscall!(pipe, fds.as_mut_ptr())?;
+
+    let reader = unsafe { OwnedFd::from_raw_fd(fds[0]) };
+    let writer = unsafe { OwnedFd::from_raw_fd(fds[1]) };
+
+    #[cfg(not(target_os = "linux"))]
+    for fd in [&reader, &writer] {
+        syscall!(fcntl, fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC)?;
+        op::make_nonblocking(fd)?;
+    }
+    Ok((reader, writer))
+}
+
+/// Creates the pipe used by the runtime to receive wakeups from other threads.
+///
+/// The runtime keeps a read on the receiving end submitted to the reactor, which is
+/// re-armed after every wakeup, so writing to the pipe interrupts a blocking wait.
+pub(crate) fn wakeup_pipe() -> Result<(Sender, Receiver)> {
+    let (receiver, sender) = pipe_fds()?;
+    Ok((Sender { fd: sender }, Receiver { fd: receiver }))
+}
 
-    let receiver = Receiver {
-        fd: unsafe { OwnedFd::from_raw_fd(fds[0]) },
+/// Creates an anonymous pipe, returning its reading and writing ends.
+///
+/// Data written to the [`PipeWriter`] can be read from the [`PipeReader`].
+/// Both ends are closed when dropped.
+///
+/// # Examples
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::net::pipe::pipe;
+///
+/// let (mut reader, mut writer) = pipe().await?;
+/// writer.write_all("hello").await.0?;
+/// drop(writer);
+///
+/// let (n, buf) = reader.read(vec![0; 16]).await;
+/// assert_eq!(&buf[..n?], b"hello");
+/// # std::io::Result::Ok(()) }).unwrap().unwrap();
+/// ```
+///
+/// # Errors
+/// This function errors if the process or the system ran out of file descriptors.
+pub async fn pipe() -> Result<(PipeReader, PipeWriter)> {
+    let (reader, writer) = pipe_fds()?;
+    let reader = PipeReader {
+        fd: reader.into_raw_fd(),
+    };
+    let writer = PipeWriter {
+        fd: writer.into_raw_fd(),
     };
+    Ok((reader, writer))
+}
+
+/// The reading end of a pipe, created with the [`pipe`] function.
+pub struct PipeReader {
+    fd: RawFd,
+}
+
+/// The writing end of a pipe, created with the [`pipe`] function.
+pub struct PipeWriter {
+    fd: RawFd,
+}
+
+impl PipeReader {
+    /// Read some data from the pipe into the buffer, returning the original buffer and
+    /// quantity of data read. A return value of 0 bytes read means the writing end of
+    /// the pipe has been closed.
+    pub async fn read<B: IoBufMut>(&mut self, buf: B) -> (Result<usize>, B) {
+        op::read_at(self.fd, buf, -1).await
+    }
 
-    op::make_nonblocking(&sender.fd)?;
-    op::make_nonblocking(&receiver.fd)?;
-    Ok((sender, receiver))
+    /// Closes the file descriptor. Calling this method is recommended
+    /// over letting the value be dropped.
+    pub async fn close(self) -> Result<()> {
+        op::close(self.into_raw_fd()).await
+    }
 }
 
+impl PipeWriter {
+    /// Write some data to the pipe from the buffer, returning the original buffer and
+    /// quantity of data written.
+    pub async fn write<B: IoBuf>(&mut self, buf: B) -> (Result<usize>, B) {
+        op::write_at(self.fd, buf, -1).await
+    }
+
+    /// Attempts to write an entire buffer to the pipe.
+    ///
+    /// This method will continuously call [`write`] until there is no more data to be
+    /// written or an error is returned. This method will not return until the entire
+    /// buffer has been successfully written or an error has occurred.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the first error that [`write`] returns.
+    ///
+    /// [`write`]: Self::write
+    pub async fn write_all<B: IoBuf>(&mut self, mut buf: B) -> (Result<()>, B) {
+        let mut n = 0;
+        while n < buf.bytes_init() {
+            let (written, buf_) = self.write(buf.slice(n..)).await;
+            buf = buf_.into_inner();
+            match written {
+                Ok(0) => {
+                    let err = Error::new(ErrorKind::WriteZero, "failed to write whole buffer");
+                    return (Err(err), buf);
+                }
+                Ok(written) => n += written,
+                Err(err) => return (Err(err), buf),
+            }
+        }
+        (Ok(()), buf)
+    }
+
+    /// Closes the file descriptor. Calling this method is recommended
+    /// over letting the value be dropped.
+    pub async fn close(self) -> Result<()> {
+        op::close(self.into_raw_fd()).await
+    }
+}
+
+macro_rules! impl_fd {
+    ($name: ident) => {
+        impl Drop for $name {
+            fn drop(&mut self) {
+                let Some(rt) = current() else {
+                    unsafe { OwnedFd::from_raw_fd(self.fd) };
+                    return;
+                };
+                rt.detach_cleanup(op::close(self.fd));
+            }
+        }
+
+        impl AsRawFd for $name {
+            fn as_raw_fd(&self) -> RawFd {
+                self.fd
+            }
+        }
+
+        impl IntoRawFd for $name {
+            fn into_raw_fd(self) -> RawFd {
+                let fd = self.fd;
+                forget(self);
+                fd
+            }
+        }
+
+        impl FromRawFd for $name {
+            unsafe fn from_raw_fd(fd: RawFd) -> Self {
+                $name { fd }
+            }
+        }
+
+        impl Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("fd", &self.fd)
+                    .finish()
+            }
+        }
+    };
+}
+
+impl_fd!(PipeReader);
+impl_fd!(PipeWriter);
+
 impl Sender {
-    pub async fn write<B: IoBuf>(&self, buf: B) -> (Result<usize, Error>, B) {
+    pub async fn write<B: IoBuf>(&self, buf: B) -> (Result<usize>, B) {
         let fd = self.fd.as_raw_fd();
         op::write_at(fd, buf, -1).await
     }
 
-    pub async fn write_nonblock(&self, buf: &[u8]) -> Result<usize, Error> {
+    pub async fn write_nonblock(&self, buf: &[u8]) -> Result<usize> {
         let fd = self.fd.as_raw_fd();
         let out = write_nonblock(fd, buf.as_ptr(), buf.len()).await;
         out
     }
 
-    pub fn write_block(&self, buf: &[u8]) -> Result<usize, Error> {
+    pub fn write_block(&self, buf: &[u8]) -> Result<usize> {
         op::make_blocking(&self.fd)?;
         let fd = self.fd.as_raw_fd();
         let len = buf.len();
@@ -58,12 +222,12 @@ impl Sender {
 }
 
 impl Receiver {
-    pub async fn read<B: IoBufMut>(&self, buf: B) -> (Result<usize, Error>, B) {
+    pub async fn read<B: IoBufMut>(&self, buf: B) -> (Result<usize>, B) {
         let fd = self.fd.as_raw_fd();
         op::read_at(fd, buf, -1).await
     }
 
-    pub async fn read_nonblock(&self, buf: &mut [u8]) -> Result<usize, Error> {
+    pub async fn read_nonblock(&self, buf: &mut [u8]) -> Result<usize> {
         let fd = self.fd.as_raw_fd();
         let len = buf.len();
         let buf = buf.as_mut_ptr();
@@ -76,7 +240,7 @@ fn pipe_smoke_test() {
     println!("Started 10");
     crate::block_on(async {
         println!("pipe()");
-        let (tx, rx) = pipe().unwrap();
+        let (tx, rx) = wakeup_pipe().unwrap();
         let buf = vec![1, 2, 3];
         println!("writing");
         let (res, _) = tx.write(buf).await;
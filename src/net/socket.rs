@@ -1,18 +1,19 @@
 #![allow(clippy::upper_case_acronyms)]
-use std::io::Result;
-use std::mem::{forget, size_of_val};
+use std::io::{Error, ErrorKind, Result};
+use std::mem::{forget, size_of, size_of_val};
 use std::net::{Shutdown, SocketAddr};
-use std::os::fd::{FromRawFd, IntoRawFd};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd};
+use std::time::Duration;
 
 use crate::buf::{IoBuf, IoBufMut};
-use crate::detach;
 use crate::reactor::op::{self};
+use crate::runtime::current_unwrap;
 use crate::utils::futures::not_thread_safe;
 use crate::utils::syscall;
 
-use libc::{SOL_SOCKET, SO_REUSEPORT};
+use libc::{SOL_SOCKET, SO_LINGER, SO_REUSEADDR, SO_REUSEPORT};
 
-use super::utils::socket_addr;
+use super::utils::{socket_addr, to_std_socket_addr};
 
 #[repr(i32)]
 pub enum Domain {
@@ -59,7 +60,7 @@ impl Socket {
     }
 
     pub async fn write<B: IoBuf>(&self, buf: B) -> (Result<usize>, B) {
-        op::write_at(self.fd, buf, 0).await
+        op::send(self.fd, buf).await
     }
 
     pub async fn recv<B: IoBufMut>(&self, buf: B) -> (Result<usize>, B) {
@@ -70,6 +71,10 @@ impl Socket {
         op::connect(self.fd, addr).await
     }
 
+    pub async fn connect_timeout(&self, addr: SocketAddr, dur: Duration) -> Result<()> {
+        op::connect_timeout(self.fd, addr, dur).await
+    }
+
     pub async fn send_to<B: IoBuf>(&self, buf: B, addr: SocketAddr) -> (Result<usize>, B) {
         op::send_to(self.fd, buf, addr).await
     }
@@ -86,26 +91,140 @@ impl Socket {
     }
 
     pub fn listen(&self, backlog: u32) -> Result<()> {
-        syscall!(listen, self.fd, backlog as i32)?;
+        let backlog = backlog.min(i32::MAX as u32) as i32;
+        syscall!(listen, self.fd, backlog)?;
         Ok(())
     }
 
-    pub fn set_reuseport(&self) -> Result<()> {
-        let optval = &1;
+    /// Sets `SO_REUSEADDR`, which allows binding to an address that still has
+    /// connections in the `TIME_WAIT` state.
+    pub fn set_reuseaddr(&self, on: bool) -> Result<()> {
+        self.set_flag(SOL_SOCKET, SO_REUSEADDR, on)
+    }
+
+    /// Sets `SO_REUSEPORT`, which allows multiple sockets to be bound
+    /// to the same address.
+    pub fn set_reuseport(&self, on: bool) -> Result<()> {
+        self.set_flag(SOL_SOCKET, SO_REUSEPORT, on)
+    }
+
+    /// Sets `SO_BINDTODEVICE`, which restricts the socket to the given network
+    /// interface, or removes the restriction when `None`. Requires `CAP_NET_RAW`,
+    /// so `EPERM` is reported with a message naming the missing capability.
+    #[cfg(target_os = "linux")]
+    pub fn set_bind_device(&self, interface: Option<&str>) -> Result<()> {
+        let name = interface.unwrap_or_default().as_bytes();
+        if name.len() >= libc::IFNAMSIZ || name.contains(&0) {
+            let msg = "invalid network interface name";
+            return Err(Error::new(ErrorKind::InvalidInput, msg));
+        }
+        let res = syscall!(
+            setsockopt,
+            self.fd,
+            SOL_SOCKET,
+            libc::SO_BINDTODEVICE,
+            name.as_ptr().cast(),
+            name.len() as u32
+        );
+        let msg = "SO_BINDTODEVICE requires the CAP_NET_RAW capability";
+        requires_capability(res.map(drop), msg)
+    }
+
+    /// Sets `IP_TRANSPARENT`, or `IPV6_TRANSPARENT` for IPv6 sockets, which allows
+    /// binding to non-local addresses and accepting connections redirected by
+    /// `TPROXY`. Requires `CAP_NET_ADMIN`, so `EPERM` is reported with a message
+    /// naming the missing capability.
+    #[cfg(target_os = "linux")]
+    pub fn set_ip_transparent(&self, on: bool) -> Result<()> {
+        let domain: i32 = self.get_opt(SOL_SOCKET, libc::SO_DOMAIN)?;
+        let res = if domain == libc::AF_INET6 {
+            self.set_flag(libc::SOL_IPV6, libc::IPV6_TRANSPARENT, on)
+        } else {
+            self.set_flag(libc::SOL_IP, libc::IP_TRANSPARENT, on)
+        };
+        let msg = "IP_TRANSPARENT requires the CAP_NET_ADMIN capability";
+        requires_capability(res, msg)
+    }
+
+    /// Sets `SO_LINGER`. The linger time is truncated to whole seconds.
+    pub fn set_linger(&self, dur: Option<Duration>) -> Result<()> {
+        let linger = libc::linger {
+            l_onoff: dur.is_some().into(),
+            l_linger: dur.map_or(0, |dur| dur.as_secs().min(i32::MAX as u64) as i32),
+        };
+        self.set_opt(SOL_SOCKET, SO_LINGER, &linger)
+    }
+
+    pub fn linger(&self) -> Result<Option<Duration>> {
+        let linger: libc::linger = self.get_opt(SOL_SOCKET, SO_LINGER)?;
+        let secs = linger.l_linger as u64;
+        Ok((linger.l_onoff != 0).then(|| Duration::from_secs(secs)))
+    }
+
+    /// Returns the address the socket is bound to, using `getsockname`.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        // Safety: zeroed is a valid value for sockaddr_storage
+        let mut addr: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+        let mut len = size_of_val(&addr) as libc::socklen_t;
+        syscall!(
+            getsockname,
+            self.fd,
+            &mut addr as *mut _ as *mut _,
+            &mut len
+        )?;
+        // Safety: sockaddr_storage is large enough for any socket address
+        to_std_socket_addr(unsafe { &*(&addr as *const libc::sockaddr_storage).cast() })
+    }
+
+    /// Returns the address the socket is connected to, using `getpeername`.
+    /// Errors with `NotConnected` if the socket is not connected.
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        // Safety: zeroed is a valid value for sockaddr_storage
+        let mut addr: libc::sockaddr_storage = unsafe { std::mem::zeroed() };
+        let mut len = size_of_val(&addr) as libc::socklen_t;
+        syscall!(
+            getpeername,
+            self.fd,
+            &mut addr as *mut _ as *mut _,
+            &mut len
+        )?;
+        // Safety: sockaddr_storage is large enough for any socket address
+        to_std_socket_addr(unsafe { &*(&addr as *const libc::sockaddr_storage).cast() })
+    }
+
+    fn set_flag(&self, level: i32, name: i32, on: bool) -> Result<()> {
+        let optval: i32 = on.into();
+        self.set_opt(level, name, &optval)
+    }
+
+    fn set_opt<T>(&self, level: i32, name: i32, optval: &T) -> Result<()> {
         let size = size_of_val(optval) as u32;
-        let fd = self.fd;
-        dbg!(self.fd);
         syscall!(
             setsockopt,
-            fd,
-            SOL_SOCKET,
-            SO_REUSEPORT,
+            self.fd,
+            level,
+            name,
             optval as *const _ as *const _,
             size
         )?;
         Ok(())
     }
 
+    fn get_opt<T>(&self, level: i32, name: i32) -> Result<T> {
+        let mut optval = std::mem::MaybeUninit::<T>::zeroed();
+        let mut size = size_of::<T>() as u32;
+        syscall!(
+            getsockopt,
+            self.fd,
+            level,
+            name,
+            optval.as_mut_ptr().cast(),
+            &mut size
+        )?;
+        // Safety: initialized by the kernel, and zeroed otherwise.
+        Ok(unsafe { optval.assume_init() })
+    }
+
     pub async fn accept(&self) -> Result<(Socket, SocketAddr)> {
         let (fd, addr) = op::accept(self.fd).await?;
         let fd = fd.into_raw_fd();
@@ -120,6 +239,17 @@ impl Socket {
     }
 }
 
+/// Replaces `EPERM` errors with a message naming the capability an option requires.
+#[cfg(target_os = "linux")]
+fn requires_capability(res: Result<()>, msg: &str) -> Result<()> {
+    match res {
+        Err(err) if err.raw_os_error() == Some(libc::EPERM) => {
+            Err(Error::new(ErrorKind::PermissionDenied, msg))
+        }
+        res => res,
+    }
+}
+
 impl FromRawFd for Socket {
     unsafe fn from_raw_fd(fd: std::os::fd::RawFd) -> Self {
         Self { fd }
@@ -132,10 +262,21 @@ impl IntoRawFd for Socket {
         fd
     }
 }
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.fd
+    }
+}
+impl AsFd for Socket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // Safety: the file descriptor is open for as long as the socket is alive
+        unsafe { BorrowedFd::borrow_raw(self.fd) }
+    }
+}
 
 impl Drop for Socket {
     fn drop(&mut self) {
-        detach(op::close(self.fd));
+        current_unwrap("detach").detach_cleanup(op::close(self.fd));
     }
 }
 
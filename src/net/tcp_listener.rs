@@ -1,13 +1,11 @@
-use crate::net::socket::{Domain, Protocol, Type};
-use crate::net::ToSocketAddrs;
+use crate::net::{BindOptions, ToSocketAddrs};
 
 use std::fmt::Debug;
 use std::io::Result;
 use std::net::SocketAddr;
-use std::os::fd::{FromRawFd, IntoRawFd};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd};
 
 use super::socket::Socket;
-use super::to_socket_addr::try_until_success;
 use super::TcpStream;
 
 /// A TCP socket server, listening for connections.
@@ -52,7 +50,7 @@ use super::TcpStream;
 /// }
 /// ```
 pub struct TcpListener {
-    socket: Socket,
+    pub(crate) socket: Socket,
 }
 
 impl TcpListener {
@@ -73,6 +71,9 @@ impl TcpListener {
     /// none of the addresses succeed in creating a listener, the error returned
     /// from the last attempt (the last address) is returned.
     ///
+    /// The listener is bound with both `SO_REUSEADDR` and `SO_REUSEPORT` enabled.
+    /// To control these options, use [`BindOptions`] instead.
+    ///
     /// # Examples
     ///
     /// Creates a TCP listener bound to `127.0.0.1:80`:
@@ -104,15 +105,7 @@ impl TcpListener {
     /// }
     /// ```
     pub async fn bind<A: ToSocketAddrs>(addr: A) -> Result<TcpListener> {
-        try_until_success(addr, |addr| async move {
-            let domain = Domain::from(addr);
-            let socket = Socket::new(domain, Type::STREAM, Protocol::TCP).await?;
-            socket.set_reuseport()?;
-            socket.bind(&addr)?;
-            socket.listen(8192)?;
-            Ok(TcpListener { socket })
-        })
-        .await
+        BindOptions::new().bind(addr).await
     }
 
     /// Accept a new incoming connection from this listener.
@@ -121,6 +114,9 @@ impl TcpListener {
     /// is established. When established, the corresponding [`TcpStream`] and the
     /// remote peer's address will be returned.
     ///
+    /// The accepted stream is created with the close-on-exec flag set, so it is
+    /// not inherited by child processes spawned with [`process::Command`](crate::process::Command).
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -138,7 +134,7 @@ impl TcpListener {
     /// ```
     pub async fn accept(&self) -> Result<(TcpStream, SocketAddr)> {
         let (socket, addr) = self.socket.accept().await?;
-        Ok((TcpStream { socket }, addr))
+        Ok((TcpStream::from_socket(socket), addr))
     }
     /// Closes the file descriptor. Calling this method is recommended
     /// over letting the value be dropped.
@@ -199,6 +195,20 @@ impl TcpListener {
         Self { socket }
     }
 
+    /// Sets the `IP_TRANSPARENT` option on this socket, which allows it to accept
+    /// connections redirected to it by a `TPROXY` firewall rule, keeping the original
+    /// destination address. To bind to a non-local address, the option must be set
+    /// before binding, with [`BindOptions::ip_transparent`].
+    ///
+    /// # Errors
+    /// Setting this option requires the `CAP_NET_ADMIN` capability. An error of kind
+    /// [`PermissionDenied`](std::io::ErrorKind::PermissionDenied) is returned for
+    /// unprivileged processes. This option is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn set_ip_transparent(&self, on: bool) -> Result<()> {
+        self.socket.set_ip_transparent(on)
+    }
+
     /// Returns the local address that this listener is bound to.
     pub fn local_addr(&self) -> Result<()> {
         todo!()
@@ -219,6 +229,18 @@ impl IntoRawFd for TcpListener {
     }
 }
 
+impl AsRawFd for TcpListener {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl AsFd for TcpListener {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.socket.as_fd()
+    }
+}
+
 impl Debug for TcpListener {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("TcpListener")
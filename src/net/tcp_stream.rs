@@ -1,14 +1,18 @@
+use std::cell::Cell;
 use std::fmt::Debug;
 use std::io::{Error, ErrorKind, Result};
-use std::net::Shutdown;
-use std::os::fd::{FromRawFd, IntoRawFd};
+use std::marker::PhantomData;
+use std::net::{Shutdown, SocketAddr};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd};
+use std::time::Duration;
 
 use crate::buf::{IoBuf, IoBufMut};
 use crate::reactor::op;
 use crate::utils::futures::not_thread_safe;
 
+use super::happy_eyeballs::{self, CONNECTION_ATTEMPT_DELAY};
 use super::socket::{Domain, Protocol, Socket, Type};
-use super::to_socket_addr::{try_until_success, ToSocketAddrs};
+use super::to_socket_addr::ToSocketAddrs;
 
 /// A TCP stream between a local and a remote socket.
 ///
@@ -55,6 +59,7 @@ use super::to_socket_addr::{try_until_success, ToSocketAddrs};
 /// [writing]: TcpStream::write
 pub struct TcpStream {
     pub(crate) socket: Socket,
+    read_timeout: Cell<Option<Duration>>,
 }
 
 impl TcpStream {
@@ -64,10 +69,14 @@ impl TcpStream {
     /// [`ToSocketAddrs`] trait can be supplied for the address; see this trait
     /// documentation for concrete examples.
     ///
-    /// If `addr` yields multiple addresses, `connect` will be attempted with
-    /// each of the addresses until a connection is successful. If none of
-    /// the addresses result in a successful connection, the error returned from
-    /// the last connection attempt (the last address) is returned.
+    /// If `addr` yields multiple addresses, connection attempts are raced
+    /// following the "Happy Eyeballs" algorithm ([RFC 8305]): addresses are
+    /// interleaved by family, and a new attempt is started every 250ms, or as
+    /// soon as the previous one fails. The first connection to succeed is
+    /// returned, and the remaining attempts are cancelled. If none of the
+    /// addresses result in a successful connection, the error returned from
+    /// the last failed attempt is returned. Use [`connect_with`] to
+    /// configure the delay between attempts.
     ///
     /// # Examples
     ///
@@ -104,20 +113,85 @@ impl TcpStream {
     ///     Ok(())
     /// }
     /// ```
+    ///
+    /// [RFC 8305]: https://datatracker.ietf.org/doc/html/rfc8305
+    /// [`connect_with`]: TcpStream::connect_with
     pub async fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self> {
-        let socket = try_until_success(addr, |addr| async move {
-            let domain = Domain::from(addr);
-            let ty = Type::STREAM;
-            let proto = Protocol::TCP;
-            let socket = Socket::new(domain, ty, proto).await?;
-            dbg!();
-            socket.connect(addr).await?;
+        Self::connect_with(addr, CONNECTION_ATTEMPT_DELAY).await
+    }
 
-            Ok(socket)
-        })
-        .await?;
-        Ok(TcpStream { socket })
+    /// Opens a TCP connection to a remote host, waiting `stagger` between
+    /// connection attempts when `addr` yields multiple addresses.
+    ///
+    /// See [`connect`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use osiris::net::TcpStream;
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let stagger = Duration::from_millis(100);
+    ///     let stream = TcpStream::connect_with("localhost:8080", stagger).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    /// [`connect`]: TcpStream::connect
+    pub async fn connect_with<A: ToSocketAddrs>(addr: A, stagger: Duration) -> Result<Self> {
+        let socket = happy_eyeballs::connect(addr, stagger).await?;
+        Ok(TcpStream::from_socket(socket))
     }
+
+    /// Opens a TCP connection to a remote host with a timeout.
+    ///
+    /// Unlike [`connect`], this takes a single address, since the timeout applies
+    /// to a single connection attempt. If the connection is not established within
+    /// `timeout`, an error of kind [`ErrorKind::TimedOut`] is returned.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// When io-uring is used, the timeout is linked to the connect operation
+    /// (`IORING_OP_LINK_TIMEOUT`, kernel 5.5+), so the kernel itself cancels the
+    /// connection attempt when it expires, without waking the runtime in between.
+    ///
+    /// # Errors
+    ///
+    /// An error of kind [`ErrorKind::InvalidInput`] is returned if the zero
+    /// [`Duration`] is passed to this method.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use osiris::net::{SocketAddr, TcpStream};
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
+    ///     let stream = TcpStream::connect_timeout(&addr, Duration::from_secs(1)).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    /// [`connect`]: TcpStream::connect
+    pub async fn connect_timeout(addr: &SocketAddr, timeout: Duration) -> Result<TcpStream> {
+        if timeout.is_zero() {
+            let msg = "cannot set a 0 duration timeout";
+            return Err(Error::new(ErrorKind::InvalidInput, msg));
+        }
+        let socket = Socket::new(Domain::from(*addr), Type::STREAM, Protocol::TCP).await?;
+        socket.connect_timeout(*addr, timeout).await?;
+        Ok(TcpStream::from_socket(socket))
+    }
+
+    pub(crate) fn from_socket(socket: Socket) -> TcpStream {
+        TcpStream {
+            socket,
+            read_timeout: Cell::new(None),
+        }
+    }
+
     /// Read some data from the stream into the buffer, returning the original buffer and quantity of data read.
     ///
     /// # Example
@@ -134,7 +208,252 @@ impl TcpStream {
     /// }
     /// ```
     pub async fn read<B: IoBufMut>(&mut self, buf: B) -> (Result<usize>, B) {
-        op::read_at(self.socket.fd, buf, 0).await
+        match self.read_timeout.get() {
+            Some(dur) => op::read_timeout(self.socket.fd, buf, 0, dur).await,
+            None => op::read_at(self.socket.fd, buf, 0).await,
+        }
+    }
+
+    /// Reads once from the stream into a newly allocated buffer of the given capacity,
+    /// returning the buffer truncated to the bytes read. An empty buffer means the
+    /// peer closed the connection.
+    ///
+    /// This allocates a buffer on every call, and the buffer keeps its full capacity
+    /// even if few bytes were read. It is convenient when buffers aren't reused, but
+    /// hot paths should prefer [`read`] with a reused buffer.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use osiris::net::TcpStream;
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let mut stream = TcpStream::connect("127.0.0.1:8080").await?;
+    ///     let request = stream.read_buf(1024).await?;
+    ///     println!("received {} bytes", request.len());
+    ///     Ok(())
+    /// }
+    /// ```
+    /// [`read`]: Self::read
+    pub async fn read_buf(&mut self, capacity: usize) -> Result<Vec<u8>> {
+        let (res, buf) = self.read(Vec::with_capacity(capacity)).await;
+        res?;
+        Ok(buf)
+    }
+
+    /// Starts receiving the data that arrives on the stream within `window`, returning
+    /// a [`RecvMulti`] that yields it in chunks of up to 4KiB as it arrives. Once the
+    /// window is over, [`RecvMulti::next`] fails with an error of kind
+    /// [`ErrorKind::TimedOut`].
+    ///
+    /// The window is not extended when data arrives. To detect idle connections,
+    /// receive over consecutive windows, and close the connection after a window
+    /// without data. This is cheaper than setting a [read timeout] on every read.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On io-uring, the whole window is served by a single multishot receive linked
+    /// to a timeout, which requires Linux 6.0 or later. The data is received into
+    /// 32KiB of buffers provided to the kernel, and copied out of them as it is
+    /// yielded. On older kernels, and on other platforms, every chunk is received
+    /// with a separate read.
+    ///
+    /// # Cancellation
+    ///
+    /// Dropping the [`RecvMulti`] cancels the receive, and any data received but not
+    /// yet yielded is lost. Its buffers are released in the background once the
+    /// kernel acknowledges the cancellation.
+    ///
+    /// # Errors
+    ///
+    /// An error of kind [`ErrorKind::InvalidInput`] is returned if the zero
+    /// [`Duration`] is passed to this method.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use osiris::net::TcpStream;
+    /// use std::io::ErrorKind;
+    /// use std::time::Duration;
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let mut stream = TcpStream::connect("127.0.0.1:8080").await?;
+    ///     loop {
+    ///         let mut recv = stream.recv_multi_timeout(Duration::from_secs(60)).await?;
+    ///         let mut idle = true;
+    ///         loop {
+    ///             match recv.next().await {
+    ///                 Ok(Some(chunk)) => {
+    ///                     idle = false;
+    ///                     println!("received {} bytes", chunk.len());
+    ///                 }
+    ///                 Ok(None) => return Ok(()),
+    ///                 Err(err) if err.kind() == ErrorKind::TimedOut => break,
+    ///                 Err(err) => return Err(err),
+    ///             }
+    ///         }
+    ///         if idle {
+    ///             drop(recv);
+    ///             return stream.close().await;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// [read timeout]: Self::set_read_timeout
+    pub async fn recv_multi_timeout(&mut self, window: Duration) -> Result<RecvMulti<'_>> {
+        if window.is_zero() {
+            let msg = "cannot set a 0 duration timeout";
+            return Err(Error::new(ErrorKind::InvalidInput, msg));
+        }
+        let recv = op::recv_multi(self.socket.fd, window).await?;
+        Ok(RecvMulti {
+            recv,
+            _stream: PhantomData,
+        })
+    }
+
+    /// Sets the read timeout to the timeout specified.
+    ///
+    /// If the value specified is [`None`], then [`read`] calls will wait
+    /// indefinitely. Otherwise, a [`read`] that doesn't complete within the timeout
+    /// fails with an error of kind [`ErrorKind::TimedOut`]. The timeout applies to
+    /// every subsequent read, including each of the reads performed by [`read_exact`].
+    ///
+    /// When a read times out, the in-flight read is cancelled before the error is
+    /// returned, so no data is lost and the stream remains usable.
+    ///
+    /// # Errors
+    ///
+    /// An error of kind [`ErrorKind::InvalidInput`] is returned if the zero
+    /// [`Duration`] is passed to this method.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use osiris::net::TcpStream;
+    /// use std::io::ErrorKind;
+    /// use std::time::Duration;
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let mut stream = TcpStream::connect("127.0.0.1:8080").await?;
+    ///     stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    ///     let (res, _) = stream.read(vec![0; 128]).await;
+    ///     if let Err(err) = res {
+    ///         assert_eq!(err.kind(), ErrorKind::TimedOut);
+    ///         println!("closing idle connection");
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    /// [`read`]: Self::read
+    /// [`read_exact`]: Self::read_exact
+    pub fn set_read_timeout(&self, dur: Option<Duration>) -> Result<()> {
+        if dur.is_some_and(|dur| dur.is_zero()) {
+            let msg = "cannot set a 0 duration timeout";
+            return Err(Error::new(ErrorKind::InvalidInput, msg));
+        }
+        self.read_timeout.set(dur);
+        Ok(())
+    }
+
+    /// Returns the read timeout of this stream, as set by [`set_read_timeout`].
+    ///
+    /// [`set_read_timeout`]: Self::set_read_timeout
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.read_timeout.get()
+    }
+
+    /// Sets the value of the `SO_LINGER` option on this socket.
+    ///
+    /// This value controls how the socket is closed when data remains to be sent.
+    /// If linger is set to `None`, closing the socket returns immediately, and the
+    /// remaining data is sent in the background by the kernel. If it is set to
+    /// `Some(dur)`, closing the socket waits until the data has been sent, or until
+    /// `dur` has elapsed, after which the remaining data is discarded. In particular,
+    /// `Some(Duration::ZERO)` discards any unsent data, and resets the connection
+    /// with an `RST` instead of a graceful shutdown. The duration is truncated to
+    /// whole seconds.
+    ///
+    /// # Interaction with `close`
+    ///
+    /// When io-uring is used, the wait happens inside the close operation, so it does
+    /// not block the thread, but [`close`](Self::close) will not complete until the
+    /// linger period is over. Otherwise, the close syscall blocks the thread for the
+    /// duration of the linger period, so a nonzero linger should be avoided. Streams
+    /// that are dropped instead of closed are closed in the background.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use osiris::net::TcpStream;
+    /// use std::time::Duration;
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let stream = TcpStream::connect("127.0.0.1:8080").await?;
+    ///     // reset the connection on close instead of shutting it down
+    ///     stream.set_linger(Some(Duration::ZERO))?;
+    ///     stream.close().await
+    /// }
+    /// ```
+    pub fn set_linger(&self, linger: Option<Duration>) -> Result<()> {
+        self.socket.set_linger(linger)
+    }
+
+    /// Gets the value of the `SO_LINGER` option on this socket.
+    ///
+    /// For more information about this option, see [`set_linger`](Self::set_linger).
+    pub fn linger(&self) -> Result<Option<Duration>> {
+        self.socket.linger()
+    }
+
+    /// Reads the exact number of bytes required to fill the buffer.
+    ///
+    /// This method will continuously call [`read`] until the whole capacity of the
+    /// buffer, as reported by `bytes_total`, has been filled or an error is returned.
+    ///
+    /// # Errors
+    ///
+    /// If the stream is closed before the buffer is filled, an error of the kind
+    /// [`ErrorKind::UnexpectedEof`] is returned. Otherwise, this function returns
+    /// the first error that [`read`] returns. In both cases, the bytes read so far
+    /// are initialized in the returned buffer.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use osiris::net::TcpStream;
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let mut stream = TcpStream::connect("127.0.0.1:8080").await?;
+    ///     let (res, header) = stream.read_exact(vec![0; 4]).await;
+    ///     res?;
+    ///     let len = u32::from_be_bytes(header.try_into().unwrap());
+    ///     println!("the next frame is {len} bytes long");
+    ///     Ok(())
+    /// }
+    /// ```
+    /// [`read`]: Self::read
+    pub async fn read_exact<B: IoBufMut>(&mut self, mut buf: B) -> (Result<()>, B) {
+        let mut n = 0;
+        while n < buf.bytes_total() {
+            let (read, buf_) = self.read(buf.slice(n..)).await;
+            buf = buf_.into_inner();
+            match read {
+                Ok(0) => {
+                    return (
+                        Err(Error::new(
+                            ErrorKind::UnexpectedEof,
+                            "failed to fill whole buffer",
+                        )),
+                        buf,
+                    )
+                }
+                Ok(read) => n += read,
+                Err(err) => return (Err(err), buf),
+            }
+        }
+        (Ok(()), buf)
     }
     /// Write some data to the stream from the buffer, returning the original buffer and quantity of data written.
     ///
@@ -152,7 +471,37 @@ impl TcpStream {
     /// }
     /// ```
     pub async fn write<B: IoBuf>(&mut self, buf: B) -> (Result<usize>, B) {
-        op::write_at(self.socket.fd, buf, 0).await
+        op::send(self.socket.fd, buf).await
+    }
+
+    /// Write some data to the stream from the buffer without copying it into kernel
+    /// memory, returning the original buffer and quantity of data written.
+    ///
+    /// This is useful for bulk transfers of large buffers, for small buffers
+    /// [`write`] is likely to be faster. The buffer is returned only after the
+    /// kernel notifies it is no longer using it.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// Zero copy sends require io-uring on Linux 6.0 or later. On older kernels,
+    /// and on other platforms, this method falls back to a regular [`write`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use osiris::net::TcpStream;
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let mut stream = TcpStream::connect("127.0.0.1:8080").await?;
+    ///     let buf = vec![0; 1 << 20];
+    ///     let (n, buf) = stream.write_zc(buf).await;
+    ///     println!("sent {} bytes", n?);
+    ///     Ok(())
+    /// }
+    /// ```
+    /// [`write`]: Self::write
+    pub async fn write_zc<B: IoBuf>(&mut self, buf: B) -> (Result<usize>, B) {
+        op::send_zc(self.socket.fd, buf).await
     }
 
     /// Attempts to write an entire buffer to the stream.
@@ -166,6 +515,8 @@ impl TcpStream {
     /// # Errors
     ///
     /// This function will return the first error that [`write`] returns.
+    /// Writing to a connection closed by the peer fails with [`ErrorKind::BrokenPipe`]
+    /// rather than raising `SIGPIPE`, which would kill the process.
     ///
     /// # Examples
     /// ```no_run
@@ -253,11 +604,31 @@ impl TcpStream {
     }
 }
 
+/// The data received on a [`TcpStream`] within a window of time, created with
+/// [`TcpStream::recv_multi_timeout`].
+pub struct RecvMulti<'a> {
+    recv: op::RecvMulti,
+    _stream: PhantomData<&'a mut TcpStream>,
+}
+
+impl RecvMulti<'_> {
+    /// Returns the next chunk of data received, or `None` once the peer has closed
+    /// the connection. Once the window is over, an error of kind
+    /// [`ErrorKind::TimedOut`] is returned.
+    pub async fn next(&mut self) -> Result<Option<Vec<u8>>> {
+        self.recv.next().await
+    }
+}
+
+impl Debug for RecvMulti<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RecvMulti")
+    }
+}
+
 impl FromRawFd for TcpStream {
     unsafe fn from_raw_fd(fd: std::os::fd::RawFd) -> Self {
-        TcpStream {
-            socket: Socket::from_raw_fd(fd),
-        }
+        TcpStream::from_socket(Socket::from_raw_fd(fd))
     }
 }
 impl IntoRawFd for TcpStream {
@@ -265,6 +636,16 @@ impl IntoRawFd for TcpStream {
         self.socket.into_raw_fd()
     }
 }
+impl AsRawFd for TcpStream {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+impl AsFd for TcpStream {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.socket.as_fd()
+    }
+}
 
 impl Debug for TcpStream {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -0,0 +1,303 @@
+//! TLS streams backed by [`rustls`].
+//!
+//! Since osiris's I/O operations take ownership of their buffers, the TLS state machine
+//! cannot read from or write to the underlying stream directly. Instead, a [`TlsStream`]
+//! reads ciphertext from its [`Transport`] into an owned buffer and feeds it to
+//! [`rustls`], and writes the ciphertext produced by [`rustls`] from another owned buffer.
+//!
+//! # Examples
+//! ```no_run
+//! use osiris::net::tls::rustls::{ClientConfig, RootCertStore};
+//! use osiris::net::tls::TlsConnector;
+//! use osiris::net::TcpStream;
+//! use std::sync::Arc;
+//!
+//! #[osiris::main]
+//! async fn main() -> std::io::Result<()> {
+//!     let roots = RootCertStore::empty();
+//!     let config = ClientConfig::builder()
+//!         .with_root_certificates(roots)
+//!         .with_no_client_auth();
+//!     let connector = TlsConnector::from(Arc::new(config));
+//!
+//!     let stream = TcpStream::connect("example.com:443").await?;
+//!     let mut stream = connector.connect("example.com", stream).await?;
+//!     stream.write_all("GET / HTTP/1.0\r\nHost: example.com\r\n\r\n").await.0?;
+//!     let (n, buf) = stream.read(vec![0; 4096]).await;
+//!     println!("{}", String::from_utf8_lossy(&buf[..n?]));
+//!     Ok(())
+//! }
+//! ```
+use std::fmt::Debug;
+use std::future::Future;
+use std::io::{self, Error, ErrorKind, Read, Result, Write};
+use std::mem::take;
+use std::sync::Arc;
+
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, Connection, ServerConfig, ServerConnection};
+
+use crate::buf::{deref, IoBuf, IoBufMut};
+
+use super::TcpStream;
+
+pub use rustls;
+
+/// The size of the buffer used to read ciphertext from the transport.
+const READ_CHUNK: usize = 16 * 1024;
+
+/// A byte stream a [`TlsStream`] can be layered on.
+pub trait Transport {
+    /// Reads some data into the buffer, returning the buffer and the number of bytes read.
+    /// A return value of zero signals the end of the stream.
+    fn read<B: IoBufMut>(&mut self, buf: B) -> impl Future<Output = (Result<usize>, B)>;
+
+    /// Writes the whole buffer into the stream.
+    fn write_all<B: IoBuf>(&mut self, buf: B) -> impl Future<Output = (Result<()>, B)>;
+}
+
+impl Transport for TcpStream {
+    fn read<B: IoBufMut>(&mut self, buf: B) -> impl Future<Output = (Result<usize>, B)> {
+        TcpStream::read(self, buf)
+    }
+
+    fn write_all<B: IoBuf>(&mut self, buf: B) -> impl Future<Output = (Result<()>, B)> {
+        TcpStream::write_all(self, buf)
+    }
+}
+
+/// Establishes TLS connections as a client.
+#[derive(Clone)]
+pub struct TlsConnector {
+    config: Arc<ClientConfig>,
+}
+
+impl From<Arc<ClientConfig>> for TlsConnector {
+    fn from(config: Arc<ClientConfig>) -> Self {
+        TlsConnector { config }
+    }
+}
+
+impl TlsConnector {
+    /// Performs the TLS handshake over `stream` as a client, validating the server's
+    /// certificate against `domain`.
+    ///
+    /// # Errors
+    /// This function errors if `domain` is not a valid DNS name or IP address, if the
+    /// handshake fails, or if the stream fails to read or write.
+    pub async fn connect<S: Transport>(&self, domain: &str, stream: S) -> Result<TlsStream<S>> {
+        let domain = ServerName::try_from(domain.to_owned())
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+        let conn = ClientConnection::new(self.config.clone(), domain).map_err(invalid_data)?;
+        TlsStream::handshake(stream, conn.into()).await
+    }
+}
+
+/// Accepts TLS connections as a server.
+#[derive(Clone)]
+pub struct TlsAcceptor {
+    config: Arc<ServerConfig>,
+}
+
+impl From<Arc<ServerConfig>> for TlsAcceptor {
+    fn from(config: Arc<ServerConfig>) -> Self {
+        TlsAcceptor { config }
+    }
+}
+
+impl TlsAcceptor {
+    /// Performs the TLS handshake over `stream` as a server.
+    ///
+    /// # Errors
+    /// This function errors if the handshake fails, or if the stream fails to read or write.
+    pub async fn accept<S: Transport>(&self, stream: S) -> Result<TlsStream<S>> {
+        let conn = ServerConnection::new(self.config.clone()).map_err(invalid_data)?;
+        TlsStream::handshake(stream, conn.into()).await
+    }
+}
+
+/// A stream encrypted with TLS. It is created with [`TlsConnector::connect`]
+/// or [`TlsAcceptor::accept`].
+pub struct TlsStream<S> {
+    io: S,
+    conn: Connection,
+    /// ciphertext read from the transport.
+    incoming: Vec<u8>,
+    /// ciphertext to be written to the transport.
+    outgoing: Vec<u8>,
+    /// scratch space for the plaintext, which is copied to the caller's buffer.
+    plain: Vec<u8>,
+}
+
+impl<S: Transport> TlsStream<S> {
+    async fn handshake(io: S, conn: Connection) -> Result<TlsStream<S>> {
+        let mut stream = TlsStream {
+            io,
+            conn,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+            plain: Vec::new(),
+        };
+        while stream.conn.is_handshaking() {
+            if stream.conn.wants_write() {
+                stream.flush().await?;
+            } else if stream.read_tls().await? == 0 {
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+        }
+        stream.flush().await?;
+        Ok(stream)
+    }
+
+    /// Reads some decrypted data into the buffer, returning the original buffer and
+    /// the quantity of data read. A return value of zero signals that the peer closed
+    /// the connection.
+    ///
+    /// # Errors
+    /// An error of kind [`ErrorKind::UnexpectedEof`] is returned if the transport is
+    /// closed without the peer sending a `close_notify` alert, and an error of kind
+    /// [`ErrorKind::InvalidData`] is returned if the peer sent invalid TLS data.
+    pub async fn read<B: IoBufMut>(&mut self, mut buf: B) -> (Result<usize>, B) {
+        let len = buf.bytes_total();
+        self.plain.resize(len, 0);
+        loop {
+            match self.conn.reader().read(&mut self.plain[..len]) {
+                Ok(n) => {
+                    // Safety: the buffer has room for `len` bytes
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(self.plain.as_ptr(), buf.stable_mut_ptr(), n);
+                        buf.set_init(n);
+                    }
+                    return (Ok(n), buf);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => {}
+                Err(err) => return (Err(err), buf),
+            }
+            if let Err(err) = self.read_tls().await {
+                return (Err(err), buf);
+            }
+        }
+    }
+
+    /// Encrypts and writes a buffer into the stream, returning how many bytes were written.
+    ///
+    /// # Errors
+    /// This function errors if the transport fails to write.
+    pub async fn write<B: IoBuf>(&mut self, buf: B) -> (Result<usize>, B) {
+        let n = match self.conn.writer().write(deref(&buf)) {
+            Ok(n) => n,
+            Err(err) => return (Err(err), buf),
+        };
+        let res = self.flush().await;
+        (res.map(|()| n), buf)
+    }
+
+    /// Encrypts and writes the entire buffer into the stream.
+    ///
+    /// # Errors
+    /// This function errors if the transport fails to write.
+    pub async fn write_all<B: IoBuf>(&mut self, buf: B) -> (Result<()>, B) {
+        let mut written = 0;
+        while written < buf.bytes_init() {
+            let res = self.conn.writer().write(&deref(&buf)[written..]);
+            let res = match res {
+                Ok(0) => Err(ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    written += n;
+                    self.flush().await
+                }
+                Err(err) => Err(err),
+            };
+            if let Err(err) = res {
+                return (Err(err), buf);
+            }
+        }
+        (Ok(()), buf)
+    }
+
+    /// Sends a `close_notify` alert to the peer, signaling that no more data will be
+    /// written. The transport is not closed.
+    ///
+    /// # Errors
+    /// This function errors if the transport fails to write.
+    pub async fn shutdown(&mut self) -> Result<()> {
+        self.conn.send_close_notify();
+        self.flush().await
+    }
+
+    /// Returns a reference to the underlying transport.
+    pub fn get_ref(&self) -> &S {
+        &self.io
+    }
+
+    /// Returns a mutable reference to the underlying transport. Reading from or writing
+    /// to it directly will likely corrupt the TLS session.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.io
+    }
+
+    /// Returns the underlying transport and the `rustls` connection.
+    pub fn into_inner(self) -> (S, Connection) {
+        (self.io, self.conn)
+    }
+
+    /// Reads ciphertext from the transport and processes it, returning
+    /// the number of bytes read.
+    async fn read_tls(&mut self) -> Result<usize> {
+        let mut incoming = take(&mut self.incoming);
+        incoming.clear();
+        incoming.reserve(READ_CHUNK);
+        let (res, incoming) = self.io.read(incoming).await;
+        self.incoming = incoming;
+        let n = res?;
+
+        let mut data = &self.incoming[..n];
+        loop {
+            // when the transport is closed this signals the end of the stream.
+            self.conn.read_tls(&mut data)?;
+            if let Err(err) = self.conn.process_new_packets() {
+                // we attempt to let the peer know about the error.
+                let _ = self.flush().await;
+                return Err(invalid_data(err));
+            }
+            if data.is_empty() {
+                return Ok(n);
+            }
+        }
+    }
+
+    /// Writes all the pending ciphertext to the transport.
+    async fn flush(&mut self) -> Result<()> {
+        while self.conn.wants_write() {
+            let mut outgoing = take(&mut self.outgoing);
+            outgoing.clear();
+            self.conn.write_tls(&mut outgoing)?;
+            let (res, outgoing) = self.io.write_all(outgoing).await;
+            self.outgoing = outgoing;
+            res?;
+        }
+        Ok(())
+    }
+}
+
+impl<S> Debug for TlsStream<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TlsStream")
+    }
+}
+
+impl Debug for TlsConnector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TlsConnector")
+    }
+}
+
+impl Debug for TlsAcceptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TlsAcceptor")
+    }
+}
+
+fn invalid_data(err: rustls::Error) -> io::Error {
+    Error::new(ErrorKind::InvalidData, err)
+}
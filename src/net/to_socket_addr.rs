@@ -1,5 +1,4 @@
 #![allow(unused_imports)]
-#[cfg(io_uring)]
 use crate::net::dns;
 use std::future::{ready, Future, Ready};
 use std::io::{Error, ErrorKind, Result};
@@ -65,10 +64,6 @@ impl<'a> ToSocketAddrs for (&'a str, u16) {
     type Iter = vec::IntoIter<SocketAddr>;
     type Fut = Pin<Box<dyn Future<Output = Result<Self::Iter>> + 'a>>;
     fn to_socket_addrs(self) -> Self::Fut {
-        // TODO: make non io-uring async
-        #[cfg(not(io_uring))]
-        return Box::pin(async move { std::net::ToSocketAddrs::to_socket_addrs(&self) });
-        #[cfg(io_uring)]
         Box::pin(async move {
             let (host, port) = self;
             let res = dns::lookup(host)
@@ -97,26 +92,19 @@ impl<'a> ToSocketAddrs for &'a (String, u16) {
     }
 }
 
-// accepts strings like 'localhost:12345'
+// accepts strings like 'localhost:12345', '[::1]:12345' or 'localhost:http'
 impl Sealed for &str {}
 impl<'a> ToSocketAddrs for &'a str {
     type Iter = vec::IntoIter<SocketAddr>;
     type Fut = Pin<Box<dyn Future<Output = Result<Self::Iter>> + 'a>>;
     fn to_socket_addrs(self) -> Self::Fut {
-        // TODO: make non io-uring async
-        #[cfg(not(io_uring))]
-        return Box::pin(async move { std::net::ToSocketAddrs::to_socket_addrs(&self) });
-        #[cfg(io_uring)]
         Box::pin(async {
-            let (host, port) = self.split_once(':').ok_or_else(|| {
-                Error::new(
-                    ErrorKind::InvalidInput,
-                    "invalid socket address, expected `<host>:<port>` syntax.",
-                )
-            })?;
-            let port: u16 = port
-                .parse()
-                .map_err(|_| Error::new(ErrorKind::InvalidInput, "invalid port value"))?;
+            // try to parse as a regular SocketAddr first
+            if let Ok(addr) = self.parse() {
+                return Ok(vec![addr].into_iter());
+            }
+            let (host, port) = split_host_port(self)?;
+            let port = parse_port(port).await?;
             let out = dns::lookup(host)
                 .await?
                 .map(|addr| SocketAddr::from((addr, port)))
@@ -127,6 +115,40 @@ impl<'a> ToSocketAddrs for &'a str {
     }
 }
 
+/// Splits a `<host>:<port>` string, where the host may be
+/// an IPv6 address enclosed in brackets.
+fn split_host_port(addr: &str) -> Result<(&str, &str)> {
+    let malformed = || {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "invalid socket address, expected `<host>:<port>` syntax.",
+        )
+    };
+    let (host, port) = match addr.strip_prefix('[') {
+        Some(addr) => {
+            let (host, rest) = addr.split_once(']').ok_or_else(malformed)?;
+            (host, rest.strip_prefix(':').ok_or_else(malformed)?)
+        }
+        None => addr.rsplit_once(':').ok_or_else(malformed)?,
+    };
+    if host.is_empty() || port.is_empty() {
+        return Err(malformed());
+    }
+    Ok((host, port))
+}
+
+/// Parses a port number, or looks up the port of a service name.
+async fn parse_port(port: &str) -> Result<u16> {
+    if let Ok(port) = port.parse() {
+        return Ok(port);
+    }
+    let invalid_port = || Error::new(ErrorKind::InvalidInput, "invalid port value");
+    if port.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(invalid_port());
+    }
+    dns::lookup_port(port).await?.ok_or_else(invalid_port)
+}
+
 impl Sealed for &[SocketAddr] {}
 impl<'a> ToSocketAddrs for &'a [SocketAddr] {
     type Iter = std::iter::Cloned<std::slice::Iter<'a, SocketAddr>>;
@@ -3,6 +3,7 @@ use super::to_socket_addr::{try_until_success, ToSocketAddrs};
 use crate::buf::{IoBuf, IoBufMut};
 use std::io::Result;
 use std::net::SocketAddr;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd};
 
 pub struct UdpSocket {
     socket: Socket,
@@ -42,6 +43,115 @@ where {
     pub async fn send_to<B: IoBuf>(&mut self, buf: B, addr: SocketAddr) -> (Result<usize>, B) {
         self.socket.send_to(buf, addr).await
     }
+
+    /// Sets the `SO_BINDTODEVICE` option on the socket, so that it only sends and
+    /// receives datagrams through the network interface named `interface`, such as
+    /// `"eth0"`. Passing `None` removes a previously set restriction.
+    ///
+    /// # Errors
+    /// Setting this option requires the `CAP_NET_RAW` capability. An error of kind
+    /// [`PermissionDenied`](std::io::ErrorKind::PermissionDenied) is returned for
+    /// unprivileged processes, and one of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput)
+    /// if the name is too long for an interface name. This option is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn bind_device(&self, interface: Option<&str>) -> Result<()> {
+        self.socket.set_bind_device(interface)
+    }
+
+    /// Returns the local address that this socket is bound to.
+    ///
+    /// This is useful to find out the port assigned by the OS when
+    /// binding to port 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # osiris::block_on(async {
+    /// use osiris::net::UdpSocket;
+    ///
+    /// let socket = UdpSocket::bind("127.0.0.1:0").await?;
+    /// let addr = socket.local_addr()?;
+    /// assert_ne!(addr.port(), 0);
+    /// # std::io::Result::Ok(())
+    /// # }).unwrap().unwrap();
+    /// ```
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Returns the address of the remote peer this socket was connected to.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`NotConnected`](std::io::ErrorKind::NotConnected)
+    /// if the socket has not been connected with [`connect`](UdpSocket::connect).
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.socket.peer_addr()
+    }
+
+    /// Turns a [`osiris::net::UdpSocket`](UdpSocket) into a [`std::net::UdpSocket`].
+    ///
+    /// It is unspecified whether the returned [`std::net::UdpSocket`] will be
+    /// set as nonblocking or not. Whichever behavior will be used should be set
+    /// after the operation.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::error::Error;
+    ///
+    /// #[osiris::main]
+    /// async fn main() -> Result<(), Box<dyn Error>> {
+    ///     let osiris_socket = osiris::net::UdpSocket::bind("127.0.0.1:0").await?;
+    ///     let std_socket = osiris_socket.into_std();
+    ///     std_socket.set_nonblocking(false)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn into_std(self) -> std::net::UdpSocket {
+        let fd = self.into_raw_fd();
+        unsafe { std::net::UdpSocket::from_raw_fd(fd) }
+    }
+
+    /// Creates a new `UdpSocket` from a `std::net::UdpSocket`.
+    ///
+    /// This function is intended to be used to wrap a UDP socket from the
+    /// standard library in the Osiris equivalent.
+    ///
+    /// This API can be used with `socket2` or `libc::socket` to customize
+    /// a socket before it is used. Alternatively, the
+    /// [`from_raw_fd`](FromRawFd::from_raw_fd) method can also be used
+    /// to create an osiris socket. Note that when osiris does not use io-uring,
+    /// the socket should be set as nonblocking before calling this function.
+    pub fn from_std(socket: std::net::UdpSocket) -> Self {
+        let fd = socket.into_raw_fd();
+        // Safety: the file descriptor is owned by the std socket.
+        unsafe { Self::from_raw_fd(fd) }
+    }
+}
+
+impl FromRawFd for UdpSocket {
+    unsafe fn from_raw_fd(fd: std::os::fd::RawFd) -> Self {
+        UdpSocket {
+            socket: Socket::from_raw_fd(fd),
+        }
+    }
+}
+
+impl IntoRawFd for UdpSocket {
+    fn into_raw_fd(self) -> std::os::fd::RawFd {
+        self.socket.into_raw_fd()
+    }
+}
+
+impl AsRawFd for UdpSocket {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+impl AsFd for UdpSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.socket.as_fd()
+    }
 }
 
 #[test]
@@ -84,3 +194,41 @@ fn udp_server_and_client() {
     .unwrap()
     .unwrap();
 }
+
+#[test]
+fn udp_std_roundtrip() {
+    crate::block_on(async {
+        let std_socket = std::net::UdpSocket::bind("127.0.0.1:2402").unwrap();
+        std_socket.set_nonblocking(true).unwrap();
+        let mut socket = UdpSocket::from_std(std_socket);
+        let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        sender.send_to(b"hello", "127.0.0.1:2402").unwrap();
+        let (n, buf) = socket.recv(vec![0; 16]).await;
+        assert_eq!(&buf[..n.unwrap()], b"hello");
+
+        let std_socket = socket.into_std();
+        assert_eq!(std_socket.local_addr().unwrap().port(), 2402);
+    })
+    .unwrap();
+}
+
+#[test]
+fn udp_addrs() {
+    use std::io::ErrorKind;
+
+    crate::block_on(async {
+        let alice = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let bob = UdpSocket::bind("[::1]:0").await.unwrap();
+        let alice_addr = alice.local_addr().unwrap();
+        assert_ne!(alice_addr.port(), 0);
+        assert!(bob.local_addr().unwrap().is_ipv6());
+
+        let err = alice.peer_addr().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotConnected);
+
+        let carol = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        carol.connect(alice_addr).await.unwrap();
+        assert_eq!(carol.peer_addr().unwrap(), alice_addr);
+    })
+    .unwrap();
+}
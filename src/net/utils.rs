@@ -1,10 +1,9 @@
-#[cfg(io_uring)]
+use crate::buf::IoBuf;
 use crate::fs::File;
 use libc::{AF_INET, AF_INET6};
 use memchr::memchr;
 use std::io::{Error, Result};
 use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
-use std::ptr::addr_of;
 
 /// A type with the same memory layout as `libc::sockaddr`. Used in converting Rust level
 /// SocketAddr* types into their system representation. The benefit of this specific
@@ -95,14 +94,14 @@ pub fn to_std_socket_addr(storage: &libc::sockaddr) -> Result<SocketAddr> {
     if storage.sa_family == AF_INET as _ {
         // SAFETY: if the `ss_family` field is `AF_INET` then storage must
         // be a `sockaddr_in`.
-        let addr: &libc::sockaddr_in = unsafe { &*(addr_of!(storage).cast()) };
+        let addr: &libc::sockaddr_in = unsafe { &*(storage as *const libc::sockaddr).cast() };
         let port = u16::from_be(addr.sin_port);
         let ip = Ipv4Addr::from(addr.sin_addr.s_addr.to_ne_bytes());
         Ok(SocketAddr::V4(SocketAddrV4::new(ip, port)))
     } else if storage.sa_family == AF_INET6 as _ {
         // SAFETY: if the `ss_family` field is `AF_INET6` then storage must
         // be a `sockaddr_in6`.
-        let addr: &libc::sockaddr_in6 = unsafe { &*(addr_of!(storage).cast()) };
+        let addr: &libc::sockaddr_in6 = unsafe { &*(storage as *const libc::sockaddr).cast() };
         let ip = Ipv6Addr::from(addr.sin6_addr.s6_addr);
         let port = u16::from_be(addr.sin6_port);
         Ok(SocketAddr::V6(SocketAddrV6::new(
@@ -130,28 +129,24 @@ pub fn remove_comment(line: &[u8]) -> &[u8] {
 pub fn is_whitespace(c: &u8) -> bool {
     matches!(c, b'\t' | b'\n' | b'\x0C' | b'\r' | b' ')
 }
-#[cfg(io_uring)]
 pub async fn lines(path: &str, capacity: usize) -> Result<LineReader> {
     let file = File::open(path).await?;
 
     Ok(LineReader {
-        end: file.metadata().await?.len(),
         file,
         buf: Vec::with_capacity(capacity),
-        seek: 0,
         char: 0,
     })
 }
-// TODO: refactor this to use file position?
-#[cfg(io_uring)]
+
+/// Reads a file line by line, using the file position, so it doesn't
+/// depend on positional reads being supported by the reactor.
 pub struct LineReader {
     file: File,
     buf: Vec<u8>,
-    seek: usize,
     char: usize,
-    end: usize,
 }
-#[cfg(io_uring)]
+
 impl LineReader {
     fn try_read_line(&mut self) -> Option<*const [u8]> {
         let buf = self.buf.get(self.char..)?;
@@ -161,23 +156,31 @@ impl LineReader {
     }
 
     pub async fn next(&mut self) -> Result<Option<&[u8]>> {
-        let line = self.try_read_line();
-        if line.is_some() {
-            return Ok(line.map(|ptr| unsafe { &*ptr }));
+        loop {
+            if let Some(line) = self.try_read_line() {
+                return Ok(Some(unsafe { &*line }));
+            }
+            if self.fetch().await? == 0 {
+                break;
+            }
         }
-        self.fetch().await?;
-        Ok(self.try_read_line().map(|ptr| unsafe { &*ptr }))
+        // the last line may not be terminated by a newline.
+        let rest = &self.buf[self.char..];
+        self.char = self.buf.len();
+        Ok(Some(rest).filter(|rest| !rest.is_empty()))
     }
 
-    async fn fetch(&mut self) -> Result<()> {
-        self.seek += self.char;
+    async fn fetch(&mut self) -> Result<usize> {
+        // discard the lines that were already read.
+        self.buf.drain(..self.char);
         self.char = 0;
-        let (res, buf) = self
-            .file
-            .read_at(std::mem::take(&mut self.buf), self.seek as _)
-            .await;
-        self.buf = buf;
-        unsafe { self.buf.set_len(res?) };
-        Ok(())
+        if self.buf.len() == self.buf.capacity() {
+            self.buf.reserve(self.buf.capacity().max(64));
+        }
+        let len = self.buf.len();
+        let buf = std::mem::take(&mut self.buf).slice(len..);
+        let (res, buf) = self.file.read(buf).await;
+        self.buf = buf.into_inner();
+        res
     }
 }
@@ -0,0 +1,271 @@
+//! Asynchronous child processes.
+//!
+//! This module provides a [`Command`] struct, similar to the standard library's
+//! [`std::process::Command`], which can be used to spawn child processes. The standard
+//! input and output of the spawned [`Child`] are exposed as osiris [pipes](crate::net::pipe),
+//! and waiting for the child to exit does not block the thread.
+//!
+//! # Examples
+//! ```
+//! use osiris::process::{Command, Stdio};
+//!
+//! #[osiris::main]
+//! async fn main() -> std::io::Result<()> {
+//!     let mut child = Command::new("echo")
+//!         .arg("hello world")
+//!         .stdout(Stdio::piped())
+//!         .spawn()?;
+//!
+//!     let mut stdout = child.stdout.take().unwrap();
+//!     let (n, buf) = stdout.read(vec![0; 64]).await;
+//!     assert_eq!(&buf[..n?], b"hello world\n");
+//!
+//!     assert!(child.wait().await?.success());
+//!     Ok(())
+//! }
+//! ```
+use std::ffi::OsStr;
+use std::fmt::{self, Debug};
+use std::io::Result;
+use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd};
+use std::path::Path;
+use std::process;
+
+pub use std::process::{ExitStatus, Stdio};
+
+use crate::net::pipe::{PipeReader, PipeWriter};
+use crate::reactor::op;
+
+/// A process builder, providing fine-grained control over how a new process should be spawned.
+///
+/// See [`std::process::Command`] for more details.
+pub struct Command {
+    inner: process::Command,
+}
+
+/// Representation of a running or exited child process.
+///
+/// Unlike the standard library's `Child`, waiting for the process to exit with
+/// [`Child::wait`] does not block the thread. Note that dropping a `Child` does
+/// not kill the process.
+pub struct Child {
+    /// The handle for writing to the child's standard input, if it was piped.
+    pub stdin: Option<PipeWriter>,
+    /// The handle for reading from the child's standard output, if it was piped.
+    pub stdout: Option<PipeReader>,
+    /// The handle for reading from the child's standard error, if it was piped.
+    pub stderr: Option<PipeReader>,
+    inner: process::Child,
+    /// A file descriptor referring to the process, which becomes readable when it exits.
+    pidfd: Option<OwnedFd>,
+}
+
+impl Command {
+    /// Constructs a new `Command` for launching the program at path `program`.
+    /// See [`std::process::Command::new`] for more details.
+    pub fn new<S: AsRef<OsStr>>(program: S) -> Command {
+        Command {
+            inner: process::Command::new(program),
+        }
+    }
+
+    /// Adds an argument to pass to the program.
+    pub fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Command {
+        self.inner.arg(arg);
+        self
+    }
+
+    /// Adds multiple arguments to pass to the program.
+    pub fn args<I, S>(&mut self, args: I) -> &mut Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.inner.args(args);
+        self
+    }
+
+    /// Inserts or updates an environment variable for the child process.
+    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Command
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.inner.env(key, val);
+        self
+    }
+
+    /// Removes an environment variable for the child process.
+    pub fn env_remove<K: AsRef<OsStr>>(&mut self, key: K) -> &mut Command {
+        self.inner.env_remove(key);
+        self
+    }
+
+    /// Clears all environment variables for the child process.
+    pub fn env_clear(&mut self) -> &mut Command {
+        self.inner.env_clear();
+        self
+    }
+
+    /// Sets the working directory for the child process.
+    pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Command {
+        self.inner.current_dir(dir);
+        self
+    }
+
+    /// Configuration for the child process's standard input handle.
+    /// It defaults to [`Stdio::inherit`].
+    pub fn stdin<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.inner.stdin(cfg);
+        self
+    }
+
+    /// Configuration for the child process's standard output handle.
+    /// It defaults to [`Stdio::inherit`].
+    pub fn stdout<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.inner.stdout(cfg);
+        self
+    }
+
+    /// Configuration for the child process's standard error handle.
+    /// It defaults to [`Stdio::inherit`].
+    pub fn stderr<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.inner.stderr(cfg);
+        self
+    }
+
+    /// Executes the command as a child process, returning a handle to it.
+    ///
+    /// # Errors
+    /// This function errors if the process could not be spawned.
+    pub fn spawn(&mut self) -> Result<Child> {
+        let mut inner = self.inner.spawn()?;
+        let pipes = (|| {
+            let stdin = inner.stdin.take().map(pipe_end).transpose()?;
+            let stdout = inner.stdout.take().map(pipe_end).transpose()?;
+            let stderr = inner.stderr.take().map(pipe_end).transpose()?;
+            Result::Ok((stdin, stdout, stderr))
+        })();
+        let (stdin, stdout, stderr) = match pipes {
+            Ok(pipes) => pipes,
+            Err(err) => {
+                // the child is killed and reaped, rather than left running unreaped.
+                let _ = inner.kill();
+                let _ = inner.wait();
+                return Err(err);
+            }
+        };
+        let pidfd = pidfd_open(inner.id());
+        Ok(Child {
+            stdin,
+            stdout,
+            stderr,
+            inner,
+            pidfd,
+        })
+    }
+}
+
+impl Child {
+    /// Returns the OS-assigned process identifier associated with this child.
+    #[must_use]
+    pub fn id(&self) -> u32 {
+        self.inner.id()
+    }
+
+    /// Forces the child process to exit with `SIGKILL`.
+    ///
+    /// # Errors
+    /// See [`std::process::Child::kill`].
+    pub fn kill(&mut self) -> Result<()> {
+        self.inner.kill()
+    }
+
+    /// Attempts to collect the exit status of the child if it has already exited.
+    ///
+    /// # Errors
+    /// See [`std::process::Child::try_wait`].
+    pub fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        self.inner.try_wait()
+    }
+
+    /// Waits for the child to exit completely, returning the status that it exited with.
+    ///
+    /// The stdin handle to the child process, if any, will be closed before waiting.
+    /// This helps avoid deadlock: it ensures that the child does not block waiting for
+    /// input from the parent, while the parent waits for the child to exit.
+    ///
+    /// # Platform-specific behavior
+    ///
+    /// On Linux 5.10 or later, the runtime is notified when the child exits. On older
+    /// kernels, and on other platforms, the child's status is polled periodically.
+    ///
+    /// # Errors
+    /// This function errors if the status of the child could not be collected.
+    pub async fn wait(&mut self) -> Result<ExitStatus> {
+        drop(self.stdin.take());
+        let Some(pidfd) = &self.pidfd else {
+            return self.poll_wait().await;
+        };
+        let inner = &mut self.inner;
+        op::nonblock(pidfd.as_raw_fd(), || {
+            inner
+                .try_wait()?
+                .ok_or_else(|| std::io::Error::from_raw_os_error(libc::EAGAIN))
+        })
+        .await
+    }
+
+    async fn poll_wait(&mut self) -> Result<ExitStatus> {
+        const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+        loop {
+            if let Some(status) = self.inner.try_wait()? {
+                return Ok(status);
+            }
+            crate::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Converts an end of a pipe created by the standard library into an osiris pipe.
+fn pipe_end<S: IntoRawFd, T: FromRawFd>(end: S) -> Result<T> {
+    let fd = unsafe { OwnedFd::from_raw_fd(end.into_raw_fd()) };
+    op::make_nonblocking(&fd)?;
+    // Safety: we own the file descriptor
+    Ok(unsafe { T::from_raw_fd(fd.into_raw_fd()) })
+}
+
+/// Opens a nonblocking pidfd for the process, which requires Linux 5.10. Returns
+/// `None` if it can't be opened for any reason, in which case the child's status
+/// is polled instead.
+#[cfg(target_os = "linux")]
+fn pidfd_open(pid: u32) -> Option<OwnedFd> {
+    let res = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, libc::PIDFD_NONBLOCK) };
+    if res < 0 {
+        return None;
+    }
+    // Safety: the syscall returned a new file descriptor
+    Some(unsafe { OwnedFd::from_raw_fd(res as _) })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pidfd_open(_: u32) -> Option<OwnedFd> {
+    None
+}
+
+impl Debug for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl Debug for Child {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Child")
+            .field("id", &self.id())
+            .field("stdin", &self.stdin)
+            .field("stdout", &self.stdout)
+            .field("stderr", &self.stderr)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,229 @@
+//! The operations of a reactor that may have fallen back to the poll driver.
+//!
+//! Each operation is forwarded to the io-uring implementation, or to the readiness
+//! based implementation if the reactor of the current runtime is backed by poll.
+//! See [`Config::allow_fallback`](crate::runtime::Config#structfield.allow_fallback).
+use std::ffi::CString;
+use std::io::Result;
+use std::net::{Shutdown, SocketAddr};
+use std::os::fd::IntoRawFd;
+use std::time::Duration;
+
+use super::{iouring, poll};
+use crate::buf::{IoBuf, IoBufMut};
+use crate::reactor;
+use crate::utils::statx;
+
+pub use super::poll::op::{read_event, write_event};
+pub use super::utils::{make_blocking, make_nonblocking, socket};
+
+/// Forwards the operation to the backend of the current reactor.
+macro_rules! dispatch {
+    ($op:ident($($arg:expr),*)) => {
+        if reactor::current().is_io_uring() {
+            iouring::op::$op($($arg),*).await
+        } else {
+            poll::op::$op($($arg),*).await
+        }
+    };
+}
+
+pub async fn close(fd: i32) -> Result<()> {
+    dispatch!(close(fd))
+}
+
+pub async fn read_at<B: IoBufMut>(fd: i32, buf: B, pos: i64) -> (Result<usize>, B) {
+    dispatch!(read_at(fd, buf, pos))
+}
+
+pub async fn read_timeout<B: IoBufMut>(
+    fd: i32,
+    buf: B,
+    pos: i64,
+    dur: Duration,
+) -> (Result<usize>, B) {
+    dispatch!(read_timeout(fd, buf, pos, dur))
+}
+
+pub async fn read_vectored_at(
+    fd: i32,
+    bufs: Vec<Vec<u8>>,
+    pos: i64,
+) -> (Result<usize>, Vec<Vec<u8>>) {
+    dispatch!(read_vectored_at(fd, bufs, pos))
+}
+
+pub async fn read_blocking_fd<B: IoBufMut>(fd: i32, buf: B, pos: i64) -> (Result<usize>, B) {
+    dispatch!(read_blocking_fd(fd, buf, pos))
+}
+
+pub async fn write_at<B: IoBuf>(fd: i32, buf: B, pos: i64) -> (Result<usize>, B) {
+    dispatch!(write_at(fd, buf, pos))
+}
+
+pub async fn write_blocking_fd<B: IoBuf>(fd: i32, buf: B, pos: i64) -> (Result<usize>, B) {
+    dispatch!(write_blocking_fd(fd, buf, pos))
+}
+
+pub async fn send<B: IoBuf>(fd: i32, buf: B) -> (Result<usize>, B) {
+    dispatch!(send(fd, buf))
+}
+
+pub async fn send_zc<B: IoBuf>(fd: i32, buf: B) -> (Result<usize>, B) {
+    dispatch!(send_zc(fd, buf))
+}
+
+pub async fn fsync(fd: i32) -> Result<()> {
+    if reactor::current().is_io_uring() {
+        iouring::op::fsync(fd).await.map(drop)
+    } else {
+        poll::op::fsync(fd).await
+    }
+}
+
+pub async fn fdatasync(fd: i32) -> Result<()> {
+    if reactor::current().is_io_uring() {
+        iouring::op::fdatasync(fd).await.map(drop)
+    } else {
+        poll::op::fdatasync(fd).await
+    }
+}
+
+pub async fn fadvise(fd: i32, offset: i64, len: i64, advice: i32) -> Result<()> {
+    dispatch!(fadvise(fd, offset, len, advice))
+}
+
+pub async fn recv<B: IoBufMut>(fd: i32, buf: B) -> (Result<usize>, B) {
+    dispatch!(recv(fd, buf))
+}
+
+/// A receive that yields the chunks of data received on a socket until a deadline,
+/// on either backend.
+pub enum RecvMulti {
+    IoUring(iouring::op::RecvMulti),
+    Poll(poll::op::RecvMulti),
+}
+
+pub async fn recv_multi(fd: i32, dur: Duration) -> Result<RecvMulti> {
+    if reactor::current().is_io_uring() {
+        iouring::op::recv_multi(fd, dur)
+            .await
+            .map(RecvMulti::IoUring)
+    } else {
+        poll::op::recv_multi(fd, dur).await.map(RecvMulti::Poll)
+    }
+}
+
+impl RecvMulti {
+    pub async fn next(&mut self) -> Result<Option<Vec<u8>>> {
+        match self {
+            RecvMulti::IoUring(recv) => recv.next().await,
+            RecvMulti::Poll(recv) => recv.next().await,
+        }
+    }
+}
+
+pub async fn statx(fd: i32, path: Option<CString>, flags: i32, mask: u32) -> Result<statx> {
+    dispatch!(statx(fd, path, flags, mask))
+}
+
+pub async fn connect(fd: i32, addr: SocketAddr) -> Result<()> {
+    dispatch!(connect(fd, addr))
+}
+
+pub async fn connect_timeout(fd: i32, addr: SocketAddr, dur: Duration) -> Result<()> {
+    dispatch!(connect_timeout(fd, addr, dur))
+}
+
+pub async fn accept(fd: i32) -> Result<(i32, SocketAddr)> {
+    if reactor::current().is_io_uring() {
+        iouring::op::accept(fd).await
+    } else {
+        let (fd, addr) = poll::op::accept(fd).await?;
+        Ok((fd.into_raw_fd(), addr))
+    }
+}
+
+pub async fn send_to<B: IoBuf>(fd: i32, buf: B, addr: SocketAddr) -> (Result<usize>, B) {
+    dispatch!(send_to(fd, buf, addr))
+}
+
+pub async fn recvmsg<B: IoBufMut, C: IoBufMut>(
+    fd: i32,
+    buf: B,
+    control: C,
+) -> (Result<(usize, Option<SocketAddr>)>, B, C) {
+    dispatch!(recvmsg(fd, buf, control))
+}
+
+pub async fn sendmsg<B: IoBuf, C: IoBuf>(
+    fd: i32,
+    buf: B,
+    addr: Option<SocketAddr>,
+    control: C,
+) -> (Result<usize>, B, C) {
+    dispatch!(sendmsg(fd, buf, addr, control))
+}
+
+pub async fn open_at(dirfd: i32, path: CString, flags: i32, mode: u32) -> Result<i32> {
+    dispatch!(open_at(dirfd, path, flags, mode))
+}
+
+pub async fn openat2(
+    dirfd: i32,
+    path: CString,
+    flags: i32,
+    mode: u32,
+    resolve: u64,
+) -> Result<i32> {
+    dispatch!(openat2(dirfd, path, flags, mode, resolve))
+}
+
+pub async fn shutdown(fd: i32, how: Shutdown) -> Result<()> {
+    dispatch!(shutdown(fd, how))
+}
+
+pub async fn mkdir_at(dirfd: i32, path: CString) -> Result<()> {
+    dispatch!(mkdir_at(dirfd, path))
+}
+
+pub async fn unlink_at(dirfd: i32, path: CString, flags: i32) -> Result<()> {
+    dispatch!(unlink_at(dirfd, path, flags))
+}
+
+pub async fn symlink(target: CString, linkpath: CString) -> Result<()> {
+    dispatch!(symlink(target, linkpath))
+}
+
+pub async fn splice(fd_in: i32, off_in: i64, fd_out: i32, off_out: i64, len: u32) -> Result<usize> {
+    dispatch!(splice(fd_in, off_in, fd_out, off_out, len))
+}
+
+pub async fn tee(fd_in: i32, fd_out: i32, len: u32) -> Result<usize> {
+    dispatch!(tee(fd_in, fd_out, len))
+}
+
+pub async fn wait_readable(fd: i32) -> Result<()> {
+    dispatch!(wait_readable(fd))
+}
+
+pub async fn wait_writable(fd: i32) -> Result<()> {
+    dispatch!(wait_writable(fd))
+}
+
+pub async fn write_nonblock(fd: i32, buf: *const u8, len: usize) -> Result<usize> {
+    dispatch!(write_nonblock(fd, buf, len))
+}
+
+pub async fn read_nonblock(fd: i32, buf: *mut u8, len: usize) -> Result<usize> {
+    dispatch!(read_nonblock(fd, buf, len))
+}
+
+/// Calls `f` until it stops failing with `EAGAIN`, waiting for `fd` to become readable in between.
+pub async fn nonblock<T>(fd: i32, f: impl FnMut() -> Result<T>) -> Result<T> {
+    dispatch!(nonblock(fd, f))
+}
+
+pub async fn sleep(dur: Duration) -> Result<()> {
+    dispatch!(sleep(dur))
+}
@@ -3,7 +3,6 @@ use crate::runtime::current;
 use crate::{detach, reactor};
 #[cfg(target_os = "linux")]
 use io_uring::cqueue;
-use io_uring::opcode::AsyncCancel;
 #[cfg(target_os = "linux")]
 use io_uring::squeue;
 #[cfg(target_os = "linux")]
@@ -24,6 +23,10 @@ use std::thread::panicking;
 /// if the io wasn't completed
 pub struct Event<T: 'static> {
     entry: Option<squeue::Entry>,
+    /// a timeout linked to the entry.
+    link: Option<squeue::Entry>,
+    /// whether the entry is a zero copy send.
+    zero_copy: bool,
     driver: reactor::Reactor,
     id: u64,
     data: Option<T>,
@@ -34,12 +37,33 @@ type Sub<T> = (Result<cqueue::Entry, io::Error>, T);
 
 impl<T> Unpin for Event<T> {}
 
+impl<T: 'static> Event<T> {
+    /// Requests the cancellation of the operation if it is in flight. The event
+    /// still needs to be polled to completion, and it will resolve either with
+    /// `ECANCELED`, or with the result of the operation if it completed before it
+    /// could be cancelled.
+    pub fn cancel(&self) -> io::Result<()> {
+        if !self.requires_cancel {
+            return Ok(());
+        }
+        self.driver.uring().cancel(self.id)
+    }
+}
+
 impl<T> Future for Event<T> {
     type Output = Sub<T>;
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if let Some(entry) = self.entry.take() {
             // Safety: invariants upheld at construction
-            match unsafe { self.driver.push(entry) } {
+            let res = match self.link.take() {
+                // Safety: invariants upheld at construction
+                Some(link) => unsafe { self.driver.push_linked(entry, link) },
+                // Safety: invariants upheld at construction
+                None if self.zero_copy => unsafe { self.driver.push_zero_copy(entry) },
+                // Safety: invariants upheld at construction
+                None => unsafe { self.driver.push(entry) },
+            };
+            match res {
                 Ok(id) => self.id = id,
                 Err(err) => return Poll::Ready((Err(err), self.data.take().unwrap())),
             };
@@ -63,7 +87,6 @@ impl<T> Future for Event<T> {
 impl<T: 'static> Drop for Event<T> {
     fn drop(&mut self) {
         if self.requires_cancel {
-            let entry = io_uring::opcode::AsyncCancel::new(self.id).build();
             let data = self.data.take().unwrap();
             let Some(rt) = current() else {
                 forget(data);
@@ -76,24 +99,76 @@ impl<T: 'static> Drop for Event<T> {
                 }
                 return;
             };
-            let cancel = unsafe { submit(entry, data) };
-            rt.executor.spawn(cancel, rt.clone(), true);
+            // if the cancellation can't be submitted we still wait for
+            // the operation to complete on its own.
+            let _ = self.cancel();
+            // the resources must be kept alive until the CQE of the
+            // operation arrives.
+            let (driver, id) = (self.driver.clone(), self.id);
+            let cleanup = async move {
+                poll_fn(|cx| driver.poll(id, cx)).await;
+                drop(data);
+            };
+            rt.executor.spawn_untracked(cleanup, None, rt.clone(), true);
         }
     }
 }
 
+/// Creates an [`Event`] that submits `entry` when first polled, and resolves to
+/// its CQE along with `data`.
+///
+/// If the event is dropped while the operation is in flight, the operation is
+/// cancelled, and `data` is kept alive by a cleanup task until the operation's
+/// CQE arrives. So heap allocations moved into `data` remain valid for as long
+/// as the kernel may access them, even if the future is dropped.
+///
+/// # Safety
+///
+/// Every pointer referenced by `entry` must either point into an allocation owned
+/// by `data` that is not moved when `data` is moved (for instance, a `Box` or a
+/// buffer's heap allocation), or be valid for `'static`. In particular, it must
+/// never point to a local of the calling future.
 #[cfg(target_os = "linux")]
-pub(crate) unsafe fn submit<T: 'static>(
-    entry: Entry,
-    data: T,
-) -> impl Future<Output = Sub<T>> + Unpin {
+pub(crate) unsafe fn submit<T: 'static>(entry: Entry, data: T) -> Event<T> {
     use crate::reactor;
 
     Event {
         entry: Some(entry),
+        link: None,
+        zero_copy: false,
         driver: reactor::current(),
         data: Some(data),
         id: 0,
         requires_cancel: false,
     }
 }
+
+/// Like [`submit`], but the operation is cancelled by the kernel if `timeout`, a
+/// `LinkTimeout` entry, expires before it completes. In that case the event resolves
+/// with `ECANCELED`.
+///
+/// # Safety
+///
+/// The same as [`submit`], for both `entry` and `timeout`.
+#[cfg(target_os = "linux")]
+pub(crate) unsafe fn submit_linked<T: 'static>(entry: Entry, timeout: Entry, data: T) -> Event<T> {
+    // Safety: upheld by the caller
+    let mut event = unsafe { submit(entry, data) };
+    event.link = Some(timeout);
+    event
+}
+
+/// Like [`submit`], but for zero copy sends. The event only resolves once the kernel
+/// no longer uses the buffer, so it is kept alive until then even if the event is
+/// dropped.
+///
+/// # Safety
+///
+/// The same as [`submit`].
+#[cfg(target_os = "linux")]
+pub(crate) unsafe fn submit_zero_copy<T: 'static>(entry: Entry, data: T) -> Event<T> {
+    // Safety: upheld by the caller
+    let mut event = unsafe { submit(entry, data) };
+    event.zero_copy = true;
+    event
+}
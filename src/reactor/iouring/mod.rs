@@ -1,9 +1,9 @@
 #![allow(warnings)]
 
-use io_uring::{cqueue, squeue, IoUring};
+use io_uring::{cqueue, opcode, squeue, IoUring, Probe};
 use std::borrow::BorrowMut;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::io;
 use std::ops::ControlFlow;
 use std::ops::ControlFlow::*;
@@ -12,18 +12,78 @@ use std::task::{Poll, Waker};
 use std::time::Duration;
 
 use crate::detach;
-use crate::runtime::Config;
+use crate::runtime::{Capabilities, Config};
 use crate::time::sleep;
 use crate::utils::{epoll_event, syscall};
 
 pub mod event;
+mod multishot;
 pub mod op;
 
+/// The `user_data` of cancellation requests. Their CQEs are not waited on,
+/// since the result of a cancellation is observed on the cancelled operation.
+const CANCEL_ID: u64 = u64::MAX;
+
+/// The `user_data` of timeouts linked to an operation. Their CQEs are not waited
+/// on either, since an expired timeout completes the operation with `ECANCELED`.
+const LINK_TIMEOUT_ID: u64 = u64::MAX - 1;
+
+/// The `user_data` of requests that return a buffer to the kernel. Their CQEs are
+/// not waited on, a failure only shows as a later `ENOBUFS` on the receive.
+const PROVIDE_BUFFERS_ID: u64 = u64::MAX - 2;
+
+/// The `io_uring_enter` flag that flushes the CQEs held in the overflow backlog.
+const IORING_ENTER_GETEVENTS: u32 = 1;
+
+/// The number of times the overflow backlog is flushed on each call to
+/// [`Driver::wake_tasks`], so it can't keep the event loop from running tasks.
+const MAX_OVERFLOW_FLUSHES: usize = 8;
+
+/// The opcodes osiris relies on for basic file and network I/O, along with the
+/// kernel version that introduced them. Operations that are not listed here, such
+/// as zero copy sends, fail with `EINVAL` when used on kernels that lack them.
+const REQUIRED_OPCODES: [(u8, &str, &str); 16] = [
+    (opcode::Readv::CODE, "Readv", "5.1"),
+    (opcode::Fsync::CODE, "Fsync", "5.1"),
+    (opcode::PollAdd::CODE, "PollAdd", "5.1"),
+    (opcode::SendMsg::CODE, "SendMsg", "5.3"),
+    (opcode::RecvMsg::CODE, "RecvMsg", "5.3"),
+    (opcode::Timeout::CODE, "Timeout", "5.4"),
+    (opcode::Accept::CODE, "Accept", "5.5"),
+    (opcode::AsyncCancel::CODE, "AsyncCancel", "5.5"),
+    (opcode::Connect::CODE, "Connect", "5.5"),
+    (opcode::LinkTimeout::CODE, "LinkTimeout", "5.5"),
+    (opcode::OpenAt::CODE, "OpenAt", "5.6"),
+    (opcode::Close::CODE, "Close", "5.6"),
+    (opcode::Statx::CODE, "Statx", "5.6"),
+    (opcode::Read::CODE, "Read", "5.6"),
+    (opcode::Write::CODE, "Write", "5.6"),
+    (opcode::Recv::CODE, "Recv", "5.6"),
+];
+
 #[non_exhaustive]
 pub(crate) struct Driver {
     // pub(crate) epoll: OwnedFd,
     /// the wakers for tasks listening for IO.
     pub(crate) wakers: HashMap<u64, ControlFlow<cqueue::Entry, Waker>>,
+    /// the ids of in flight zero copy sends, which post a second CQE once the
+    /// kernel no longer uses the buffer.
+    zero_copy: HashSet<u64>,
+    /// the first CQE of zero copy sends, held until their notification arrives.
+    partial: HashMap<u64, cqueue::Entry>,
+    /// the CQEs of multishot operations that haven't been consumed yet, along with
+    /// the waker of the task consuming them.
+    multishot: HashMap<u64, (VecDeque<cqueue::Entry>, Option<Waker>)>,
+    /// the buffer group ids that were released, and can be handed out again.
+    free_groups: Vec<u16>,
+    /// the last buffer group id handed out for the first time.
+    buffer_group: u16,
+    /// the number of CQEs the kernel reported as dropped so far.
+    dropped: u32,
+    /// the features supported by the kernel.
+    pub(crate) capabilities: Capabilities,
+    /// the opcodes supported by the kernel.
+    probe: Probe,
     /// this value corresponds to the last occupied id.
     /// This id will be stored in io-uring's `user_data` attribute
     event_id: u64,
@@ -39,9 +99,19 @@ impl Driver {
         let wakers = HashMap::with_capacity(config.init_capacity);
         #[cfg(target_os = "linux")]
         let io_uring = config.io_uring()?;
+        let probe = check_support(&io_uring)?;
+        let capabilities = capabilities(&probe, kernel_version());
         let event_id = 0;
         let driver = Driver {
             wakers,
+            zero_copy: HashSet::new(),
+            partial: HashMap::new(),
+            multishot: HashMap::new(),
+            free_groups: Vec::new(),
+            buffer_group: 0,
+            dropped: 0,
+            capabilities,
+            probe,
             event_id: 1,
             io_uring,
         };
@@ -55,15 +125,80 @@ impl Driver {
     }
 
     pub fn submit_and_wait(&mut self) -> io::Result<()> {
-        self.io_uring.submit_and_wait(1)?;
+        match self.io_uring.submit_and_wait(1) {
+            // a signal arrived, the event loop will call us again.
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+            res => {
+                res?;
+            }
+        }
         self.wake_tasks();
         Ok(())
     }
 
+    /// Wakes the tasks of every completed event. When the completion queue fills up,
+    /// the kernel keeps the CQEs that did not fit in a backlog, which is only flushed
+    /// into the queue when entering the kernel, so it is drained in a few rounds. What
+    /// is left of it is drained on the next call.
     pub fn wake_tasks(&mut self) {
+        for _ in 0..MAX_OVERFLOW_FLUSHES {
+            self.reap();
+            self.check_dropped();
+            if !self.io_uring.submission().cq_overflow() {
+                return;
+            }
+            // `submit` doesn't enter the kernel while the SQ polling thread is
+            // awake, so the backlog is flushed by entering it directly.
+            let submitter = self.io_uring.submitter();
+            // Safety: no arguments are passed
+            let res =
+                unsafe { submitter.enter::<libc::sigset_t>(0, 0, IORING_ENTER_GETEVENTS, None) };
+            match res {
+                Err(err) if err.kind() != io::ErrorKind::Interrupted => {
+                    eprintln!("osiris: failed to flush the io-uring completion backlog: {err}");
+                    return;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Reports CQEs the kernel could not keep. This should not happen since
+    /// `IORING_FEAT_NODROP` is required, unless the kernel ran out of memory.
+    fn check_dropped(&mut self) {
+        let dropped = self.io_uring.completion().overflow();
+        if dropped != self.dropped {
+            let lost = dropped.wrapping_sub(self.dropped);
+            self.dropped = dropped;
+            eprintln!("osiris: the io-uring completion queue overflowed and {lost} completions were dropped, the tasks waiting on them will never be woken. Consider increasing `Config::queue_entries`.");
+        }
+    }
+
+    fn reap(&mut self) {
         let cqueue = self.io_uring.completion();
-        for cevent in cqueue {
-            let Entry::Occupied(mut entry) = self.wakers.entry(cevent.user_data()) else {
+        for mut cevent in cqueue {
+            let id = cevent.user_data();
+            if id == CANCEL_ID || id == LINK_TIMEOUT_ID || id == PROVIDE_BUFFERS_ID {
+                continue;
+            }
+            if let Some((cqes, waker)) = self.multishot.get_mut(&id) {
+                cqes.push_back(cevent);
+                waker.take().map(Waker::wake);
+                continue;
+            }
+            if self.zero_copy.contains(&id) {
+                if cqueue::more(cevent.flags()) {
+                    // the send will post a notification, so we hold on
+                    // to this one until it arrives.
+                    self.partial.insert(id, cevent);
+                    continue;
+                }
+                // the last CQE is only a notification that the buffer is
+                // no longer in use, the result is held by the first one.
+                self.zero_copy.remove(&id);
+                cevent = self.partial.remove(&id).unwrap_or(cevent);
+            }
+            let Entry::Occupied(mut entry) = self.wakers.entry(id) else {
                 unreachable!(
                         "This is a bug in osiris: a waker has been lost, a CQE was recieved but no associated waker was found."
                     );
@@ -77,6 +212,11 @@ impl Driver {
         }
     }
 
+    /// Returns whether the kernel supports the given opcode.
+    pub fn is_supported(&self, opcode: u8) -> bool {
+        self.probe.is_supported(opcode)
+    }
+
     #[inline]
     pub fn event_id(&mut self) -> u64 {
         self.event_id += 1;
@@ -106,6 +246,74 @@ impl Driver {
         }
     }
 
+    /// Polls the next CQE of a multishot operation pushed with
+    /// [`push_multishot`](Driver::push_multishot). Once its last CQE is returned,
+    /// the one without the `IORING_CQE_F_MORE` flag, the operation is forgotten.
+    pub fn poll_multishot(&mut self, id: u64, waker: &Waker) -> Poll<cqueue::Entry> {
+        let Some((cqes, pending)) = self.multishot.get_mut(&id) else {
+            unreachable!(
+                "This is a bug in osiris: a multishot operation was polled after its last CQE."
+            );
+        };
+        let Some(cqe) = cqes.pop_front() else {
+            *pending = Some(waker.clone());
+            return Poll::Pending;
+        };
+        if !cqueue::more(cqe.flags()) {
+            self.multishot.remove(&id);
+        }
+        Poll::Ready(cqe)
+    }
+
+    /// Like [`push_linked`](Driver::push_linked), but for operations that post a CQE
+    /// for every completion, which are polled with [`poll_multishot`](Driver::poll_multishot).
+    ///
+    /// # Safety
+    ///
+    /// The same as [`push`](Driver::push), for both entries.
+    pub unsafe fn push_multishot(
+        &mut self,
+        entry: squeue::Entry,
+        timeout: squeue::Entry,
+    ) -> io::Result<u64> {
+        // Safety: Invariants must be upheld by the caller.
+        let id = unsafe { self.push_linked(entry, timeout)? };
+        self.multishot.insert(id, (VecDeque::new(), None));
+        Ok(id)
+    }
+
+    /// Returns a buffer to its group, without waiting for the request to complete.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must remain valid until it is consumed by an operation, or removed
+    /// from the group.
+    pub unsafe fn provide_buffer(&mut self, buf: *mut u8, len: i32, group: u16, bid: u16) {
+        let entry = opcode::ProvideBuffers::new(buf, len, 1, group, bid).build();
+        // Safety: upheld by the caller. If the entry can't be pushed the buffer is
+        // lost, and receives fail with ENOBUFS once the group runs out.
+        let _ = unsafe { self.push_entry(entry.user_data(PROVIDE_BUFFERS_ID)) };
+    }
+
+    /// Returns an unused buffer group id, for operations that select a buffer when
+    /// they complete. The id is in use until it is released with
+    /// [`release_buffer_group`](Driver::release_buffer_group).
+    pub fn buffer_group(&mut self) -> io::Result<u16> {
+        if let Some(group) = self.free_groups.pop() {
+            return Ok(group);
+        }
+        if self.buffer_group == u16::MAX {
+            return Err(io::Error::other("every buffer group id is in use"));
+        }
+        self.buffer_group += 1;
+        Ok(self.buffer_group)
+    }
+
+    /// Releases a buffer group id. Its buffers must have been removed from the group.
+    pub fn release_buffer_group(&mut self, group: u16) {
+        self.free_groups.push(group);
+    }
+
     /// Attempts to push an entry into the queue, returning an available id
     /// for the entry.
     /// If the queue is full, an error is returned.
@@ -116,8 +324,64 @@ impl Driver {
     /// be valid for the entire duration of the operation, otherwise it may cause memory problems.
     pub unsafe fn push(&mut self, entry: squeue::Entry) -> std::io::Result<u64> {
         let id = self.event_id();
-        let entry = entry.user_data(id);
+        // Safety: Invariants must be upheld by the caller.
+        unsafe { self.push_entry(entry.user_data(id))? };
+        Ok(id)
+    }
+
+    /// Like [`push`](Driver::push), but for zero copy sends. Their task is only woken
+    /// once the kernel notifies that the buffer is no longer in use, with the CQE
+    /// holding the result of the send.
+    ///
+    /// # Safety
+    ///
+    /// The same as [`push`](Driver::push).
+    pub unsafe fn push_zero_copy(&mut self, entry: squeue::Entry) -> io::Result<u64> {
+        // Safety: Invariants must be upheld by the caller.
+        let id = unsafe { self.push(entry)? };
+        self.zero_copy.insert(id);
+        Ok(id)
+    }
+
+    /// Like [`push`](Driver::push), but `timeout` is linked to the entry, so the
+    /// kernel cancels the operation if the timeout expires before it completes.
+    /// `timeout` must be a `LinkTimeout` entry.
+    ///
+    /// # Safety
+    ///
+    /// The same as [`push`](Driver::push), for both entries.
+    pub unsafe fn push_linked(
+        &mut self,
+        entry: squeue::Entry,
+        timeout: squeue::Entry,
+    ) -> io::Result<u64> {
+        let id = self.event_id();
+        let entry = entry.flags(squeue::Flags::IO_LINK).user_data(id);
+        let timeout = timeout.user_data(LINK_TIMEOUT_ID);
+        // the entries must be submitted together, otherwise the link is broken.
+        let mut queue = self.io_uring.submission();
+        if queue.capacity() - queue.len() < 2 {
+            drop(queue);
+            self.io_uring.submit()?;
+            queue = self.io_uring.submission();
+        }
+        // Safety: Invariants must be upheld by the caller.
+        unsafe { queue.push_multiple(&[entry, timeout]) }
+            .map_err(|_| io::Error::other("the submission queue is full"))?;
+        Ok(id)
+    }
 
+    /// Requests the cancellation of the in flight operation with the given id.
+    /// The operation will complete with `ECANCELED`, or with its result if it
+    /// could not be cancelled in time, so its resources must be kept alive until
+    /// its CQE arrives.
+    pub fn cancel(&mut self, id: u64) -> io::Result<()> {
+        let entry = opcode::AsyncCancel::new(id).build().user_data(CANCEL_ID);
+        // Safety: the cancel entry does not reference any resources
+        unsafe { self.push_entry(entry) }
+    }
+
+    unsafe fn push_entry(&mut self, entry: squeue::Entry) -> io::Result<()> {
         let mut queue = self.io_uring.submission();
 
         if queue.is_full() {
@@ -130,6 +394,102 @@ impl Driver {
             unsafe { queue.push(&entry) };
             drop(queue);
         }
-        Ok(id)
+        Ok(())
     }
 }
+
+/// Probes the opcodes supported by the kernel, returning an error if any of the
+/// features osiris requires is missing.
+fn check_support(io_uring: &IoUring) -> io::Result<Probe> {
+    let params = io_uring.params();
+    if !params.is_feature_nodrop() || !params.is_feature_submit_stable() {
+        return Err(unsupported(
+            "osiris requires io_uring completions to never be dropped (kernel 5.5+)",
+        ));
+    }
+    let mut probe = Probe::new();
+    if io_uring.submitter().register_probe(&mut probe).is_err() {
+        return Err(unsupported(
+            "osiris requires io_uring opcode probing (kernel 5.6+)",
+        ));
+    }
+    check_opcodes(&probe)?;
+    Ok(probe)
+}
+
+fn check_opcodes(probe: &Probe) -> io::Result<()> {
+    for (code, name, version) in REQUIRED_OPCODES {
+        if !probe.is_supported(code) {
+            let msg = format!("osiris requires io_uring opcode {name} (kernel {version}+)");
+            return Err(unsupported(&msg));
+        }
+    }
+    Ok(())
+}
+
+/// Derives the supported features from the probed opcodes. Multishot requests
+/// reuse the opcodes of their single shot counterparts, so they are detected
+/// from the kernel version instead.
+fn capabilities(probe: &Probe, version: (u32, u32)) -> Capabilities {
+    Capabilities {
+        io_uring: true,
+        multishot_accept: version >= (5, 19),
+        multishot_recv: version >= (6, 0),
+        send_zc: probe.is_supported(opcode::SendZc::CODE),
+        provide_buffers: probe.is_supported(opcode::ProvideBuffers::CODE),
+        openat2: probe.is_supported(opcode::OpenAt2::CODE),
+        splice: probe.is_supported(opcode::Splice::CODE)
+            && probe.is_supported(opcode::Tee::CODE),
+    }
+}
+
+/// Returns the major and minor version of the running kernel, as reported by `uname`.
+/// Returns `(0, 0)` if the version could not be determined.
+fn kernel_version() -> (u32, u32) {
+    // Safety: zeroed is a valid value for utsname
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    if syscall!(uname, &mut uts).is_err() {
+        return (0, 0);
+    }
+    // Safety: the release is nul terminated by the kernel
+    let release = unsafe { std::ffi::CStr::from_ptr(uts.release.as_ptr()) };
+    parse_version(&release.to_string_lossy()).unwrap_or((0, 0))
+}
+
+/// Parses the major and minor version out of a kernel release such as `6.1.0-13-amd64`.
+fn parse_version(release: &str) -> Option<(u32, u32)> {
+    let mut parts = release.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+fn unsupported(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, msg)
+}
+
+#[test]
+fn missing_opcode() {
+    let err = check_opcodes(&Probe::new()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    assert_eq!(
+        err.to_string(),
+        "osiris requires io_uring opcode Readv (kernel 5.1+)"
+    );
+}
+
+#[test]
+fn kernel_release() {
+    assert_eq!(parse_version("6.1.0-13-amd64"), Some((6, 1)));
+    assert_eq!(parse_version("5.19.17"), Some((5, 19)));
+    assert_eq!(parse_version("6.18"), Some((6, 18)));
+    assert_eq!(parse_version("unknown"), None);
+}
+
+#[test]
+fn multishot_from_version() {
+    let old = capabilities(&Probe::new(), (5, 15));
+    assert!(old.io_uring && !old.multishot_accept && !old.multishot_recv);
+    let new = capabilities(&Probe::new(), (6, 1));
+    assert!(new.multishot_accept && new.multishot_recv);
+}
@@ -0,0 +1,242 @@
+//! Multishot receives, which post a completion for every chunk of data received
+//! until they are cancelled, using buffers selected by the kernel from a group
+//! provided upfront.
+use std::future::poll_fn;
+use std::io::{Error, Result};
+use std::mem::{forget, take};
+use std::time::{Duration, Instant};
+
+use io_uring::cqueue;
+use io_uring::opcode::{LinkTimeout, ProvideBuffers, RemoveBuffers};
+use io_uring::types::{Fd, Timespec};
+
+use super::event::submit;
+use crate::reactor::utils::timed_out;
+use crate::reactor::{self, Reactor};
+use crate::runtime::current_unwrap;
+
+/// The size of each provided buffer, which bounds the size of the chunks received.
+const BUF_SIZE: usize = 4096;
+/// The number of buffers provided for each receive.
+const BUF_COUNT: u16 = 8;
+
+/// A receive that yields the chunks of data received on a socket until a deadline.
+pub struct RecvMulti {
+    fd: i32,
+    deadline: Instant,
+    state: State,
+    multishot: Option<Multishot>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// no receive is in flight.
+    Idle,
+    /// the receive with the given id is in flight.
+    Armed(u64),
+    /// the peer closed the connection.
+    Eof,
+    /// the deadline has passed.
+    TimedOut,
+}
+
+/// The resources referenced by an in flight multishot receive.
+struct Multishot {
+    driver: Reactor,
+    group: BufferGroup,
+    /// the timeout linked to the receive, read by the kernel on submission.
+    timespec: Box<Timespec>,
+}
+
+/// The buffers provided to the kernel under a group id. When dropped, they are
+/// removed from the group before they are freed, and the id is released.
+struct BufferGroup {
+    driver: Reactor,
+    /// the memory of the provided buffers, which must not be freed while
+    /// they belong to the group.
+    buffers: Box<[u8]>,
+    id: u16,
+}
+
+/// Receives on `fd` until `dur` elapses. Multishot receives require kernel 6.0,
+/// on older kernels every chunk is received with a separate request.
+pub async fn recv_multi(fd: i32, dur: Duration) -> Result<RecvMulti> {
+    let deadline = Instant::now() + dur;
+    let driver = reactor::current();
+    let capabilities = driver.capabilities();
+    let mut recv = RecvMulti {
+        fd,
+        deadline,
+        state: State::Idle,
+        multishot: None,
+    };
+    if !capabilities.multishot_recv || !capabilities.provide_buffers {
+        return Ok(recv);
+    }
+    // without a free group id, chunks are received with separate requests.
+    let Ok(id) = driver.uring().buffer_group() else {
+        return Ok(recv);
+    };
+    let mut group = BufferGroup {
+        driver: driver.clone(),
+        buffers: vec![0; BUF_SIZE * BUF_COUNT as usize].into_boxed_slice(),
+        id,
+    };
+    let len = BUF_SIZE as i32;
+    let sqe = ProvideBuffers::new(group.buffers.as_mut_ptr(), len, BUF_COUNT, id, 0).build();
+    // Safety: the group owns the buffers, and it is kept alive until the CQE
+    // arrives, even if this future is dropped.
+    let (cqe, group) = unsafe { submit(sqe, group).await };
+    cqe?;
+    recv.multishot = Some(Multishot {
+        driver,
+        group,
+        timespec: Box::new(Timespec::new()),
+    });
+    Ok(recv)
+}
+
+impl RecvMulti {
+    /// Returns the next chunk of data received, `None` once the peer closed the
+    /// connection, or an error of kind `TimedOut` once the deadline passed.
+    pub async fn next(&mut self) -> Result<Option<Vec<u8>>> {
+        let Some(multishot) = &self.multishot else {
+            return self.next_single().await;
+        };
+        let driver = multishot.driver.clone();
+        loop {
+            let id = match self.state {
+                State::Armed(id) => id,
+                State::Idle => {
+                    self.arm()?;
+                    continue;
+                }
+                State::Eof => return Ok(None),
+                State::TimedOut => return Err(timed_out()),
+            };
+            let cqe = poll_fn(|cx| driver.uring().poll_multishot(id, cx.waker())).await;
+            if !cqueue::more(cqe.flags()) {
+                // the receive is over, it is submitted again if needed.
+                self.state = State::Idle;
+            }
+            let res = cqe.result();
+            let chunk = cqueue::buffer_select(cqe.flags())
+                .map(|bid| self.take_buffer(bid, res.max(0) as usize));
+            match res {
+                1.. => return Ok(chunk),
+                0 => {
+                    self.state = State::Eof;
+                    return Ok(None);
+                }
+                // the buffers ran out, they have been returned by now.
+                _ if -res == libc::ENOBUFS => continue,
+                // the linked timeout expired.
+                _ if -res == libc::ECANCELED => {
+                    self.state = State::TimedOut;
+                    return Err(timed_out());
+                }
+                _ => return Err(Error::from_raw_os_error(-res)),
+            }
+        }
+    }
+
+    /// Receives a chunk with a separate request, for kernels without multishot receives.
+    async fn next_single(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.state == State::Eof {
+            return Ok(None);
+        }
+        let dur = self.deadline.saturating_duration_since(Instant::now());
+        if dur.is_zero() {
+            return Err(timed_out());
+        }
+        let buf = Vec::with_capacity(BUF_SIZE);
+        let (res, buf) = super::op::read_timeout(self.fd, buf, 0, dur).await;
+        if res? == 0 {
+            self.state = State::Eof;
+            return Ok(None);
+        }
+        Ok(Some(buf))
+    }
+
+    /// Submits the multishot receive, linked to a timeout for the rest of the window.
+    fn arm(&mut self) -> Result<()> {
+        let multishot = self.multishot.as_mut().unwrap();
+        let dur = self.deadline.saturating_duration_since(Instant::now());
+        if dur.is_zero() {
+            self.state = State::TimedOut;
+            return Ok(());
+        }
+        *multishot.timespec = Timespec::new().sec(dur.as_secs()).nsec(dur.subsec_nanos());
+        let sqe = io_uring::opcode::RecvMulti::new(Fd(self.fd), multishot.group.id).build();
+        let timeout = LinkTimeout::new(&*multishot.timespec).build();
+        // Safety: the buffers and the timespec live until the last CQE arrives
+        let id = unsafe { multishot.driver.uring().push_multishot(sqe, timeout)? };
+        self.state = State::Armed(id);
+        Ok(())
+    }
+
+    /// Copies the chunk out of a provided buffer, and returns the buffer to the group.
+    fn take_buffer(&mut self, bid: u16, len: usize) -> Vec<u8> {
+        let multishot = self.multishot.as_mut().unwrap();
+        let buf = &mut multishot.group.buffers[bid as usize * BUF_SIZE..][..BUF_SIZE];
+        let chunk = buf[..len].to_vec();
+        let (ptr, group) = (buf.as_mut_ptr(), multishot.group.id);
+        // Safety: the buffers are removed from the group before they are freed
+        unsafe {
+            multishot
+                .driver
+                .uring()
+                .provide_buffer(ptr, BUF_SIZE as i32, group, bid)
+        };
+        chunk
+    }
+}
+
+impl Drop for RecvMulti {
+    fn drop(&mut self) {
+        let Some(multishot) = self.multishot.take() else {
+            return;
+        };
+        let State::Armed(id) = self.state else {
+            return;
+        };
+        // the buffers are kept alive until the receive is over.
+        let cleanup = async move {
+            let driver = multishot.driver.clone();
+            // if the cancellation can't be submitted the receive
+            // still ends on its own when the timeout expires.
+            let _ = driver.uring().cancel(id);
+            loop {
+                let cqe = poll_fn(|cx| driver.uring().poll_multishot(id, cx.waker())).await;
+                if !cqueue::more(cqe.flags()) {
+                    break;
+                }
+            }
+            drop(multishot);
+        };
+        current_unwrap("RecvMulti").detach_cleanup(cleanup);
+    }
+}
+
+impl Drop for BufferGroup {
+    fn drop(&mut self) {
+        let buffers = take(&mut self.buffers);
+        let (driver, id) = (self.driver.clone(), self.id);
+        let cleanup = async move {
+            let sqe = RemoveBuffers::new(BUF_COUNT, id).build();
+            // Safety: no resource tracking needed
+            let (cqe, _) = unsafe { submit(sqe, ()).await };
+            match cqe {
+                // the group is gone, or it was never created.
+                Ok(_) => (),
+                Err(err) if err.raw_os_error() == Some(libc::ENOENT) => (),
+                // the buffers may still belong to the group, so they are leaked
+                // rather than freed, and the id is not handed out again.
+                Err(_) => return forget(buffers),
+            }
+            driver.uring().release_buffer_group(id);
+            drop(buffers);
+        };
+        current_unwrap("RecvMulti").detach_cleanup(cleanup);
+    }
+}
@@ -1,11 +1,12 @@
 #![allow(warnings)]
-use crate::utils::{statx, syscall, STATX_ALL};
+use crate::utils::{statx, syscall};
 use io_uring::opcode::{
-    self, Accept, Close, Connect, Fsync, MkDirAt, OpenAt, PollAdd, PollRemove, Read, Recv, SendMsg,
-    Socket, Statx, SymlinkAt, Timeout, UnlinkAt, Write,
+    self, Accept, Close, Connect, Fadvise, Fsync, LinkTimeout, MkDirAt, OpenAt, OpenAt2, PollAdd,
+    PollRemove, Read, Readv, Recv, RecvMsg, SendMsg, SendZc, Socket, Splice, Statx, SymlinkAt, Tee,
+    Timeout, UnlinkAt, Write,
 };
-use io_uring::types::{Fd, FsyncFlags, Timespec};
-use libc::{iovec, msghdr, timespec, AT_FDCWD};
+use io_uring::types::{Fd, FsyncFlags, OpenHow, Timespec};
+use libc::{iovec, msghdr, timespec};
 use std::ffi::CString;
 use std::fmt::Debug;
 use std::future::{poll_fn, Future, Pending};
@@ -17,12 +18,19 @@ use std::pin::Pin;
 use std::task::{ready, Poll};
 use std::time::Duration;
 
-use super::event::submit;
+use super::event::{submit, submit_linked, submit_zero_copy};
 use crate::buf::{IoBuf, IoBufMut};
 use crate::net::utils::{socket_addr, to_std_socket_addr};
+use crate::reactor;
+use crate::reactor::utils::{iovecs_mut, set_len_vectored};
+use crate::reactor::utils::{recv_addr, timed_out, MSG_NOSIGNAL};
+use crate::time::timeout_or_continue;
 
 pub use super::super::utils::{make_blocking, make_nonblocking, socket};
+pub use super::multishot::{recv_multi, RecvMulti};
 pub use {read_at as fs_read, write_at as fs_write};
+// io-uring offloads operations on blocking file descriptors to its worker threads.
+pub use {read_at as read_blocking_fd, write_at as write_blocking_fd};
 
 /// Attempts to close a file descriptor
 pub async fn close(fd: i32) -> Result<()> {
@@ -48,6 +56,68 @@ pub async fn read_at<B: IoBufMut>(fd: i32, mut buf: B, pos: i64) -> (Result<usiz
     (Ok(len), buf)
 }
 
+/// Like [`read_at`], but the read is cancelled if it does not complete
+/// within `dur`, in which case an error of kind `TimedOut` is returned.
+pub async fn read_timeout<B: IoBufMut>(
+    fd: i32,
+    mut buf: B,
+    pos: i64,
+    dur: Duration,
+) -> (Result<usize>, B) {
+    let sqe = Read::new(Fd(fd), buf.stable_mut_ptr(), buf.bytes_total() as _)
+        .offset(pos as _)
+        .build();
+    // Safety: the resource (buf) was passed to submit
+    let mut event = unsafe { submit(sqe, buf) };
+    let (cqe, mut buf) = match timeout_or_continue(dur, &mut event).await {
+        Ok(out) => out,
+        Err(event) => {
+            // if the cancellation can't be submitted the read
+            // will eventually complete on its own.
+            let _ = event.cancel();
+            event.await
+        }
+    };
+
+    let cqe = match cqe {
+        Ok(cqe) => cqe,
+        Err(err) if err.raw_os_error() == Some(libc::ECANCELED) => {
+            return (Err(timed_out()), buf);
+        }
+        Err(err) => return (Err(err), buf),
+    };
+    let len = cqe.result() as usize;
+
+    // initialized by io-uring
+    unsafe { buf.set_init(len) };
+
+    (Ok(len), buf)
+}
+
+/// Attempts to read from a file descriptor into multiple buffers, filling them in order
+pub async fn read_vectored_at(
+    fd: i32,
+    mut bufs: Vec<Vec<u8>>,
+    pos: i64,
+) -> (Result<usize>, Vec<Vec<u8>>) {
+    let iovecs = iovecs_mut(&mut bufs);
+    let sqe = Readv::new(Fd(fd), iovecs.as_ptr(), iovecs.len() as _)
+        .offset(pos as _)
+        .build();
+    // Safety: both the iovecs and the buffers are guarded
+    let (cqe, (_, mut bufs)) = unsafe { submit(sqe, (iovecs, bufs)).await };
+
+    let Ok(cqe) = cqe else {
+        return (cqe.map(|_| unreachable!()), bufs);
+    };
+    let len = cqe.result() as usize;
+
+    // initialized by io-uring
+    unsafe { set_len_vectored(&mut bufs, len) };
+
+    (Ok(len), bufs)
+}
+
 /// Attempts to write to a file descriptor
 pub async fn write_at<B: IoBuf>(fd: i32, buf: B, pos: i64) -> (Result<usize>, B) {
     let sqe = Write::new(Fd(fd), buf.stable_ptr(), buf.bytes_init() as _)
@@ -57,6 +127,38 @@ pub async fn write_at<B: IoBuf>(fd: i32, buf: B, pos: i64) -> (Result<usize>, B)
     (cqe.map(|cqe| cqe.result() as usize), buf)
 }
 
+/// Sends the buffer on a connected socket. A closed peer fails with `EPIPE`
+/// rather than raising `SIGPIPE`.
+pub async fn send<B: IoBuf>(fd: i32, buf: B) -> (Result<usize>, B) {
+    let sqe = opcode::Send::new(Fd(fd), buf.stable_ptr(), buf.bytes_init() as _)
+        .flags(MSG_NOSIGNAL)
+        .build();
+    // Safety: the buffer is guarded
+    let (cqe, buf) = unsafe { submit(sqe, buf).await };
+    (cqe.map(|cqe| cqe.result() as usize), buf)
+}
+
+/// Attempts to send the buffer without copying it into kernel memory.
+///
+/// Zero copy sends post two CQEs: the first one holds the result of the send,
+/// and the second one notifies that the kernel no longer uses the buffer. The
+/// buffer is only returned after the notification arrives, and if the future is
+/// dropped, it is kept alive until then.
+///
+/// Zero copy sends require Linux 6.0 or later. On older kernels this falls back
+/// to a regular send.
+pub async fn send_zc<B: IoBuf>(fd: i32, buf: B) -> (Result<usize>, B) {
+    if !reactor::current().uring().capabilities.send_zc {
+        return send(fd, buf).await;
+    }
+    let sqe = SendZc::new(Fd(fd), buf.stable_ptr(), buf.bytes_init() as _)
+        .flags(MSG_NOSIGNAL)
+        .build();
+    // Safety: the buffer is guarded
+    let (cqe, buf) = unsafe { submit_zero_copy(sqe, buf).await };
+    (cqe.map(|cqe| cqe.result() as usize), buf)
+}
+
 /// Performs an fsync call
 pub async fn fsync(fd: i32) -> Result<i32> {
     let sqe = Fsync::new(Fd(fd)).flags(FsyncFlags::all()).build();
@@ -73,6 +175,16 @@ pub async fn fdatasync(fd: i32) -> Result<i32> {
     Ok(res)
 }
 
+/// Declares the expected access pattern for a region of a file
+pub async fn fadvise(fd: i32, offset: i64, len: i64, advice: i32) -> Result<()> {
+    let sqe = Fadvise::new(Fd(fd), len as _, advice)
+        .offset(offset as _)
+        .build();
+    // Safety: no resource tracking needed
+    unsafe { submit(sqe, ()).await.0? };
+    Ok(())
+}
+
 /// Creates a socket
 pub async fn async_socket(
     domain: i32,
@@ -92,8 +204,12 @@ pub async fn recv<B: IoBufMut>(fd: i32, mut buf: B) -> (Result<usize>, B) {
     let len = buf.bytes_total() as u32;
     let ptr = buf.stable_mut_ptr();
     let sqe = Recv::new(Fd(fd), ptr, len).build();
-    let (res, buf) = unsafe { submit(sqe, buf).await };
+    let (res, mut buf) = unsafe { submit(sqe, buf).await };
     let res = res.map(|r| r.result() as usize);
+    if let Ok(len) = res {
+        // initialized by io-uring
+        unsafe { buf.set_init(len) };
+    }
     (res, buf)
 }
 
@@ -102,12 +218,13 @@ pub async fn epoll_ctl(epfd: i32, fd: i32) {}
 /// Performs a statx "system call" on a file or path
 /// The value for `fd` can either be an opened file descriptor
 /// or `libc::AT_FDCWD` and the path value will be used.
+/// The `mask` selects the fields the kernel is asked to fill.
 ///
 /// # Examples
 /// ```ignore
-/// let statx = op::statx(libc::AT_FDCWD, Some(path)).await?;
+/// let statx = op::statx(libc::AT_FDCWD, Some(path), 0, STATX_ALL).await?;
 /// ```
-pub async fn stat(fd: i32, path: Option<CString>, flags: i32) -> Result<statx> {
+pub async fn statx(fd: i32, path: Option<CString>, flags: i32, mask: u32) -> Result<statx> {
     let pathname = path
         .as_ref()
         .map(|x| x.as_ptr())
@@ -115,7 +232,7 @@ pub async fn stat(fd: i32, path: Option<CString>, flags: i32) -> Result<statx> {
     let statx = std::mem::MaybeUninit::<statx>::uninit();
     let mut statx = Box::new(statx);
     let sqe = Statx::new(Fd(fd), pathname, statx.as_mut_ptr().cast())
-        .mask(STATX_ALL)
+        .mask(mask)
         .flags(if path.is_none() {
             flags | libc::AT_EMPTY_PATH
         } else {
@@ -137,6 +254,22 @@ pub async fn connect(fd: i32, addr: SocketAddr) -> Result<()> {
     Ok(())
 }
 
+/// Like [`connect`], but the kernel cancels the connection attempt if it does not
+/// complete within `dur`, in which case an error of kind `TimedOut` is returned.
+pub async fn connect_timeout(fd: i32, addr: SocketAddr, dur: Duration) -> Result<()> {
+    let (addr, len) = socket_addr(&addr);
+    let timespec = Timespec::new().sec(dur.as_secs()).nsec(dur.subsec_nanos());
+    let data = Box::new((addr, timespec));
+    let sqe = Connect::new(Fd(fd), data.0.as_ptr().cast(), len).build();
+    let timeout = LinkTimeout::new(&data.1).build();
+    // Safety: the address and the timespec were passed to submit
+    let (cqe, _) = unsafe { submit_linked(sqe, timeout, data).await };
+    match cqe {
+        Err(err) if err.raw_os_error() == Some(libc::ECANCELED) => Err(timed_out()),
+        cqe => cqe.map(drop),
+    }
+}
+
 pub async fn send_to<B: IoBuf>(fd: i32, buf: B, addr: SocketAddr) -> (Result<usize>, B) {
     // we define the iovec from the buffer
     let msg_iov: iovec = iovec {
@@ -159,14 +292,105 @@ pub async fn send_to<B: IoBuf>(fd: i32, buf: B, addr: SocketAddr) -> (Result<usi
     msg.0.msg_iov = &mut msg.1;
     msg.0.msg_iovlen = 1;
 
-    let sqe = SendMsg::new(Fd(fd), &msg.0).build();
+    let sqe = SendMsg::new(Fd(fd), &msg.0)
+        .flags(MSG_NOSIGNAL as _)
+        .build();
     let (res, (_, buf)) = unsafe { submit(sqe, (msg, buf)).await };
     let res = res.map(|sqe| sqe.result() as usize);
     (res, buf)
 }
 
-pub async fn open_at(path: CString, flags: i32, mode: u32) -> Result<i32> {
-    let entry = OpenAt::new(Fd(libc::AT_FDCWD), path.as_ptr())
+/// Receives a message into `buf`, and its ancillary data into `control`.
+///
+/// Returns the number of bytes received and the address of the sender, if any.
+/// The control buffer is initialized up to the length of the received control
+/// messages, which can be iterated with [`control_messages`](crate::reactor::utils::control_messages).
+pub async fn recvmsg<B: IoBufMut, C: IoBufMut>(
+    fd: i32,
+    mut buf: B,
+    mut control: C,
+) -> (Result<(usize, Option<SocketAddr>)>, B, C) {
+    let msg_iov = iovec {
+        iov_base: buf.stable_mut_ptr().cast(),
+        iov_len: buf.bytes_total(),
+    };
+    let msghdr: msghdr = unsafe { zeroed() };
+    let addr: libc::sockaddr_storage = unsafe { zeroed() };
+
+    // we allocate everything once
+    let mut msg = Box::new((msghdr, msg_iov, addr));
+
+    msg.0.msg_name = &mut msg.2 as *mut _ as *mut _;
+    msg.0.msg_namelen = size_of_val(&msg.2) as _;
+    msg.0.msg_iov = &mut msg.1;
+    msg.0.msg_iovlen = 1;
+    msg.0.msg_control = control.stable_mut_ptr().cast();
+    msg.0.msg_controllen = control.bytes_total() as _;
+
+    let sqe = RecvMsg::new(Fd(fd), &mut msg.0).build();
+    // Safety: the message header and both buffers are guarded
+    let (cqe, (msg, mut buf, mut control)) = unsafe { submit(sqe, (msg, buf, control)).await };
+    let cqe = match cqe {
+        Ok(cqe) => cqe,
+        Err(err) => return (Err(err), buf, control),
+    };
+    let len = cqe.result() as usize;
+    // Safety: initialized by io-uring
+    unsafe {
+        buf.set_init(len);
+        control.set_init(msg.0.msg_controllen as _);
+    }
+    let addr = recv_addr(&msg.2, msg.0.msg_namelen);
+    (Ok((len, addr)), buf, control)
+}
+
+/// Sends the message in `buf` along with the ancillary data in `control`. The
+/// address is only required for sockets that are not connected.
+pub async fn sendmsg<B: IoBuf, C: IoBuf>(
+    fd: i32,
+    buf: B,
+    addr: Option<SocketAddr>,
+    control: C,
+) -> (Result<usize>, B, C) {
+    let msg_iov = iovec {
+        iov_base: buf.stable_ptr().cast_mut().cast(),
+        iov_len: buf.bytes_init(),
+    };
+    let msghdr: msghdr = unsafe { zeroed() };
+    let (addr, len) = match addr {
+        Some(addr) => {
+            let (addr, len) = socket_addr(&addr);
+            (Some(addr), len)
+        }
+        None => (None, 0),
+    };
+
+    // we allocate everything once
+    let mut msg = Box::new((msghdr, msg_iov, addr));
+
+    if let Some(addr) = &mut msg.2 {
+        msg.0.msg_name = addr as *mut _ as *mut _;
+        msg.0.msg_namelen = len;
+    }
+    msg.0.msg_iov = &mut msg.1;
+    msg.0.msg_iovlen = 1;
+    if control.bytes_init() != 0 {
+        msg.0.msg_control = control.stable_ptr().cast_mut().cast();
+        msg.0.msg_controllen = control.bytes_init() as _;
+    }
+
+    let sqe = SendMsg::new(Fd(fd), &msg.0)
+        .flags(MSG_NOSIGNAL as _)
+        .build();
+    // Safety: the message header and both buffers are guarded
+    let (res, (_, buf, control)) = unsafe { submit(sqe, (msg, buf, control)).await };
+    let res = res.map(|cqe| cqe.result() as usize);
+    (res, buf, control)
+}
+
+/// Opens a file relative to the directory `dirfd`, which may be `AT_FDCWD`.
+pub async fn open_at(dirfd: i32, path: CString, flags: i32, mode: u32) -> Result<i32> {
+    let entry = OpenAt::new(Fd(dirfd), path.as_ptr())
         .flags(flags)
         .mode(mode)
         .build();
@@ -176,16 +400,40 @@ pub async fn open_at(path: CString, flags: i32, mode: u32) -> Result<i32> {
     Ok(cqe?.result())
 }
 
+/// Opens a file relative to `dirfd` like [`open_at`], restricting how the path
+/// is resolved with the `RESOLVE_*` flags of `openat2(2)`. Requires Linux 5.6.
+pub async fn openat2(
+    dirfd: i32,
+    path: CString,
+    flags: i32,
+    mode: u32,
+    resolve: u64,
+) -> Result<i32> {
+    let how = OpenHow::new()
+        .flags(flags as _)
+        .mode(mode as _)
+        .resolve(resolve);
+    let how = Box::new(how);
+    let entry = OpenAt2::new(Fd(dirfd), path.as_ptr(), &*how).build();
+
+    // Safety: both the pathname and the open_how struct are submitted
+    let (cqe, _) = unsafe { submit(entry, (path, how)) }.await;
+    Ok(cqe?.result())
+}
+
 pub async fn accept(fd: i32) -> Result<(i32, SocketAddr)> {
-    let addr: libc::sockaddr = unsafe { zeroed() };
-    let mut addr = Box::new(addr);
-    let mut len = size_of_val(&addr) as _;
-    let sqe = Accept::new(Fd(fd), &mut *addr as *mut _ as _, &mut len).build();
+    let addr: libc::sockaddr_storage = unsafe { zeroed() };
+    // the address length is written by the kernel, so it must be
+    // guarded along with the address.
+    let mut addr = Box::new((addr, size_of_val(&addr) as libc::socklen_t));
+    let sqe = Accept::new(Fd(fd), &mut addr.0 as *mut _ as _, &mut addr.1)
+        .flags(libc::SOCK_CLOEXEC)
+        .build();
+    // Safety: the address and its length are guarded
     let (cqe, addr) = unsafe { submit(sqe, addr).await };
     let socket = cqe?.result();
-    // TODO: fix the parsinf of SocketAddr
-    // let addr = "127.0.0.1:8000".parse().unwrap();
-    let addr = to_std_socket_addr(&addr)?;
+    // Safety: `sockaddr_storage` is large enough for any socket address
+    let addr = to_std_socket_addr(unsafe { &*(&addr.0 as *const libc::sockaddr_storage).cast() })?;
     Ok((socket, addr))
 }
 
@@ -201,8 +449,8 @@ pub async fn shutdown(fd: i32, how: Shutdown) -> Result<()> {
     Ok(())
 }
 
-pub async fn mkdir_at(path: CString) -> Result<()> {
-    let sqe = MkDirAt::new(Fd(libc::AT_FDCWD), path.as_ptr()).build();
+pub async fn mkdir_at(dirfd: i32, path: CString) -> Result<()> {
+    let sqe = MkDirAt::new(Fd(dirfd), path.as_ptr()).mode(0o777).build();
     let (cqe, _) = unsafe { submit(sqe, path).await };
     cqe.map(|_| ())
 }
@@ -211,26 +459,71 @@ pub async fn mkdir_at(path: CString) -> Result<()> {
 /// relative to the directory referred to by the file descriptor dirfd
 /// (rather than relative to the current working directory of the calling
 /// process, as is done by unlink(2) and rmdir(2) for a relative pathname).
-pub async fn unlink_at(path: CString, flags: i32) -> Result<()> {
-    let sqe = UnlinkAt::new(Fd(AT_FDCWD), path.as_ptr())
-        .flags(flags)
-        .build();
+pub async fn unlink_at(dirfd: i32, path: CString, flags: i32) -> Result<()> {
+    let sqe = UnlinkAt::new(Fd(dirfd), path.as_ptr()).flags(flags).build();
     // Safety: the path is protected by submit
     let (cqe, _) = unsafe { submit(sqe, path).await };
     cqe.map(|_| ())
 }
 
-pub async fn poll_add(fd: i32, multi: bool) -> Result<()> {
-    let sqe = PollAdd::new(Fd(fd), 0).multi(multi).build();
+pub async fn poll_add(fd: i32, mask: u32, multi: bool) -> Result<()> {
+    let sqe = PollAdd::new(Fd(fd), mask).multi(multi).build();
     let (cqe, _) = unsafe { submit(sqe, ()).await };
-    dbg!("fd is ready");
     cqe.map(|_| ())
 }
 
+/// Moves up to `len` bytes from `fd_in` to `fd_out`, where an offset of `-1` means the
+/// current position of the file descriptor is used.
+pub async fn splice(fd_in: i32, off_in: i64, fd_out: i32, off_out: i64, len: u32) -> Result<usize> {
+    loop {
+        let sqe = Splice::new(Fd(fd_in), off_in, Fd(fd_out), off_out, len).build();
+        // Safety: no resource tracking needed
+        let res = unsafe { submit(sqe, ()).await.0 };
+        match res {
+            Ok(cqe) => return Ok(cqe.result() as usize),
+            // nonblocking pipes fail instead of waiting
+            Err(err) if err.raw_os_error() == Some(libc::EAGAIN) => {
+                poll_add(fd_in, libc::POLLIN as _, false).await?;
+                poll_add(fd_out, libc::POLLOUT as _, false).await?;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Duplicates up to `len` bytes from the pipe `fd_in` to the pipe `fd_out`.
+pub async fn tee(fd_in: i32, fd_out: i32, len: u32) -> Result<usize> {
+    loop {
+        let sqe = Tee::new(Fd(fd_in), Fd(fd_out), len).build();
+        // Safety: no resource tracking needed
+        let res = unsafe { submit(sqe, ()).await.0 };
+        match res {
+            Ok(cqe) => return Ok(cqe.result() as usize),
+            Err(err) if err.raw_os_error() == Some(libc::EAGAIN) => {
+                poll_add(fd_in, libc::POLLIN as _, false).await?;
+                poll_add(fd_out, libc::POLLOUT as _, false).await?;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Waits for `fd` to become readable.
+pub async fn wait_readable(fd: i32) -> Result<()> {
+    poll_add(fd, libc::POLLIN as _, false).await
+}
+
+/// Waits for `fd` to become writable.
+pub async fn wait_writable(fd: i32) -> Result<()> {
+    poll_add(fd, libc::POLLOUT as _, false).await
+}
+
 pub async fn write_nonblock(fd: i32, buf: *const u8, len: usize) -> Result<usize> {
-    nonblock(fd, || syscall!(write, fd, buf.cast(), len))
-        .await
-        .map(|written| written as usize)
+    wait_nonblock(fd, libc::POLLOUT as _, || {
+        syscall!(write, fd, buf.cast(), len)
+    })
+    .await
+    .map(|written| written as usize)
 }
 
 pub async fn read_nonblock(fd: i32, buf: *mut u8, len: usize) -> Result<usize> {
@@ -239,7 +532,12 @@ pub async fn read_nonblock(fd: i32, buf: *mut u8, len: usize) -> Result<usize> {
         .map(|read| read as usize)
 }
 
-pub async fn nonblock<T: Debug>(fd: i32, mut f: impl FnMut() -> Result<T>) -> Result<T> {
+/// Calls `f` until it stops failing with `EAGAIN`, waiting for `fd` to become readable in between.
+pub async fn nonblock<T>(fd: i32, f: impl FnMut() -> Result<T>) -> Result<T> {
+    wait_nonblock(fd, libc::POLLIN as _, f).await
+}
+
+async fn wait_nonblock<T>(fd: i32, mask: u32, mut f: impl FnMut() -> Result<T>) -> Result<T> {
     loop {
         let result = f();
         let Err(err) = result else {
@@ -248,7 +546,9 @@ pub async fn nonblock<T: Debug>(fd: i32, mut f: impl FnMut() -> Result<T>) -> Re
         let Some(libc::EAGAIN) = err.raw_os_error() else {
             return Err(err);
         };
-        poll_add(fd, true).await;
+        // a oneshot poll, since events resolve on their first CQE. The CQEs a
+        // multishot poll posts after it would have no task waiting for them.
+        poll_add(fd, mask, false).await?;
     }
 }
 
@@ -45,13 +45,15 @@ impl Driver {
         })
     }
 
+    /// Blocks until at least one event is ready. Wakeups coming from other
+    /// threads are delivered through a pipe registered with the reactor,
+    /// so they interrupt the wait too.
     pub fn submit_and_wait(&mut self) -> io::Result<()> {
-        self.submit(&libc::timespec {
-            tv_nsec: 0,
-            tv_sec: 60,
-        })
+        self.submit(std::ptr::null())
     }
 
+    /// Submits the queued events and collects the ready ones, where a null
+    /// `timeout` means to block indefinitely.
     #[rustfmt::skip]
     fn submit(&mut self, timeout: *const libc::timespec) -> io::Result<()> {
         let kq         = self.fd.as_raw_fd();
@@ -59,7 +61,13 @@ impl Driver {
         let eventlist  = self.queue.as_mut_ptr();
         let nevents    = self.queue.capacity() as i32;
         let nchanges   = self.queue.len() as i32;
-        let len        = syscall!(kevent, kq, changelist, nchanges, eventlist, nevents, timeout)?;
+        let len = match syscall!(kevent, kq, changelist, nchanges, eventlist, nevents, timeout) {
+            Ok(len) => len,
+            // a signal arrived, the changelist has been processed so the
+            // event loop can call us again.
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => 0,
+            Err(err) => return Err(err),
+        };
         unsafe { self.queue.set_len(len as usize) };
         self.wake_tasks();
         Ok(())
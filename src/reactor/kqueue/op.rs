@@ -66,6 +66,12 @@ pub async fn fdatasync(fd: i32) -> Result<()> {
     Ok(())
 }
 
+/// `posix_fadvise` is not available on every BSD, and the advice is only a hint,
+/// so it is ignored.
+pub async fn fadvise(_fd: i32, _offset: i64, _len: i64, _advice: i32) -> Result<()> {
+    Ok(())
+}
+
 /// Submits a timeout operation to the queue
 pub async fn sleep(dur: Duration) -> Result<()> {
     let mut event = zeroed;
@@ -8,7 +8,9 @@ use ::{
 };
 
 #[cfg(io_uring)]
-pub(crate) use iouring::{op, Driver};
+pub(crate) use dispatch as op;
+#[cfg(io_uring)]
+pub(crate) use poll::Event;
 
 #[cfg(all(target_os = "linux", not(io_uring)))]
 pub(crate) use poll::{op, Driver, Event};
@@ -16,7 +18,10 @@ pub(crate) use poll::{op, Driver, Event};
 #[cfg(kqueue)]
 pub(crate) use kqueue::{op, Driver, Event};
 
-use crate::runtime::Config;
+use crate::runtime::{Capabilities, Config};
+
+#[cfg(io_uring)]
+pub(crate) mod dispatch;
 
 // #[cfg(target_os = "linux")]
 // mod epoll;
@@ -26,14 +31,65 @@ mod iouring;
 #[cfg(kqueue)]
 mod kqueue;
 
-#[cfg(not(io_uring))]
 mod nonblocking;
 
-#[cfg(poll)]
+#[cfg(target_os = "linux")]
 mod poll;
 
 mod utils;
 
+/// The driver backing the reactor. When io-uring is not available on the host,
+/// and [`Config::allow_fallback`](Config#structfield.allow_fallback) is set, the
+/// readiness based poll driver is used instead.
+// there is a single driver per runtime, so its size doesn't matter.
+#[cfg(io_uring)]
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum Driver {
+    IoUring(iouring::Driver),
+    Poll(poll::Driver),
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Forces the runtimes created on this thread to fall back to the poll driver.
+    pub(crate) static FORCE_FALLBACK: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+#[cfg(io_uring)]
+impl Driver {
+    pub fn new(config: Config) -> io::Result<Driver> {
+        #[cfg(test)]
+        if FORCE_FALLBACK.with(std::cell::Cell::get) {
+            return Ok(Driver::Poll(poll::Driver::new(config)?));
+        }
+        let err = match iouring::Driver::new(config.clone()) {
+            Ok(driver) => return Ok(Driver::IoUring(driver)),
+            Err(err) => err,
+        };
+        // io-uring may be missing, blocked by a seccomp filter, or too old.
+        let unavailable = matches!(err.raw_os_error(), Some(libc::ENOSYS | libc::EPERM))
+            || err.kind() == io::ErrorKind::Unsupported;
+        if !(config.allow_fallback && unavailable) {
+            return Err(err);
+        }
+        Ok(Driver::Poll(poll::Driver::new(config)?))
+    }
+
+    pub fn submit_and_yield(&mut self) -> io::Result<()> {
+        match self {
+            Driver::IoUring(driver) => driver.submit_and_yield(),
+            Driver::Poll(driver) => driver.submit_and_yield(),
+        }
+    }
+
+    pub fn submit_and_wait(&mut self) -> io::Result<()> {
+        match self {
+            Driver::IoUring(driver) => driver.submit_and_wait(),
+            Driver::Poll(driver) => driver.submit_and_wait(),
+        }
+    }
+}
+
 /// The driver stores the wakers for all the tasks that
 /// are waiting for IO and it will wake them when it is
 #[derive(Clone)]
@@ -59,10 +115,53 @@ impl Reactor {
         Ok(())
     }
 
-    pub fn driver(&self) -> RefMut<'_, Driver> {
+    /// Returns the readiness based driver.
+    #[cfg(not(io_uring))]
+    pub fn poller(&self) -> RefMut<'_, Driver> {
         self.0.borrow_mut()
     }
 
+    /// Returns the poll driver.
+    ///
+    /// # Panics
+    /// If the reactor is backed by io-uring.
+    #[cfg(io_uring)]
+    pub fn poller(&self) -> RefMut<'_, poll::Driver> {
+        RefMut::map(self.0.borrow_mut(), |driver| match driver {
+            Driver::Poll(driver) => driver,
+            Driver::IoUring(_) => unreachable!("the reactor is not backed by poll"),
+        })
+    }
+
+    /// Returns the io-uring driver.
+    ///
+    /// # Panics
+    /// If the reactor fell back to the poll driver.
+    #[cfg(io_uring)]
+    pub fn uring(&self) -> RefMut<'_, iouring::Driver> {
+        RefMut::map(self.0.borrow_mut(), |driver| match driver {
+            Driver::IoUring(driver) => driver,
+            Driver::Poll(_) => unreachable!("the reactor is not backed by io-uring"),
+        })
+    }
+
+    /// Returns whether the reactor is backed by io-uring, as opposed to the
+    /// poll driver it may fall back to.
+    #[cfg(io_uring)]
+    pub fn is_io_uring(&self) -> bool {
+        matches!(*self.0.borrow(), Driver::IoUring(_))
+    }
+
+    /// Returns the io-uring features supported by the kernel, which are all
+    /// unsupported if the reactor is not backed by io-uring.
+    pub fn capabilities(&self) -> Capabilities {
+        #[cfg(io_uring)]
+        if let Driver::IoUring(driver) = &*self.0.borrow() {
+            return driver.capabilities;
+        }
+        Capabilities::default()
+    }
+
     /// This function is used to poll the driver about a specific event.
     ///
     /// When polled, the driver will update the waker for the IO event, and
@@ -70,7 +169,7 @@ impl Reactor {
     #[cfg(io_uring)]
     #[inline]
     pub fn poll(&self, id: u64, cx: &mut Context) -> Poll<cqueue::Entry> {
-        self.0.borrow_mut().poll(id, cx.waker())
+        self.uring().poll(id, cx.waker())
     }
 
     /// Attempts to push an entry into the queue.
@@ -83,7 +182,31 @@ impl Reactor {
     #[cfg(io_uring)]
     pub unsafe fn push(&self, entry: Entry) -> std::io::Result<u64> {
         // Safety: Invariants must be upheld by the caller.
-        unsafe { self.0.borrow_mut().push(entry) }
+        unsafe { self.uring().push(entry) }
+    }
+
+    /// Attempts to push a zero copy send into the queue.
+    /// See [`iouring::Driver::push_zero_copy`].
+    ///
+    /// # Safety
+    ///
+    /// The same as [`push`](Reactor::push).
+    #[cfg(io_uring)]
+    pub unsafe fn push_zero_copy(&self, entry: Entry) -> std::io::Result<u64> {
+        // Safety: Invariants must be upheld by the caller.
+        unsafe { self.uring().push_zero_copy(entry) }
+    }
+
+    /// Attempts to push an entry into the queue, linked to a timeout.
+    /// See [`iouring::Driver::push_linked`].
+    ///
+    /// # Safety
+    ///
+    /// The same as [`push`](Reactor::push), for both entries.
+    #[cfg(io_uring)]
+    pub unsafe fn push_linked(&self, entry: Entry, timeout: Entry) -> std::io::Result<u64> {
+        // Safety: Invariants must be upheld by the caller.
+        unsafe { self.uring().push_linked(entry, timeout) }
     }
 }
 fn current() -> Reactor {
@@ -91,3 +214,58 @@ fn current() -> Reactor {
         "attempted to perform async I/O from the outside of an osiris runtime context.";
     crate::runtime::current().expect(ERR_MSG).reactor
 }
+
+#[cfg(io_uring)]
+#[test]
+fn poll_fallback() {
+    use crate::net::{TcpListener, TcpStream};
+    use crate::time::{sleep, Duration};
+
+    FORCE_FALLBACK.with(|force| force.set(true));
+    let rt = Config::default().allow_fallback(true).build().unwrap();
+    FORCE_FALLBACK.with(|force| force.set(false));
+    assert!(!rt.reactor.is_io_uring());
+    assert_eq!(rt.capabilities(), crate::runtime::Capabilities::default());
+
+    rt.block_on(async {
+        sleep(Duration::from_millis(1)).await;
+        let listener = TcpListener::bind("127.0.0.1:7014").await.unwrap();
+        let client = crate::spawn(async {
+            let mut stream = TcpStream::connect("127.0.0.1:7014").await.unwrap();
+            stream.write_all("ping").await.0.unwrap();
+        });
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let (n, buf) = stream.read(vec![0; 16]).await;
+        assert_eq!(&buf[..n.unwrap()], b"ping");
+        client.await;
+
+        let contents = crate::fs::read("Cargo.toml").await.unwrap();
+        assert!(contents.starts_with(b"[workspace]"));
+    })
+    .unwrap();
+}
+
+#[cfg(io_uring)]
+#[test]
+fn poll_fallback_wakeup() {
+    use std::time::{Duration, Instant};
+
+    FORCE_FALLBACK.with(|force| force.set(true));
+    let rt = Config::default().allow_fallback(true).build().unwrap();
+    FORCE_FALLBACK.with(|force| force.set(false));
+
+    // the reactor blocks indefinitely until the blocking
+    // task wakes us up from the thread pool.
+    let start = Instant::now();
+    let out = rt
+        .block_on(async {
+            crate::task::spawn_blocking(|| {
+                std::thread::sleep(Duration::from_millis(20));
+                1
+            })
+            .await
+        })
+        .unwrap();
+    assert_eq!(out, 1);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
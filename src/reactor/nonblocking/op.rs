@@ -1,10 +1,14 @@
-use libc::{iovec, msghdr, AT_FDCWD, AT_SYMLINK_NOFOLLOW};
+use libc::{iovec, msghdr};
 use submit::submit_once;
 
 use crate::buf::{IoBuf, IoBufMut};
 use crate::net::utils::{socket_addr, to_std_socket_addr};
-use crate::reactor::op::{make_nonblocking, read_event, write_event};
+use crate::reactor::op::{read_event, write_event};
+use crate::reactor::utils::{
+    iovecs_mut, recv_addr, retry_interrupted, set_len_vectored, timed_out, MSG_NOSIGNAL,
+};
 use crate::task::spawn_blocking;
+use crate::time::timeout;
 use crate::utils::{statx, syscall};
 
 use std::ffi::CString;
@@ -12,12 +16,19 @@ use std::io::{Error, Result};
 use std::mem::{size_of_val, zeroed};
 use std::net::{Shutdown, SocketAddr};
 use std::os::fd::{FromRawFd, OwnedFd};
+use std::time::{Duration, Instant};
 
-use super::submit;
+use super::{submit, wait};
 
 pub async fn fs_read<B: IoBufMut + Send + Sync>(fd: i32, mut buf: B) -> (Result<usize>, B) {
     spawn_blocking(move || {
-        let r = syscall!(read, fd, buf.stable_mut_ptr().cast(), buf.bytes_total());
+        let r = retry_interrupted(|| {
+            syscall!(read, fd, buf.stable_mut_ptr().cast(), buf.bytes_total())
+        });
+        if let Ok(n) = r {
+            // Safety: initialized by the kernel
+            unsafe { buf.set_init(n as usize) };
+        }
         (r.map(|n| n as usize), buf)
     })
     .await
@@ -25,30 +36,29 @@ pub async fn fs_read<B: IoBufMut + Send + Sync>(fd: i32, mut buf: B) -> (Result<
 
 pub async fn fs_write<B: IoBuf + Send + Sync>(fd: i32, buf: B) -> (Result<usize>, B) {
     spawn_blocking(move || {
-        let r = syscall!(write, fd, buf.stable_ptr().cast(), buf.bytes_total());
+        let r = retry_interrupted(|| {
+            syscall!(write, fd, buf.stable_ptr().cast(), buf.bytes_total())
+        });
         (r.map(|n| n as usize), buf)
     })
     .await
 }
 
-pub async fn mkdir_at(path: CString) -> Result<()> {
-    spawn_blocking(move || syscall!(mkdirat, libc::AT_FDCWD, path.as_ptr(), 0o666)).await?;
+pub async fn mkdir_at(dirfd: i32, path: CString) -> Result<()> {
+    spawn_blocking(move || syscall!(mkdirat, dirfd, path.as_ptr(), 0o777)).await?;
     Ok(())
 }
 
-pub async fn statx(fd: i32, path: Option<CString>, flags: i32) -> Result<statx> {
+/// Emulates statx with the `stat` family of system calls. The `mask`
+/// is ignored, since they always return the basic stats.
+pub async fn statx(fd: i32, path: Option<CString>, flags: i32, _mask: u32) -> Result<statx> {
     let stat = spawn_blocking(move || {
         let mut stat: libc::stat = unsafe { zeroed() };
 
         match path {
-            None if flags != AT_SYMLINK_NOFOLLOW => syscall!(fstat, fd, &mut stat)?,
-            Some(path) if flags != AT_SYMLINK_NOFOLLOW => syscall!(stat, path.as_ptr(), &mut stat)?,
-            Some(path) => syscall!(lstat, path.as_ptr(), &mut stat)?,
-            None => {
-                let mut path = [0 as libc::c_char; libc::PATH_MAX as _];
-                syscall!(fcntl, fd, libc::F_GETPATH, &mut path)?;
-                syscall!(lstat, path.as_ptr(), &mut stat)?
-            }
+            // the file descriptor is already resolved, so there is no link to follow
+            None => syscall!(fstat, fd, &mut stat)?,
+            Some(path) => syscall!(fstatat, fd, path.as_ptr(), &mut stat, flags)?,
         };
 
         Result::Ok(stat)
@@ -57,13 +67,43 @@ pub async fn statx(fd: i32, path: Option<CString>, flags: i32) -> Result<statx>
     Ok(statx::from_stat(stat))
 }
 
-pub async fn unlink_at(path: CString, flags: i32) -> Result<()> {
-    spawn_blocking(move || syscall!(unlinkat, AT_FDCWD, path.as_ptr(), flags)).await?;
+pub async fn unlink_at(dirfd: i32, path: CString, flags: i32) -> Result<()> {
+    spawn_blocking(move || syscall!(unlinkat, dirfd, path.as_ptr(), flags)).await?;
     Ok(())
 }
 
-pub async fn open_at(path: CString, flags: i32, mode: libc::mode_t) -> Result<i32> {
-    spawn_blocking(move || syscall!(openat, AT_FDCWD, path.as_ptr(), flags, mode as u32)).await
+/// Opens a file relative to the directory `dirfd`, which may be `AT_FDCWD`.
+pub async fn open_at(dirfd: i32, path: CString, flags: i32, mode: libc::mode_t) -> Result<i32> {
+    spawn_blocking(move || syscall!(openat, dirfd, path.as_ptr(), flags, mode as u32)).await
+}
+
+/// Opens a file relative to `dirfd` like [`open_at`], restricting how the path
+/// is resolved with the `RESOLVE_*` flags of `openat2(2)`. Requires Linux 5.6.
+#[cfg(target_os = "linux")]
+pub async fn openat2(
+    dirfd: i32,
+    path: CString,
+    flags: i32,
+    mode: u32,
+    resolve: u64,
+) -> Result<i32> {
+    let how = crate::utils::open_how {
+        flags: flags as _,
+        mode: mode as _,
+        resolve,
+    };
+    spawn_blocking(move || {
+        syscall!(
+            syscall,
+            libc::SYS_openat2,
+            dirfd,
+            path.as_ptr(),
+            &how as *const _,
+            std::mem::size_of_val(&how),
+        )
+        .map(|fd| fd as i32)
+    })
+    .await
 }
 
 pub async fn read_at<B: IoBufMut>(fd: i32, mut buf: B, _pos: i64) -> (Result<usize>, B) {
@@ -74,11 +114,111 @@ pub async fn read_at<B: IoBufMut>(fd: i32, mut buf: B, _pos: i64) -> (Result<usi
     (res, buf)
 }
 
+pub async fn read_timeout<B: IoBufMut>(
+    fd: i32,
+    mut buf: B,
+    _pos: i64,
+    dur: Duration,
+) -> (Result<usize>, B) {
+    let read = read_nonblock(fd, buf.stable_mut_ptr(), buf.bytes_total());
+    let res = timeout(dur, read)
+        .await
+        .unwrap_or_else(|_| Err(timed_out()));
+    if let Ok(val) = res {
+        unsafe { buf.set_init(buf.bytes_init().max(val)) };
+    };
+    (res, buf)
+}
+
+/// Reads from a file descriptor that may be in blocking mode, and cannot be made
+/// nonblocking because it is shared with other processes, such as the standard input.
+/// The read is only performed once the file descriptor is readable, so it usually won't
+/// block. This is best-effort: if another process reads the data between the readiness
+/// notification and the read, the read blocks the thread until more data arrives.
+pub async fn read_blocking_fd<B: IoBufMut>(fd: i32, mut buf: B, _pos: i64) -> (Result<usize>, B) {
+    if let Err(err) = wait(read_event(fd)).await {
+        return (Err(err), buf);
+    }
+    let res = retry_interrupted(|| {
+        syscall!(read, fd, buf.stable_mut_ptr().cast(), buf.bytes_total())
+    });
+    let res = res.map(|n| n as usize);
+    if let Ok(val) = res {
+        unsafe { buf.set_init(buf.bytes_init().max(val)) };
+    };
+    (res, buf)
+}
+
+/// Writes to a file descriptor that may be in blocking mode, such as the standard output.
+/// At most `PIPE_BUF` bytes are written once the file descriptor is writable, which
+/// guarantees the write won't block on pipes.
+pub async fn write_blocking_fd<B: IoBuf>(fd: i32, buf: B, _pos: i64) -> (Result<usize>, B) {
+    if let Err(err) = wait(write_event(fd)).await {
+        return (Err(err), buf);
+    }
+    let len = buf.bytes_init().min(libc::PIPE_BUF);
+    let res = retry_interrupted(|| syscall!(write, fd, buf.stable_ptr().cast(), len));
+    (res.map(|n| n as usize), buf)
+}
+
+pub async fn read_vectored_at(
+    fd: i32,
+    mut bufs: Vec<Vec<u8>>,
+    pos: i64,
+) -> (Result<usize>, Vec<Vec<u8>>) {
+    spawn_blocking(move || {
+        let iovecs = iovecs_mut(&mut bufs);
+        let res = retry_interrupted(|| {
+            if pos < 0 {
+                syscall!(readv, fd, iovecs.as_ptr(), iovecs.len() as _)
+            } else {
+                syscall!(preadv, fd, iovecs.as_ptr(), iovecs.len() as _, pos as _)
+            }
+        });
+        let res = res.map(|n| n as usize);
+        if let Ok(len) = res {
+            // Safety: initialized by the kernel
+            unsafe { set_len_vectored(&mut bufs, len) };
+        }
+        (res, bufs)
+    })
+    .await
+}
+
 pub async fn write_at<B: IoBuf>(fd: i32, buf: B, _pos: i64) -> (Result<usize>, B) {
     let res = write_nonblock(fd, buf.stable_ptr(), buf.bytes_total()).await;
     (res, buf)
 }
 
+/// Sends the buffer on a connected socket. A closed peer fails with `EPIPE`
+/// rather than raising `SIGPIPE`.
+pub async fn send<B: IoBuf>(fd: i32, buf: B) -> (Result<usize>, B) {
+    let (ptr, len) = (buf.stable_ptr(), buf.bytes_init());
+    let event = write_event(fd);
+    let res = submit(event, || syscall!(send, fd, ptr.cast(), len, MSG_NOSIGNAL)).await;
+    (res.map(|n| n as usize), buf)
+}
+
+/// Zero copy sends are only supported on io-uring, so this is a regular send.
+pub async fn send_zc<B: IoBuf>(fd: i32, buf: B) -> (Result<usize>, B) {
+    send(fd, buf).await
+}
+
+/// Calls `f` until it stops failing with `EAGAIN`, waiting for `fd` to become readable in between.
+pub async fn nonblock<T>(fd: i32, f: impl FnMut() -> Result<T>) -> Result<T> {
+    submit(read_event(fd), f).await
+}
+
+/// Waits for `fd` to become readable.
+pub async fn wait_readable(fd: i32) -> Result<()> {
+    wait(read_event(fd)).await
+}
+
+/// Waits for `fd` to become writable.
+pub async fn wait_writable(fd: i32) -> Result<()> {
+    wait(write_event(fd)).await
+}
+
 pub async fn read_nonblock(fd: i32, buf: *mut u8, len: usize) -> Result<usize> {
     let event = read_event(fd);
     let res = submit(event, || syscall!(read, fd, buf.cast(), len)).await?;
@@ -91,9 +231,49 @@ pub async fn recv<B: IoBufMut>(fd: i32, mut buf: B) -> (Result<usize>, B) {
         syscall!(recv, fd, buf.stable_mut_ptr().cast(), buf.bytes_total(), 0)
     })
     .await;
+    if let Ok(n) = res {
+        // Safety: initialized by the kernel
+        unsafe { buf.set_init(n as usize) };
+    }
     (res.map(|v| v as _), buf)
 }
 
+/// A receive that yields the chunks of data received on a socket until a deadline.
+/// Multishot receives are only supported on io-uring, so every chunk is received
+/// with a separate call.
+pub struct RecvMulti {
+    fd: i32,
+    deadline: Instant,
+    eof: bool,
+}
+
+/// Receives on `fd` until `dur` elapses.
+pub async fn recv_multi(fd: i32, dur: Duration) -> Result<RecvMulti> {
+    let deadline = Instant::now() + dur;
+    Ok(RecvMulti {
+        fd,
+        deadline,
+        eof: false,
+    })
+}
+
+impl RecvMulti {
+    /// Returns the next chunk of data received, `None` once the peer closed the
+    /// connection, or an error of kind `TimedOut` once the deadline passed.
+    pub async fn next(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.eof {
+            return Ok(None);
+        }
+        let dur = self.deadline.saturating_duration_since(Instant::now());
+        if dur.is_zero() {
+            return Err(timed_out());
+        }
+        let (res, buf) = read_timeout(self.fd, Vec::with_capacity(4096), 0, dur).await;
+        self.eof = res? == 0;
+        Ok((!self.eof).then_some(buf))
+    }
+}
+
 pub async fn recvfrom<B: IoBufMut>(fd: i32, mut buf: B) -> (Result<(usize, SocketAddr)>, B) {
     let event = read_event(fd);
 
@@ -112,6 +292,10 @@ pub async fn recvfrom<B: IoBufMut>(fd: i32, mut buf: B) -> (Result<(usize, Socke
     })
     .await;
 
+    if let Ok(n) = res {
+        // Safety: initialized by the kernel
+        unsafe { buf.set_init(n as usize) };
+    }
     let res = res.and_then(|read| {
         let sockaddr = to_std_socket_addr(&sockaddr)?;
         Ok((read as _, sockaddr))
@@ -130,6 +314,12 @@ pub async fn connect(fd: i32, addr: SocketAddr) -> Result<()> {
     Ok(())
 }
 
+pub async fn connect_timeout(fd: i32, addr: SocketAddr, dur: Duration) -> Result<()> {
+    timeout(dur, connect(fd, addr))
+        .await
+        .unwrap_or_else(|_| Err(timed_out()))
+}
+
 fn retrieve_connection_error(fd: i32) -> Result<()> {
     let optval = &mut 0;
     let optlen = &mut size_of_val(optval);
@@ -153,12 +343,28 @@ pub async fn accept(fd: i32) -> Result<(OwnedFd, SocketAddr)> {
     let mut address_len = size_of_val(&address) as u32;
     let event = read_event(fd);
 
+    // accepted sockets are close-on-exec, so they are not leaked to child processes
+    #[cfg(target_os = "linux")]
+    let fd = submit(event, || {
+        let flags = libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK;
+        syscall!(accept4, fd, &mut address, &mut address_len, flags)
+    })
+    .await?;
+    #[cfg(not(target_os = "linux"))]
     let fd = submit(event, || {
         syscall!(accept, fd, &mut address, &mut address_len)
     })
     .await?;
     let fd = unsafe { OwnedFd::from_raw_fd(fd) };
-    make_nonblocking(&fd)?;
+    #[cfg(not(target_os = "linux"))]
+    {
+        use crate::reactor::op::make_nonblocking;
+        use std::os::fd::AsRawFd;
+        syscall!(fcntl, fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC)?;
+        make_nonblocking(&fd)?;
+        #[cfg(target_vendor = "apple")]
+        crate::reactor::utils::set_nosigpipe(&fd)?;
+    }
     let address = to_std_socket_addr(&address)?;
     Ok((fd, address))
 }
@@ -181,12 +387,92 @@ pub async fn send_to<B: IoBuf>(fd: i32, buf: B, addr: SocketAddr) -> (Result<usi
     msghdr.msg_namelen = len;
 
     let event = write_event(fd);
-    let res = submit(event, || syscall!(sendmsg, fd, &msghdr, 0))
+    let res = submit(event, || syscall!(sendmsg, fd, &msghdr, MSG_NOSIGNAL))
         .await
         .map(|s| s as _);
     (res, buf)
 }
 
+/// Receives a message into `buf`, and its ancillary data into `control`.
+///
+/// Returns the number of bytes received and the address of the sender, if any.
+/// The control buffer is initialized up to the length of the received control
+/// messages, which can be iterated with [`control_messages`](crate::reactor::utils::control_messages).
+pub async fn recvmsg<B: IoBufMut, C: IoBufMut>(
+    fd: i32,
+    mut buf: B,
+    mut control: C,
+) -> (Result<(usize, Option<SocketAddr>)>, B, C) {
+    let mut msghdr: msghdr = unsafe { zeroed() };
+
+    // we define the iovec from the buffer
+    let mut msg_iov = iovec {
+        iov_base: buf.stable_mut_ptr().cast(),
+        iov_len: buf.bytes_total(),
+    };
+    msghdr.msg_iov = &mut msg_iov;
+    msghdr.msg_iovlen = 1;
+
+    // the kernel writes the address of the sender here
+    let mut addr: libc::sockaddr_storage = unsafe { zeroed() };
+    msghdr.msg_name = &mut addr as *mut _ as *mut _;
+    msghdr.msg_namelen = size_of_val(&addr) as _;
+
+    msghdr.msg_control = control.stable_mut_ptr().cast();
+    msghdr.msg_controllen = control.bytes_total() as _;
+
+    let event = read_event(fd);
+    let res = submit(event, || syscall!(recvmsg, fd, &mut msghdr, 0)).await;
+    let len = match res {
+        Ok(len) => len as usize,
+        Err(err) => return (Err(err), buf, control),
+    };
+    // Safety: initialized by the kernel
+    unsafe {
+        buf.set_init(len);
+        control.set_init(msghdr.msg_controllen as _);
+    }
+    let addr = recv_addr(&addr, msghdr.msg_namelen);
+    (Ok((len, addr)), buf, control)
+}
+
+/// Sends the message in `buf` along with the ancillary data in `control`. The
+/// address is only required for sockets that are not connected.
+pub async fn sendmsg<B: IoBuf, C: IoBuf>(
+    fd: i32,
+    buf: B,
+    addr: Option<SocketAddr>,
+    control: C,
+) -> (Result<usize>, B, C) {
+    let mut msghdr: msghdr = unsafe { zeroed() };
+
+    // we define the iovec from the buffer
+    let mut msg_iov = iovec {
+        iov_base: buf.stable_ptr().cast_mut().cast(),
+        iov_len: buf.bytes_init(),
+    };
+    msghdr.msg_iov = &mut msg_iov;
+    msghdr.msg_iovlen = 1;
+
+    // we set the address, if any
+    let mut addr = addr.map(|addr| socket_addr(&addr));
+    if let Some((addr, len)) = &mut addr {
+        msghdr.msg_name = addr as *mut _ as *mut _;
+        msghdr.msg_namelen = *len;
+    }
+
+    if control.bytes_init() != 0 {
+        msghdr.msg_control = control.stable_ptr().cast_mut().cast();
+        msghdr.msg_controllen = control.bytes_init() as _;
+    }
+
+    let event = write_event(fd);
+    let res = submit(event, || syscall!(sendmsg, fd, &msghdr, MSG_NOSIGNAL))
+        .await
+        .map(|s| s as _);
+    (res, buf, control)
+}
+
 pub async fn close(fd: i32) -> Result<()> {
     syscall!(close, fd).map(|_| ())
 }
@@ -1,4 +1,5 @@
 use crate::reactor::{self, Event};
+use crate::task::consume_budget;
 use std::future::poll_fn;
 use std::io::{self};
 use std::task::Poll;
@@ -8,7 +9,7 @@ pub struct Guard(u64);
 impl Drop for Guard {
     fn drop(&mut self) {
         let reactor = reactor::current();
-        let mut driver = reactor.driver();
+        let mut driver = reactor.poller();
         driver.remove_waker(self.0);
     }
 }
@@ -21,7 +22,7 @@ pub async fn wait(event: Event) -> io::Result<()> {
             return Poll::Ready(Ok(()));
         }
         submitted = true;
-        let res = reactor::current().driver().push(event, cx.waker().clone());
+        let res = reactor::current().poller().push(event, cx.waker().clone());
         match res {
             Err(err) => Poll::Ready(Err(err)),
             Ok(id) => {
@@ -33,18 +34,23 @@ pub async fn wait(event: Event) -> io::Result<()> {
     .await
 }
 
+/// Performs the system call, waiting for the event whenever it would block.
+///
+/// System calls interrupted by a signal (`EINTR`) are retried right away, and
+/// the ones that would block (`EAGAIN`, which is the same as `EWOULDBLOCK`) are
+/// retried once the event is ready.
 pub async fn submit<F, T>(event: Event, mut f: F) -> io::Result<T>
 where
     F: FnMut() -> io::Result<T>,
 {
+    consume_budget().await;
     loop {
         match f() {
-            Err(err) => {
-                let Some(libc::EAGAIN | libc::EINPROGRESS) = err.raw_os_error() else {
-                    return Err(err);
-                };
-                wait(event).await?;
-            }
+            Err(err) => match err.raw_os_error() {
+                Some(libc::EINTR) => continue,
+                Some(libc::EAGAIN | libc::EINPROGRESS) => wait(event).await?,
+                _ => return Err(err),
+            },
             result => return result,
         }
     }
@@ -53,13 +59,16 @@ where
 /// Note: this function is used for connect mostly.
 /// In connect the system call needs to be performed before the wait, however,
 /// after the wait,
+///
+/// A connect interrupted by a signal (`EINTR`) keeps going in the background,
+/// so it is waited on just like one that is in progress.
 pub async fn submit_once<F>(event: Event, f: F) -> io::Result<()>
 where
     F: FnOnce() -> io::Result<i32>,
 {
     match f() {
         Err(err) => {
-            let Some(libc::EAGAIN | libc::EINPROGRESS) = err.raw_os_error() else {
+            let Some(libc::EAGAIN | libc::EINPROGRESS | libc::EINTR) = err.raw_os_error() else {
                 return Err(err);
             };
             wait(event).await
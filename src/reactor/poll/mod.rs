@@ -12,7 +12,6 @@
 
 use std::io;
 use std::task::Waker;
-use std::time::Duration;
 
 use crate::runtime::Config;
 use crate::utils::syscall;
@@ -45,20 +44,27 @@ impl Driver {
     }
 
     pub fn submit_and_yield(&mut self) -> io::Result<()> {
-        self.submit(Duration::ZERO)
+        self.submit(0)
     }
 
+    /// Blocks until at least one event is ready. Wakeups coming from other
+    /// threads are delivered through a pipe registered with the reactor,
+    /// so they interrupt the wait too.
     pub fn submit_and_wait(&mut self) -> io::Result<()> {
-        let timeout = Duration::from_secs(60);
-        self.submit(timeout)
+        self.submit(-1)
     }
 
-    #[rustfmt::skip]
-    fn submit(&mut self, timeout: Duration) -> io::Result<()> {
-        let timeout = timeout.as_millis() as i32;
+    /// Polls the registered events, with a timeout in milliseconds,
+    /// where `-1` means to block indefinitely.
+    fn submit(&mut self, timeout: i32) -> io::Result<()> {
         let len = self.fds.len() as u64;
         let fds = self.fds.as_mut_ptr();
-        let to_wake = syscall!(poll, fds, len as _, timeout)?;
+        let to_wake = match syscall!(poll, fds, len as _, timeout) {
+            Ok(to_wake) => to_wake,
+            // a signal arrived, the event loop will call us again.
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => return Ok(()),
+            Err(err) => return Err(err),
+        };
         self.wake_tasks(to_wake);
         Ok(())
     }
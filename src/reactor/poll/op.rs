@@ -50,6 +50,60 @@ pub async fn fdatasync(fd: i32) -> Result<()> {
     Ok(())
 }
 
+/// Declares the expected access pattern for a region of a file. Unlike most calls,
+/// `posix_fadvise` returns the error number instead of setting `errno`.
+pub async fn fadvise(fd: i32, offset: i64, len: i64, advice: i32) -> Result<()> {
+    spawn_blocking(move || {
+        // Safety: the call only reads its integer arguments
+        match unsafe { libc::posix_fadvise(fd, offset, len, advice) } {
+            0 => Ok(()),
+            errno => Err(Error::from_raw_os_error(errno)),
+        }
+    })
+    .await
+}
+
+/// Moves up to `len` bytes from `fd_in` to `fd_out`, where an offset of `-1` means the
+/// current position of the file descriptor is used.
+pub async fn splice(fd_in: i32, off_in: i64, fd_out: i32, off_out: i64, len: u32) -> Result<usize> {
+    let (mut off_in, mut off_out) = (off_in, off_out);
+    let off_in = if off_in < 0 {
+        null_mut()
+    } else {
+        &mut off_in as *mut i64
+    };
+    let off_out = if off_out < 0 {
+        null_mut()
+    } else {
+        &mut off_out as *mut i64
+    };
+    let flags = libc::SPLICE_F_NONBLOCK | libc::SPLICE_F_MOVE;
+    loop {
+        match syscall!(splice, fd_in, off_in, fd_out, off_out, len as usize, flags) {
+            Ok(n) => return Ok(n as usize),
+            Err(err) if err.raw_os_error() == Some(libc::EAGAIN) => {
+                wait(read_event(fd_in)).await?;
+                wait(write_event(fd_out)).await?;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Duplicates up to `len` bytes from the pipe `fd_in` to the pipe `fd_out`.
+pub async fn tee(fd_in: i32, fd_out: i32, len: u32) -> Result<usize> {
+    loop {
+        match syscall!(tee, fd_in, fd_out, len as usize, libc::SPLICE_F_NONBLOCK) {
+            Ok(n) => return Ok(n as usize),
+            Err(err) if err.raw_os_error() == Some(libc::EAGAIN) => {
+                wait(read_event(fd_in)).await?;
+                wait(write_event(fd_out)).await?;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
 pub async fn sleep(dur: Duration) -> Result<()> {
     let mut event = zeroed;
 
@@ -1,16 +1,47 @@
 use std::convert::Infallible;
-use std::io::Result;
+use std::io::{Error, ErrorKind, Result};
+use std::mem::size_of;
+use std::net::SocketAddr;
 use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::ptr::read_unaligned;
 
+use crate::net::utils::to_std_socket_addr;
 use crate::utils::syscall;
 
+/// Sending to a socket whose peer is gone raises `SIGPIPE`, which kills the process
+/// by default, so sends pass `MSG_NOSIGNAL` to fail with `EPIPE` instead. Apple
+/// platforms lack the flag, so `SO_NOSIGPIPE` is set on their sockets instead.
+#[cfg(not(target_vendor = "apple"))]
+pub const MSG_NOSIGNAL: i32 = libc::MSG_NOSIGNAL;
+#[cfg(target_vendor = "apple")]
+pub const MSG_NOSIGNAL: i32 = 0;
+
 pub fn socket(domain: i32, ty: i32, proto: i32, _: Option<Infallible>) -> Result<OwnedFd> {
     let fd = syscall!(socket, domain as _, ty, proto as _)?;
     let fd = unsafe { OwnedFd::from_raw_fd(fd) };
     make_nonblocking(&fd)?;
+    #[cfg(target_vendor = "apple")]
+    set_nosigpipe(&fd)?;
     Ok(fd)
 }
 
+/// Sets `SO_NOSIGPIPE`, see [`MSG_NOSIGNAL`].
+#[cfg(target_vendor = "apple")]
+pub fn set_nosigpipe(fd: &OwnedFd) -> Result<()> {
+    let on: i32 = 1;
+    let size = size_of::<i32>() as u32;
+    let opt = libc::SO_NOSIGPIPE;
+    syscall!(
+        setsockopt,
+        fd.as_raw_fd(),
+        libc::SOL_SOCKET,
+        opt,
+        &on as *const _ as _,
+        size
+    )?;
+    Ok(())
+}
+
 pub fn make_nonblocking(fd: &OwnedFd) -> Result<()> {
     let fd = fd.as_raw_fd();
     let flags = syscall!(fcntl, fd, libc::F_GETFL)?;
@@ -24,3 +55,151 @@ pub fn make_blocking(fd: &OwnedFd) -> Result<()> {
     syscall!(fcntl, fd, libc::F_SETFL, options & !libc::O_NONBLOCK)?;
     Ok(())
 }
+
+/// Calls `f` until it stops failing with `EINTR`, which blocking system calls
+/// return when a signal handler runs on the thread.
+pub fn retry_interrupted<T>(mut f: impl FnMut() -> Result<T>) -> Result<T> {
+    loop {
+        match f() {
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            res => return res,
+        }
+    }
+}
+
+/// The error returned by operations that did not complete in time.
+pub fn timed_out() -> Error {
+    Error::new(ErrorKind::TimedOut, "operation timed out")
+}
+
+/// Creates one `iovec` for each buffer, spanning its entire capacity.
+pub fn iovecs_mut(bufs: &mut [Vec<u8>]) -> Vec<libc::iovec> {
+    bufs.iter_mut()
+        .map(|buf| libc::iovec {
+            iov_base: buf.as_mut_ptr().cast(),
+            iov_len: buf.capacity(),
+        })
+        .collect()
+}
+
+/// Sets the length of each buffer after `len` bytes were read into them in order.
+///
+/// # Safety
+/// The first `len` bytes of the combined buffers must have been initialized.
+pub unsafe fn set_len_vectored(bufs: &mut [Vec<u8>], mut len: usize) {
+    for buf in bufs {
+        let n = len.min(buf.capacity());
+        // Safety: guaranteed by the caller
+        unsafe { buf.set_len(n) };
+        len -= n;
+    }
+}
+
+/// Converts the address written by `recvmsg`. Returns `None` if no address was
+/// received, or if it is not an IP address, as is the case for unix sockets.
+pub fn recv_addr(storage: &libc::sockaddr_storage, len: libc::socklen_t) -> Option<SocketAddr> {
+    if len == 0 {
+        return None;
+    }
+    // Safety: `sockaddr_storage` is large enough for any socket address
+    let addr = unsafe { &*(storage as *const libc::sockaddr_storage).cast() };
+    to_std_socket_addr(addr).ok()
+}
+
+/// Appends a control message to a buffer of ancillary data to be passed to `sendmsg`.
+pub fn push_control_message(control: &mut Vec<u8>, level: i32, ty: i32, data: &[u8]) {
+    // Safety: these macros only perform arithmetic
+    let (space, len) = unsafe {
+        (
+            libc::CMSG_SPACE(data.len() as _) as usize,
+            libc::CMSG_LEN(data.len() as _) as usize,
+        )
+    };
+    let header_len = len - data.len();
+    let start = control.len();
+    control.resize(start + space, 0);
+
+    // Safety: zeroed is a valid value for cmsghdr
+    let mut header: libc::cmsghdr = unsafe { std::mem::zeroed() };
+    header.cmsg_len = len as _;
+    header.cmsg_level = level;
+    header.cmsg_type = ty;
+    let header_ptr = control[start..].as_mut_ptr().cast::<libc::cmsghdr>();
+    // Safety: the buffer was resized to fit the header
+    unsafe { header_ptr.write_unaligned(header) };
+    control[start + header_len..start + len].copy_from_slice(data);
+}
+
+/// Iterates over the control messages of the ancillary data received by `recvmsg`,
+/// yielding the level, type, and data of each message.
+pub fn control_messages(control: &[u8]) -> impl Iterator<Item = (i32, i32, &[u8])> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let rest = control.get(offset..)?;
+        if rest.len() < size_of::<libc::cmsghdr>() {
+            return None;
+        }
+        // Safety: the buffer holds a full header
+        let header: libc::cmsghdr = unsafe { read_unaligned(rest.as_ptr().cast()) };
+        // Safety: these macros only perform arithmetic
+        let header_len = unsafe { libc::CMSG_LEN(0) } as usize;
+        let len = header.cmsg_len as usize;
+        let data = rest.get(header_len..len)?;
+        // Safety: these macros only perform arithmetic
+        offset += unsafe { libc::CMSG_SPACE(data.len() as _) } as usize;
+        Some((header.cmsg_level, header.cmsg_type, data))
+    })
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn recvmsg_pktinfo() {
+    use crate::reactor::op;
+    use std::net::{Ipv4Addr, UdpSocket};
+
+    crate::block_on(async {
+        let receiver = UdpSocket::bind("127.0.0.1:2403").unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:2404").unwrap();
+        receiver.set_nonblocking(true).unwrap();
+        sender.set_nonblocking(true).unwrap();
+        let on: libc::c_int = 1;
+        syscall!(
+            setsockopt,
+            receiver.as_raw_fd(),
+            libc::IPPROTO_IP,
+            libc::IP_PKTINFO,
+            &on as *const _ as _,
+            size_of::<libc::c_int>() as _,
+        )
+        .unwrap();
+
+        // the source address is chosen through the control message.
+        let mut pktinfo: libc::in_pktinfo = unsafe { std::mem::zeroed() };
+        pktinfo.ipi_spec_dst.s_addr = u32::from_ne_bytes([127, 0, 0, 1]);
+        let data = unsafe {
+            std::slice::from_raw_parts(
+                &pktinfo as *const _ as *const u8,
+                size_of::<libc::in_pktinfo>(),
+            )
+        };
+        let mut control = vec![];
+        push_control_message(&mut control, libc::IPPROTO_IP, libc::IP_PKTINFO, data);
+        let addr = receiver.local_addr().unwrap();
+        let (res, _, _) = op::sendmsg(sender.as_raw_fd(), b"ping", Some(addr), control).await;
+        assert_eq!(res.unwrap(), 4);
+
+        let buf = Vec::with_capacity(16);
+        let control = Vec::with_capacity(64);
+        let (res, buf, control) = op::recvmsg(receiver.as_raw_fd(), buf, control).await;
+        let (len, from) = res.unwrap();
+        assert_eq!(&buf[..len], b"ping");
+        assert_eq!(from, Some(sender.local_addr().unwrap()));
+
+        let (level, ty, data) = control_messages(&control).next().unwrap();
+        assert_eq!((level, ty), (libc::IPPROTO_IP, libc::IP_PKTINFO));
+        let pktinfo: libc::in_pktinfo = unsafe { read_unaligned(data.as_ptr().cast()) };
+        let local = Ipv4Addr::from(pktinfo.ipi_addr.s_addr.to_ne_bytes());
+        assert_eq!(local, Ipv4Addr::LOCALHOST);
+    })
+    .unwrap();
+}
@@ -0,0 +1,39 @@
+/// The io-uring features supported by the kernel a runtime is running on.
+/// See [`Runtime::capabilities`](super::Runtime::capabilities).
+///
+/// Features are detected when the runtime is built, from the opcodes reported by
+/// `IORING_REGISTER_PROBE` and, for flags that cannot be probed, from the kernel
+/// version. Every feature is reported as unsupported when the runtime is not backed
+/// by io-uring, as is the case on macOS, or when it fell back to the poll driver.
+///
+/// # Examples
+/// ```
+/// # osiris::block_on(async {
+/// let capabilities = osiris::runtime::current().unwrap().capabilities();
+/// if capabilities.send_zc {
+///     // use zero copy sends
+/// }
+/// # }).unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Whether the runtime is backed by io-uring.
+    pub io_uring: bool,
+    /// Whether a single accept request can post a completion for every incoming
+    /// connection (`IORING_ACCEPT_MULTISHOT`, kernel 5.19+).
+    pub multishot_accept: bool,
+    /// Whether a single receive request can post a completion for every received
+    /// message (`IORING_RECV_MULTISHOT`, kernel 6.0+).
+    pub multishot_recv: bool,
+    /// Whether zero copy sends are supported (`IORING_OP_SEND_ZC`, kernel 6.0+).
+    pub send_zc: bool,
+    /// Whether buffers can be provided to the kernel to be picked by reads as they
+    /// complete (`IORING_OP_PROVIDE_BUFFERS`, kernel 5.7+).
+    pub provide_buffers: bool,
+    /// Whether files can be opened with `openat2` (`IORING_OP_OPENAT2`, kernel 5.6+).
+    pub openat2: bool,
+    /// Whether data can be moved between file descriptors without copying it to
+    /// user space (`IORING_OP_SPLICE` and `IORING_OP_TEE`, kernel 5.8+).
+    pub splice: bool,
+}
@@ -1,16 +1,38 @@
 use super::executor::Executor;
 use super::Runtime;
+use crate::net::dns::DnsCache;
 use crate::reactor::Reactor;
+use crate::time::Timers;
+use std::any::Any;
+use std::fmt::Debug;
+use std::io::{Error, ErrorKind};
 use std::rc::Rc;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[cfg(io_uring)]
 use io_uring::IoUring;
 
 /// Configuration struct for an osiris runtime.
 /// The default values for configuration options should not be considered stable.
+///
+/// A configuration is started with [`Config::builder`], adjusted with its chainable
+/// setters, and turned into a [`Runtime`] with [`Config::build`]. Each setter links to
+/// the field that documents the option it sets, along with its default value.
+///
 /// # Example
 /// ```rust
+/// # use osiris::runtime::Config;
+/// # fn __() -> Result<(), std::io::Error> {
+/// let runtime = Config::builder()
+///     .queue_entries(1024)
+///     .event_interval(61)
+///     .build()?;
+/// # Ok(())}
+/// ```
+///
+/// Alternatively, the fields can be overriden with the struct update syntax:
+/// ```rust
 /// # use osiris::runtime::{Config, Runtime, Mode};
 /// # fn __() -> Result<(), std::io::Error> {
 /// // we override the values we want to change
@@ -40,11 +62,31 @@ pub struct Config {
     /// Overall, unless the application is dealing with a very heavy load of I/O events,
     /// a smaller value will likely be fine.
     ///
-    /// This value is silently capped to 4096.
+    /// This value is silently capped to 4096. The completion queue is sized to eight
+    /// times this value. If it still fills up, the completions that did not fit are
+    /// held by the kernel and delivered once there is room.
     pub queue_entries: u32,
-    /// Determines whether the kernel will be notified for events, or whether it will be continuously
-    /// polling for them. This value does nothing for platforms other that linux.
-    /// By default this value is set to `Notify`.
+    /// Sets the number of tasks the executor will poll before polling the reactor
+    /// for IO events. It defaults to 128.
+    ///
+    /// A small value will make the runtime more responsive to IO events, while a
+    /// big value will minimize the overhead of polling the reactor when there are
+    /// many tasks ready to run.
+    pub event_interval: u32,
+    /// Sets the number of times a task may make progress on runtime resources, such as
+    /// sockets or channels, before it is forced to yield back to the executor. It
+    /// defaults to 128.
+    ///
+    /// This prevents tasks that are always able to make progress from starving other
+    /// tasks and the reactor. To opt a future out of the budget, see
+    /// [`task::unconstrained`](crate::task::unconstrained).
+    pub budget: u32,
+    /// Determines whether the runtime blocks the thread while it waits for events, or whether
+    /// it keeps polling for them. By default this value is set to `Notify`.
+    ///
+    /// Polling minimizes the latency of IO events, since the thread never goes to sleep
+    /// and the kernel does not need to wake it up, at the cost of keeping a CPU core busy
+    /// while the runtime is idle. See [`Mode`] for more details.
     pub mode: Mode,
     /// Determines the initial allocation size. When the runtime is expected to run for a
     /// long period of time, or it is expected to manage millions of tasks then a bigger value
@@ -59,6 +101,53 @@ pub struct Config {
     /// be picked.
     pub thread_pool: ThreadPoolConfig,
 
+    /// Determines whether the results of DNS lookups are cached by the runtime.
+    /// Entries are kept for the minimum TTL of the records in the DNS answer, and
+    /// names that could not be resolved are cached for a short fixed duration.
+    /// It defaults to `true`.
+    pub dns_cache: bool,
+
+    /// Sets the maximum number of tasks that may be alive on the runtime at the same
+    /// time. It defaults to `None`, meaning there is no limit.
+    ///
+    /// Once the limit is reached, [`spawn`](crate::spawn) and [`detach`](crate::detach)
+    /// panic, and [`try_spawn`](crate::task::try_spawn) returns an error, until some of
+    /// the running tasks complete. This works as a safety valve against programs that
+    /// spawn tasks faster than they complete, such as an accept loop under heavy load,
+    /// which would otherwise grow the memory usage of the process without bound. Tasks
+    /// spawned by the runtime itself, like the ones closing file descriptors in the
+    /// background, are not subject to this limit.
+    pub max_tasks: Option<usize>,
+
+    /// Determines whether the runtime falls back to the poll reactor when io-uring is not
+    /// available on the host. It defaults to `false`.
+    ///
+    /// io-uring may be unavailable because the kernel is too old, or because its system calls
+    /// are blocked, as some container runtimes do with seccomp filters. With this option set,
+    /// a binary built with the `io-uring` feature can still run on such hosts, at the cost of
+    /// the performance benefits of io-uring. This value does nothing when the `io-uring`
+    /// feature is disabled.
+    pub allow_fallback: bool,
+
+    /// Sets a hook that is called whenever a spawned task panics while it is being polled.
+    /// It defaults to `None`.
+    ///
+    /// The hook receives the id of the task and the panic payload. It is called before the
+    /// payload is stored in the task, so it observes the panics of detached tasks too, which
+    /// would otherwise go unnoticed since nobody joins them. This makes it a good place to
+    /// log the panics and record metrics. Panics of the future passed to
+    /// [`block_on`](Runtime::block_on) propagate to the caller instead, and panics raised
+    /// by the hook itself are ignored.
+    pub on_task_panic: Option<TaskPanicHook>,
+
+    /// Determines what happens when a task spawned with [`detach`](crate::detach) panics.
+    /// It defaults to [`DetachedPanicPolicy::Swallow`].
+    ///
+    /// Nobody is expected to join a detached task, so its panic is otherwise lost, and a
+    /// server may keep running with a crashed background task. See [`DetachedPanicPolicy`]
+    /// for the available policies.
+    pub detached_panic_policy: DetachedPanicPolicy,
+
     // Do not use this field. Changes related to this field are considered breaking changes.
     // To construct a value of this type use `Config::default()`. Additional fields may be added
     // any time
@@ -66,26 +155,94 @@ pub struct Config {
     pub do_not_use_this_field: (),
 }
 
-/// Determines whether the kernel will be notified for events, or whether it will be continuously
-/// polling for them.
+/// Determines whether the runtime blocks the thread while it waits for events, or whether
+/// it keeps polling for them.
 #[derive(Clone, Debug, Default)]
 #[non_exhaustive]
 pub enum Mode {
-    /// The kernel will be notified of submissions with a context switch.
-    /// This configuration is best when a moderate amount of IO is expected.
+    /// When the runtime runs out of tasks to run, it blocks the thread until an IO event
+    /// completes, and the kernel is notified of submissions with a system call.
+    /// This configuration is the most energy efficient, and it is best when a moderate
+    /// amount of IO is expected.
     #[default]
     Notify,
-    /// The kernel will poll the io-uring submission queue, which skips
-    /// a system call notification. This configuration should only be used
-    /// if a really big amount of IO is expected.
+    /// When the runtime runs out of tasks to run, it keeps polling for completed IO
+    /// events for up to `idle_timeout` milliseconds before blocking the thread. With
+    /// io-uring, the kernel will also poll the submission queue, which skips the system
+    /// call notification.
+    ///
+    /// This configuration minimizes latency at the cost of CPU consumption, so it should
+    /// only be used if a really big amount of IO is expected, or if latency is critical.
     Polling {
-        /// The maximum amount of time the OS thread will poll before
+        /// The maximum amount of time the runtime and the kernel will poll before
         /// sleeping. It is messured in milliseconds. It is recommended
         /// to have this be a low value to minimize CPU consumption.
         idle_timeout: u32,
     },
 }
 
+impl Mode {
+    /// Returns whether the runtime should keep polling for events,
+    /// having been idle since `idle_since`.
+    pub(crate) fn should_spin(&self, idle_since: Instant) -> bool {
+        match self {
+            Mode::Notify => false,
+            Mode::Polling { idle_timeout } => {
+                idle_since.elapsed() < Duration::from_millis(u64::from(*idle_timeout))
+            }
+        }
+    }
+}
+
+/// Determines what happens when a task spawned with [`detach`](crate::detach) panics.
+/// See [`Config::detached_panic_policy`](Config#structfield.detached_panic_policy).
+///
+/// The policy is applied as soon as the task panics, regardless of whether its
+/// [`DetachHandle`](crate::task::DetachHandle) is awaited later on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DetachedPanicPolicy {
+    /// The panic payload is stored in the task, and it is dropped along with the task
+    /// unless the [`DetachHandle`](crate::task::DetachHandle) is awaited.
+    #[default]
+    Swallow,
+    /// The panic is written to the standard error along with the id of the task.
+    Log,
+    /// The panic is written to the standard error, and the process is aborted with
+    /// [`std::process::abort`], so a supervisor can restart it.
+    AbortProcess,
+}
+
+/// A hook called with the id and the panic payload of a task that panicked.
+/// See [`Config::on_task_panic`](Config#structfield.on_task_panic).
+#[derive(Clone)]
+pub struct TaskPanicHook(Arc<HookFn>);
+
+type HookFn = dyn Fn(u64, &(dyn Any + Send)) + Send + Sync;
+
+impl TaskPanicHook {
+    /// Creates a new hook from a function.
+    pub fn new<F>(hook: F) -> TaskPanicHook
+    where
+        F: Fn(u64, &(dyn Any + Send)) + Send + Sync + 'static,
+    {
+        TaskPanicHook(Arc::new(hook))
+    }
+
+    pub(crate) fn call(&self, task_id: u64, payload: &(dyn Any + Send)) {
+        (self.0)(task_id, payload);
+    }
+}
+
+impl Debug for TaskPanicHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TaskPanicHook")
+    }
+}
+
+/// Configuration for the thread pool shared by every runtime, which runs
+/// [`spawn_blocking`](crate::task::spawn_blocking) closures.
+/// See [`Config::thread_pool`](Config#structfield.thread_pool).
 #[derive(Clone, Debug)]
 pub struct ThreadPoolConfig {
     /// Max amount of time a worker may be idle before it exits.
@@ -104,9 +261,16 @@ impl Default for Config {
     fn default() -> Self {
         Config {
             queue_entries: 128,
+            event_interval: 128,
+            budget: 128,
             mode: Mode::default(),
             init_capacity: 1024,
             thread_pool: ThreadPoolConfig::default(),
+            dns_cache: true,
+            max_tasks: None,
+            allow_fallback: false,
+            on_task_panic: None,
+            detached_panic_policy: DetachedPanicPolicy::default(),
             do_not_use_this_field: (),
         }
     }
@@ -123,15 +287,133 @@ impl Default for ThreadPoolConfig {
 }
 
 impl Config {
+    /// Creates a configuration with the default values, to be adjusted with the
+    /// chainable setters. This is equivalent to [`Config::default`].
+    #[must_use]
+    pub fn builder() -> Self {
+        Config::default()
+    }
+
+    /// Sets the number of entries of the submission queue.
+    /// See [`Config::queue_entries`](Config#structfield.queue_entries) for more details.
+    #[must_use]
+    pub fn queue_entries(mut self, entries: u32) -> Self {
+        self.queue_entries = entries;
+        self
+    }
+
+    /// Sets the number of tasks polled before the reactor is polled for IO events.
+    /// See [`Config::event_interval`](Config#structfield.event_interval) for more details.
+    #[must_use]
+    pub fn event_interval(mut self, interval: u32) -> Self {
+        self.event_interval = interval;
+        self
+    }
+
+    /// Sets the cooperative budget given to each task every time it is polled.
+    /// See [`Config::budget`](Config#structfield.budget) for more details.
+    #[must_use]
+    pub fn budget(mut self, budget: u32) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Sets the initial capacity of the executor queue.
+    /// See [`Config::init_capacity`](Config#structfield.init_capacity) for more details.
+    #[must_use]
+    pub fn init_capacity(mut self, capacity: usize) -> Self {
+        self.init_capacity = capacity;
+        self
+    }
+
+    /// Sets the configuration of the shared thread pool.
+    /// See [`Config::thread_pool`](Config#structfield.thread_pool) for more details.
+    #[must_use]
+    pub fn thread_pool(mut self, thread_pool: ThreadPoolConfig) -> Self {
+        self.thread_pool = thread_pool;
+        self
+    }
+
+    /// Sets whether the runtime blocks or keeps polling while it waits for events.
+    /// See [`Config::mode`](Config#structfield.mode) for more details.
+    #[must_use]
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Enables or disables the runtime's DNS cache.
+    /// See [`Config::dns_cache`](Config#structfield.dns_cache) for more details.
+    #[must_use]
+    pub fn dns_cache(mut self, enabled: bool) -> Self {
+        self.dns_cache = enabled;
+        self
+    }
+
+    /// Sets the maximum number of tasks that may be alive at the same time.
+    /// See [`Config::max_tasks`](Config#structfield.max_tasks) for more details.
+    #[must_use]
+    pub fn max_tasks(mut self, max_tasks: usize) -> Self {
+        self.max_tasks = Some(max_tasks);
+        self
+    }
+
+    /// Enables or disables falling back to the poll reactor when io-uring is unavailable.
+    /// See [`Config::allow_fallback`](Config#structfield.allow_fallback) for more details.
+    #[must_use]
+    pub fn allow_fallback(mut self, enabled: bool) -> Self {
+        self.allow_fallback = enabled;
+        self
+    }
+
+    /// Sets the hook called whenever a spawned task panics.
+    /// See [`Config::on_task_panic`](Config#structfield.on_task_panic) for more details.
+    ///
+    /// # Examples
+    /// ```
+    /// use osiris::runtime::Config;
+    ///
+    /// let rt = Config::default()
+    ///     .on_task_panic(|id, _payload| eprintln!("task {id} panicked"))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[must_use]
+    pub fn on_task_panic<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(u64, &(dyn Any + Send)) + Send + Sync + 'static,
+    {
+        self.on_task_panic = Some(TaskPanicHook::new(hook));
+        self
+    }
+
+    /// Sets what happens when a detached task panics.
+    /// See [`Config::detached_panic_policy`](Config#structfield.detached_panic_policy) for more details.
+    #[must_use]
+    pub fn detached_panic_policy(mut self, policy: DetachedPanicPolicy) -> Self {
+        self.detached_panic_policy = policy;
+        self
+    }
+
     /// Creates the configured Runtime.
     /// The returned Runtime instance is ready to spawn tasks.
     ///
     /// # Errors
-    /// If the async primitives could not be instantiated.
+    /// If the async primitives could not be instantiated. With the io-uring backend,
+    /// an error of kind [`Unsupported`](std::io::ErrorKind::Unsupported) is returned if
+    /// the kernel is too old for the io-uring operations osiris relies on, naming the
+    /// missing operation and the kernel version that introduced it.
+    ///
+    /// An error of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput) is returned if
+    /// `queue_entries`, `event_interval`, `budget`, or the maximum number of workers of the
+    /// thread pool is zero, since the runtime cannot make progress with such values.
     pub fn build(self) -> std::io::Result<Runtime> {
+        self.validate()?;
         let executor = Rc::new(Executor::new(self.clone())?);
         let reactor = Reactor::new(self.clone())?;
         let rt = Runtime {
+            dns_cache: self.dns_cache.then(DnsCache::default),
+            timers: Timers::default(),
             config: self,
             executor,
             reactor,
@@ -139,12 +421,35 @@ impl Config {
         Ok(rt)
     }
 
+    /// Rejects the zero values of the options the runtime can't make progress without.
+    /// Any combination of valid values is accepted, since the options don't constrain
+    /// each other.
+    fn validate(&self) -> std::io::Result<()> {
+        let fields = [
+            ("queue_entries", self.queue_entries),
+            ("event_interval", self.event_interval),
+            ("budget", self.budget),
+            ("thread_pool.max_workers", self.thread_pool.max_workers),
+        ];
+        for (name, value) in fields {
+            if value == 0 {
+                let msg = format!("invalid runtime configuration: `{name}` must not be zero");
+                return Err(Error::new(ErrorKind::InvalidInput, msg));
+            }
+        }
+        Ok(())
+    }
+
     #[cfg(io_uring)]
     pub(crate) fn io_uring(self) -> std::io::Result<IoUring> {
         let mut builder = IoUring::builder();
         if let Mode::Polling { idle_timeout } = self.mode {
             builder.setup_sqpoll(idle_timeout);
         }
-        builder.build(self.queue_entries.min(4096))
+        let entries = self.queue_entries.min(4096);
+        // multishot operations post many CQEs per SQE, so the completion queue
+        // is made larger than the default of twice the submission queue.
+        builder.setup_cqsize(entries * 8);
+        builder.build(entries)
     }
 }
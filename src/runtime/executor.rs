@@ -1,5 +1,7 @@
+use super::config::{DetachedPanicPolicy, TaskPanicHook};
 use super::{Config, Runtime};
 use crate::net::pipe;
+use crate::task::coop::with_budget;
 use crate::task::Task;
 use std::any::Any;
 use std::cell::{Cell, RefCell};
@@ -11,7 +13,7 @@ use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::Arc;
-use std::task::Context;
+use std::task::{Context, Poll, Waker};
 
 pub(crate) struct Executor {
     /// The run queue holds all tasks that are currently ready to do progress,
@@ -25,6 +27,41 @@ pub(crate) struct Executor {
     /// A pipe sender used for wakeups across threads.
     pub(crate) sender: Arc<pipe::Sender>,
     pub(crate) receiver: Rc<pipe::Receiver>,
+    /// This bool states wheather a task is forwarding the wakeups sent through the pipe.
+    pub(crate) forwarding: Cell<bool>,
+    /// The number of spawned tasks that haven't completed or been aborted yet.
+    pub(crate) active_tasks: Rc<Cell<usize>>,
+    /// Tasks that yielded to the reactor. They are woken once the reactor
+    /// has submitted the pending IO events.
+    pub(crate) deferred: RefCell<Vec<Waker>>,
+    /// Called whenever a task panics while being polled.
+    pub(crate) on_task_panic: Option<TaskPanicHook>,
+    /// What to do when a detached task panics.
+    pub(crate) detached_panic_policy: DetachedPanicPolicy,
+}
+
+/// A future that is accounted for in the executor's active task count.
+/// The count is decremented when the future gets dropped, which happens
+/// as soon as the task completes, panics or is aborted.
+struct Tracked<F> {
+    future: F,
+    _guard: ActiveGuard,
+}
+
+struct ActiveGuard(Rc<Cell<usize>>);
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
+
+impl<F: Future> Future for Tracked<F> {
+    type Output = F::Output;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        // Safety: the future is structurally pinned.
+        unsafe { self.map_unchecked_mut(|this| &mut this.future) }.poll(cx)
+    }
 }
 
 fn catch_unwind<T>(f: impl FnOnce() -> T) -> Result<T, Box<dyn Any + Send>> {
@@ -33,14 +70,26 @@ fn catch_unwind<T>(f: impl FnOnce() -> T) -> Result<T, Box<dyn Any + Send>> {
 
 impl Executor {
     /// Creates a new executor
-    pub fn new(Config { init_capacity, .. }: Config) -> Result<Executor, Error> {
-        let (sender, receiver) = pipe::pipe()?;
+    pub fn new(
+        Config {
+            init_capacity,
+            on_task_panic,
+            detached_panic_policy,
+            ..
+        }: Config,
+    ) -> Result<Executor, Error> {
+        let (sender, receiver) = pipe::wakeup_pipe()?;
         Ok(Executor {
             queue: RefCell::new(VecDeque::with_capacity(init_capacity)),
             main_handle: Cell::new(true),
             task_id: Cell::default(),
             sender: Arc::new(sender),
             receiver: Rc::new(receiver),
+            forwarding: Cell::new(false),
+            active_tasks: Rc::default(),
+            deferred: RefCell::default(),
+            on_task_panic,
+            detached_panic_policy,
         })
     }
 
@@ -51,13 +100,40 @@ impl Executor {
     }
 
     /// Spawns a task onto the executor
-    pub fn spawn<F>(&self, future: F, rt: Runtime, ignore_abort: bool) -> Task
+    pub fn spawn<F>(
+        &self,
+        future: F,
+        name: Option<Rc<str>>,
+        rt: Runtime,
+        ignore_abort: bool,
+    ) -> Task
+    where
+        F: Future + 'static,
+    {
+        let active_tasks = self.active_tasks.clone();
+        active_tasks.set(active_tasks.get() + 1);
+        let future = Tracked {
+            future,
+            _guard: ActiveGuard(active_tasks),
+        };
+        self.spawn_untracked(future, name, rt, ignore_abort)
+    }
+
+    /// Spawns a task onto the executor without accounting for it in the
+    /// active task count. This is used for the runtime's own tasks.
+    pub fn spawn_untracked<F>(
+        &self,
+        future: F,
+        name: Option<Rc<str>>,
+        rt: Runtime,
+        ignore_abort: bool,
+    ) -> Task
     where
         F: Future + 'static,
     {
         let mut queue = self.queue.borrow_mut();
         let task_id = self.task_id();
-        let task = Task::new(future, task_id, rt, ignore_abort);
+        let task = Task::new(future, task_id, name, rt, ignore_abort);
         queue.push_back(task.clone());
         task
     }
@@ -82,13 +158,14 @@ impl Executor {
             let future: Pin<&mut F> = unsafe { transmute(ptr) };
             future.poll(cx)
         });
-        self.spawn(future, rt, false)
+        self.spawn_untracked(future, None, rt, false)
     }
 
     /// It polls at most `ticks` futures. It may poll less futures than
-    /// the specified number of ticks.
+    /// the specified number of ticks. Each task is given `budget` units of
+    /// cooperative budget every time it is polled.
     #[inline]
-    pub fn poll(&self, task_id: &Cell<Option<u64>>, len: u32) {
+    pub fn poll(&self, task_id: &Cell<Option<u64>>, len: u32, budget: u32) {
         for _ in 0..len {
             // we retrieve the queue of woken tasks
             let mut run_queue = self.queue.borrow_mut();
@@ -104,14 +181,59 @@ impl Executor {
             let waker = task.clone().waker();
             let cx = &mut Context::from_waker(&waker);
 
-            if let Err(payload) = catch_unwind(|| task.poll(cx)) {
+            if let Err(payload) = catch_unwind(|| with_budget(budget, || task.poll(cx))) {
+                if let Some(hook) = &self.on_task_panic {
+                    catch_unwind(|| hook.call(task.id(), &*payload)).ok();
+                }
+                if task.is_detached() {
+                    self.detached_panic(&task, &*payload);
+                }
                 task.panic(payload);
             };
+            task_id.set(None);
+        }
+    }
+
+    /// Applies the detached panic policy to a task that panicked.
+    fn detached_panic(&self, task: &Task, payload: &(dyn Any + Send)) {
+        if self.detached_panic_policy == DetachedPanicPolicy::Swallow {
+            return;
+        }
+        let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+            msg
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            msg.as_str()
+        } else {
+            "Box<dyn Any>"
+        };
+        match task.name() {
+            Some(name) => eprintln!(
+                "osiris: detached task '{name}' ({}) panicked: {msg}",
+                task.id()
+            ),
+            None => eprintln!("osiris: detached task {} panicked: {msg}", task.id()),
+        }
+        if self.detached_panic_policy == DetachedPanicPolicy::AbortProcess {
+            std::process::abort();
+        }
+    }
+
+    /// Wakes the task once the reactor has been driven.
+    pub fn defer(&self, waker: Waker) {
+        self.deferred.borrow_mut().push(waker);
+    }
+
+    /// Wakes the tasks that yielded to the reactor.
+    /// It must be called after the reactor has been driven.
+    pub fn wake_deferred(&self) {
+        let deferred = std::mem::take(&mut *self.deferred.borrow_mut());
+        for waker in deferred {
+            waker.wake();
         }
     }
 
     /// returns true if there is no more work to do
     pub fn is_idle(&self) -> bool {
-        self.queue.borrow().len() == 0
+        self.queue.borrow().len() == 0 && self.deferred.borrow().is_empty()
     }
 }
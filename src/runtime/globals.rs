@@ -13,4 +13,10 @@ thread_local! {
     pub(crate) static TASK_ID: Cell<Option<u64>> = Cell::new(None);
 }
 
+thread_local! {
+    /// This is the cooperative budget of the task currently being executed.
+    /// It is `None` when the task is unconstrained, or outside of a task.
+    pub(crate) static BUDGET: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
 pub(crate) static THREAD_POOL: OnceLock<ThreadPool> = OnceLock::new();
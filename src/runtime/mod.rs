@@ -59,10 +59,11 @@
 //! }
 //! ```
 //!
+use crate::net::dns::DnsCache;
 use crate::reactor::Reactor;
 use crate::runtime::waker::{forward_multithreaded_wakeups, main_waker};
-use crate::spawn;
-use crate::task::JoinHandle;
+use crate::task::{DetachHandle, JoinHandle};
+use crate::time::{timeout, Timers};
 use executor::Executor;
 use std::cell::Cell;
 use std::future::Future;
@@ -70,11 +71,14 @@ use std::io;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
-pub use config::{Config, Mode};
-pub(crate) use globals::{RUNTIME, TASK_ID, THREAD_POOL};
+pub use capabilities::Capabilities;
+pub use config::{Config, DetachedPanicPolicy, Mode, TaskPanicHook, ThreadPoolConfig};
+pub(crate) use globals::{BUDGET, RUNTIME, TASK_ID, THREAD_POOL};
 pub(crate) use thread_pool::ThreadPool;
 
+mod capabilities;
 mod config;
 mod executor;
 mod globals;
@@ -89,6 +93,8 @@ pub struct Runtime {
     pub(crate) config: Config,
     pub(crate) executor: Rc<Executor>,
     pub(crate) reactor: Reactor,
+    pub(crate) dns_cache: Option<DnsCache>,
+    pub(crate) timers: Timers,
 }
 
 impl Runtime {
@@ -111,7 +117,12 @@ impl Runtime {
     /// which the future spawns internally will be executed on the runtime.
     ///
     /// Any spawned tasks will be suspended after `block_on` returns. Calling
-    /// `block_on` again will resume previously spawned tasks.
+    /// `block_on` again on the same runtime will resume previously spawned tasks,
+    /// including the ones waiting on IO or timers, which are woken as their events
+    /// complete. Tasks are not dropped along with the runtime either: every task holds
+    /// a handle to its runtime, so the tasks that are still pending once the runtime is
+    /// dropped are leaked along with it. This includes the free function
+    /// [`block_on`](fn@block_on), which creates a runtime just for the call.
     ///
     /// # Panics
     ///
@@ -144,8 +155,7 @@ impl Runtime {
     where
         F: Future,
     {
-        let msg = "called `block_on` from the inside of another osiris runtime.";
-        assert!(current().is_none(), "{}", msg);
+        assert!(current().is_none(), "{}", NESTED_RUNTIME);
 
         // we enter the runtime context so functions like `spawn` are
         // available.
@@ -155,10 +165,24 @@ impl Runtime {
         let future = unsafe { Pin::new_unchecked(&mut future) };
 
         // we want to forward any wakeup calls coming from other
-        // threads.
-        let _handle = spawn(forward_multithreaded_wakeups(
-            self.executor.receiver.clone(),
-        ));
+        // threads, unless `tick` already left a task doing so.
+        // The guard stops the forwarding task when this function returns or
+        // unwinds, so a later call spawns a new one.
+        struct Forward<'a>(&'a Runtime, Option<JoinHandle<()>>);
+        impl<'a> Drop for Forward<'a> {
+            fn drop(&mut self) {
+                if let Some(forward) = self.1.take() {
+                    drop(forward);
+                    self.0.executor.forwarding.set(false);
+                }
+            }
+        }
+        let forward = if self.executor.forwarding.get() {
+            None
+        } else {
+            Some(self.forward_wakeups())
+        };
+        let forward = Forward(self, forward);
 
         // # Safety:
         // This operation is safe because the task will not outlive the function scope.
@@ -176,18 +200,166 @@ impl Runtime {
         // by polling the JoinHandle before polling the main task.
         self.executor.main_handle.set(true);
 
-        TASK_ID.with(|task_id| self.event_loop(handle, task_id))
+        let out = TASK_ID.with(|task_id| self.event_loop(handle, task_id));
+
+        drop(forward);
+
+        let pending = self.active_task_count();
+        if cfg!(debug_assertions) && pending != 0 {
+            eprintln!("osiris: `block_on` returned with {pending} tasks still pending. These may be leaked detached tasks.");
+        }
+        out
+    }
+
+    /// Returns the number of tasks spawned onto this runtime that haven't completed or
+    /// been aborted yet. This excludes the future passed to [`block_on`](Runtime::block_on)
+    /// and the runtime's own tasks, such as those closing the file descriptors of dropped
+    /// files and sockets.
+    ///
+    /// This can be used to check in tests that no detached tasks were leaked.
+    ///
+    /// # Examples
+    /// ```
+    /// # osiris::block_on(async {
+    /// use osiris::runtime::current;
+    /// use osiris::task::{detach, yield_now};
+    ///
+    /// let rt = current().unwrap();
+    /// assert_eq!(rt.active_task_count(), 0);
+    /// detach(async { yield_now().await });
+    /// assert_eq!(rt.active_task_count(), 1);
+    /// yield_now().await;
+    /// yield_now().await;
+    /// assert_eq!(rt.active_task_count(), 0);
+    /// # }).unwrap();
+    /// ```
+    #[must_use]
+    pub fn active_task_count(&self) -> usize {
+        self.executor.active_tasks.get()
+    }
+
+    /// Returns the io-uring features supported by the kernel this runtime is running on,
+    /// so applications can pick a faster path when it is available. See [`Capabilities`].
+    ///
+    /// # Examples
+    /// ```
+    /// use osiris::runtime::Runtime;
+    ///
+    /// let rt = Runtime::new().unwrap();
+    /// let capabilities = rt.capabilities();
+    /// if !capabilities.io_uring {
+    ///     assert!(!capabilities.multishot_accept);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn capabilities(&self) -> Capabilities {
+        self.reactor.capabilities()
+    }
+
+    /// Runs a future to completion on the osiris runtime, like [`Runtime::block_on`],
+    /// but returns an error instead of panicking when called from the inside of another
+    /// osiris runtime.
+    ///
+    /// This is useful for libraries that may be used both standalone and from within
+    /// osiris, which can fall back to awaiting the future directly.
+    ///
+    /// # Errors
+    /// This function errors if called from the inside of another osiris runtime.
+    pub fn try_block_on<F>(&self, future: F) -> io::Result<F::Output>
+    where
+        F: Future,
+    {
+        if current().is_some() {
+            return Err(nested_runtime());
+        }
+        self.block_on(future)
+    }
+
+    /// Runs a single iteration of the runtime: it polls the tasks that are ready
+    /// to make progress, and then submits their I/O to the reactor and collects any
+    /// completed events, without blocking. It returns `true` if any task was polled.
+    ///
+    /// This allows embedding osiris inside of another event loop. Note that timers
+    /// and I/O will only advance while `tick` is being called. The runtime is entered
+    /// for the duration of the call, but tasks must be spawned from the inside of an
+    /// [`enter`](Runtime::enter) guard.
+    ///
+    /// # Errors
+    /// This function errors if the reactor failed to submit the I/O events.
+    ///
+    /// # Panics
+    /// Panics if called from the inside of an osiris task.
+    ///
+    /// # Examples
+    /// ```
+    /// use osiris::runtime::Runtime;
+    /// use osiris::task::{detach, yield_now};
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let rt = Runtime::new()?;
+    /// let _guard = rt.enter();
+    /// let done = Rc::new(Cell::new(false));
+    /// let flag = done.clone();
+    /// detach(async move {
+    ///     yield_now().await;
+    ///     flag.set(true);
+    /// });
+    ///
+    /// while !done.get() {
+    ///     rt.tick()?;
+    /// }
+    /// # Ok(())}
+    /// ```
+    pub fn tick(&self) -> io::Result<bool> {
+        assert!(
+            TASK_ID.with(Cell::get).is_none(),
+            "called `Runtime::tick` from the inside of an osiris task."
+        );
+        let _h = self.enter();
+
+        if !self.executor.forwarding.get() {
+            // the forwarding task must outlive this call, so it is leaked
+            // for as long as the runtime is alive.
+            let mut forward = self.forward_wakeups();
+            forward.detach();
+        }
+
+        let progress = !self.executor.is_idle();
+        TASK_ID.with(|task_id| {
+            self.executor
+                .poll(task_id, self.config.event_interval, self.config.budget);
+        });
+        self.reactor.submit_and_yield()?;
+        self.executor.wake_deferred();
+        Ok(progress)
     }
+
+    /// Spawns the task that forwards the wakeups coming from other threads.
+    fn forward_wakeups(&self) -> JoinHandle<()> {
+        let forward = forward_multithreaded_wakeups(self.executor.receiver.clone());
+        let task = self
+            .executor
+            .spawn_untracked(forward, None, self.clone(), false);
+        self.executor.forwarding.set(true);
+        // Safety: both types are the output of the forwarding future
+        unsafe { JoinHandle::new(task) }
+    }
+
     /// This is the main loop
     fn event_loop<T>(&self, handle: &mut JoinHandle<T>, task_id: TaskId) -> io::Result<T> {
         let Runtime {
             executor,
             reactor,
             config,
+            ..
         } = self;
 
         let handel_waker = main_waker();
         let handle_cx = &mut Context::from_waker(&handel_waker);
+        // the instant the executor ran out of tasks, used in polling mode.
+        let mut idle_since = None;
 
         loop {
             // we must poll the JoinHandle before polling the executor.
@@ -200,13 +372,21 @@ impl Runtime {
                     return Ok(out);
                 }
             }
-            executor.poll(task_id, config.queue_entries);
+            executor.poll(task_id, config.event_interval, config.budget);
 
-            if executor.is_idle() && !executor.main_handle.get() {
-                reactor.submit_and_wait()?;
-            } else {
+            if !executor.is_idle() || executor.main_handle.get() {
+                idle_since = None;
                 reactor.submit_and_yield()?;
+            } else if config
+                .mode
+                .should_spin(*idle_since.get_or_insert_with(Instant::now))
+            {
+                std::hint::spin_loop();
+                reactor.submit_and_yield()?;
+            } else {
+                reactor.submit_and_wait()?;
             }
+            executor.wake_deferred();
         }
     }
     /// Enters the runtime context. While the guard is in scope
@@ -226,29 +406,89 @@ impl Runtime {
     }
 
     /// Spawns a new task onto the runtime returning a `JoinHandle` for that task.    
+    ///
+    /// # Panics
+    /// Panics if the runtime's [`max_tasks`](Config#structfield.max_tasks) limit is reached.
+    #[track_caller]
     #[must_use = "This task is immediatly cancelled after spawn. osiris tasks are cancelled on drop, you may want to use `detach()`."]
     pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
     where
         F: Future + 'static,
     {
-        self._spawn(future, false)
+        match self.try_spawn(future) {
+            Ok(handle) => handle,
+            Err(err) => panic!("{err}"),
+        }
     }
 
-    pub fn detach<F>(&self, future: F) -> JoinHandle<F::Output>
+    /// Spawns a new task onto the runtime returning a `JoinHandle` for that task, or
+    /// an error if the runtime's [`max_tasks`](Config#structfield.max_tasks) limit is reached.
+    ///
+    /// # Errors
+    /// This function errors if the number of tasks alive on the runtime has reached
+    /// the configured limit.
+    pub fn try_spawn<F>(&self, future: F) -> io::Result<JoinHandle<F::Output>>
     where
         F: Future + 'static,
     {
-        let mut task = self.spawn(future);
-        task.detach();
-        task
+        self.try_spawn_named(future, None)
     }
 
-    /// Spawns a new task onto the runtime returning a `JoinHandle` for that task.    
+    /// Spawns a new task with an optional name, failing if the runtime's
+    /// [`max_tasks`](Config#structfield.max_tasks) limit is reached.
+    pub(crate) fn try_spawn_named<F>(
+        &self,
+        future: F,
+        name: Option<Rc<str>>,
+    ) -> io::Result<JoinHandle<F::Output>>
+    where
+        F: Future + 'static,
+    {
+        if let Some(max_tasks) = self.config.max_tasks {
+            if self.active_task_count() >= max_tasks {
+                let msg = format!(
+                    "osiris: cannot spawn a task, the limit of {max_tasks} tasks was reached."
+                );
+                return Err(io::Error::other(msg));
+            }
+        }
+        let task = self.executor.spawn(future, name, self.clone(), false);
+        // Safety: both types are F::Output
+        Ok(unsafe { JoinHandle::new(task) })
+    }
+
+    /// Spawns a new detached task onto the runtime.
+    ///
+    /// # Panics
+    /// Panics if the runtime's [`max_tasks`](Config#structfield.max_tasks) limit is reached.
+    #[track_caller]
+    pub fn detach<F>(&self, future: F) -> DetachHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        DetachHandle::new(self.spawn(future))
+    }
+
+    /// Spawns a detached task that cleans up after a dropped resource, such as
+    /// closing its file descriptor. These tasks are not subject to the `max_tasks`
+    /// limit, since they can't fail.
+    pub(crate) fn detach_cleanup<F>(&self, future: F)
+    where
+        F: Future + 'static,
+    {
+        self._spawn(future, false).detach();
+    }
+
+    /// Spawns one of the runtime's own tasks, returning a `JoinHandle` for that task.
+    /// These are not counted by [`active_task_count`](Runtime::active_task_count), nor
+    /// subject to the `max_tasks` limit.
     pub(crate) fn _spawn<F>(&self, future: F, ignore_abort: bool) -> JoinHandle<F::Output>
     where
         F: Future + 'static,
     {
-        let task = self.executor.spawn(future, self.clone(), ignore_abort);
+        let task = self
+            .executor
+            .spawn_untracked(future, None, self.clone(), ignore_abort);
         // Safety: both types are F::Output
         unsafe { JoinHandle::new(task) }
     }
@@ -292,6 +532,71 @@ pub fn block_on<F: Future>(f: F) -> io::Result<F::Output> {
     Runtime::new()?.block_on(f)
 }
 
+/// Run a future to completion on the current thread, like [`block_on`], but
+/// returns an error instead of panicking if called from the inside of another
+/// osiris runtime.
+///
+/// # Errors
+/// Errors if called from the inside of another osiris runtime, or if the
+/// io-ring could not be allocated.
+///
+/// # Examples
+/// ```
+/// use osiris::runtime::try_block_on;
+///
+/// try_block_on(async {
+///     // runtimes cannot be nested
+///     assert!(try_block_on(async {}).is_err());
+/// })
+/// .unwrap();
+/// ```
+pub fn try_block_on<F: Future>(f: F) -> io::Result<F::Output> {
+    if current().is_some() {
+        return Err(nested_runtime());
+    }
+    Runtime::new()?.block_on(f)
+}
+
+/// Run a future to completion on the current thread, like [`block_on`], but gives up
+/// once `dur` has elapsed, returning `Ok(None)`.
+///
+/// The timer is serviced by the same runtime that drives the future. When the timeout
+/// expires, the future is dropped, cancelling any operation it was waiting on. Like with
+/// [`block_on`], the tasks spawned by the future are not dropped when this function
+/// returns, whether the future completed or not. Those still pending, including detached
+/// ones, are leaked along with the runtime, so they should be aborted or awaited by the future.
+///
+/// # Errors
+/// Errors if the io-ring could not be allocated.
+///
+/// # Panics
+/// Panics if called from the inside of another osiris runtime.
+/// Runtimes cannot be nested.
+///
+/// # Examples
+/// ```
+/// use osiris::runtime::block_on_timeout;
+/// use osiris::time::sleep;
+/// use std::time::Duration;
+///
+/// let out = block_on_timeout(async { 1 }, Duration::from_secs(1)).unwrap();
+/// assert_eq!(out, Some(1));
+///
+/// let hang = sleep(Duration::from_secs(60));
+/// let out = block_on_timeout(hang, Duration::from_millis(10)).unwrap();
+/// assert_eq!(out, None);
+/// ```
+pub fn block_on_timeout<F: Future>(f: F, dur: Duration) -> io::Result<Option<F::Output>> {
+    let out = block_on(timeout(dur, f))?;
+    Ok(out.ok())
+}
+
+const NESTED_RUNTIME: &str = "called `block_on` from the inside of another osiris runtime.";
+
+fn nested_runtime() -> io::Error {
+    io::Error::other(NESTED_RUNTIME)
+}
+
 #[track_caller]
 #[inline]
 pub(crate) fn current_unwrap(fun: &str) -> Runtime {
@@ -0,0 +1,183 @@
+use std::cell::RefCell;
+use std::fmt::{self, Debug};
+use std::future::poll_fn;
+use std::task::{Poll, Waker};
+
+/// A barrier enables multiple tasks to synchronize the beginning of some computation.
+///
+/// Each generation of the barrier waits for `n` tasks to call [`wait`](Barrier::wait),
+/// and then releases all of them at once. After that, the barrier is reset, so it
+/// can be reused for the next phase of the computation.
+///
+/// Like the rest of the [`sync`](crate::sync) module, the barrier is meant to
+/// synchronize tasks, so it does not implement the `Send` and `Sync` traits.
+///
+/// # Examples
+///
+/// ```
+/// # osiris::block_on(async {
+/// use std::rc::Rc;
+/// use osiris::{spawn, sync::Barrier};
+///
+/// let barrier = Rc::new(Barrier::new(10));
+/// let mut handles = Vec::with_capacity(10);
+/// for _ in 0..10 {
+///     let barrier = barrier.clone();
+///     // The same messages will be printed together.
+///     // You will NOT see any interleaving.
+///     handles.push(spawn(async move {
+///         println!("before wait");
+///         let result = barrier.wait().await;
+///         println!("after wait");
+///         result
+///     }));
+/// }
+///
+/// let mut leaders = 0;
+/// for handle in handles {
+///     if handle.await.is_leader() {
+///         leaders += 1;
+///     }
+/// }
+/// assert_eq!(leaders, 1);
+/// # }).unwrap();
+/// ```
+pub struct Barrier {
+    n: usize,
+    state: RefCell<State>,
+}
+
+struct State {
+    /// the number of tasks that have arrived in the current generation.
+    arrived: usize,
+    generation: u64,
+    wakers: Vec<Waker>,
+}
+
+/// A `BarrierWaitResult` is returned by [`Barrier::wait`] when all tasks
+/// in the barrier have rendezvoused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrierWaitResult(bool);
+
+impl Barrier {
+    /// Creates a new barrier that can block a given number of tasks.
+    ///
+    /// A barrier will block `n`-1 tasks which call [`wait`](Barrier::wait) and
+    /// then wake up all tasks at once when the `n`th task calls `wait`. A barrier
+    /// created with `n == 0` behaves like one created with `n == 1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use osiris::sync::Barrier;
+    ///
+    /// let barrier = Barrier::new(10);
+    /// ```
+    #[must_use]
+    pub fn new(n: usize) -> Barrier {
+        Barrier {
+            n: n.max(1),
+            state: RefCell::new(State {
+                arrived: 0,
+                generation: 0,
+                wakers: Vec::new(),
+            }),
+        }
+    }
+
+    /// Waits until all tasks have rendezvoused here.
+    ///
+    /// Barriers are re-usable after all tasks have rendezvoused once, and can
+    /// be used continuously.
+    ///
+    /// A single (arbitrary) task will receive a [`BarrierWaitResult`] that returns
+    /// `true` from [`BarrierWaitResult::is_leader`] when returning from this function,
+    /// and all other tasks will receive a result that will return `false` from `is_leader`.
+    ///
+    /// # Cancel safety
+    ///
+    /// A task is counted as arrived as soon as this future is first polled. If the future
+    /// is dropped before the barrier is released, the task is still counted as arrived
+    /// for the current generation.
+    pub async fn wait(&self) -> BarrierWaitResult {
+        let generation = {
+            let mut state = self.state.borrow_mut();
+            state.arrived += 1;
+            if state.arrived == self.n {
+                state.arrived = 0;
+                state.generation += 1;
+                let wakers = std::mem::take(&mut state.wakers);
+                drop(state);
+                wakers.into_iter().for_each(Waker::wake);
+                return BarrierWaitResult(true);
+            }
+            state.generation
+        };
+        let mut index: Option<usize> = None;
+        poll_fn(|cx| {
+            let mut state = self.state.borrow_mut();
+            if state.generation != generation {
+                return Poll::Ready(BarrierWaitResult(false));
+            }
+            match index {
+                Some(i) => state.wakers[i].clone_from(cx.waker()),
+                None => {
+                    index = Some(state.wakers.len());
+                    state.wakers.push(cx.waker().clone());
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl BarrierWaitResult {
+    /// Returns `true` if this task from wait is the "leader task".
+    ///
+    /// Only one task will have `true` returned from their result, all other tasks
+    /// will have `false` returned.
+    #[must_use]
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+impl Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("Barrier")
+            .field("n", &self.n)
+            .field("arrived", &state.arrived)
+            .field("generation", &state.generation)
+            .finish()
+    }
+}
+
+#[test]
+fn barrier_has_one_leader() {
+    use crate::{block_on, spawn};
+    use std::rc::Rc;
+
+    block_on(async {
+        let barrier = Rc::new(Barrier::new(4));
+        for generation in 1..=3 {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    spawn({
+                        let barrier = barrier.clone();
+                        async move { barrier.wait().await.is_leader() }
+                    })
+                })
+                .collect();
+            let mut leaders = 0;
+            for handle in handles {
+                leaders += usize::from(handle.await);
+            }
+            assert_eq!(leaders, 1);
+            assert_eq!(barrier.state.borrow().generation, generation);
+            assert!(barrier.state.borrow().wakers.is_empty());
+        }
+    })
+    .unwrap();
+}
@@ -7,7 +7,13 @@
 //! good choice.
 //!
 
-pub use mutex::{Error as MutexError, Guard as MutexGuard, Mutex};
+pub use barrier::{Barrier, BarrierWaitResult};
+pub use mutex::{
+    Error as MutexError, Fairness as MutexFairness, Guard as MutexGuard, Mutex,
+    OwnedGuard as OwnedMutexGuard,
+};
 
+pub mod barrier;
 pub mod mpmc;
 pub mod mutex;
+pub mod watch;
@@ -119,14 +119,27 @@
 //!     rx.recv().await;
 //! }
 //! ```
+//!
+//! ## Streams and sinks
+//!
+//! With the `stream` feature enabled, [`Sender::into_sink`] and [`Receiver::into_stream`]
+//! adapt the channel halves to the `Sink` and `Stream` traits of the `futures` crate.
+
+#[cfg(feature = "stream")]
+pub use stream::{ReceiverStream, SenderSink};
 
+use crate::task::consume_budget;
+use crate::time::sleep_until;
 use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
-use std::future::poll_fn;
-use std::rc::Rc;
+use std::future::{poll_fn, Future};
+use std::mem::forget;
+use std::pin::pin;
+use std::rc::{Rc, Weak};
 use std::task::{Poll, Waker};
+use std::time::{Duration, Instant};
 
 /// The sending-half of osiris's asynchronous [`channel`] type.
 ///
@@ -194,13 +207,53 @@ pub struct Sender<T>(Rc<RefCell<Channel<T>>>);
 /// ```
 pub struct Receiver<T>(Rc<RefCell<Channel<T>>>);
 
+/// A sender that does not keep the channel alive, created with [`Sender::downgrade`].
+///
+/// Weak senders don't count towards the number of senders of the channel, so
+/// receivers observe the disconnection once every [`Sender`] is dropped, even if
+/// weak senders remain. To send values, a weak sender must be upgraded into a
+/// [`Sender`] with [`upgrade`](WeakSender::upgrade), which only succeeds while
+/// there are other senders alive.
+///
+/// # Examples
+///
+/// ```
+/// use osiris::sync::mpmc::channel;
+///
+/// #[osiris::main]
+/// async fn main() {
+///     let (tx, rx) = channel(1);
+///     let weak = tx.downgrade();
+///
+///     weak.upgrade().unwrap().send(1).await.unwrap();
+///     assert_eq!(rx.recv().await, Ok(1));
+///
+///     drop(tx);
+///     assert!(weak.upgrade().is_none());
+///     assert!(rx.recv().await.is_err());
+/// }
+/// ```
+pub struct WeakSender<T>(Weak<RefCell<Channel<T>>>);
+
+/// A receiver that does not keep the channel alive, created with [`Receiver::downgrade`].
+///
+/// Weak receivers don't count towards the number of receivers of the channel, so
+/// sends fail once every [`Receiver`] is dropped, even if weak receivers remain. A
+/// weak receiver can be upgraded into a [`Receiver`] with
+/// [`upgrade`](WeakReceiver::upgrade) while there are other receivers alive.
+pub struct WeakReceiver<T>(Weak<RefCell<Channel<T>>>);
+
 struct Channel<T> {
     /// reference count for the number of senders
     senders: u32,
     /// reference count for the number of receivers
     receivers: u32,
+    /// whether the channel was closed explicitly with `close`
+    closed: bool,
     /// queue of items to be sent
     queue: Queue<T>,
+    /// number of slots in the queue held by permits
+    reserved: usize,
     sender_id: u32,
     receiver_id: u32,
     send_wakers: VecDeque<(u32, Waker)>,
@@ -215,6 +268,13 @@ struct Channel<T> {
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub struct SendError<T>(pub T);
 
+/// A slot in the channel reserved with [`Sender::reserve`].
+///
+/// Dropping the permit without sending a value releases the slot.
+pub struct Permit<'a, T> {
+    sender: &'a Sender<T>,
+}
+
 /// An error returned from the [`recv`] function on a [`Receiver`].
 ///
 /// The [`recv`] operation can only fail if the sending half of a
@@ -225,6 +285,20 @@ pub struct SendError<T>(pub T);
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub struct RecvError;
 
+/// An error returned from the [`recv_timeout`] and [`recv_deadline`] functions
+/// on a [`Receiver`].
+///
+/// [`recv_timeout`]: Receiver::recv_timeout
+/// [`recv_deadline`]: Receiver::recv_deadline
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvTimeoutError {
+    /// No value was sent before the deadline was reached, but the channel is
+    /// still connected.
+    Timeout,
+    /// Every [`Sender`] has disconnected, and there are no buffered values left.
+    Disconnected,
+}
+
 enum Queue<T> {
     Rendezvous(Option<T>),
     Bounded(VecDeque<T>),
@@ -284,11 +358,13 @@ pub fn channel<T>(bound: usize) -> (Sender<T>, Receiver<T>) {
     let channel = Channel {
         senders: 1,
         receivers: 1,
+        closed: false,
         sender_id: 0,
         receiver_id: 0,
         send_wakers: VecDeque::new(),
         recv_waiters: VecDeque::new(),
         queue,
+        reserved: 0,
     };
 
     let channel = Rc::new(RefCell::new(channel));
@@ -332,9 +408,10 @@ impl<T> Sender<T> {
     pub async fn send(&self, item: T) -> Result<(), SendError<T>> {
         let mut item = Some(item);
         let mut waker_guard = None;
+        consume_budget().await;
         poll_fn(|cx| {
             let mut ch = self.channel().borrow_mut();
-            if ch.receivers == 0 && item.is_some() {
+            if ch.send_closed() && item.is_some() {
                 // no receivers, returning error
                 let item = item.take().unwrap();
                 return Poll::Ready(Err(SendError(item)));
@@ -349,7 +426,8 @@ impl<T> Sender<T> {
 
             if item.is_some() {
                 // trying to send item
-                let Ok(_) = ch.queue.try_push(&mut item) else {
+                let reserved = ch.reserved;
+                let Ok(_) = ch.queue.try_push(&mut item, reserved) else {
                     // reached max capacity, waiting
                     drop(ch);
                     waker_guard = Some(self.push_sender(cx.waker().clone()));
@@ -372,6 +450,105 @@ impl<T> Sender<T> {
         .await
     }
 
+    /// Waits for capacity in the channel, and reserves one slot for a value
+    /// to be sent later through the returned [`Permit`].
+    ///
+    /// Sending through the permit never waits, so this method is useful when
+    /// a value should only be produced once it is known that it can be sent, or
+    /// when a value must not be lost if the send is cancelled, for instance in a
+    /// `select!` style loop. The slot is released if the permit is dropped
+    /// without sending a value.
+    ///
+    /// Note that for rendezvous channels, [`Permit::send`] does not wait for the
+    /// value to be received.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if every [`Receiver`] has disconnected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use osiris::sync::mpmc::channel;
+    ///
+    /// #[osiris::main]
+    /// async fn main() {
+    ///     let (tx, rx) = channel(1);
+    ///
+    ///     // wait for capacity before building the value
+    ///     let permit = tx.reserve().await.unwrap();
+    ///     let value = vec![0u8; 1024];
+    ///     permit.send(value);
+    ///
+    ///     assert_eq!(rx.recv().await.unwrap().len(), 1024);
+    /// }
+    /// ```
+    pub async fn reserve(&self) -> Result<Permit<'_, T>, SendError<()>> {
+        let mut waker_guard = None;
+        consume_budget().await;
+        poll_fn(|cx| {
+            let mut ch = self.channel().borrow_mut();
+            if ch.send_closed() {
+                return Poll::Ready(Err(SendError(())));
+            }
+
+            // if there is a queue, we put ourselves at the end
+            if !ch.send_wakers.is_empty() && waker_guard.is_none() {
+                drop(ch);
+                waker_guard = Some(self.push_sender(cx.waker().clone()));
+                return Poll::Pending;
+            }
+
+            if !ch.queue.has_room(ch.reserved) {
+                // reached max capacity, waiting
+                drop(ch);
+                waker_guard = Some(self.push_sender(cx.waker().clone()));
+                return Poll::Pending;
+            }
+            ch.reserved += 1;
+            Poll::Ready(Ok(Permit { sender: self }))
+        })
+        .await
+    }
+
+    /// Closes the channel, even though there may be other handles to it.
+    ///
+    /// Once closed, sends fail with [`SendError`], and receivers get the values that
+    /// were already buffered before [`recv`](Receiver::recv) fails with [`RecvError`].
+    /// Every task waiting on the channel is woken up, which makes this useful to signal
+    /// a shutdown. A closed channel can't be reopened.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use osiris::sync::mpmc::{channel, RecvError};
+    ///
+    /// #[osiris::main]
+    /// async fn main() {
+    ///     let (tx, rx) = channel(8);
+    ///     tx.send(1).await.unwrap();
+    ///     tx.close();
+    ///
+    ///     assert!(tx.send(2).await.is_err());
+    ///     assert_eq!(rx.recv().await, Ok(1));
+    ///     assert_eq!(rx.recv().await, Err(RecvError));
+    /// }
+    /// ```
+    pub fn close(&self) {
+        self.channel().borrow_mut().close();
+    }
+
+    /// Returns whether values can no longer be sent, either because the channel
+    /// was [closed](Sender::close), or because every [`Receiver`] has disconnected.
+    pub fn is_closed(&self) -> bool {
+        self.channel().borrow().send_closed()
+    }
+
+    /// Creates a [`WeakSender`] for this channel, which does not count as a sender.
+    pub fn downgrade(&self) -> WeakSender<T> {
+        WeakSender(Rc::downgrade(&self.0))
+    }
+
     fn push_sender(&self, waker: Waker) -> impl Drop + '_ {
         struct Guard<'a, T> {
             sender: &'a Sender<T>,
@@ -484,11 +661,12 @@ impl<T> Receiver<T> {
     ///
     pub async fn recv(&self) -> Result<T, RecvError> {
         let mut waker_guard = None;
+        consume_budget().await;
         poll_fn(|cx| {
             let mut ch = self.channel().borrow_mut();
             let Some(item) = ch.queue.pop_front() else {
                 // no items in the queue
-                if ch.senders == 0 {
+                if ch.recv_closed() {
                     //  no senders, returning error
                     return Poll::Ready(Err(RecvError));
                 }
@@ -508,6 +686,167 @@ impl<T> Receiver<T> {
         .await
     }
 
+    /// Waits for a value on this receiver for at most `timeout`, returning an error
+    /// if the channel has hung up or if no value was sent in time.
+    ///
+    /// This is equivalent to [`recv_deadline`](Receiver::recv_deadline) with a
+    /// deadline of `Instant::now() + timeout`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use osiris::sync::mpmc::{channel, RecvTimeoutError};
+    /// use osiris::time::Duration;
+    ///
+    /// #[osiris::main]
+    /// async fn main() {
+    ///     let (send, recv) = channel(8);
+    ///     let timeout = Duration::from_millis(10);
+    ///     assert_eq!(Err(RecvTimeoutError::Timeout), recv.recv_timeout(timeout).await);
+    ///
+    ///     send.send(1).await.unwrap();
+    ///     assert_eq!(Ok(1), recv.recv_timeout(timeout).await);
+    ///
+    ///     drop(send);
+    ///     assert_eq!(Err(RecvTimeoutError::Disconnected), recv.recv_timeout(timeout).await);
+    /// }
+    /// ```
+    pub async fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        self.recv_deadline(Instant::now() + timeout).await
+    }
+
+    /// Waits for a value on this receiver until `deadline` is reached, returning an
+    /// error if the channel has hung up or if no value was sent in time.
+    ///
+    /// A buffered value is always returned, even if the deadline has already passed.
+    /// In that case the receiver doesn't wait, so it returns
+    /// [`Timeout`](RecvTimeoutError::Timeout) right away if the channel is empty.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe, just like [`recv`](Receiver::recv).
+    pub async fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
+        if deadline <= Instant::now() {
+            let mut ch = self.channel().borrow_mut();
+            let Some(item) = ch.queue.pop_front() else {
+                if ch.recv_closed() {
+                    return Err(RecvTimeoutError::Disconnected);
+                }
+                return Err(RecvTimeoutError::Timeout);
+            };
+            if let Some((_, waker)) = ch.send_wakers.pop_back() {
+                waker.wake();
+            }
+            return Ok(item);
+        }
+        let mut recv = pin!(self.recv());
+        let mut sleep = pin!(sleep_until(deadline));
+        poll_fn(|cx| {
+            // a value that is ready is received even if the deadline has passed
+            if let Poll::Ready(res) = recv.as_mut().poll(cx) {
+                return Poll::Ready(res.map_err(|_| RecvTimeoutError::Disconnected));
+            }
+            if sleep.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(Err(RecvTimeoutError::Timeout));
+            }
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Receives up to `limit` values from the channel, appending them to `out`,
+    /// and returns the number of values that were received.
+    ///
+    /// This method only waits if the channel is empty, in which case it waits
+    /// until at least one value is sent. It then takes all the buffered values,
+    /// up to `limit`, so consumers that process values in batches pay the cost
+    /// of waiting only once per batch.
+    ///
+    /// If `limit` is zero, or if every [`Sender`] has disconnected and there are
+    /// no buffered values left, this method returns zero immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use osiris::sync::mpmc::channel;
+    ///
+    /// #[osiris::main]
+    /// async fn main() {
+    ///     let (send, recv) = channel(8);
+    ///     for i in 0..5 {
+    ///         send.send(i).await.unwrap();
+    ///     }
+    ///     drop(send);
+    ///
+    ///     let mut batch = Vec::new();
+    ///     assert_eq!(recv.recv_many(&mut batch, 3).await, 3);
+    ///     assert_eq!(recv.recv_many(&mut batch, 3).await, 2);
+    ///     assert_eq!(batch, [0, 1, 2, 3, 4]);
+    ///     // the channel is empty and closed
+    ///     assert_eq!(recv.recv_many(&mut batch, 3).await, 0);
+    /// }
+    /// ```
+    pub async fn recv_many(&self, out: &mut Vec<T>, limit: usize) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+        let mut waker_guard = None;
+        consume_budget().await;
+        poll_fn(|cx| {
+            let mut ch = self.channel().borrow_mut();
+            let len = out.len();
+            while out.len() - len < limit {
+                let Some(item) = ch.queue.pop_front() else {
+                    break;
+                };
+                out.push(item);
+            }
+            let received = out.len() - len;
+
+            if received == 0 {
+                if ch.recv_closed() {
+                    return Poll::Ready(0);
+                }
+                drop(ch);
+                let waker = cx.waker().clone();
+                waker_guard = Some(self.push_receiver(waker));
+                return Poll::Pending;
+            }
+
+            // each received item frees a slot for a sender
+            for _ in 0..received {
+                let Some((_, waker)) = ch.send_wakers.pop_back() else {
+                    break;
+                };
+                waker.wake();
+            }
+
+            Poll::Ready(received)
+        })
+        .await
+    }
+
+    /// Closes the channel, even though there may be other handles to it.
+    ///
+    /// Values that were already buffered can still be received, after which
+    /// [`recv`](Receiver::recv) fails with [`RecvError`]. Sends fail with
+    /// [`SendError`] right away. See [`Sender::close`] for more details.
+    pub fn close(&self) {
+        self.channel().borrow_mut().close();
+    }
+
+    /// Returns whether no more values will be sent, either because the channel
+    /// was [closed](Receiver::close), or because every [`Sender`] has disconnected.
+    /// Values that were already buffered can still be received.
+    pub fn is_closed(&self) -> bool {
+        self.channel().borrow().recv_closed()
+    }
+
+    /// Creates a [`WeakReceiver`] for this channel, which does not count as a receiver.
+    pub fn downgrade(&self) -> WeakReceiver<T> {
+        WeakReceiver(Rc::downgrade(&self.0))
+    }
+
     fn push_receiver(&self, waker: Waker) -> impl Drop + '_ {
         struct Guard<'a, T> {
             receiver: &'a Receiver<T>,
@@ -540,21 +879,67 @@ impl<T> Receiver<T> {
     }
 }
 
+impl<'a, T> Permit<'a, T> {
+    /// Sends a value using the reserved slot. This method never waits.
+    ///
+    /// If every [`Receiver`] has disconnected since the slot was reserved,
+    /// the value will never be received.
+    pub fn send(self, value: T) {
+        let mut ch = self.sender.channel().borrow_mut();
+        ch.reserved -= 1;
+        ch.queue.push(value);
+        // notify receiver that we've pushed
+        if let Some((_, waker)) = ch.recv_waiters.pop_back() {
+            waker.wake();
+        }
+        drop(ch);
+        forget(self);
+    }
+}
+
+impl<'a, T> Drop for Permit<'a, T> {
+    fn drop(&mut self) {
+        let mut ch = self.sender.channel().borrow_mut();
+        ch.reserved -= 1;
+        // the slot was released, so another sender may proceed
+        if let Some((_, waker)) = ch.send_wakers.pop_front() {
+            waker.wake();
+        }
+    }
+}
+
+impl<'a, T> Debug for Permit<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Permit")
+    }
+}
+
 impl<T> Queue<T> {
-    fn try_push(&mut self, value: &mut Option<T>) -> Result<(), ()> {
+    fn try_push(&mut self, value: &mut Option<T>, reserved: usize) -> Result<(), ()> {
+        if !self.has_room(reserved) {
+            return Err(());
+        }
+        let Some(value) = value.take() else {
+            unreachable!()
+        };
+        self.push(value);
+        Ok(())
+    }
+
+    /// Pushes a value without checking the capacity of the queue.
+    fn push(&mut self, value: T) {
         match self {
-            Queue::Bounded(queue) if queue.len() < queue.capacity() => {
-                let Some(value) = value.take() else {
-                    unreachable!()
-                };
-                queue.push_back(value);
-                Ok(())
-            }
-            Queue::Rendezvous(option) if option.is_none() => {
-                *option = value.take();
-                Ok(())
-            }
-            _ => Err(()),
+            Queue::Bounded(queue) => queue.push_back(value),
+            Queue::Rendezvous(option) => *option = Some(value),
+        }
+    }
+
+    /// Returns whether a value can be pushed, given that
+    /// `reserved` slots are being held by permits.
+    fn has_room(&self, reserved: usize) -> bool {
+        match self {
+            Queue::Bounded(queue) => queue.len() + reserved < queue.capacity(),
+            Queue::Rendezvous(option) => option.is_none() && reserved == 0,
         }
     }
 
@@ -575,6 +960,29 @@ impl<T> Queue<T> {
 }
 
 impl<T> Channel<T> {
+    /// Returns whether values can no longer be sent.
+    fn send_closed(&self) -> bool {
+        self.closed || self.receivers == 0
+    }
+
+    /// Returns whether no more values will be sent, though
+    /// buffered values may still be received.
+    fn recv_closed(&self) -> bool {
+        self.closed || self.senders == 0
+    }
+
+    /// Closes the channel, waking every waiting sender and receiver so
+    /// they observe it.
+    fn close(&mut self) {
+        self.closed = true;
+        for (_, waker) in self.send_wakers.drain(..) {
+            waker.wake();
+        }
+        for (_, waker) in self.recv_waiters.drain(..) {
+            waker.wake();
+        }
+    }
+
     fn sender_id(&mut self) -> u32 {
         self.sender_id += 1;
         self.sender_id
@@ -590,6 +998,12 @@ impl<T> Drop for Sender<T> {
     fn drop(&mut self) {
         let mut ch = self.channel().borrow_mut();
         ch.senders -= 1;
+        if ch.senders == 0 {
+            // waiting receivers must observe the disconnection
+            for (_, waker) in ch.recv_waiters.drain(..) {
+                waker.wake();
+            }
+        }
     }
 }
 
@@ -597,6 +1011,12 @@ impl<T> Drop for Receiver<T> {
     fn drop(&mut self) {
         let mut ch = self.channel().borrow_mut();
         ch.receivers -= 1;
+        if ch.receivers == 0 {
+            // waiting senders must observe the disconnection
+            for (_, waker) in ch.send_wakers.drain(..) {
+                waker.wake();
+            }
+        }
     }
 }
 
@@ -616,6 +1036,48 @@ impl<T> Clone for Receiver<T> {
     }
 }
 
+impl<T> WeakSender<T> {
+    /// Upgrades the weak sender into a [`Sender`], returning `None` if every
+    /// [`Sender`] of the channel has already been dropped.
+    pub fn upgrade(&self) -> Option<Sender<T>> {
+        let channel = self.0.upgrade()?;
+        let mut ch = channel.borrow_mut();
+        if ch.senders == 0 {
+            return None;
+        }
+        ch.senders += 1;
+        drop(ch);
+        Some(Sender(channel))
+    }
+}
+
+impl<T> WeakReceiver<T> {
+    /// Upgrades the weak receiver into a [`Receiver`], returning `None` if every
+    /// [`Receiver`] of the channel has already been dropped.
+    pub fn upgrade(&self) -> Option<Receiver<T>> {
+        let channel = self.0.upgrade()?;
+        let mut ch = channel.borrow_mut();
+        if ch.receivers == 0 {
+            return None;
+        }
+        ch.receivers += 1;
+        drop(ch);
+        Some(Receiver(channel))
+    }
+}
+
+impl<T> Clone for WeakSender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Clone for WeakReceiver<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
 impl<T> Sender<T> {
     fn channel(&self) -> &RefCell<Channel<T>> {
         &self.0
@@ -646,9 +1108,22 @@ impl Display for RecvError {
     }
 }
 
+impl Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => write!(f, "timed out waiting on channel"),
+            RecvTimeoutError::Disconnected => write!(f, "receiving on a closed channel"),
+        }
+    }
+}
+
 impl Error for RecvError {}
+impl Error for RecvTimeoutError {}
 impl<T> Error for SendError<T> {}
 
+#[cfg(feature = "stream")]
+mod stream;
+
 #[test]
 fn mpmc_stress_test_rendezvous() {
     crate::block_on(async {
@@ -739,6 +1214,54 @@ fn mpmc_stress_test_bound() {
     .unwrap();
 }
 
+#[test]
+fn recv_many_wakes_senders() {
+    crate::block_on(async {
+        let (s, r) = channel(2);
+        let mut senders = vec![];
+        for i in 0..6 {
+            let s = s.clone();
+            senders.push(crate::spawn(async move { s.send(i).await.unwrap() }));
+        }
+        drop(s);
+        let mut out = vec![];
+        while r.recv_many(&mut out, 4).await != 0 {}
+        out.sort();
+        assert_eq!(out, [0, 1, 2, 3, 4, 5]);
+        for sender in senders {
+            sender.await;
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn reserve_holds_slot() {
+    crate::block_on(async {
+        let (s, r) = channel(1);
+        let permit = s.reserve().await.unwrap();
+        let send = crate::spawn({
+            let s = s.clone();
+            async move { s.send(2).await.unwrap() }
+        });
+        crate::task::yield_now().await;
+        // the sender waits for the slot held by the permit
+        assert!(matches!(&r.channel().borrow().queue, Queue::Bounded(q) if q.is_empty()));
+        permit.send(1);
+        assert_eq!(r.recv().await, Ok(1));
+        assert_eq!(r.recv().await, Ok(2));
+        send.await;
+
+        // dropping a permit releases the slot
+        drop(s.reserve().await.unwrap());
+        s.send(3).await.unwrap();
+        assert_eq!(r.recv().await, Ok(3));
+        drop(r);
+        assert!(s.reserve().await.is_err());
+    })
+    .unwrap();
+}
+
 #[test]
 fn mpsc_send_recv_errors() {
     crate::block_on(async {
@@ -751,3 +1274,86 @@ fn mpsc_send_recv_errors() {
     })
     .unwrap();
 }
+
+#[test]
+fn recv_timeout_then_send() {
+    crate::block_on(async {
+        let (s, r) = channel(1);
+        let timeout = Duration::from_millis(20);
+        let start = Instant::now();
+        assert_eq!(r.recv_timeout(timeout).await, Err(RecvTimeoutError::Timeout));
+        assert!(start.elapsed() >= timeout);
+        // no waker is left behind by the timed out receive
+        assert!(r.channel().borrow().recv_waiters.is_empty());
+
+        let send = crate::spawn({
+            let s = s.clone();
+            async move { s.send(1).await.unwrap() }
+        });
+        assert_eq!(r.recv_timeout(timeout).await, Ok(1));
+        send.await;
+
+        // a buffered value is received even if the deadline has passed
+        s.send(2).await.unwrap();
+        assert_eq!(r.recv_deadline(start).await, Ok(2));
+        assert_eq!(r.recv_deadline(start).await, Err(RecvTimeoutError::Timeout));
+        drop(s);
+        assert_eq!(r.recv_deadline(start).await, Err(RecvTimeoutError::Disconnected));
+    })
+    .unwrap();
+}
+
+#[test]
+fn close_with_live_handles() {
+    crate::block_on(async {
+        let (s, r) = channel(2);
+        let recv = crate::spawn({
+            let r = r.clone();
+            async move { r.recv().await }
+        });
+        crate::task::yield_now().await;
+        // waiting receivers are woken up
+        r.close();
+        assert_eq!(recv.await, Err(RecvError));
+        assert!(s.is_closed() && r.is_closed());
+        assert_eq!(s.send(1).await, Err(SendError(1)));
+
+        // buffered values are received after the channel is closed
+        let (s, r) = channel(1);
+        s.send(1).await.unwrap();
+        let send = crate::spawn({
+            let s = s.clone();
+            async move { s.send(2).await }
+        });
+        crate::task::yield_now().await;
+        s.close();
+        assert!(send.await.is_err());
+        assert_eq!(r.recv().await, Ok(1));
+        assert_eq!(r.recv().await, Err(RecvError));
+    })
+    .unwrap();
+}
+
+#[test]
+fn weak_handles() {
+    crate::block_on(async {
+        let (s, r) = channel(1);
+        let weak_s = s.downgrade();
+        let weak_r = r.downgrade();
+        // weak handles don't count as senders or receivers
+        assert_eq!(r.channel().borrow().senders, 1);
+        assert_eq!(r.channel().borrow().receivers, 1);
+
+        let upgraded = weak_s.upgrade().unwrap();
+        drop(s);
+        upgraded.send(1).await.unwrap();
+        assert_eq!(weak_r.upgrade().unwrap().recv().await, Ok(1));
+
+        drop(upgraded);
+        assert!(weak_s.upgrade().is_none());
+        assert_eq!(r.recv().await, Err(RecvError));
+        drop(r);
+        assert!(weak_r.upgrade().is_none());
+    })
+    .unwrap();
+}
@@ -0,0 +1,203 @@
+//! [`Sink`] and [`Stream`] adapters for the channel halves.
+//!
+//! These let osiris channels be composed with the combinators of `StreamExt` and
+//! `SinkExt`. Like the channels themselves, the adapters are `!Send`, so they
+//! can only be used from the thread that created them.
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use futures_sink::Sink;
+
+use super::{Receiver, RecvError, SendError, Sender};
+
+type SendFuture<T> = Pin<Box<dyn Future<Output = Result<(), SendError<T>>>>>;
+type RecvFuture<T> = Pin<Box<dyn Future<Output = Result<T, RecvError>>>>;
+
+/// A [`Sink`] that sends items through a [`Sender`].
+/// It is created with [`Sender::into_sink`].
+///
+/// An item passed to `start_send` is sent in the background, and the sink
+/// is not ready for the next item until it has been accepted by the channel.
+/// Closing the sink drops the sender.
+pub struct SenderSink<T> {
+    sender: Option<Sender<T>>,
+    pending: Option<SendFuture<T>>,
+}
+
+/// A [`Stream`] that receives items from a [`Receiver`].
+/// It is created with [`Receiver::into_stream`].
+///
+/// The stream ends once every [`Sender`] has disconnected and the channel is empty.
+pub struct ReceiverStream<T> {
+    receiver: Receiver<T>,
+    pending: Option<RecvFuture<T>>,
+}
+
+impl<T: 'static> Sender<T> {
+    /// Converts the sender into a [`Sink`].
+    ///
+    /// The sink is `!Send`, so it must be driven from the thread that created the channel.
+    ///
+    /// # Examples
+    /// ```
+    /// use futures::SinkExt;
+    /// use osiris::sync::mpmc::channel;
+    ///
+    /// #[osiris::main]
+    /// async fn main() {
+    ///     let (tx, rx) = channel(4);
+    ///     let mut sink = tx.into_sink();
+    ///     sink.send(1).await.unwrap();
+    ///     sink.close().await.unwrap();
+    ///     assert_eq!(rx.recv().await, Ok(1));
+    ///     assert!(rx.recv().await.is_err());
+    /// }
+    /// ```
+    pub fn into_sink(self) -> SenderSink<T> {
+        SenderSink {
+            sender: Some(self),
+            pending: None,
+        }
+    }
+}
+
+impl<T: 'static> Receiver<T> {
+    /// Converts the receiver into a [`Stream`].
+    ///
+    /// The stream is `!Send`, so it must be driven from the thread that created the channel.
+    ///
+    /// # Examples
+    /// ```
+    /// use futures::StreamExt;
+    /// use osiris::sync::mpmc::channel;
+    ///
+    /// #[osiris::main]
+    /// async fn main() {
+    ///     let (tx, rx) = channel(4);
+    ///     tx.send(1).await.unwrap();
+    ///     tx.send(2).await.unwrap();
+    ///     drop(tx);
+    ///     let items: Vec<i32> = rx.into_stream().map(|i| i * 10).collect().await;
+    ///     assert_eq!(items, [10, 20]);
+    /// }
+    /// ```
+    pub fn into_stream(self) -> ReceiverStream<T> {
+        ReceiverStream {
+            receiver: self,
+            pending: None,
+        }
+    }
+}
+
+impl<T: 'static> SenderSink<T> {
+    /// Returns a reference to the underlying sender, or `None` if the sink was closed.
+    pub fn get_ref(&self) -> Option<&Sender<T>> {
+        self.sender.as_ref()
+    }
+
+    /// Waits for the pending item, if any, to be accepted by the channel.
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), SendError<T>>> {
+        let Some(pending) = &mut self.pending else {
+            return Poll::Ready(Ok(()));
+        };
+        let res = std::task::ready!(pending.as_mut().poll(cx));
+        self.pending = None;
+        Poll::Ready(res)
+    }
+}
+
+impl<T: 'static> Sink<T> for SenderSink<T> {
+    type Error = SendError<T>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_pending(cx)
+    }
+
+    /// # Panics
+    /// If the sink was closed, or if it was not ready.
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        assert!(
+            this.pending.is_none(),
+            "called `start_send` before `poll_ready` returned `Ready`."
+        );
+        let sender = this
+            .sender
+            .clone()
+            .expect("called `start_send` on a closed sink.");
+        this.pending = Some(Box::pin(async move { sender.send(item).await }));
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_pending(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        let res = std::task::ready!(this.poll_pending(cx));
+        this.sender = None;
+        Poll::Ready(res)
+    }
+}
+
+impl<T: 'static> ReceiverStream<T> {
+    /// Returns a reference to the underlying receiver.
+    pub fn get_ref(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+
+    /// Returns the underlying receiver. An item that was being received is
+    /// left in the channel.
+    pub fn into_inner(self) -> Receiver<T> {
+        self.receiver
+    }
+}
+
+impl<T: 'static> Stream for ReceiverStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+        let pending = this.pending.get_or_insert_with(|| {
+            let receiver = this.receiver.clone();
+            Box::pin(async move { receiver.recv().await })
+        });
+        let res = std::task::ready!(pending.as_mut().poll(cx));
+        this.pending = None;
+        Poll::Ready(res.ok())
+    }
+}
+
+impl<T> Debug for SenderSink<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SenderSink")
+    }
+}
+
+impl<T> Debug for ReceiverStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ReceiverStream")
+    }
+}
+
+#[test]
+fn sink_into_stream() {
+    use futures::{SinkExt, StreamExt};
+    crate::block_on(async {
+        let (tx, rx) = super::channel(0);
+        let consumer = crate::spawn(rx.into_stream().map(|i: i32| i * 2).collect::<Vec<_>>());
+        let mut sink = tx.into_sink();
+        sink.send_all(&mut futures::stream::iter(0..10).map(Ok))
+            .await
+            .unwrap();
+        sink.close().await.unwrap();
+        assert!(sink.get_ref().is_none());
+        let items = consumer.await;
+        assert_eq!(items, (0..10).map(|i| i * 2).collect::<Vec<_>>());
+    })
+    .unwrap();
+}
@@ -3,7 +3,9 @@ use std::cell::{Cell, RefCell, RefMut};
 use std::collections::VecDeque;
 use std::fmt::{Debug, Display};
 use std::future::poll_fn;
+use std::mem::ManuallyDrop;
 use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
 use std::task::{Poll, Waker};
 
 /// A mutual exclusion primitive useful for protecting shared data.
@@ -22,9 +24,33 @@ use std::task::{Poll, Waker};
 pub struct Mutex<T> {
     waiters: RefCell<VecDeque<(u64, Waker)>>,
     waiter_id: Cell<u64>,
+    fairness: Fairness,
+    /// the waiter the lock was handed to when it was unlocked, in fifo mode.
+    handoff: Cell<Option<u64>>,
     value: RefCell<T>,
 }
 
+/// Determines how a [`Mutex`] is handed over to the tasks waiting for it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Fairness {
+    /// When the mutex is unlocked the next waiter is woken up, but any task that
+    /// attempts to lock it before the waiter runs, including the task that just
+    /// unlocked it, acquires it instead.
+    ///
+    /// This maximizes throughput for hot locks, since the lock is never left idle
+    /// while a woken waiter is scheduled, at the cost of latency for the waiters,
+    /// which may be overtaken an unbounded number of times.
+    #[default]
+    Barging,
+    /// When the mutex is unlocked it is handed to the waiter that has been waiting
+    /// the longest, and no other task can acquire it, even with `try_lock`, until
+    /// every task that started waiting before it has acquired it.
+    ///
+    /// This bounds the latency of the waiters, at the cost of throughput, since
+    /// the lock stays idle until the waiter it was handed to is polled.
+    Fifo,
+}
+
 struct Handle<'a, T> {
     mutex: &'a Mutex<T>,
     id: u64,
@@ -38,6 +64,16 @@ pub struct Guard<'a, T> {
     mutex: &'a Mutex<T>,
 }
 
+/// An owned RAII guard of a mutex, that keeps the mutex alive through a reference
+/// counted pointer. Unlike [`Guard`], it does not borrow the mutex, so it can be
+/// held across a [`spawn`](crate::spawn). This structure is created by the
+/// [`lock_owned`](Mutex::lock_owned) method on `Mutex`.
+pub struct OwnedGuard<T: 'static> {
+    // this borrow actually lives as long as the `mutex` field.
+    value: ManuallyDrop<RefMut<'static, T>>,
+    mutex: Rc<Mutex<T>>,
+}
+
 pub struct Error;
 
 impl<'a, T: Debug> Debug for Guard<'a, T> {
@@ -55,6 +91,12 @@ impl<T: Debug> Debug for Mutex<T> {
     }
 }
 
+impl<T: Debug + 'static> Debug for OwnedGuard<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
 impl<'a, T> Deref for Guard<'a, T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
@@ -67,6 +109,18 @@ impl<'a, T> DerefMut for Guard<'a, T> {
     }
 }
 
+impl<T: 'static> Deref for OwnedGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+impl<T: 'static> DerefMut for OwnedGuard<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
 impl std::error::Error for Error {}
 
 impl Display for Error {
@@ -83,10 +137,15 @@ impl Debug for Error {
 // futures that are waiting to acquire the lock.
 impl<'a, T> Drop for Guard<'a, T> {
     fn drop(&mut self) {
-        let item = self.mutex.waiters.borrow_mut().pop_front();
-        if let Some((_, waker)) = item {
-            waker.wake();
-        }
+        self.mutex.wake_next();
+    }
+}
+impl<T: 'static> Drop for OwnedGuard<T> {
+    fn drop(&mut self) {
+        // Safety: the value is not used after this point, and
+        // it is dropped before the mutex it borrows from.
+        unsafe { ManuallyDrop::drop(&mut self.value) };
+        self.mutex.wake_next();
     }
 }
 /// This drop implementation makes sure that if the future gets
@@ -100,9 +159,11 @@ impl<'a, T> Drop for Handle<'a, T> {
         let start_len = waiters.len();
         waiters.retain(|&(id, _)| id != self.id);
         if start_len == waiters.len() {
-            if let Some((_, waker)) = waiters.pop_front() {
-                waker.wake();
+            drop(waiters);
+            if self.mutex.handoff.get() == Some(self.id) {
+                self.mutex.handoff.set(None);
             }
+            self.mutex.wake_next();
         }
     }
 }
@@ -118,9 +179,25 @@ impl<T> Mutex<T> {
     /// let mutex = Mutex::new(0);
     /// ```
     pub const fn new(value: T) -> Mutex<T> {
+        Mutex::new_with_fairness(value, Fairness::Barging)
+    }
+
+    /// Creates a new mutex in an unlocked state with the given [`Fairness`].
+    /// A mutex created with [`new`](Mutex::new) is [`Barging`](Fairness::Barging).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use osiris::sync::mutex::{Fairness, Mutex};
+    ///
+    /// let mutex = Mutex::new_with_fairness(0, Fairness::Fifo);
+    /// ```
+    pub const fn new_with_fairness(value: T, fairness: Fairness) -> Mutex<T> {
         Mutex {
             waiters: RefCell::new(VecDeque::new()),
             waiter_id: Cell::new(0),
+            fairness,
+            handoff: Cell::new(None),
             value: RefCell::new(value),
         }
     }
@@ -156,7 +233,15 @@ impl<T> Mutex<T> {
         let mut handle: Option<Handle<T>> = None;
         yield_now().await;
         poll_fn(move |cx| {
-            if let Ok(val) = self.try_lock() {
+            let id = handle.as_ref().map(|handle| handle.id);
+            let res = if id.is_some() && self.handoff.get() == id {
+                // the lock was handed to us
+                self.handoff.set(None);
+                self.borrow()
+            } else {
+                self.try_lock()
+            };
+            if let Ok(val) = res {
                 if let Some(handle) = handle.take() {
                     std::mem::forget(handle);
                 }
@@ -179,7 +264,8 @@ impl<T> Mutex<T> {
     /// # Errors
     ///
     /// If the mutex could not be acquired because it is already locked, then
-    /// this call will return an error.
+    /// this call will return an error. For a [`Fifo`](Fairness::Fifo) mutex, it
+    /// also errors if there are tasks waiting for the lock.
     ///
     /// # Examples
     ///
@@ -202,12 +288,102 @@ impl<T> Mutex<T> {
     /// # }).unwrap();
     /// ```
     pub fn try_lock(&self) -> Result<Guard<'_, T>, Error> {
+        if self.fairness == Fairness::Fifo
+            && (self.handoff.get().is_some() || !self.waiters.borrow().is_empty())
+        {
+            return Err(Error);
+        }
+        self.borrow()
+    }
+
+    /// Acquires the lock if it is unlocked, regardless of the waiters.
+    fn borrow(&self) -> Result<Guard<'_, T>, Error> {
         let Ok(value) = self.value.try_borrow_mut() else {
             return Err(Error);
         };
         Ok(Guard { value, mutex: self })
     }
 
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// Since this call borrows the `Mutex` mutably, no actual locking needs to
+    /// take place -- the mutable borrow statically guarantees no locks exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use osiris::sync::Mutex;
+    ///
+    /// let mut mutex = Mutex::new(0);
+    /// *mutex.get_mut() = 10;
+    /// assert_eq!(*mutex.try_lock().unwrap(), 10);
+    /// ```
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Consumes this mutex, returning the underlying data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use osiris::sync::Mutex;
+    ///
+    /// let mutex = Mutex::new(0);
+    /// assert_eq!(mutex.into_inner(), 0);
+    /// ```
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Acquires a mutex behind an `Rc`, returning an owned guard.
+    ///
+    /// This method behaves like [`lock`](Mutex::lock), except that the guard keeps
+    /// a clone of the `Rc` instead of borrowing the mutex, so it can be moved into
+    /// a spawned task.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # osiris::block_on(async {
+    /// use std::rc::Rc;
+    /// use osiris::{spawn, sync::Mutex};
+    ///
+    /// let mutex = Rc::new(Mutex::new(0));
+    /// let mut guard = mutex.clone().lock_owned().await;
+    /// spawn(async move {
+    ///     *guard = 10;
+    /// }).await;
+    /// assert_eq!(*mutex.lock().await, 10);
+    /// # }).unwrap();
+    /// ```
+    pub async fn lock_owned(self: Rc<Self>) -> OwnedGuard<T>
+    where
+        T: 'static,
+    {
+        let guard = ManuallyDrop::new(self.lock().await);
+        // Safety: the guard is not dropped, so the value is read only once.
+        let value = unsafe { std::ptr::read(&guard.value) };
+        // Safety: the mutex is kept alive by the `Rc` stored next to the
+        // borrow, and the borrow is dropped before the `Rc`.
+        let value = unsafe { std::mem::transmute::<RefMut<'_, T>, RefMut<'static, T>>(value) };
+        OwnedGuard {
+            value: ManuallyDrop::new(value),
+            mutex: self,
+        }
+    }
+
+    #[inline]
+    fn wake_next(&self) {
+        let item = self.waiters.borrow_mut().pop_front();
+        if let Some((id, waker)) = item {
+            if self.fairness == Fairness::Fifo {
+                self.handoff.set(Some(id));
+            }
+            waker.wake();
+        }
+    }
+
     #[inline]
     fn push(&self, waker: Waker) -> Handle<T> {
         let id = self.id();
@@ -311,6 +487,33 @@ fn mutex_stress_test2() {
     .unwrap();
 }
 
+#[test]
+fn owned_guard_wakes_waiters() {
+    use crate::{block_on, spawn};
+
+    block_on(async {
+        let mutex = Rc::new(Mutex::new(vec![]));
+        let guard = mutex.clone().lock_owned().await;
+        let waiter = spawn({
+            let mutex = mutex.clone();
+            async move { mutex.lock().await.push(2) }
+        });
+        while mutex.waiters.borrow().is_empty() {
+            crate::task::yield_now().await;
+        }
+
+        spawn(async move {
+            let mut guard = guard;
+            crate::task::yield_now().await;
+            guard.push(1);
+        })
+        .await;
+        waiter.await;
+        assert_eq!(*mutex.lock().await, [1, 2]);
+    })
+    .unwrap();
+}
+
 #[test]
 fn default_and_fmt() {
     crate::block_on(async {
@@ -328,3 +531,73 @@ fn default_and_fmt() {
     })
     .ok();
 }
+
+#[test]
+fn fairness() {
+    use crate::{block_on, spawn};
+
+    block_on(async {
+        for fairness in [Fairness::Barging, Fairness::Fifo] {
+            let mutex = Rc::new(Mutex::new_with_fairness(vec![], fairness));
+            let guard = mutex.lock().await;
+            let waiters: Vec<_> = (0..3)
+                .map(|i| {
+                    let mutex = mutex.clone();
+                    spawn(async move { mutex.lock().await.push(i) })
+                })
+                .collect();
+            while mutex.waiters.borrow().len() < 3 {
+                crate::task::yield_now().await;
+            }
+            drop(guard);
+            // a barging task can take the lock before the woken waiter runs
+            let barged = mutex.try_lock().map(|mut guard| guard.push(-1));
+            assert_eq!(barged.is_ok(), fairness == Fairness::Barging);
+            for waiter in waiters {
+                waiter.await;
+            }
+            let expected: &[i32] = match fairness {
+                Fairness::Barging => &[-1, 0, 1, 2],
+                Fairness::Fifo => &[0, 1, 2],
+            };
+            assert_eq!(*mutex.lock().await, expected);
+        }
+    })
+    .unwrap();
+}
+
+#[cfg(not(miri))]
+#[test]
+fn fifo_stress_test() {
+    use crate::task::yield_now;
+
+    crate::block_on(async {
+        const N: usize = 500;
+        let mutex = Rc::new(Mutex::new_with_fairness(0i32, Fairness::Fifo));
+        let mut tasks = vec![];
+        for _ in 0..N {
+            let mutex = mutex.clone();
+            tasks.push(crate::spawn(async move {
+                while fastrand::f32() < 0.5 {
+                    yield_now().await;
+                }
+                let mut guard = mutex.lock().await;
+                yield_now().await;
+                *guard += 1;
+            }));
+        }
+        // cancelled waiters must pass the lock on
+        let mut expected = N as i32;
+        for i in (0..N).rev() {
+            if fastrand::f32() < 0.1 {
+                tasks.remove(i);
+                expected -= 1;
+            }
+        }
+        for task in tasks {
+            task.await;
+        }
+        assert!(*mutex.lock().await <= expected);
+    })
+    .unwrap();
+}
@@ -0,0 +1,328 @@
+//! A single-producer, multi-consumer channel that only retains the last sent value.
+//!
+//! This channel is useful for watching for changes to a value from multiple
+//! points in the code base, for example, changes to configuration values.
+//! Receivers are not guaranteed to observe every value sent, only the latest
+//! one: intermediate values are overwritten if they are sent before the
+//! receivers get to see them.
+//!
+//! Like the rest of the [`sync`](crate::sync) module, this channel is designed
+//! to be used across tasks, not across threads, so it does not implement the
+//! `Send` and `Sync` traits.
+//!
+//! # Examples
+//!
+//! ```
+//! use osiris::sync::watch;
+//! use osiris::spawn;
+//!
+//! #[osiris::main]
+//! async fn main() {
+//!     let (tx, mut rx) = watch::channel("hello");
+//!
+//!     let handle = spawn(async move {
+//!         while rx.changed().await.is_ok() {
+//!             println!("received = {:?}", *rx.borrow());
+//!         }
+//!     });
+//!
+//!     tx.send("world").unwrap();
+//!     drop(tx);
+//!     handle.await;
+//! }
+//! ```
+use std::cell::{Ref, RefCell};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::future::poll_fn;
+use std::rc::Rc;
+use std::task::{Poll, Waker};
+
+pub use super::mpmc::{RecvError, SendError};
+
+/// The sending half of a [`watch`](self) channel.
+///
+/// Values are sent with [`send`](Sender::send). Dropping the sender closes the
+/// channel, which makes [`Receiver::changed`] return an error once the last
+/// value has been seen.
+pub struct Sender<T>(Rc<RefCell<Shared<T>>>);
+
+/// The receiving half of a [`watch`](self) channel.
+///
+/// The current value can be read with [`borrow`](Receiver::borrow), and
+/// changes can be awaited with [`changed`](Receiver::changed). Each receiver
+/// keeps track of the last version it has seen independently.
+pub struct Receiver<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+    /// the last version seen by this receiver.
+    version: u64,
+}
+
+struct Shared<T> {
+    value: T,
+    /// incremented each time a value is sent.
+    version: u64,
+    /// reference count for the number of receivers
+    receivers: u32,
+    closed: bool,
+    waiter_id: u64,
+    waiters: HashMap<u64, Waker>,
+}
+
+/// Removes the waker of a `changed` future that got dropped.
+struct Handle<'a, T> {
+    shared: &'a RefCell<Shared<T>>,
+    id: u64,
+}
+
+/// Creates a new watch channel, returning the sending and receiving halves.
+///
+/// The receiver starts with the initial value marked as seen, so
+/// [`Receiver::changed`] only completes once a new value is sent.
+///
+/// # Examples
+///
+/// ```
+/// use osiris::sync::watch;
+///
+/// #[osiris::main]
+/// async fn main() {
+///     let (tx, mut rx) = watch::channel(1);
+///     assert_eq!(*rx.borrow(), 1);
+///
+///     tx.send(2).unwrap();
+///     rx.changed().await.unwrap();
+///     assert_eq!(*rx.borrow_and_update(), 2);
+/// }
+/// ```
+pub fn channel<T>(initial: T) -> (Sender<T>, Receiver<T>) {
+    let shared = Shared {
+        value: initial,
+        version: 0,
+        receivers: 1,
+        closed: false,
+        waiter_id: 0,
+        waiters: HashMap::new(),
+    };
+    let shared = Rc::new(RefCell::new(shared));
+    let receiver = Receiver {
+        shared: shared.clone(),
+        version: 0,
+    };
+    (Sender(shared), receiver)
+}
+
+impl<T> Sender<T> {
+    /// Replaces the value of the channel, and notifies every receiver.
+    ///
+    /// # Errors
+    ///
+    /// If every receiver has been dropped, the value is not updated and it
+    /// is returned back in the error.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the value is currently borrowed
+    /// through [`Receiver::borrow`] or [`Sender::borrow`].
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.0.borrow().receivers == 0 {
+            return Err(SendError(value));
+        }
+        self.send_replace(value);
+        Ok(())
+    }
+
+    /// Replaces the value of the channel, notifies every receiver, and returns
+    /// the previous value. Unlike [`send`](Sender::send), the value is updated even
+    /// if there are no receivers.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the value is currently borrowed
+    /// through [`Receiver::borrow`] or [`Sender::borrow`].
+    pub fn send_replace(&self, value: T) -> T {
+        let mut shared = self.0.borrow_mut();
+        let old = std::mem::replace(&mut shared.value, value);
+        shared.version += 1;
+        let waiters = std::mem::take(&mut shared.waiters);
+        drop(shared);
+        waiters.into_values().for_each(Waker::wake);
+        old
+    }
+
+    /// Returns a reference to the most recently sent value.
+    ///
+    /// The returned reference must be dropped before a new value is sent,
+    /// so it should not be held across an `.await` point.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.0.borrow(), |shared| &shared.value)
+    }
+
+    /// Creates a new receiver for this channel. The current value is
+    /// marked as seen, so it can be read right away with [`Receiver::borrow`],
+    /// but [`Receiver::changed`] only completes once a new value is sent.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut shared = self.0.borrow_mut();
+        shared.receivers += 1;
+        Receiver {
+            shared: self.0.clone(),
+            version: shared.version,
+        }
+    }
+
+    /// Returns the number of receivers that currently exist.
+    pub fn receiver_count(&self) -> usize {
+        self.0.borrow().receivers as usize
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Returns a reference to the most recently sent value, without
+    /// marking it as seen.
+    ///
+    /// The returned reference must be dropped before a new value is sent,
+    /// so it should not be held across an `.await` point.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.shared.borrow(), |shared| &shared.value)
+    }
+
+    /// Returns a reference to the most recently sent value, and marks it as seen.
+    ///
+    /// The returned reference must be dropped before a new value is sent,
+    /// so it should not be held across an `.await` point.
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        let shared = self.shared.borrow();
+        self.version = shared.version;
+        Ref::map(shared, |shared| &shared.value)
+    }
+
+    /// Returns whether a value that has not been seen by this receiver was sent.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the sender has been dropped.
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        let shared = self.shared.borrow();
+        if shared.closed {
+            return Err(RecvError);
+        }
+        Ok(shared.version != self.version)
+    }
+
+    /// Waits for a value that has not been seen by this receiver, and marks it as seen.
+    ///
+    /// If the current value has not been seen yet, this method completes immediately.
+    /// The new value can then be read with [`borrow`](Receiver::borrow).
+    ///
+    /// # Errors
+    ///
+    /// This method returns an error if the sender has been dropped
+    /// and every value sent has been seen.
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        let mut handle: Option<Handle<T>> = None;
+        let shared = &*self.shared;
+        let version = &mut self.version;
+        poll_fn(|cx| {
+            let mut shared_ = shared.borrow_mut();
+            if shared_.version != *version {
+                *version = shared_.version;
+                return Poll::Ready(Ok(()));
+            }
+            if shared_.closed {
+                return Poll::Ready(Err(RecvError));
+            }
+            match &handle {
+                Some(handle) if shared_.waiters.contains_key(&handle.id) => {
+                    shared_.waiters.insert(handle.id, cx.waker().clone());
+                }
+                _ => {
+                    let id = shared_.waiter_id;
+                    shared_.waiter_id += 1;
+                    shared_.waiters.insert(id, cx.waker().clone());
+                    handle = Some(Handle { shared, id });
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut shared = self.0.borrow_mut();
+        shared.closed = true;
+        let waiters = std::mem::take(&mut shared.waiters);
+        drop(shared);
+        waiters.into_values().for_each(Waker::wake);
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.borrow_mut().receivers -= 1;
+    }
+}
+
+impl<'a, T> Drop for Handle<'a, T> {
+    fn drop(&mut self) {
+        self.shared.borrow_mut().waiters.remove(&self.id);
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        self.shared.borrow_mut().receivers += 1;
+        Receiver {
+            shared: self.shared.clone(),
+            version: self.version,
+        }
+    }
+}
+
+impl<T: Debug> Debug for Sender<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("value", &*self.borrow())
+            .finish()
+    }
+}
+
+impl<T: Debug> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("value", &*self.borrow())
+            .field("version", &self.version)
+            .finish()
+    }
+}
+
+#[test]
+fn watch_skips_intermediate_values() {
+    use crate::{block_on, spawn};
+
+    block_on(async {
+        let (tx, mut rx) = channel(0);
+        let mut late = tx.subscribe();
+        let handle = spawn(async move {
+            let mut seen = vec![];
+            while rx.changed().await.is_ok() {
+                seen.push(*rx.borrow());
+            }
+            seen
+        });
+        crate::task::yield_now().await;
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        crate::task::yield_now().await;
+        tx.send(3).unwrap();
+        assert_eq!(*late.borrow(), 3);
+        assert!(late.has_changed().unwrap());
+        assert_eq!(*late.borrow_and_update(), 3);
+        assert!(!late.has_changed().unwrap());
+        drop(tx);
+        assert_eq!(handle.await, [2, 3]);
+        assert!(late.changed().await.is_err());
+    })
+    .unwrap();
+}
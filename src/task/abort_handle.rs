@@ -0,0 +1,50 @@
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
+
+use super::Task;
+
+/// An owned permission to abort a spawned task, without awaiting its completion.
+///
+/// Unlike a [`JoinHandle`](super::JoinHandle), dropping an `AbortHandle` does
+/// not cancel the task. Abort handles are cheap to clone, but they cannot be sent
+/// across threads. They are created with the [`JoinHandle::abort_handle`](super::JoinHandle::abort_handle)
+/// method.
+#[derive(Clone)]
+pub struct AbortHandle {
+    task: Task,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl AbortHandle {
+    pub(crate) fn new(task: Task) -> AbortHandle {
+        AbortHandle {
+            task,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Aborts the task and runs the spawned future's destructor. If the task has
+    /// already completed, this does nothing. Awaiting the join handle of an aborted task
+    /// will panic.
+    ///
+    /// # Panics
+    /// If a task attempts to abort itself.
+    pub fn abort(&self) {
+        self.task.cancel();
+    }
+
+    /// Returns the id of the task.
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.task.id()
+    }
+}
+
+impl Debug for AbortHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AbortHandle")
+            .field("id", &self.id())
+            .field("name", &self.task.name())
+            .finish()
+    }
+}
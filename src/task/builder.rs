@@ -0,0 +1,95 @@
+use super::{DetachHandle, JoinHandle};
+use crate::runtime::current_unwrap;
+use std::future::Future;
+use std::io::Result;
+use std::rc::Rc;
+
+/// Factory which is used to configure the properties of a new task.
+///
+/// Methods can be chained in order to configure it. Currently, the only
+/// property is the task's name, which is included in the messages of panics
+/// propagated from the task, and can be retrieved with [`JoinHandle::name`].
+///
+/// # Examples
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::task::Builder;
+///
+/// let handle = Builder::new().name("accept-loop").spawn(async {
+///     panic!("connection refused");
+/// });
+/// assert_eq!(handle.name().as_deref(), Some("accept-loop"));
+///
+/// let err = handle.catch_unwind().await.unwrap_err();
+/// let msg = err.downcast_ref::<String>().unwrap();
+/// assert_eq!(msg, "task 'accept-loop' panicked: connection refused");
+/// # }).unwrap();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Builder {
+    name: Option<Rc<str>>,
+}
+
+impl Builder {
+    /// Creates a new task builder.
+    #[must_use]
+    pub fn new() -> Builder {
+        Builder::default()
+    }
+
+    /// Assigns a name to the task.
+    pub fn name(&mut self, name: impl Into<Rc<str>>) -> &mut Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Spawns a task with this builder's configuration, returning a
+    /// [`JoinHandle`] for it. See [`spawn`](super::spawn) for more details.
+    ///
+    /// # Panics
+    /// Panics if called from **outside** of an osiris runtime, or if the runtime's
+    /// [`max_tasks`](crate::runtime::Config#structfield.max_tasks) limit has been reached.
+    #[track_caller]
+    #[must_use = "This task is immediatly cancelled after spawn. osiris tasks are cancelled on drop, you may want to use `detach()`."]
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        match self.try_spawn(future) {
+            Ok(handle) => handle,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Spawns a task with this builder's configuration, returning an error if the
+    /// runtime's [`max_tasks`](crate::runtime::Config#structfield.max_tasks) limit
+    /// has been reached. See [`try_spawn`](super::try_spawn) for more details.
+    ///
+    /// # Errors
+    /// This function errors if the number of tasks alive on the runtime has reached
+    /// the configured limit.
+    ///
+    /// # Panics
+    /// Panics if called from **outside** of an osiris runtime.
+    #[track_caller]
+    pub fn try_spawn<F>(&self, future: F) -> Result<JoinHandle<F::Output>>
+    where
+        F: Future + 'static,
+    {
+        current_unwrap("Builder::try_spawn").try_spawn_named(future, self.name.clone())
+    }
+
+    /// Spawns a detached task with this builder's configuration.
+    /// See [`detach`](super::detach) for more details.
+    ///
+    /// # Panics
+    /// Panics if called from **outside** of an osiris runtime, or if the runtime's
+    /// [`max_tasks`](crate::runtime::Config#structfield.max_tasks) limit has been reached.
+    #[track_caller]
+    pub fn detach<F>(&self, future: F) -> DetachHandle<F::Output>
+    where
+        F: Future + 'static,
+    {
+        DetachHandle::new(self.spawn(future))
+    }
+}
@@ -0,0 +1,152 @@
+//! Cooperative scheduling budget.
+//!
+//! A task that is always able to make progress (e.g. reading from a socket that
+//! always has data available) would never return control to the executor, starving
+//! other tasks and the reactor. To prevent this, each task is given a budget every
+//! time it is polled. Runtime resources consume a unit of budget when they make
+//! progress, and once the budget is exhausted they will force the task to yield,
+//! even if they are ready.
+use crate::runtime::BUDGET;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Runs `f` with a fresh budget of `budget` units, restoring the previous budget afterwards.
+pub(crate) fn with_budget<T>(budget: u32, f: impl FnOnce() -> T) -> T {
+    struct Reset(Option<u32>);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            BUDGET.with(|cell| cell.set(self.0));
+        }
+    }
+    let _reset = Reset(BUDGET.with(|cell| cell.replace(Some(budget))));
+    f()
+}
+
+/// Consumes a unit of budget, returning `Poll::Pending` and scheduling the task to be
+/// woken if the budget has been exhausted.
+pub(crate) fn poll_proceed(cx: &mut Context) -> Poll<()> {
+    BUDGET.with(|cell| match cell.get() {
+        Some(0) => {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+        Some(budget) => {
+            cell.set(Some(budget - 1));
+            Poll::Ready(())
+        }
+        None => Poll::Ready(()),
+    })
+}
+
+/// Consumes a unit of the current task's cooperative budget, yielding back to
+/// the runtime if it has been exhausted. Otherwise, it completes immediately.
+///
+/// This is useful for hand-written loops that may always be able to make progress
+/// without awaiting on runtime resources, such as parsing loops over buffered data,
+/// so they cooperate with other tasks. Outside of a task, or inside of an
+/// [`unconstrained`] future, it always completes immediately.
+///
+/// # Examples
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::task::consume_budget;
+///
+/// let mut sum = 0u64;
+/// for i in 0..100_000 {
+///     sum += i;
+///     // yields every once in a while
+///     consume_budget().await;
+/// }
+/// # }).unwrap();
+/// ```
+pub async fn consume_budget() {
+    poll_fn(poll_proceed).await;
+}
+
+/// Future for the [`unconstrained`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Unconstrained<F> {
+    future: F,
+}
+
+impl<F: Future> Future for Unconstrained<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Safety: the future is structurally pinned.
+        let future = unsafe { self.map_unchecked_mut(|this| &mut this.future) };
+        let budget = BUDGET.with(|cell| cell.replace(None));
+        let out = future.poll(cx);
+        BUDGET.with(|cell| cell.set(budget));
+        out
+    }
+}
+
+/// Turns off cooperative scheduling for the wrapped future.
+///
+/// By default, osiris forces tasks to yield back to the runtime once they have
+/// made progress on a number of runtime resources, determined by
+/// [`Config::budget`](crate::runtime::Config#structfield.budget). Futures wrapped
+/// in `unconstrained` will never be forced to yield, which may starve other tasks
+/// and the reactor, so it should be used with care.
+///
+/// # Examples
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::sync::mpmc::channel;
+/// use osiris::task::unconstrained;
+///
+/// let (tx, rx) = channel(1024);
+/// for i in 0..1024 {
+///     tx.send(i).await.unwrap();
+/// }
+/// // drain the whole channel without yielding to other tasks
+/// unconstrained(async {
+///     for i in 0..1024 {
+///         assert_eq!(rx.recv().await.unwrap(), i);
+///     }
+/// })
+/// .await;
+/// # }).unwrap();
+/// ```
+pub fn unconstrained<F: Future>(future: F) -> Unconstrained<F> {
+    Unconstrained { future }
+}
+
+#[cfg(test)]
+async fn drain_while(budgeted: bool) -> bool {
+    use crate::sync::mpmc::channel;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let (tx, rx) = channel(1024);
+    for i in 0..1024 {
+        tx.send(i).await.unwrap();
+    }
+    let polled = Rc::new(Cell::new(false));
+    let _other = crate::spawn({
+        let polled = polled.clone();
+        async move { polled.set(true) }
+    });
+    let drain = async {
+        for _ in 0..1024 {
+            rx.recv().await.unwrap();
+        }
+        polled.get()
+    };
+    if budgeted {
+        drain.await
+    } else {
+        unconstrained(drain).await
+    }
+}
+
+#[test]
+fn budget_forces_yield() {
+    crate::block_on(async {
+        assert!(drain_while(true).await);
+        assert!(!drain_while(false).await);
+    })
+    .unwrap();
+}
@@ -1,6 +1,8 @@
-use super::JoinHandle;
+use super::{Catch, DetachHandle, JoinHandle};
 use crate::runtime::current_unwrap;
 use std::future::Future;
+use std::io::Result;
+use std::pin::Pin;
 
 /// Spawns a new asynchronous task, returning a
 /// [`JoinHandle`](JoinHandle) for it. When the [`JoinHandle`](JoinHandle)
@@ -18,7 +20,8 @@ use std::future::Future;
 /// using the [`Runtime::enter()`](crate::runtime::Runtime::enter) method.
 ///
 /// # Panics
-/// Panics if called from **outside** of an osiris runtime.
+/// Panics if called from **outside** of an osiris runtime, or if the runtime's
+/// [`max_tasks`](crate::runtime::Config#structfield.max_tasks) limit has been reached.
 ///
 #[track_caller]
 #[must_use = "This task is immediatly cancelled after spawn. osiris tasks are cancelled on drop, you may want to use `detach()`."]
@@ -29,9 +32,118 @@ where
     current_unwrap("spawn").spawn(future)
 }
 
-/// Spawns a new asynchronous task, returning a
-/// [`JoinHandle`](JoinHandle) for it. Unlike `spawn`, detached tasks
-/// will not be cancelled when the [`JoinHandle`](JoinHandle) gets dropped.
+/// Spawns a boxed, type erased future as a new asynchronous task, returning a
+/// [`JoinHandle`] for it. See [`spawn`] for more details.
+///
+/// The task stores the box itself rather than the future it points to, so the task
+/// allocation has the same small size regardless of how large the future is. This
+/// is useful to spawn a collection of futures of different types that were erased
+/// to a common `dyn Future`, or to keep a very large future out of the task.
+///
+/// # Examples
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::task::{spawn_boxed, yield_now};
+/// use std::future::Future;
+/// use std::pin::Pin;
+///
+/// let futures: Vec<Pin<Box<dyn Future<Output = u32>>>> = vec![
+///     Box::pin(async { 1 }),
+///     Box::pin(async {
+///         yield_now().await;
+///         2
+///     }),
+/// ];
+/// let handles: Vec<_> = futures.into_iter().map(spawn_boxed).collect();
+/// let mut sum = 0;
+/// for handle in handles {
+///     sum += handle.await;
+/// }
+/// assert_eq!(sum, 3);
+/// # }).unwrap();
+/// ```
+///
+/// # Panics
+/// Panics if called from **outside** of an osiris runtime, or if the runtime's
+/// [`max_tasks`](crate::runtime::Config#structfield.max_tasks) limit has been reached.
+#[track_caller]
+#[must_use = "This task is immediatly cancelled after spawn. osiris tasks are cancelled on drop, you may want to use `detach()`."]
+pub fn spawn_boxed<T: 'static>(future: Pin<Box<dyn Future<Output = T>>>) -> JoinHandle<T> {
+    current_unwrap("spawn_boxed").spawn(future)
+}
+
+/// Spawns a new asynchronous task like [`spawn`], returning a [`Catch`] future that
+/// resolves to a [`JoinError`](super::JoinError) if the task panics or is aborted.
+///
+/// By default, awaiting a task that panicked resumes the panic in the awaiting task,
+/// so a single misbehaving task can bring down its parent. With `spawn_catching` the
+/// panic is returned as an error instead, so the parent can handle it and continue.
+/// The spawned task is cancelled when the returned future gets dropped.
+///
+/// # Examples
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::task::spawn_catching;
+///
+/// let err = spawn_catching(async { panic!("boom") }).await.unwrap_err();
+/// assert!(err.is_panic());
+/// # }).unwrap();
+/// ```
+///
+/// # Panics
+/// Panics if called from **outside** of an osiris runtime, or if the runtime's
+/// [`max_tasks`](crate::runtime::Config#structfield.max_tasks) limit has been reached.
+#[track_caller]
+pub fn spawn_catching<F>(future: F) -> Catch<<F as Future>::Output>
+where
+    F: Future + 'static,
+{
+    current_unwrap("spawn_catching").spawn(future).catch()
+}
+
+/// Spawns a new asynchronous task like [`spawn`], but returns an error instead of
+/// panicking if the runtime's [`max_tasks`] limit has been reached.
+///
+/// This can be used to shed load instead of crashing, for example, by refusing
+/// incoming connections until some of the running tasks complete.
+///
+/// # Errors
+/// This function errors if the number of tasks alive on the runtime has reached
+/// the configured limit.
+///
+/// # Panics
+/// Panics if called from **outside** of an osiris runtime.
+///
+/// # Examples
+/// ```
+/// use osiris::runtime::Config;
+/// use osiris::task::{try_spawn, yield_now};
+///
+/// let rt = Config::default().max_tasks(1).build().unwrap();
+/// rt.block_on(async {
+///     let handle = try_spawn(yield_now()).unwrap();
+///     assert!(try_spawn(yield_now()).is_err());
+///     handle.await;
+///     assert!(try_spawn(yield_now()).is_ok());
+/// })
+/// .unwrap();
+/// ```
+///
+/// [`max_tasks`]: crate::runtime::Config#structfield.max_tasks
+#[track_caller]
+pub fn try_spawn<F>(future: F) -> Result<JoinHandle<<F as Future>::Output>>
+where
+    F: Future + 'static,
+{
+    current_unwrap("try_spawn").try_spawn(future)
+}
+
+/// Spawns a new asynchronous task, returning a [`DetachHandle`] for it. Unlike
+/// [`spawn`], detached tasks will not be cancelled when their handle gets dropped.
+///
+/// The returned handle can be dropped right away, in which case the task runs until
+/// completion, and there is no way to stop it. If it is kept around instead, it can be
+/// used to [`abort`](DetachHandle::abort) the task later, or to await its output.
 ///
 /// Spawning a task enables the future to be executed concurrently with respect to other tasks.
 /// The spawned task will execute on the current thread.
@@ -45,9 +157,10 @@ where
 /// using the [`Runtime::enter()`](crate::runtime::Runtime::enter) method.
 ///
 /// # Panics
-/// Panics if called from **outside** of an osiris runtime.
+/// Panics if called from **outside** of an osiris runtime, or if the runtime's
+/// [`max_tasks`](crate::runtime::Config#structfield.max_tasks) limit has been reached.
 #[track_caller]
-pub fn detach<F>(future: F) -> JoinHandle<<F as Future>::Output>
+pub fn detach<F>(future: F) -> DetachHandle<<F as Future>::Output>
 where
     F: Future + 'static,
 {
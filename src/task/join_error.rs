@@ -0,0 +1,84 @@
+use std::any::Any;
+use std::fmt::{Debug, Display};
+
+/// The reason a task failed to complete.
+pub(crate) enum Failure {
+    Panic(Box<dyn Any + Send>),
+    Aborted,
+}
+
+/// An error returned when joining a task that panicked or was aborted.
+/// It is returned by awaiting a [`Catch`](super::Catch) future.
+pub struct JoinError {
+    id: u64,
+    failure: Failure,
+}
+
+impl JoinError {
+    pub(crate) fn new(id: u64, failure: Failure) -> JoinError {
+        JoinError { id, failure }
+    }
+
+    /// Returns the id of the task that failed.
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns true if the task panicked.
+    #[must_use]
+    pub fn is_panic(&self) -> bool {
+        matches!(self.failure, Failure::Panic(_))
+    }
+
+    /// Returns true if the task was aborted.
+    #[must_use]
+    pub fn is_aborted(&self) -> bool {
+        matches!(self.failure, Failure::Aborted)
+    }
+
+    /// Consumes the error, returning the panic payload if the task panicked,
+    /// or the error itself otherwise.
+    ///
+    /// # Errors
+    /// If the task was aborted instead of panicking.
+    pub fn try_into_panic(self) -> Result<Box<dyn Any + Send>, JoinError> {
+        match self.failure {
+            Failure::Panic(payload) => Ok(payload),
+            Failure::Aborted => Err(self),
+        }
+    }
+
+    /// Returns the panic message of the task, if it panicked with a string message.
+    fn message(&self) -> Option<&str> {
+        let Failure::Panic(payload) = &self.failure else {
+            return None;
+        };
+        if let Some(msg) = payload.downcast_ref::<&str>() {
+            Some(msg)
+        } else {
+            payload.downcast_ref::<String>().map(String::as_str)
+        }
+    }
+}
+
+impl Display for JoinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.failure, self.message()) {
+            (Failure::Panic(_), Some(msg)) => write!(f, "task {} panicked: {msg}", self.id),
+            (Failure::Panic(_), None) => write!(f, "task {} panicked", self.id),
+            (Failure::Aborted, _) => write!(f, "task {} was aborted", self.id),
+        }
+    }
+}
+
+impl Debug for JoinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.failure {
+            Failure::Panic(_) => write!(f, "JoinError::Panic({}, {:?})", self.id, self.message()),
+            Failure::Aborted => write!(f, "JoinError::Aborted({})", self.id),
+        }
+    }
+}
+
+impl std::error::Error for JoinError {}
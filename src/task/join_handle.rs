@@ -1,12 +1,15 @@
 use std::any::Any;
 use std::future::Future;
 use std::marker::PhantomData;
+use std::panic::resume_unwind;
 use std::pin::Pin;
+use std::rc::Rc;
 use std::task::{Context, Poll};
 
 use crate::utils::futures::catch_unwind;
 
-use super::Task;
+use super::join_error::Failure;
+use super::{AbortHandle, JoinError, Task};
 
 /// A handle to the spawned task. By default the task will be cancelled
 /// when the join handle gets dropped. In order to detach on drop the
@@ -39,6 +42,13 @@ impl<T> JoinHandle<T> {
         self.task.id()
     }
 
+    /// Returns the name of the task, if it was spawned with a
+    /// [`Builder`](super::Builder) that set one.
+    #[must_use]
+    pub fn name(&self) -> Option<Rc<str>> {
+        self.task.name()
+    }
+
     /// Aborts the task and runs the spawned future's destructor.
     /// Unlike, other runtimes, osiris tasks are guaranteed to be cancelled immediately.
     /// This is primarily intended for aborting detached tasks, since normal tasks can be
@@ -51,6 +61,34 @@ impl<T> JoinHandle<T> {
         self.detached = false;
     }
 
+    /// Returns a new [`AbortHandle`] that can be used to abort the task without
+    /// owning the join handle. Dropping the returned handle will not cancel the task.
+    ///
+    /// # Examples
+    /// ```
+    /// # osiris::block_on(async {
+    /// use osiris::task::{spawn, yield_now};
+    ///
+    /// let handle = spawn(async {
+    ///     loop {
+    ///         yield_now().await;
+    ///     }
+    /// });
+    /// let abort = handle.abort_handle();
+    ///
+    /// // the supervisor gets to cancel the task, while
+    /// // we keep ownership of the join handle
+    /// spawn(async move { abort.abort() }).await;
+    ///
+    /// // joining an aborted task panics
+    /// assert!(handle.catch_unwind().await.is_err());
+    /// # }).unwrap();
+    /// ```
+    #[must_use]
+    pub fn abort_handle(&self) -> AbortHandle {
+        AbortHandle::new(self.task.clone())
+    }
+
     /// Joins the task catching any propagated panics.
     ///
     /// # Errors
@@ -59,6 +97,153 @@ impl<T> JoinHandle<T> {
     pub async fn catch_unwind(self) -> Result<T, Box<dyn Any + Send + 'static>> {
         catch_unwind(self).await
     }
+
+    /// Converts the handle into a future that resolves to a [`JoinError`] if the task
+    /// panicked or was aborted, instead of propagating the panic to the awaiting task.
+    ///
+    /// Unlike [`catch_unwind`](JoinHandle::catch_unwind), the panic is never resumed, so
+    /// it doesn't run through any other `catch_unwind` boundary, and aborted tasks are
+    /// reported as errors too. Dropping the returned future cancels the task if it has not
+    /// completed, but it won't propagate its panic.
+    ///
+    /// # Examples
+    /// ```
+    /// # osiris::block_on(async {
+    /// use osiris::task::spawn;
+    ///
+    /// let handle = spawn(async {
+    ///     panic!("invalid request");
+    /// });
+    /// let err = handle.catch().await.unwrap_err();
+    /// assert!(err.is_panic());
+    /// assert!(err.to_string().ends_with("panicked: invalid request"));
+    /// # }).unwrap();
+    /// ```
+    pub fn catch(mut self) -> Catch<T> {
+        // the handle no longer aborts the task, the `Catch` cancels it instead.
+        self.detached = true;
+        Catch { handle: self }
+    }
+
+    fn poll_join(&self, cx: &mut Context<'_>) -> Poll<Result<T, Failure>> {
+        let mut output: Poll<Result<T, Failure>> = Poll::Pending;
+        let ptr = &mut output as *mut _ as *mut ();
+        // Safety:
+        // The output type is the same as the JoinHandle since a
+        // JoinHandle<T> cannot be constructed from a task of a
+        // type different from T.
+        unsafe { self.task.task().poll_join(cx, ptr) };
+        output
+    }
+}
+
+/// A future that joins a task, resolving to a [`JoinError`] if it panicked or was
+/// aborted. It is created by [`JoinHandle::catch`] and [`spawn_catching`](super::spawn_catching).
+#[must_use = "This task is immediatly cancelled after spawn. osiris tasks are cancelled on drop, you may want to use `detach()`."]
+pub struct Catch<T> {
+    handle: JoinHandle<T>,
+}
+
+impl<T> Catch<T> {
+    /// Returns the id of the task.
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.handle.id()
+    }
+
+    /// Returns a new [`AbortHandle`] that can be used to abort the task.
+    #[must_use]
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.handle.abort_handle()
+    }
+}
+
+impl<T> Drop for Catch<T> {
+    fn drop(&mut self) {
+        self.handle.task.cancel();
+    }
+}
+
+impl<T> Future for Catch<T> {
+    type Output = Result<T, JoinError>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let out = std::task::ready!(self.handle.poll_join(cx));
+        Poll::Ready(out.map_err(|failure| JoinError::new(self.handle.id(), failure)))
+    }
+}
+
+/// A handle to a detached task. It is returned by [`detach`](super::detach).
+///
+/// Unlike a [`JoinHandle`], dropping a `DetachHandle` does not cancel the task, which
+/// keeps running independently of its parent. Unlike a dropped handle, however, a
+/// `DetachHandle` that is kept around can still [`abort`](DetachHandle::abort) the task
+/// later on, or await its completion.
+///
+/// # Panics
+/// Awaiting the handle will panic if the task panicked or was aborted.
+pub struct DetachHandle<T> {
+    handle: JoinHandle<T>,
+}
+
+impl<T> Unpin for DetachHandle<T> {}
+
+impl<T> DetachHandle<T> {
+    pub(crate) fn new(mut handle: JoinHandle<T>) -> DetachHandle<T> {
+        handle.detach();
+        handle.task.set_detached();
+        DetachHandle { handle }
+    }
+
+    /// Returns the id of the task.
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.handle.id()
+    }
+
+    /// Returns the name of the task, if it was spawned with a
+    /// [`Builder`](super::Builder) that set one.
+    #[must_use]
+    pub fn name(&self) -> Option<Rc<str>> {
+        self.handle.name()
+    }
+
+    /// Aborts the task and runs the spawned future's destructor. If the task has
+    /// already completed, this does nothing.
+    ///
+    /// # Examples
+    /// ```
+    /// # osiris::block_on(async {
+    /// use osiris::task::{detach, yield_now};
+    ///
+    /// let handle = detach(async {
+    ///     loop {
+    ///         yield_now().await;
+    ///     }
+    /// });
+    /// yield_now().await;
+    /// handle.abort();
+    /// # }).unwrap();
+    /// ```
+    ///
+    /// # Panics
+    /// If a task attempts to abort itself.
+    pub fn abort(&self) {
+        self.handle.task.cancel();
+    }
+
+    /// Returns a new [`AbortHandle`] that can be used to abort the task.
+    #[must_use]
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.handle.abort_handle()
+    }
+}
+
+impl<T> Future for DetachHandle<T> {
+    type Output = T;
+    #[track_caller]
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.handle).poll(cx)
+    }
 }
 
 impl<T> JoinHandle<T> {
@@ -84,14 +269,12 @@ impl<T> Drop for JoinHandle<T> {
 
 impl<T> Future for JoinHandle<T> {
     type Output = T;
+    #[track_caller]
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let mut output: Poll<T> = Poll::Pending;
-        let ptr = &mut output as *mut _ as *mut ();
-        // Safety:
-        // The output type is the same as the JoinHandle since a
-        // JoinHandle<T> cannot be constructed from a task of a
-        // type different from T.
-        unsafe { self.task.task().poll_join(cx, ptr) };
-        output
+        match std::task::ready!(self.poll_join(cx)) {
+            Ok(output) => Poll::Ready(output),
+            Err(Failure::Panic(error)) => resume_unwind(error),
+            Err(Failure::Aborted) => panic!("attempted to join a task that has been aborted."),
+        }
     }
 }
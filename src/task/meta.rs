@@ -1,10 +1,14 @@
 use crate::runtime::Runtime;
+use std::cell::Cell;
+use std::rc::Rc;
 
 /// Task related metadata.
 #[derive(Clone)]
 pub(crate) struct Metadata {
     /// unique id for the task.
     pub id: u64,
+    /// an optional name given to the task with a [`Builder`](super::Builder).
+    pub name: Option<Rc<str>>,
     /// a reference to the runtime where the task was spawned.
     pub rt: Runtime,
     /// whether to ignore abort signals.
@@ -16,4 +20,8 @@ pub(crate) struct Metadata {
     /// Those tasks are marked with ignore_abort so they don't get
     /// aborted and respawned on a loop.
     pub ignore_abort: bool,
+    /// whether the task was detached with [`detach`](crate::task::detach),
+    /// so its panics are handled by the runtime's
+    /// [`detached_panic_policy`](crate::runtime::Config#structfield.detached_panic_policy).
+    pub detached: Cell<bool>,
 }
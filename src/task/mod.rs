@@ -114,8 +114,23 @@
 //! # };
 //! ```
 //!
-//! `spawn`, `JoinHandle`, and `JoinError` are present when the "rt"
-//! feature flag is enabled.
+//! Alternatively, [`spawn_catching`] returns a future that resolves to a [`JoinError`]
+//! when the task panics or is aborted, without ever resuming the panic in the parent.
+//! This is useful for servers that want to log a failed task and continue.
+//!
+//! ```no_run
+//! use osiris::task;
+//!
+//! # async {
+//! let join = task::spawn_catching(async {
+//!     panic!("something bad happened!")
+//! });
+//!
+//! if let Err(err) = join.await {
+//!     eprintln!("{err}");
+//! }
+//! # };
+//! ```
 //!
 //! [`task::spawn`]: crate::task::spawn()
 //! [future]: std::future::Future
@@ -123,6 +138,7 @@
 //! [`JoinHandle`]: crate::task::JoinHandle
 //! [thread_join]: std::thread::JoinHandle
 //! [`JoinError`]: crate::task::JoinError
+//! [`spawn_catching`]: crate::task::spawn_catching
 //!
 //! ### Blocking and Yielding
 //!
@@ -208,16 +224,24 @@
 use self::shared_task::SharedTask;
 
 use std::any::Any;
-
+use std::rc::Rc;
 use std::task::{Context, Waker};
 
-pub use fns::{detach, id, spawn};
-pub use join_handle::JoinHandle;
+pub use abort_handle::AbortHandle;
+pub use builder::Builder;
+pub use coop::{consume_budget, unconstrained, Unconstrained};
+pub use fns::{detach, id, spawn, spawn_boxed, spawn_catching, try_spawn};
+pub use join_error::JoinError;
+pub use join_handle::{Catch, DetachHandle, JoinHandle};
 pub use spawn_blocking::spawn_blocking;
 pub(crate) use waker::waker;
-pub use yield_now::yield_now;
+pub use yield_now::{yield_now, yield_to_reactor};
 
+mod abort_handle;
+mod builder;
+pub(crate) mod coop;
 mod fns;
+mod join_error;
 mod join_handle;
 mod meta;
 mod raw_task;
@@ -235,6 +259,11 @@ impl Task {
         self.meta().id
     }
 
+    #[inline]
+    pub(crate) fn name(&self) -> Option<Rc<str>> {
+        self.meta().name
+    }
+
     pub(crate) fn poll(&self, cx: &mut Context) {
         self.task().poll(cx);
     }
@@ -245,11 +274,34 @@ impl Task {
             self.task().abort();
         }
     }
+    /// Aborts the task only if it has not completed yet.
+    pub(crate) fn cancel(&self) {
+        if !self.meta().ignore_abort {
+            self.task().cancel();
+        }
+    }
     /// Sets the panic payload for the task in case it panicked while being polled
     pub(crate) fn panic(&self, payload: Box<dyn Any + Send>) {
+        let payload = match self.name() {
+            Some(name) => named_panic(&name, payload),
+            None => payload,
+        };
         self.task().panic(payload);
     }
     pub(crate) fn waker(self) -> Waker {
         waker(self)
     }
 }
+
+/// Prefixes the message of a panic with the name of the task, so the task
+/// can be identified once the panic is propagated to its parent.
+fn named_panic(name: &str, payload: Box<dyn Any + Send>) -> Box<dyn Any + Send> {
+    let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+        *msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg
+    } else {
+        return payload;
+    };
+    Box::new(format!("task '{name}' panicked: {msg}"))
+}
@@ -9,16 +9,23 @@ pub(crate) trait RawTask {
     /// This function is used to abort a task in place. Currently self aborting tasks
     /// are not supported.
     fn abort(self: Pin<&Self>);
+    /// This function is used to abort a task that has not completed yet. Unlike `abort`,
+    /// completed and panicked tasks are left untouched, so their output can still be
+    /// joined.
+    fn cancel(self: Pin<&Self>);
     /// This function is used to poll the future and drive it to completion. This method
     /// is called by the executor.
     fn poll(self: Pin<&Self>, cx: &mut Context);
     /// This function will check if the task has finished and it will take the value
     /// in that case. This method is called by the join handle when it's polled.
     ///
+    /// If the task panicked or was aborted, the failure is returned as an error
+    /// instead of being propagated.
+    ///
     /// # Safety
     /// The caller must uphold that the pointer `out: *mut ()` points to a valid
-    /// memory location of the type `Poll<F::Output>`, where `F` is the spawned
-    /// future of the associated task.
+    /// memory location of the type `Poll<Result<F::Output, Failure>>`, where `F`
+    /// is the spawned future of the associated task.
     unsafe fn poll_join(self: Pin<&Self>, cx: &mut Context, ptr: *mut ());
 
     /// This function is used to register that the task has panicked so it can
@@ -11,10 +11,12 @@ use super::meta::Metadata;
 use super::raw_task::RawTask;
 use super::task_repr::TaskRepr;
 use std::alloc::{dealloc, Layout};
+use std::cell::Cell;
 use std::future::Future;
 use std::mem::forget;
 use std::pin::Pin;
 use std::ptr::drop_in_place;
+use std::rc::Rc;
 use std::sync::atomic::Ordering::*;
 use std::sync::atomic::{self, AtomicUsize};
 use std::thread::{current, ThreadId};
@@ -68,11 +70,19 @@ fn alloc_layout<T: ?Sized>(task: &T) -> (Layout, isize) {
 
 impl SharedTask {
     /// Creates a new shared task.
-    pub fn new<F: Future + 'static>(f: F, id: u64, rt: Runtime, ignore_abort: bool) -> Self {
+    pub fn new<F: Future + 'static>(
+        f: F,
+        id: u64,
+        name: Option<Rc<str>>,
+        rt: Runtime,
+        ignore_abort: bool,
+    ) -> Self {
         let meta = Metadata {
             id,
+            name,
             rt,
             ignore_abort,
+            detached: Cell::new(false),
         };
         let task = TaskRepr::new(f);
         SharedTask::from_raw_task(task, meta)
@@ -89,6 +99,17 @@ impl SharedTask {
         self.inner().meta.clone()
     }
 
+    /// Marks the task as detached. See [`Metadata::detached`].
+    #[inline]
+    pub fn set_detached(&self) {
+        self.inner().meta.detached.set(true);
+    }
+
+    #[inline]
+    pub fn is_detached(&self) -> bool {
+        self.inner().meta.detached.get()
+    }
+
     /// Takes a raw pointer and converts it into an owned [`SharedTask`]
     #[inline]
     pub unsafe fn from_raw(ptr: *const ()) -> SharedTask {
@@ -188,7 +209,7 @@ fn thread_safety_stress_test() {
     }
 
     let rt = Runtime::new().unwrap();
-    let last_task = SharedTask::new(async {}, 1, rt, false);
+    let last_task = SharedTask::new(async {}, 1, None, rt, false);
     let task = last_task.clone();
 
     std::thread::scope(move |s| {
@@ -1,3 +1,4 @@
+use super::join_error::Failure;
 use super::raw_task::RawTask;
 use std::any::Any;
 use std::cell::{Cell, RefCell};
@@ -86,8 +87,8 @@ where
     }
     /// # Safety
     /// The caller must uphold that the pointer `out: *mut ()` points to a valid
-    /// memory location of the type `Poll<F::Output>`, where `F` is the spawned
-    /// future of the associated task.
+    /// memory location of the type `Poll<Result<F::Output, Failure>>`, where `F`
+    /// is the spawned future of the associated task.
     #[track_caller]
     unsafe fn poll_join(self: Pin<&Self>, cx: &mut Context, out: *mut ()) {
         self.insert_waker(cx);
@@ -100,26 +101,21 @@ where
             // we can move anything now that we know the pin ended.
             let payload = replace(payload, Payload::Taken);
 
-            match payload {
-                Payload::Ready { output } => {
-                    let out: *mut Poll<F::Output> = out.cast();
-                    // Safety:
-                    // the caller must uphold that the transmuted type is correct.
-                    unsafe {
-                        *out = Poll::Ready(output);
-                    }
-                }
+            let output = match payload {
+                Payload::Ready { output } => Ok(output),
                 Payload::Taken => {
                     panic!("polled a JoinHandle future after returning Poll::Ready(..).");
                 }
-                Payload::Panic { error } => resume_unwind(error),
-                Payload::Aborted => {
-                    unreachable!(
-                        "attempted to join a task that has been aborted. This is a bug in osiris."
-                    )
-                }
+                Payload::Panic { error } => Err(Failure::Panic(error)),
+                Payload::Aborted => Err(Failure::Aborted),
                 // Safety: we already checked for this case
                 Payload::Pending { .. } => unsafe { unreachable_unchecked() },
+            };
+            let out: *mut Poll<Result<F::Output, Failure>> = out.cast();
+            // Safety:
+            // the caller must uphold that the transmuted type is correct.
+            unsafe {
+                *out = Poll::Ready(output);
             }
         }
     }
@@ -158,6 +154,19 @@ where
             resume_unwind(error);
         }
     }
+    fn cancel(self: Pin<&Self>) {
+        let mut payload = self
+            .payload
+            .try_borrow_mut()
+            .expect("A task attempted to abort itself. This is not supported.");
+        if !matches!(&*payload, Payload::Pending { .. }) {
+            return;
+        }
+        *payload = Payload::Aborted;
+        drop(payload);
+        self.wake_join_handle();
+    }
+
     fn panic(self: Pin<&Self>, error: Box<dyn Any + Send>) {
         let mut payload = self.payload.borrow_mut();
         *payload = Payload::Panic { error };
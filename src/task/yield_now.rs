@@ -1,6 +1,8 @@
 use std::future::{poll_fn, Future};
 use std::task::Poll;
 
+use crate::runtime::current_unwrap;
+
 /// Yields execution back to the runtime.
 ///
 /// A task yields by awaiting on `yield_now()`, and may resume when that future
@@ -44,3 +46,45 @@ pub fn yield_now() -> impl Future<Output = ()> + Unpin {
         }
     })
 }
+
+/// Yields execution back to the runtime, and resumes only after the reactor has
+/// submitted the pending IO events to the kernel and reaped the completed ones.
+///
+/// Unlike [`yield_now`], which only puts the task at the back of the run queue,
+/// this function guarantees that the reactor is driven before the task is polled
+/// again. This is useful in loops that queue many IO operations, and want them to
+/// be dispatched before continuing, without waiting for any of them to complete.
+///
+/// # Panics
+/// This function panics if called from the outside of an osiris runtime.
+///
+/// # Examples
+/// ```
+/// use osiris::task::{spawn, yield_to_reactor};
+/// use osiris::time::{sleep, Duration};
+///
+/// #[osiris::main]
+/// async fn main() {
+///     let timers: Vec<_> = (0..8)
+///         .map(|_| spawn(sleep(Duration::from_millis(10))))
+///         .collect();
+///     // the timers are submitted to the kernel before we continue
+///     yield_to_reactor().await;
+///     for timer in timers {
+///         timer.await;
+///     }
+/// }
+/// ```
+pub fn yield_to_reactor() -> impl Future<Output = ()> + Unpin {
+    let mut ready = false;
+    poll_fn(move |cx| {
+        if ready {
+            Poll::Ready(())
+        } else {
+            ready = true;
+            let rt = current_unwrap("yield_to_reactor");
+            rt.executor.defer(cx.waker().clone());
+            Poll::Pending
+        }
+    })
+}
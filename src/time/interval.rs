@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+
+use super::sleep_until;
+
+/// Creates a new [`Interval`] that yields with interval of `period`. The first
+/// tick completes immediately.
+///
+/// An interval will tick indefinitely. At any time, the [`Interval`] value can
+/// be dropped. This cancels the interval.
+///
+/// If a tick is missed because the task took too long to call [`Interval::tick`]
+/// again, the following ticks are delayed so they are spaced by `period` from
+/// the moment the missed tick completed.
+///
+/// # Panics
+///
+/// This function panics if `period` is zero.
+///
+/// # Examples
+///
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::time::{interval, Duration};
+///
+/// let mut interval = interval(Duration::from_millis(10));
+///
+/// interval.tick().await; // ticks immediately
+/// interval.tick().await; // ticks after 10ms
+/// interval.tick().await; // ticks after 10ms
+/// # }).unwrap();
+/// ```
+pub fn interval(period: Duration) -> Interval {
+    assert!(!period.is_zero(), "`period` must be non-zero.");
+    Interval {
+        next: Instant::now(),
+        period,
+    }
+}
+
+/// Interval returned by [`interval`].
+#[derive(Debug)]
+pub struct Interval {
+    next: Instant,
+    period: Duration,
+}
+
+impl Interval {
+    /// Completes when the next instant in the interval has been reached,
+    /// and returns that instant.
+    ///
+    /// # Cancellation
+    ///
+    /// This method is cancellation safe. If the returned future is dropped,
+    /// the tick is not consumed.
+    ///
+    /// # Panics
+    ///
+    /// This future panics if polled outside the context of an osiris runtime.
+    pub async fn tick(&mut self) -> Instant {
+        sleep_until(self.next).await;
+        let tick = self.next;
+        let now = Instant::now();
+        self.next = if tick + self.period < now {
+            now + self.period
+        } else {
+            tick + self.period
+        };
+        tick
+    }
+
+    /// Returns the period of the interval.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
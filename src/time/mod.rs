@@ -4,13 +4,23 @@
 //!
 //! * [`sleep`] is a future that does no work and completes after a minimum duration has elapsed.
 //!
+//! * [`interval`](interval::interval): Yields at a fixed period. The first tick completes
+//!   immediately.
+//!
 //! * [`timeout`](timeout::timeout): Wraps a future or stream, setting an upper bound to the amount
 //!   of time it is allowed to execute. If the future or stream does not
 //!   complete in time, then it is canceled and an error is returned.
 //!
+//! * [`timeout_or_continue`](timeout::timeout_or_continue): Like `timeout`, but the future is
+//!   returned instead of canceled if it does not complete in time.
+//!
 //! These types are sufficient for handling a large number of scenarios
 //! involving time.
 //!
+//! Short timers are batched on a per-runtime timer wheel, so a large number of
+//! concurrent sleeps and timeouts share a single reactor wakeup, and cancelling
+//! them does not require submitting any work to the reactor.
+//!
 //! These types must be used from within the context of the [`Runtime`](crate::runtime::Runtime).
 //!
 //! # Examples
@@ -47,11 +57,19 @@
 //! ```
 //!
 
+pub use interval::{interval, Interval};
 pub use std::time::Duration;
-pub use timeout::timeout;
+pub use timeout::{timeout, timeout_or_continue};
+
+pub(crate) use wheel::Timers;
 
 use crate::reactor::op;
+use std::time::Instant;
+use wheel::{Sleep, SPAN};
+
+pub mod interval;
 pub mod timeout;
+mod wheel;
 
 /// Waits until `duration` has elapsed. An asynchronous analog to
 /// `std::thread::sleep`.
@@ -85,5 +103,17 @@ pub mod timeout;
 /// This future panics if called outside the context of
 /// an osiris runtime.
 pub async fn sleep(time: Duration) {
-    op::sleep(time).await.unwrap();
+    if time >= SPAN {
+        return op::sleep(time).await.unwrap();
+    }
+    Sleep::new(Instant::now() + time).await;
+}
+
+/// Waits until `deadline` is reached.
+pub(crate) async fn sleep_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline.saturating_duration_since(now) >= SPAN {
+        return op::sleep(deadline - now).await.unwrap();
+    }
+    Sleep::new(deadline).await;
 }
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 use std::future::{poll_fn, Future};
-use std::pin::Pin;
+use std::pin::{pin, Pin};
 use std::task::Poll::*;
 use std::time::Duration;
 
@@ -59,6 +59,69 @@ pub async fn timeout<F: Future>(dur: Duration, mut f: F) -> Result<F::Output, Er
     .await
 }
 
+/// Requires a `Future` to complete before the specified duration has elapsed,
+/// returning the still pending future if it doesn't.
+///
+/// Unlike [`timeout`], the future is not canceled when the duration elapses, so
+/// any partial progress it made is preserved. The returned future can be awaited
+/// again, or passed to `timeout_or_continue` to extend the deadline. Since the
+/// future is returned by value, it must be [`Unpin`]. `!Unpin` futures can be
+/// pinned with [`Box::pin`].
+///
+/// # Examples
+/// ```
+/// # osiris::block_on(async {
+/// use osiris::time::{sleep, timeout_or_continue, Duration};
+///
+/// let mut future = Box::pin(sleep(Duration::from_millis(50)));
+/// let mut retries = 0;
+/// loop {
+///     match timeout_or_continue(Duration::from_millis(10), future).await {
+///         Ok(()) => break,
+///         Err(pending) => future = pending,
+///     }
+///     retries += 1;
+/// }
+/// assert!(retries > 0);
+/// # }).unwrap();
+/// ```
+///
+/// # Panics
+/// This function panics if polled outside a runtime context.
+pub async fn timeout_or_continue<F>(dur: Duration, f: F) -> Result<F::Output, F>
+where
+    F: Future + Unpin,
+{
+    let mut sleep = pin!(sleep(dur));
+    let mut f = Some(f);
+    poll_fn(move |cx| {
+        if sleep.as_mut().poll(cx).is_ready() {
+            return Ready(Err(f.take().unwrap()));
+        }
+
+        if let Ready(val) = Pin::new(f.as_mut().unwrap()).poll(cx) {
+            return Ready(Ok(val));
+        }
+        Pending
+    })
+    .await
+}
+
+#[test]
+fn timeout_or_continue_() {
+    crate::block_on(async {
+        let future = Box::pin(sleep(Duration::from_millis(50)));
+
+        let future = timeout_or_continue(Duration::from_millis(10), future)
+            .await
+            .unwrap_err();
+
+        let out = timeout_or_continue(Duration::from_millis(100), future).await;
+        assert!(out.is_ok());
+    })
+    .unwrap();
+}
+
 #[test]
 fn timeout_() {
     crate::block_on(async {
@@ -0,0 +1,275 @@
+//! A hashed timer wheel used to batch short timers.
+//!
+//! Submitting one io_uring timeout per sleep means that every timer costs an SQE
+//! to arm it, and another one to cancel it if the sleep gets dropped early, which
+//! is the common case for timeouts. Instead, short sleeps are registered on a
+//! per-runtime wheel of [`SLOTS`] slots, each one [`TICK`] wide. A single driver
+//! task sleeps on the reactor until the earliest occupied tick, and wakes every
+//! timer that has expired by then. Registering and dropping a timer on the wheel
+//! never touches the reactor, unless the new timer is earlier than the one the
+//! driver is currently sleeping on.
+//!
+//! Sleeps longer than [`SPAN`] don't fit on the wheel, so they are submitted to
+//! the reactor directly.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::{poll_fn, Future};
+use std::pin::{pin, Pin};
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use crate::reactor::op;
+use crate::runtime::current_unwrap;
+
+/// The resolution of the wheel.
+const TICK: Duration = Duration::from_millis(1);
+/// The number of slots in the wheel.
+const SLOTS: usize = 1024;
+/// Sleeps at least this long are not registered on the wheel.
+pub(crate) const SPAN: Duration = Duration::from_millis(SLOTS as u64 - 1);
+
+/// A per-runtime timer wheel.
+#[derive(Clone)]
+pub(crate) struct Timers(Rc<RefCell<Wheel>>);
+
+struct Wheel {
+    /// the instant corresponding to tick zero.
+    start: Instant,
+    /// the next tick to be processed. Every registered timer
+    /// expires within `SLOTS` ticks of this one.
+    tick: u64,
+    /// the wakers of the registered timers, keyed by their id.
+    slots: Vec<HashMap<u64, Waker>>,
+    /// the number of registered timers.
+    len: usize,
+    next_id: u64,
+    /// whether the driver task is alive.
+    running: bool,
+    /// the tick the driver is currently sleeping until.
+    armed: Option<u64>,
+    /// set when a timer earlier than `armed` is registered.
+    rearm: bool,
+    driver: Option<Waker>,
+}
+
+/// The position of a timer in the wheel.
+#[derive(Clone, Copy)]
+struct Key {
+    slot: usize,
+    id: u64,
+}
+
+impl Default for Timers {
+    fn default() -> Self {
+        let wheel = Wheel {
+            start: Instant::now(),
+            tick: 0,
+            slots: (0..SLOTS).map(|_| HashMap::new()).collect(),
+            len: 0,
+            next_id: 0,
+            running: false,
+            armed: None,
+            rearm: false,
+            driver: None,
+        };
+        Timers(Rc::new(RefCell::new(wheel)))
+    }
+}
+
+impl Timers {
+    /// Registers a timer expiring at `deadline`, which must be less
+    /// than `SPAN` away from now.
+    fn register(&self, deadline: Instant, waker: Waker) -> Key {
+        self.advance(Instant::now());
+        let mut wheel = self.0.borrow_mut();
+        let tick = wheel.tick_for(deadline).max(wheel.tick);
+        debug_assert!(tick < wheel.tick + SLOTS as u64);
+        let slot = tick as usize % SLOTS;
+        let id = wheel.next_id;
+        wheel.next_id += 1;
+        wheel.slots[slot].insert(id, waker);
+        wheel.len += 1;
+
+        if !wheel.running {
+            wheel.running = true;
+            drop(wheel);
+            let rt = current_unwrap("sleep");
+            rt.executor
+                .spawn_untracked(drive(self.clone()), None, rt.clone(), true);
+        } else if wheel.armed.is_some_and(|armed| tick < armed) {
+            wheel.rearm = true;
+            if let Some(driver) = wheel.driver.take() {
+                drop(wheel);
+                driver.wake();
+            }
+        }
+        Key { slot, id }
+    }
+
+    /// Updates the waker of a registered timer. Returns false if
+    /// the timer is no longer registered.
+    fn update(&self, key: Key, waker: &Waker) -> bool {
+        let mut wheel = self.0.borrow_mut();
+        let Some(old) = wheel.slots[key.slot].get_mut(&key.id) else {
+            return false;
+        };
+        if !old.will_wake(waker) {
+            old.clone_from(waker);
+        }
+        true
+    }
+
+    fn remove(&self, key: Key) {
+        let mut wheel = self.0.borrow_mut();
+        if wheel.slots[key.slot].remove(&key.id).is_some() {
+            wheel.len -= 1;
+        }
+    }
+
+    /// Wakes every timer that has expired by `now`.
+    fn advance(&self, now: Instant) {
+        let mut expired = vec![];
+        {
+            let mut wheel = self.0.borrow_mut();
+            let end = wheel.elapsed(now);
+            // only the slots of the elapsed ticks need to be
+            // visited, and each slot needs to be visited at most once.
+            let start = wheel.tick.max(end.saturating_sub(SLOTS as u64));
+            for tick in start..end {
+                let slot = &mut wheel.slots[tick as usize % SLOTS];
+                expired.extend(slot.drain().map(|(_, waker)| waker));
+            }
+            wheel.len -= expired.len();
+            wheel.tick = wheel.tick.max(end);
+        }
+        expired.into_iter().for_each(Waker::wake);
+    }
+
+    /// Returns the instant at which the earliest registered timer expires,
+    /// and records it as the driver's wakeup. If no timers are registered,
+    /// the driver is marked as stopped.
+    fn arm(&self) -> Option<Instant> {
+        let mut wheel = self.0.borrow_mut();
+        wheel.rearm = false;
+        wheel.armed = None;
+        if wheel.len == 0 {
+            wheel.running = false;
+            return None;
+        }
+        let next = (wheel.tick..wheel.tick + SLOTS as u64)
+            .find(|&tick| !wheel.slots[tick as usize % SLOTS].is_empty());
+        let Some(tick) = next else {
+            wheel.running = false;
+            return None;
+        };
+        wheel.armed = Some(tick);
+        Some(wheel.start + Duration::from_nanos(tick * TICK.as_nanos() as u64))
+    }
+}
+
+impl Wheel {
+    /// Returns the first tick that starts at or after `instant`.
+    fn tick_for(&self, instant: Instant) -> u64 {
+        let nanos = instant.saturating_duration_since(self.start).as_nanos();
+        nanos.div_ceil(TICK.as_nanos()) as u64
+    }
+
+    /// Returns the number of ticks that have started by `instant`.
+    fn elapsed(&self, instant: Instant) -> u64 {
+        let nanos = instant.saturating_duration_since(self.start).as_nanos();
+        (nanos / TICK.as_nanos()) as u64 + 1
+    }
+}
+
+/// The driver task. It sleeps on the reactor until the earliest
+/// registered timer expires, and exits once the wheel is empty.
+async fn drive(timers: Timers) {
+    while let Some(deadline) = timers.arm() {
+        let dur = deadline.saturating_duration_since(Instant::now());
+        let mut sleep = pin!(op::sleep(dur));
+        poll_fn(|cx| {
+            if sleep.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(());
+            }
+            let mut wheel = timers.0.borrow_mut();
+            if wheel.rearm {
+                return Poll::Ready(());
+            }
+            wheel.driver = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await;
+        timers.advance(Instant::now());
+    }
+}
+
+/// A future that completes at `deadline`, registered on the wheel.
+pub(crate) struct Sleep {
+    timers: Timers,
+    deadline: Instant,
+    key: Option<Key>,
+}
+
+impl Sleep {
+    pub fn new(deadline: Instant) -> Sleep {
+        let timers = current_unwrap("sleep").timers;
+        Sleep {
+            timers,
+            deadline,
+            key: None,
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            if let Some(key) = self.key.take() {
+                self.timers.remove(key);
+            }
+            return Poll::Ready(());
+        }
+        match self.key {
+            Some(key) if self.timers.update(key, cx.waker()) => {}
+            _ => self.key = Some(self.timers.register(self.deadline, cx.waker().clone())),
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.timers.remove(key);
+        }
+    }
+}
+
+#[test]
+fn wheel_batches_timers() {
+    use crate::{block_on, spawn};
+    block_on(async {
+        let timers = current_unwrap("sleep").timers;
+        let start = Instant::now();
+        let mut handles = vec![];
+        for i in 0..1000 {
+            let dur = Duration::from_millis(i % 50);
+            handles.push(spawn(async move {
+                Sleep::new(start + dur).await;
+                assert!(start.elapsed() >= dur);
+            }));
+        }
+        // cancel every other timer
+        let handles: Vec<_> = handles.into_iter().step_by(2).collect();
+        crate::task::yield_now().await;
+        assert!(timers.0.borrow().running);
+        assert!(timers.0.borrow().len <= 500);
+        for handle in handles {
+            handle.await;
+        }
+        assert_eq!(timers.0.borrow().len, 0);
+    })
+    .unwrap();
+}
@@ -1,11 +1,21 @@
 #![allow(non_camel_case_types)]
 
-pub use stat::{statx, statx_timestamp};
+pub use stat::*;
 
 pub(crate) mod buf;
 pub(crate) mod futures;
 pub(crate) mod stat;
 
+/// The argument of the `openat2` system call, which
+/// is not exposed by all versions of libc.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+pub struct open_how {
+    pub flags: u64,
+    pub mode: u64,
+    pub resolve: u64,
+}
+
 #[repr(C)]
 pub struct epoll_event {
     pub events: u32,
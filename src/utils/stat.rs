@@ -1,4 +1,18 @@
+pub const STATX_TYPE: u32 = 0x0001;
+pub const STATX_MODE: u32 = 0x0002;
+pub const STATX_NLINK: u32 = 0x0004;
+pub const STATX_UID: u32 = 0x0008;
+pub const STATX_GID: u32 = 0x0010;
+pub const STATX_ATIME: u32 = 0x0020;
+pub const STATX_MTIME: u32 = 0x0040;
+pub const STATX_CTIME: u32 = 0x0080;
+pub const STATX_INO: u32 = 0x0100;
+pub const STATX_SIZE: u32 = 0x0200;
+pub const STATX_BLOCKS: u32 = 0x0400;
 pub const STATX_BASIC_STATS: u32 = 0x07ff;
+pub const STATX_BTIME: u32 = 0x0800;
+pub const STATX_ALL: u32 = 0x0fff;
+pub const STATX_DIOALIGN: u32 = 0x2000;
 
 /// We need to copy this because libc::statx
 /// is not available in musl
@@ -25,7 +39,10 @@ pub struct statx {
     pub stx_rdev_minor: u32,
     pub stx_dev_major: u32,
     pub stx_dev_minor: u32,
-    __statx_pad2: [u64; 14],
+    pub stx_mnt_id: u64,
+    pub stx_dio_mem_align: u32,
+    pub stx_dio_offset_align: u32,
+    __statx_pad2: [u64; 12],
 }
 #[derive(Clone, Copy)]
 #[repr(C)]
@@ -60,7 +77,10 @@ impl statx {
                 stx_rdev_minor: libc::minor(stat.st_rdev) as _,
                 stx_dev_major: libc::major(stat.st_dev) as _,
                 stx_dev_minor: libc::minor(stat.st_dev) as _,
-                __statx_pad2: [0; 14],
+                stx_mnt_id: 0,
+                stx_dio_mem_align: 0,
+                stx_dio_offset_align: 0,
+                __statx_pad2: [0; 12],
             }
         }
     }
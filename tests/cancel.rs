@@ -0,0 +1,87 @@
+use osiris::buf::IoBuf;
+use osiris::net::{TcpListener, TcpStream, UdpSocket};
+use osiris::runtime::current;
+use osiris::task::yield_now;
+use osiris::time::{timeout, Duration};
+use std::cell::Cell;
+use std::future::{poll_fn, Future};
+use std::pin::pin;
+use std::rc::Rc;
+use std::task::Poll;
+
+/// Polls the future once, so its operation gets submitted, and drops it.
+async fn poll_once(future: impl Future) {
+    let mut future = pin!(future);
+    poll_fn(|cx| {
+        let _ = future.as_mut().poll(cx);
+        Poll::Ready(())
+    })
+    .await;
+}
+
+/// A buffer that records when it is dropped.
+struct FlaggedBuf(Vec<u8>, Rc<Cell<bool>>);
+
+impl Drop for FlaggedBuf {
+    fn drop(&mut self) {
+        self.1.set(true);
+    }
+}
+
+// Safety: the pointers are those of the vector, which is never reallocated.
+unsafe impl IoBuf for FlaggedBuf {
+    fn stable_ptr(&self) -> *const u8 {
+        self.0.stable_ptr()
+    }
+    fn bytes_init(&self) -> usize {
+        self.0.bytes_init()
+    }
+    fn bytes_total(&self) -> usize {
+        self.0.bytes_total()
+    }
+}
+
+#[osiris::test]
+async fn dropped_send_to() {
+    let mut socket = UdpSocket::bind("127.0.0.1:2407").await.unwrap();
+    let addr = "127.0.0.1:2408".parse().unwrap();
+    for _ in 0..64 {
+        poll_once(socket.send_to(vec![1; 512], addr)).await;
+    }
+    let (n, _) = socket.send_to(vec![1; 512], addr).await;
+    assert_eq!(n.unwrap(), 512);
+}
+
+#[osiris::test]
+async fn dropped_send_to_outlives_cqe() {
+    let mut socket = UdpSocket::bind("127.0.0.1:2409").await.unwrap();
+    let addr = "127.0.0.1:2410".parse().unwrap();
+    let dropped = Rc::new(Cell::new(false));
+    let buf = FlaggedBuf(vec![1; 512], dropped.clone());
+    poll_once(socket.send_to(buf, addr)).await;
+    // the SQE was pushed but no CQE has been reaped yet, so the buffer, and
+    // the `msghdr` submitted along with it, must still be alive.
+    if current().unwrap().capabilities().io_uring {
+        assert!(!dropped.get());
+    }
+    // they are released once the CQE of the operation is reaped.
+    while !dropped.get() {
+        yield_now().await;
+    }
+}
+
+#[osiris::test]
+async fn dropped_accept() {
+    let listener = TcpListener::bind("127.0.0.1:7010").await.unwrap();
+    for _ in 0..8 {
+        let accept = timeout(Duration::from_millis(1), listener.accept()).await;
+        assert!(accept.is_err());
+    }
+    // the cancellations are submitted before the accept below, so the
+    // dropped accepts can't take its connection.
+    let connect = osiris::spawn(TcpStream::connect("127.0.0.1:7010"));
+    let (_, addr) = listener.accept().await.unwrap();
+    connect.await.unwrap();
+    assert_eq!(addr.ip(), std::net::Ipv4Addr::LOCALHOST);
+    assert_ne!(addr.port(), 0);
+}
@@ -1,6 +1,9 @@
 use std::io::ErrorKind;
+use std::time::Duration;
 
-use osiris::net::{TcpListener, TcpStream};
+use osiris::net::{
+    BindOptions, BufferedStream, LengthDelimitedCodec, SocketAddr, TcpListener, TcpStream,
+};
 use osiris::spawn;
 
 #[osiris::test]
@@ -18,3 +21,433 @@ async fn connection_successful() {
     listener.accept().await.unwrap();
     task.await;
 }
+
+#[osiris::test]
+async fn connection_falls_back_to_next_address() {
+    let listener = TcpListener::bind("127.0.0.1:7001").await.unwrap();
+    let task = spawn(async {
+        let addrs = [
+            SocketAddr::from(([127, 0, 0, 1], 10001)),
+            SocketAddr::from(([127, 0, 0, 1], 7001)),
+        ];
+        let stagger = Duration::from_secs(60);
+        TcpStream::connect_with(&addrs[..], stagger).await.unwrap();
+    });
+    listener.accept().await.unwrap();
+    task.await;
+}
+
+#[osiris::test]
+async fn write_zero_copy() {
+    let listener = TcpListener::bind("127.0.0.1:7002").await.unwrap();
+    let task = spawn(async {
+        let mut stream = TcpStream::connect("127.0.0.1:7002").await.unwrap();
+        let (n, buf) = stream.write_zc(b"hello world".to_vec()).await;
+        assert_eq!(n.unwrap(), buf.len());
+    });
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let (n, buf) = stream.read(vec![0; 32]).await;
+    assert_eq!(&buf[..n.unwrap()], b"hello world");
+    task.await;
+}
+
+#[osiris::test]
+async fn length_delimited_frames() {
+    let listener = TcpListener::bind("127.0.0.1:7003").await.unwrap();
+    let task = spawn(async {
+        let mut stream = TcpStream::connect("127.0.0.1:7003").await.unwrap();
+        // two frames in a single write, followed by a frame split across writes
+        let bytes = b"\x00\x02hi\x00\x05hello\x00\x05wo".to_vec();
+        stream.write_all(bytes).await.0.unwrap();
+        osiris::time::sleep(Duration::from_millis(10)).await;
+        stream.write_all(b"rld".to_vec()).await.0.unwrap();
+
+        let mut framed = LengthDelimitedCodec::new()
+            .length_field_length(2)
+            .framed(stream);
+        assert_eq!(framed.next_frame().await.unwrap().unwrap(), b"pong");
+    });
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut framed = LengthDelimitedCodec::new()
+        .length_field_length(2)
+        .max_frame_length(16)
+        .framed(stream);
+    assert_eq!(framed.next_frame().await.unwrap().unwrap(), b"hi");
+    assert_eq!(framed.next_frame().await.unwrap().unwrap(), b"hello");
+    assert_eq!(framed.next_frame().await.unwrap().unwrap(), b"world");
+    framed.send_frame("pong").await.unwrap();
+    assert!(framed.send_frame(vec![0; 17]).await.is_err());
+    task.await;
+    assert_eq!(framed.next_frame().await.unwrap(), None);
+}
+
+#[osiris::test]
+async fn cancelled_next_frame_keeps_partial_frame() {
+    use osiris::time::{sleep, timeout};
+
+    let listener = TcpListener::bind("127.0.0.1:7023").await.unwrap();
+    let task = spawn(async {
+        let mut stream = TcpStream::connect("127.0.0.1:7023").await.unwrap();
+        stream.write_all(b"\x00\x05wo".to_vec()).await.0.unwrap();
+        sleep(Duration::from_millis(100)).await;
+        stream.write_all(b"rld".to_vec()).await.0.unwrap();
+    });
+    let (stream, _) = listener.accept().await.unwrap();
+    let mut framed = LengthDelimitedCodec::new()
+        .length_field_length(2)
+        .framed(stream);
+    // the first half of the frame is received before the timeout expires.
+    let res = timeout(Duration::from_millis(20), framed.next_frame()).await;
+    assert!(res.is_err());
+    assert_eq!(framed.next_frame().await.unwrap().unwrap(), b"world");
+    task.await;
+}
+
+#[osiris::test]
+async fn read_exact() {
+    let listener = TcpListener::bind("127.0.0.1:7004").await.unwrap();
+    let task = spawn(async {
+        let mut stream = TcpStream::connect("127.0.0.1:7004").await.unwrap();
+        stream.write_all(b"hello".to_vec()).await.0.unwrap();
+        osiris::time::sleep(Duration::from_millis(10)).await;
+        stream.write_all(b" world!".to_vec()).await.0.unwrap();
+    });
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let (res, buf) = stream.read_exact(vec![0; 11]).await;
+    res.unwrap();
+    assert_eq!(buf, b"hello world");
+    task.await;
+    let (res, buf) = stream.read_exact(Vec::with_capacity(4)).await;
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(buf, b"!");
+}
+
+#[osiris::test]
+async fn rebind_after_close() {
+    let options = BindOptions::new().reuseport(false).clone();
+    let listener = options.bind("127.0.0.1:7005").await.unwrap();
+    let err = options.bind("127.0.0.1:7005").await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::AddrInUse);
+
+    let client = TcpStream::connect("127.0.0.1:7005").await.unwrap();
+    let (stream, _) = listener.accept().await.unwrap();
+    // the server closes first, so its side of the connection is left in TIME_WAIT.
+    stream.close().await.unwrap();
+    client.close().await.unwrap();
+    listener.close().await.unwrap();
+
+    let listener = options.bind("127.0.0.1:7005").await.unwrap();
+    listener.close().await.unwrap();
+}
+
+#[osiris::test]
+async fn bind_with_backlog() {
+    let listener = BindOptions::new()
+        .backlog(1)
+        .bind("127.0.0.1:7006")
+        .await
+        .unwrap();
+    #[cfg(target_os = "linux")]
+    assert_eq!(listen_backlog(7006), Some(1));
+    let client = TcpStream::connect("127.0.0.1:7006").await.unwrap();
+    let (stream, _) = listener.accept().await.unwrap();
+    stream.close().await.unwrap();
+    client.close().await.unwrap();
+}
+
+/// Returns the backlog of the IPv4 socket listening on `port`. For listening sockets,
+/// `/proc/net/tcp` reports it in the `tx_queue` column.
+#[cfg(target_os = "linux")]
+fn listen_backlog(port: u16) -> Option<u32> {
+    const TCP_LISTEN: &str = "0A";
+    let table = std::fs::read_to_string("/proc/net/tcp").unwrap();
+    table.lines().skip(1).find_map(|line| {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        let local_port = fields[1].rsplit(':').next()?;
+        if u16::from_str_radix(local_port, 16).ok()? != port || fields[3] != TCP_LISTEN {
+            return None;
+        }
+        let (tx_queue, _) = fields[4].split_once(':')?;
+        u32::from_str_radix(tx_queue, 16).ok()
+    })
+}
+
+#[cfg(target_os = "linux")]
+#[osiris::test]
+async fn bind_to_device() {
+    let err = BindOptions::new()
+        .bind_device(Some("an-interface-name-too-long"))
+        .bind("127.0.0.1:7016")
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    let res = BindOptions::new()
+        .bind_device(Some("lo"))
+        .bind("127.0.0.1:7016")
+        .await;
+    let listener = match res {
+        Ok(listener) => listener,
+        Err(err) => {
+            // binding to a device requires CAP_NET_RAW
+            assert_eq!(err.kind(), ErrorKind::PermissionDenied, "{err}");
+            return;
+        }
+    };
+    let client = TcpStream::connect("127.0.0.1:7016").await.unwrap();
+    let (stream, _) = listener.accept().await.unwrap();
+    stream.close().await.unwrap();
+    client.close().await.unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[osiris::test]
+async fn ip_transparent() {
+    // binding to an address that isn't local requires IP_TRANSPARENT
+    let res = BindOptions::new()
+        .ip_transparent(true)
+        .bind("192.0.2.1:7021")
+        .await;
+    let listener = match res {
+        Ok(listener) => listener,
+        Err(err) => {
+            // setting IP_TRANSPARENT requires CAP_NET_ADMIN
+            assert_eq!(err.kind(), ErrorKind::PermissionDenied, "{err}");
+            return;
+        }
+    };
+    listener.set_ip_transparent(false).unwrap();
+    listener.close().await.unwrap();
+}
+
+#[osiris::test]
+async fn read_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:7007").await.unwrap();
+    let task = spawn(async {
+        let mut stream = TcpStream::connect("127.0.0.1:7007").await.unwrap();
+        osiris::time::sleep(Duration::from_millis(100)).await;
+        stream.write_all(b"hello".to_vec()).await.0.unwrap();
+    });
+    let (mut stream, _) = listener.accept().await.unwrap();
+    assert!(stream.set_read_timeout(Some(Duration::ZERO)).is_err());
+    stream
+        .set_read_timeout(Some(Duration::from_millis(20)))
+        .unwrap();
+
+    let (res, buf) = stream.read(vec![0; 5]).await;
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::TimedOut);
+    // the stream is still usable after the timeout
+    stream.set_read_timeout(None).unwrap();
+    let (res, buf) = stream.read(buf).await;
+    assert_eq!(&buf[..res.unwrap()], b"hello");
+    task.await;
+}
+
+#[osiris::test]
+async fn linger() {
+    let listener = TcpListener::bind("127.0.0.1:7008").await.unwrap();
+    let stream = TcpStream::connect("127.0.0.1:7008").await.unwrap();
+    let (_peer, _) = listener.accept().await.unwrap();
+    assert_eq!(stream.linger().unwrap(), None);
+    stream.set_linger(Some(Duration::from_secs(5))).unwrap();
+    assert_eq!(stream.linger().unwrap(), Some(Duration::from_secs(5)));
+    stream.set_linger(Some(Duration::ZERO)).unwrap();
+    assert_eq!(stream.linger().unwrap(), Some(Duration::ZERO));
+    stream.set_linger(None).unwrap();
+    assert_eq!(stream.linger().unwrap(), None);
+    stream.close().await.unwrap();
+}
+
+#[osiris::test]
+async fn borrow_fd() {
+    use std::os::fd::{AsFd, AsRawFd};
+
+    let listener = TcpListener::bind("127.0.0.1:7011").await.unwrap();
+    let task = spawn(TcpStream::connect("127.0.0.1:7011"));
+    listener.accept().await.unwrap();
+    let stream = task.await.unwrap();
+    assert_eq!(stream.as_fd().as_raw_fd(), stream.as_raw_fd());
+    // the fd can be used by other crates without giving up ownership
+    let fd = stream.as_fd().try_clone_to_owned().unwrap();
+    let peer = std::net::TcpStream::from(fd).peer_addr().unwrap();
+    assert_eq!(peer, SocketAddr::from(([127, 0, 0, 1], 7011)));
+    stream.close().await.unwrap();
+}
+
+#[osiris::test]
+async fn accepted_stream_is_cloexec() {
+    use std::os::fd::AsRawFd;
+
+    let listener = TcpListener::bind("127.0.0.1:7018").await.unwrap();
+    let client = TcpStream::connect("127.0.0.1:7018").await.unwrap();
+    let (stream, _) = listener.accept().await.unwrap();
+    let flags = unsafe { libc::fcntl(stream.as_raw_fd(), libc::F_GETFD) };
+    assert_ne!(flags & libc::FD_CLOEXEC, 0);
+    stream.close().await.unwrap();
+    client.close().await.unwrap();
+}
+
+#[test]
+fn write_to_closed_peer() {
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+
+    // the test harness ignores SIGPIPE, so the writes run in a child
+    // process that restores its default action of killing the process.
+    if std::env::var_os("OSIRIS_SIGPIPE_CHILD").is_none() {
+        let output = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "write_to_closed_peer", "--nocapture"])
+            .env("OSIRIS_SIGPIPE_CHILD", "1")
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.signal(), None, "{stderr}");
+        assert!(output.status.success(), "{stderr}");
+        return;
+    }
+    // Safety: no other thread is handling signals
+    unsafe { libc::signal(libc::SIGPIPE, libc::SIG_DFL) };
+    osiris::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:7017").await.unwrap();
+        let mut client = TcpStream::connect("127.0.0.1:7017").await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        stream.close().await.unwrap();
+        // the first writes may succeed until the peer resets the connection, after
+        // which writes fail instead of raising SIGPIPE and killing the process.
+        let err = loop {
+            if let (Err(err), _) = client.write_all(vec![0; 1 << 16]).await {
+                break err;
+            }
+        };
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::BrokenPipe | ErrorKind::ConnectionReset
+        ));
+        client.close().await.unwrap();
+    })
+    .unwrap();
+}
+
+#[osiris::test]
+async fn connect_timeout() {
+    let addr = SocketAddr::from(([127, 0, 0, 1], 7019));
+    let listener = TcpListener::bind(addr).await.unwrap();
+    let stream = TcpStream::connect_timeout(&addr, Duration::from_secs(5))
+        .await
+        .unwrap();
+    listener.accept().await.unwrap();
+    stream.close().await.unwrap();
+
+    let err = TcpStream::connect_timeout(&addr, Duration::ZERO)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    // packets to this address are dropped, so the attempt hangs until it times out,
+    // unless the network is unreachable in the sandbox.
+    let addr = SocketAddr::from(([10, 255, 255, 1], 80));
+    let err = TcpStream::connect_timeout(&addr, Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.kind(),
+            ErrorKind::TimedOut | ErrorKind::NetworkUnreachable | ErrorKind::HostUnreachable
+        ),
+        "{err}"
+    );
+}
+
+#[osiris::test]
+async fn read_buf() {
+    let listener = TcpListener::bind("127.0.0.1:7020").await.unwrap();
+    let mut client = TcpStream::connect("127.0.0.1:7020").await.unwrap();
+    let (mut stream, _) = listener.accept().await.unwrap();
+    client.write_all("hello world").await.0.unwrap();
+    client.close().await.unwrap();
+    let mut received = vec![];
+    loop {
+        let buf = stream.read_buf(4).await.unwrap();
+        if buf.is_empty() {
+            break;
+        }
+        assert!(buf.len() <= 4);
+        received.extend(buf);
+    }
+    assert_eq!(received, b"hello world");
+}
+
+#[osiris::test]
+async fn recv_multi_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:7022").await.unwrap();
+    let mut client = TcpStream::connect("127.0.0.1:7022").await.unwrap();
+    let (mut stream, _) = listener.accept().await.unwrap();
+    client.write_all("hello").await.0.unwrap();
+
+    let window = Duration::from_millis(200);
+    let mut recv = stream.recv_multi_timeout(window).await.unwrap();
+    assert_eq!(recv.next().await.unwrap().unwrap(), b"hello");
+    // nothing else arrives within the window
+    let err = recv.next().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    drop(recv);
+
+    let mut recv = stream.recv_multi_timeout(window).await.unwrap();
+    client.write_all(vec![1; 10000]).await.0.unwrap();
+    client.close().await.unwrap();
+    let mut received = 0;
+    while let Some(chunk) = recv.next().await.unwrap() {
+        assert!(chunk.iter().all(|&byte| byte == 1));
+        received += chunk.len();
+    }
+    assert_eq!(received, 10000);
+
+    let err = stream.recv_multi_timeout(Duration::ZERO).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[osiris::test]
+async fn buffered_writes() {
+    let listener = TcpListener::bind("127.0.0.1:7015").await.unwrap();
+    let task = spawn(async {
+        let stream = TcpStream::connect("127.0.0.1:7015").await.unwrap();
+        let mut stream = BufferedStream::with_capacity(16, stream);
+        stream.write_all("hello").await.0.unwrap();
+        stream.write_all(" world").await.0.unwrap();
+        assert_eq!(stream.buffer(), b"hello world");
+        // doesn't fit in the buffer, so it is flushed first
+        stream.write_all(" and everyone else").await.0.unwrap();
+        assert!(stream.buffer().is_empty());
+        stream.write_all("!").await.0.unwrap();
+        stream.close().await.unwrap();
+    });
+    let (mut stream, _) = listener.accept().await.unwrap();
+    task.await;
+    let (res, buf) = stream.read_exact(vec![0; 30]).await;
+    res.unwrap();
+    assert_eq!(buf, b"hello world and everyone else!");
+}
+
+#[osiris::test]
+async fn cancelled_flush_keeps_buffered_data() {
+    use osiris::time::timeout;
+
+    const LEN: usize = 32 * 1024 * 1024;
+    let listener = TcpListener::bind("127.0.0.1:7024").await.unwrap();
+    let stream = TcpStream::connect("127.0.0.1:7024").await.unwrap();
+    let (mut peer, _) = listener.accept().await.unwrap();
+    let mut stream = BufferedStream::with_capacity(LEN + 1, stream);
+    let data: Vec<u8> = (0..LEN).map(|i| (i % 251) as u8).collect();
+    stream.write_all(data.clone()).await.0.unwrap();
+    // the peer isn't reading, so the socket buffers fill up before the flush completes.
+    let res = timeout(Duration::from_millis(50), stream.flush()).await;
+    assert!(res.is_err());
+    assert!(!stream.buffer().is_empty());
+    let task = spawn(async move {
+        let (res, buf) = peer.read_exact(vec![0; LEN]).await;
+        res.unwrap();
+        buf
+    });
+    stream.close().await.unwrap();
+    assert!(task.await == data);
+}
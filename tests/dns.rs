@@ -88,3 +88,36 @@ async fn bind_udp_socket_bad() {
 
     assert!(osiris::net::UdpSocket::bind(INPUT_23076).await.is_err())
 }
+
+#[osiris::test]
+async fn to_socket_addr_str_ipv6() {
+    let a = sa6(Ipv6Addr::LOCALHOST, 8080);
+    assert_eq!(Ok(vec![a]), tsa("[::1]:8080").await);
+
+    let a = sa6(Ipv6Addr::new(0x2a02, 0x6b8, 0, 1, 0, 0, 0, 1), 53);
+    assert_eq!(Ok(vec![a]), tsa("[2a02:6b8:0:1::1]:domain").await);
+}
+
+#[osiris::test]
+async fn to_socket_addr_str_service() {
+    let a = sa4(Ipv4Addr::new(127, 0, 0, 1), 80);
+    assert_eq!(Ok(vec![a]), tsa("127.0.0.1:http").await);
+    assert!(tsa("localhost:http").await.unwrap().contains(&a));
+}
+
+#[osiris::test]
+async fn to_socket_addr_str_malformed() {
+    for addr in [
+        "localhost",
+        "[::1]",
+        "[::1:8080",
+        "[::1]8080",
+        ":8080",
+        "localhost:",
+        "localhost:99999",
+        "localhost:not-a-service",
+    ] {
+        let err = addr.to_socket_addrs().await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput, "{addr}");
+    }
+}
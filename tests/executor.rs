@@ -184,3 +184,443 @@ fn self_abort() {
     })
     .unwrap();
 }
+
+#[test]
+fn test_nested_block_on() {
+    let rt = osiris::runtime::Runtime::new().unwrap();
+    block_on(async {
+        let err = rt.try_block_on(async {}).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+        assert!(osiris::runtime::try_block_on(async {}).is_err());
+    })
+    .unwrap();
+    assert_eq!(rt.try_block_on(async { 1 }).unwrap(), 1);
+}
+
+#[test]
+fn block_on_resumes_tasks() {
+    use osiris::runtime::Runtime;
+    use osiris::time::sleep;
+    use std::time::Duration;
+
+    let rt = Runtime::new().unwrap();
+    let polls = Rc::new(Cell::new(0));
+    let slept = Rc::new(Cell::new(false));
+    let yielder = rt
+        .block_on(async {
+            let polls = polls.clone();
+            let yielder = detach(async move {
+                loop {
+                    polls.set(polls.get() + 1);
+                    yield_now().await;
+                }
+            });
+            let slept = slept.clone();
+            detach(async move {
+                sleep(Duration::from_millis(10)).await;
+                slept.set(true);
+            });
+            yield_now().await;
+            yielder.abort_handle()
+        })
+        .unwrap();
+    let before = polls.get();
+    assert!(before > 0);
+    assert!(!slept.get());
+    assert_eq!(rt.active_task_count(), 2);
+
+    // the tasks are resumed where they left off
+    rt.block_on(sleep(Duration::from_millis(50))).unwrap();
+    assert!(polls.get() > before);
+    assert!(slept.get());
+    assert_eq!(rt.active_task_count(), 1);
+
+    // the task that never completes has to be aborted, or it is leaked
+    yielder.abort();
+    assert_eq!(rt.active_task_count(), 0);
+}
+
+#[test]
+fn test_consume_budget() {
+    use osiris::task::{consume_budget, unconstrained};
+    block_on(async {
+        let ticks = Rc::new(Cell::new(0));
+        let _ticker = spawn({
+            let ticks = ticks.clone();
+            async move {
+                loop {
+                    ticks.set(ticks.get() + 1);
+                    yield_now().await;
+                }
+            }
+        });
+
+        for _ in 0..1024 {
+            consume_budget().await;
+        }
+        // the default budget is 128, so the loop must have yielded at least 7 times
+        assert!(ticks.get() >= 7);
+
+        ticks.set(0);
+        unconstrained(async {
+            for _ in 0..1024 {
+                consume_budget().await;
+            }
+        })
+        .await;
+        assert_eq!(ticks.get(), 0);
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_abort_handle() {
+    block_on(async {
+        let dropped = Rc::new(Cell::new(false));
+        let handle = spawn({
+            let dropped = dropped.clone();
+            async move {
+                let _guard = DropGuard(dropped);
+                loop {
+                    yield_now().await;
+                }
+            }
+        });
+        let abort = handle.abort_handle();
+        // dropping a clone must not cancel the task
+        drop(abort.clone());
+        stall().await;
+        assert!(!dropped.get());
+
+        spawn(async move { abort.abort() }).await;
+        assert!(dropped.get());
+        assert!(handle.catch_unwind().await.is_err());
+
+        // aborting a completed task leaves its output untouched
+        let handle = spawn(async { 10 });
+        stall().await;
+        handle.abort_handle().abort();
+        assert_eq!(handle.await, 10);
+    })
+    .unwrap();
+}
+
+struct DropGuard(Rc<Cell<bool>>);
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[test]
+fn test_active_task_count() {
+    block_on(async {
+        let rt = osiris::runtime::current().unwrap();
+        let handle = spawn(stall());
+        let panics = spawn(async { panic!() });
+        assert_eq!(rt.active_task_count(), 2);
+
+        drop(handle);
+        assert_eq!(rt.active_task_count(), 1);
+
+        assert!(panics.catch_unwind().await.is_err());
+        assert_eq!(rt.active_task_count(), 0);
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_max_tasks() {
+    let rt = osiris::runtime::Config::default()
+        .max_tasks(2)
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        let first = spawn(stall());
+        detach(stall());
+        let Err(err) = task::try_spawn(stall()) else {
+            panic!("spawned past the task limit");
+        };
+        assert!(err.to_string().contains("limit of 2 tasks"));
+        let panicked = catch_unwind(|| detach(stall())).is_err();
+        assert!(panicked);
+        first.await;
+        task::try_spawn(stall()).unwrap().await;
+    })
+    .unwrap();
+}
+
+#[test]
+fn cleanup_tasks_are_not_counted() {
+    let rt = osiris::runtime::Config::default()
+        .max_tasks(1)
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        let rt = osiris::runtime::current().unwrap();
+        let file = osiris::fs::File::open("tests/fs_test_files/bar.txt")
+            .await
+            .unwrap();
+        // closing the file descriptor is left to a cleanup task.
+        drop(file);
+        assert_eq!(rt.active_task_count(), 0);
+        task::try_spawn(stall()).unwrap().await;
+    })
+    .unwrap();
+}
+
+#[test]
+fn completion_queue_overflow() {
+    // the completion queue only has room for 8 events, so most of
+    // them are held by the kernel until the queue is drained.
+    let rt = osiris::runtime::Config::default()
+        .queue_entries(1)
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        let reads: Vec<_> = (0..64)
+            .map(|_| spawn(osiris::fs::read("tests/fs_test_files/bar.txt")))
+            .collect();
+        for read in reads {
+            assert!(!read.await.unwrap().is_empty());
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn config_builder() {
+    use osiris::runtime::{Config, ThreadPoolConfig};
+    use std::io::ErrorKind;
+
+    let rt = Config::builder()
+        .queue_entries(256)
+        .event_interval(61)
+        .budget(32)
+        .init_capacity(64)
+        .thread_pool(ThreadPoolConfig::default())
+        .build()
+        .unwrap();
+    assert_eq!(rt.block_on(async { 1 }).unwrap(), 1);
+
+    let Err(err) = Config::builder().event_interval(0).build() else {
+        panic!("built a runtime with an event interval of 0");
+    };
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("`event_interval`"), "{err}");
+}
+
+#[osiris::test]
+async fn spawn_boxed_futures() {
+    use std::future::Future;
+    use std::pin::Pin;
+
+    let large = async {
+        let buf = [1u8; 16 * 1024];
+        yield_now().await;
+        buf.iter().map(|&b| b as usize).sum::<usize>()
+    };
+    let futures: Vec<Pin<Box<dyn Future<Output = usize>>>> =
+        vec![Box::pin(large), Box::pin(async { 1 }), Box::pin(stall_for(3))];
+    let handles: Vec<_> = futures.into_iter().map(task::spawn_boxed).collect();
+    let mut outputs = vec![];
+    for handle in handles {
+        outputs.push(handle.await);
+    }
+    assert_eq!(outputs, [16 * 1024, 1, 3]);
+}
+
+async fn stall_for(n: usize) -> usize {
+    for _ in 0..n {
+        yield_now().await;
+    }
+    n
+}
+
+#[osiris::test]
+async fn named_task_panics() {
+    let handle = task::Builder::new()
+        .name("worker")
+        .spawn(async { panic!("out of {}", "work") });
+    assert_eq!(handle.name().as_deref(), Some("worker"));
+    let err = handle.catch_unwind().await.unwrap_err();
+    let msg = err.downcast_ref::<String>().unwrap();
+    assert_eq!(msg, "task 'worker' panicked: out of work");
+
+    // unnamed tasks keep their payload untouched.
+    let handle = spawn(async { panic!("unnamed") });
+    assert!(handle.name().is_none());
+    let err = handle.catch_unwind().await.unwrap_err();
+    assert_eq!(err.downcast_ref::<&str>(), Some(&"unnamed"));
+}
+
+#[test]
+fn tick_drives_timers() {
+    use osiris::runtime::Runtime;
+    use osiris::time::sleep;
+    use std::time::Duration;
+
+    let rt = Runtime::new().unwrap();
+    let guard = rt.enter();
+    let done = Rc::new(Cell::new(false));
+    let flag = done.clone();
+    detach(async move {
+        sleep(Duration::from_millis(10)).await;
+        flag.set(true);
+    });
+    assert!(rt.tick().unwrap());
+    while !done.get() {
+        rt.tick().unwrap();
+    }
+    assert!(!rt.tick().unwrap());
+    // the runtime can still be used with `block_on` afterwards.
+    drop(guard);
+    rt.block_on(async { yield_now().await }).unwrap();
+}
+
+#[test]
+fn catch_join_errors() {
+    install();
+    block_on(async {
+        let ok = task::spawn_catching(async { 1 }).await;
+        assert_eq!(ok.unwrap(), 1);
+
+        let err = task::spawn_catching(async { panic!("boom") })
+            .await
+            .unwrap_err();
+        assert!(err.is_panic());
+        let payload = err.try_into_panic().unwrap();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"boom"));
+
+        let catch = spawn(stall()).catch();
+        let id = catch.id();
+        catch.abort_handle().abort();
+        let err = catch.await.unwrap_err();
+        assert!(err.is_aborted());
+        assert_eq!(err.id(), id);
+        assert_eq!(err.to_string(), format!("task {id} was aborted"));
+
+        // dropping a panicked task doesn't propagate the panic either
+        let catch = task::spawn_catching(async { panic!("boom") });
+        yield_now().await;
+        drop(catch);
+    })
+    .unwrap();
+}
+
+#[test]
+fn yield_to_reactor_drives_reactor() {
+    use osiris::runtime::Runtime;
+    use osiris::task::yield_to_reactor;
+
+    let rt = Runtime::new().unwrap();
+    let _guard = rt.enter();
+    let yielded = Rc::new(Cell::new(0));
+    let deferred = Rc::new(Cell::new(0));
+    let (a, b) = (yielded.clone(), deferred.clone());
+    detach(async move {
+        yield_now().await;
+        a.set(1);
+    });
+    detach(async move {
+        yield_to_reactor().await;
+        b.set(1);
+    });
+    // a task that yields is polled again in the same tick, while
+    // a task that yields to the reactor waits for the reactor.
+    rt.tick().unwrap();
+    assert_eq!((yielded.get(), deferred.get()), (1, 0));
+    assert!(rt.tick().unwrap());
+    assert_eq!(deferred.get(), 1);
+}
+
+#[test]
+fn detach_handle_abort() {
+    block_on(async {
+        let counter = Rc::new(Cell::new(0));
+        let count = |counter: Rc<Cell<i32>>| async move {
+            loop {
+                counter.set(counter.get() + 1);
+                yield_now().await;
+            }
+        };
+        // dropping the handle does not cancel the task
+        drop(detach(count(counter.clone())));
+        yield_now().await;
+        let before = counter.get();
+        yield_now().await;
+        assert!(counter.get() > before);
+
+        let counter = Rc::new(Cell::new(0));
+        let handle = detach(count(counter.clone()));
+        yield_now().await;
+        handle.abort();
+        let before = counter.get();
+        yield_now().await;
+        assert_eq!(counter.get(), before);
+
+        // the output of a detached task can still be awaited
+        assert_eq!(detach(async { 1 }).await, 1);
+    })
+    .unwrap();
+}
+
+#[test]
+fn on_task_panic_hook() {
+    use std::sync::{Arc, Mutex};
+
+    let panics = Arc::new(Mutex::new(Vec::new()));
+    let observed = panics.clone();
+    let rt = osiris::runtime::Config::default()
+        .on_task_panic(move |id, payload| {
+            let msg = payload.downcast_ref::<&str>().copied().unwrap_or_default();
+            observed.lock().unwrap().push((id, msg.to_string()));
+        })
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        let id = detach(async { panic!("detached") }).id();
+        yield_now().await;
+        assert_eq!(*panics.lock().unwrap(), [(id, "detached".to_string())]);
+
+        let handle = spawn(async { panic!("joined") });
+        let id = handle.id();
+        assert!(handle.catch_unwind().await.is_err());
+        assert_eq!(panics.lock().unwrap()[1], (id, "joined".to_string()));
+    })
+    .unwrap();
+}
+
+#[test]
+fn detached_panic_aborts_process() {
+    use osiris::runtime::{Config, DetachedPanicPolicy};
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+
+    if std::env::var_os("OSIRIS_DETACHED_PANIC_CHILD").is_some() {
+        let rt = Config::default()
+            .detached_panic_policy(DetachedPanicPolicy::AbortProcess)
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            // joined tasks are not subject to the policy
+            assert!(spawn(async { panic!("joined") })
+                .catch_unwind()
+                .await
+                .is_err());
+            detach(async { panic!("detached") });
+            yield_now().await;
+        })
+        .unwrap();
+        return;
+    }
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "detached_panic_aborts_process", "--nocapture"])
+        .env("OSIRIS_DETACHED_PANIC_CHILD", "1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.signal(), Some(libc::SIGABRT));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("panicked: detached"), "{stderr}");
+}
@@ -1,6 +1,9 @@
+use std::os::unix::fs::MetadataExt;
+
+use osiris::buf::AlignedVec;
 use osiris::fs::{
-    self, create_dir, metadata, remove_dir, remove_file, symlink, symlink_metadata, File,
-    OpenOptions,
+    self, create_dir, metadata, remove_dir, remove_file, symlink, symlink_metadata, Advice, Dir,
+    File, OpenOptions, ResolveFlags, StatxMask,
 };
 
 #[osiris::test]
@@ -16,6 +19,31 @@ async fn test_metadata() {
     let _ = dbg!(bar.modified());
     let _ = dbg!(bar.accessed());
     let _ = dbg!(bar.is_symlink());
+
+    let std = std::fs::metadata("tests/fs_test_files/bar.txt").unwrap();
+    assert_eq!(bar.dev(), std.dev());
+    assert_eq!(bar.ino(), std.ino());
+    assert_eq!(bar.nlink(), std.nlink());
+    assert_eq!(bar.uid(), std.uid());
+    assert_eq!(bar.gid(), std.gid());
+    assert_eq!(bar.blksize(), std.blksize());
+    assert_eq!(bar.blocks(), std.blocks());
+}
+
+#[osiris::test]
+async fn test_special_file_types() {
+    let null = metadata("/dev/null").await.unwrap().file_type();
+    assert!(null.is_char_device());
+    assert!(!null.is_block_device() && !null.is_socket() && !null.is_file());
+
+    let path = "tests/fs_test_files/file_types.sock";
+    let _ = std::fs::remove_file(path);
+    let listener = std::os::unix::net::UnixListener::bind(path).unwrap();
+    let socket = metadata(path).await.unwrap().file_type();
+    assert!(socket.is_socket());
+    assert!(!socket.is_char_device() && !socket.is_fifo() && !socket.is_dir());
+    drop(listener);
+    remove_file(path).await.unwrap();
 }
 
 #[osiris::test]
@@ -46,6 +74,142 @@ async fn read_write_test() {
     assert!(metadata(path).await.is_err());
 }
 
+#[osiris::test]
+async fn large_offsets() {
+    let path = "tests/fs_test_files/large_offsets.txt";
+    let mut file = File::create(path).await.unwrap();
+    // offsets past 4GiB are not truncated, the file is sparse
+    let pos = 5 << 30;
+    file.write_at("contents", pos).await.0.unwrap();
+    let (res, buf) = file.read_at(vec![0; 8], pos).await;
+    assert_eq!(&buf[..res.unwrap()], b"contents");
+
+    // offsets that don't fit in a file are rejected
+    let (res, _) = file.read_at(vec![0; 8], u64::MAX).await;
+    assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    let (res, _) = file.write_all_at("contents", i64::MAX as u64 + 1).await;
+    assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    file.close().await.unwrap();
+    remove_file(path).await.unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[osiris::test]
+async fn creation_mode() {
+    // the umask is read from procfs, since setting it affects other tests
+    let status = std::fs::read_to_string("/proc/self/status").unwrap();
+    let umask = status
+        .lines()
+        .find_map(|line| line.strip_prefix("Umask:"))
+        .unwrap();
+    let umask = u32::from_str_radix(umask.trim(), 8).unwrap();
+
+    let path = "tests/fs_test_files/creation_mode.txt";
+    // the mode only applies to new files, so one left behind by a failed run is removed.
+    let _ = std::fs::remove_file(path);
+    File::create(path).await.unwrap().close().await.unwrap();
+    let mode = std::fs::metadata(path).unwrap().mode() & 0o777;
+    assert_eq!(mode, 0o666 & !umask);
+    remove_file(path).await.unwrap();
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .mode(0o640)
+        .open(path)
+        .await
+        .unwrap();
+    file.close().await.unwrap();
+    let mode = std::fs::metadata(path).unwrap().mode() & 0o777;
+    assert_eq!(mode, 0o640 & !umask);
+    remove_file(path).await.unwrap();
+}
+
+#[osiris::test]
+async fn advise_and_set_len() {
+    let path = "tests/fs_test_files/advise_and_set_len.txt";
+    let mut file = File::create(path).await.unwrap();
+    file.write_at("hello world", 0).await.0.unwrap();
+    for advice in [
+        Advice::Sequential,
+        Advice::Random,
+        Advice::WillNeed,
+        Advice::DontNeed,
+    ] {
+        file.advise(0, 0, advice).await.unwrap();
+    }
+    file.advise(6, 5, Advice::Normal).await.unwrap();
+
+    file.set_len(5).await.unwrap();
+    assert_eq!(file.metadata().await.unwrap().len(), 5);
+    file.set_len(8).await.unwrap();
+    let (res, buf) = file.read_at(vec![1; 16], 0).await;
+    assert_eq!(&buf[..res.unwrap()], b"hello\0\0\0");
+    file.close().await.unwrap();
+    remove_file(path).await.unwrap();
+}
+
+#[osiris::test]
+async fn memory_map() {
+    let path = "tests/fs_test_files/memory_map.txt";
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(path)
+        .await
+        .unwrap();
+    file.set_len(5000).await.unwrap();
+
+    // offsets that aren't page aligned are supported.
+    // Safety: the file is not modified while it is mapped
+    let mut map = unsafe { file.memory_map_mut(4090, 10) }.await.unwrap();
+    map.copy_from_slice(b"0123456789");
+    map.flush().await.unwrap();
+    drop(map);
+    let (res, buf) = file.read_at(vec![0; 10], 4090).await;
+    assert_eq!(&buf[..res.unwrap()], b"0123456789");
+
+    // Safety: the file is not modified while it is mapped
+    let map = unsafe { file.memory_map(4095, 3) }.await.unwrap();
+    map.advise(Advice::WillNeed).unwrap();
+    assert_eq!(&map[..], b"567");
+    drop(map);
+    // Safety: nothing is mapped
+    let err = unsafe { file.memory_map(0, 0) }.await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    file.close().await.unwrap();
+    remove_file(path).await.unwrap();
+}
+
+#[osiris::test]
+async fn write_all_at_test() {
+    let path = "tests/fs_test_files/write_all_at_test.txt";
+    let file = File::create(path).await.unwrap();
+    let data: Vec<u8> = (0..=255).cycle().take(1 << 20).collect();
+    let (res, data) = file.write_all_at(data, 16).await;
+    res.unwrap();
+    file.write_all_at("header", 0).await.0.unwrap();
+    file.close().await.unwrap();
+    let contents = std::fs::read(path).unwrap();
+    assert_eq!(&contents[..6], b"header");
+    assert_eq!(&contents[6..16], &[0; 10]);
+    assert_eq!(&contents[16..], &data[..]);
+    remove_file(path).await.unwrap();
+}
+
+#[osiris::test]
+async fn read_exact_at_test() {
+    let file = File::open("tests/fs_test_files/bar.txt").await.unwrap();
+    let (res, buf) = file.read_exact_at(Vec::with_capacity(4), 3).await;
+    res.unwrap();
+    assert_eq!(buf, b"3456");
+
+    let (res, _) = file.read_exact_at(vec![0; 4], 8).await;
+    let err = res.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
 /// this test creates a file and writes to it twice using
 /// `write` to make sure that the file position is advanced
 /// properly
@@ -64,6 +228,23 @@ async fn seekable_file_test() {
     remove_file(path).await.unwrap();
 }
 
+#[osiris::test]
+async fn read_vectored_test() {
+    let mut file = File::open("tests/fs_test_files/bar.txt").await.unwrap();
+    let bufs = vec![vec![1; 3], Vec::with_capacity(4), Vec::with_capacity(16)];
+    let (res, bufs) = file.read_vectored_at(bufs, 1).await;
+    assert_eq!(res.unwrap(), 9);
+    assert_eq!(bufs[0], b"123");
+    assert_eq!(bufs[1], b"4567");
+    assert_eq!(bufs[2], b"89");
+
+    let bufs = vec![Vec::with_capacity(16), vec![1; 4]];
+    let (res, bufs) = file.read_vectored(bufs).await;
+    assert_eq!(res.unwrap(), 10);
+    assert_eq!(bufs[0], b"0123456789");
+    assert!(bufs[1].is_empty());
+}
+
 #[osiris::test]
 async fn create_new() {
     let path = "tests/fs_test_files/create_new.txt";
@@ -108,6 +289,202 @@ async fn test_symlinks() {
 
     assert!(!fs::metadata(&link).await.unwrap().is_symlink());
 
+    assert_eq!(fs::read_link(&link).await.unwrap(), path);
+    assert_eq!(fs::canonicalize(&link).await.unwrap(), path);
+    let relative = "tests/fs_test_files/../fs_test_files/test_symlinks_link.txt";
+    assert_eq!(fs::canonicalize(relative).await.unwrap(), path);
+    let err = fs::read_link(&path).await.unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+
     fs::remove_file(path).await.unwrap();
     fs::remove_file(link).await.unwrap();
 }
+
+#[osiris::test]
+async fn test_seek() {
+    use std::io::SeekFrom;
+    let path = "tests/fs_test_files/test_seek.txt";
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .await
+        .unwrap();
+    file.write("0123456789").await.0.unwrap();
+    assert_eq!(file.stream_position().await.unwrap(), 10);
+
+    assert_eq!(file.seek(SeekFrom::Start(2)).await.unwrap(), 2);
+    file.write("ab").await.0.unwrap();
+    assert_eq!(file.seek(SeekFrom::Current(-1)).await.unwrap(), 3);
+    assert_eq!(file.seek(SeekFrom::End(-3)).await.unwrap(), 7);
+    file.seek(SeekFrom::End(-11)).await.unwrap_err();
+
+    file.seek(SeekFrom::Start(0)).await.unwrap();
+    let (n, buf) = file.read(vec![0; 16]).await;
+    assert_eq!(&buf[..n.unwrap()], b"01ab456789");
+
+    file.seek(SeekFrom::End(-4)).await.unwrap();
+    let (n, buf) = file.read(vec![0; 16]).await;
+    assert_eq!(&buf[..n.unwrap()], b"6789");
+    remove_file(path).await.unwrap();
+}
+
+#[osiris::test]
+async fn test_symlink_errors() {
+    let pwd = std::env::current_dir().unwrap();
+    let missing = pwd.join("tests/fs_test_files/test_symlink_errors_missing");
+    let err = fs::read_link(&missing).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    let err = fs::canonicalize(&missing).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    // a link pointing to itself can't be resolved
+    let looped = pwd.join("tests/fs_test_files/test_symlink_errors_loop");
+    symlink(&looped, &looped).await.unwrap();
+    let err = fs::canonicalize(&looped).await.unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ELOOP));
+    fs::remove_file(looped).await.unwrap();
+}
+
+#[osiris::test]
+async fn test_resolve_flags() {
+    let mut options = OpenOptions::new();
+    options.read(true).resolve_flags(ResolveFlags::BENEATH);
+    options.open("tests/fs_test_files/bar.txt").await.unwrap();
+
+    // paths escaping the current directory are rejected
+    for path in ["/etc/hostname", "../crate/Cargo.toml"] {
+        let Err(err) = options.open(path).await else {
+            panic!("opened {path} outside of the current directory");
+        };
+        assert_eq!(err.raw_os_error(), Some(libc::EXDEV));
+    }
+
+    let link = "tests/fs_test_files/test_resolve_flags_link";
+    symlink("bar.txt", link).await.unwrap();
+    options.open(link).await.unwrap();
+    let Err(err) = options
+        .resolve_flags(ResolveFlags::BENEATH | ResolveFlags::NO_SYMLINKS)
+        .open(link)
+        .await
+    else {
+        panic!("followed a symlink with `NO_SYMLINKS` set");
+    };
+    assert_eq!(err.raw_os_error(), Some(libc::ELOOP));
+    remove_file(link).await.unwrap();
+
+    // the mode is only passed when the file may be created
+    let path = "tests/fs_test_files/test_resolve_flags_new";
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .resolve_flags(ResolveFlags::BENEATH)
+        .open(path)
+        .await
+        .unwrap();
+    remove_file(path).await.unwrap();
+}
+
+#[osiris::test]
+async fn test_remove_file() {
+    let path = "tests/fs_test_files/test_remove_file";
+    File::create(path).await.unwrap();
+    remove_file(path).await.unwrap();
+    assert!(!std::path::Path::new(path).exists());
+    let err = remove_file(path).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    // directories are not removed
+    let err = remove_file("tests/fs_test_files").await.unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EISDIR));
+}
+
+#[osiris::test]
+async fn test_metadata_with() {
+    let file = File::open("tests/fs_test_files/bar.txt").await.unwrap();
+    let mask = StatxMask::SIZE | StatxMask::MTIME;
+    assert!(mask.contains(StatxMask::SIZE));
+    assert!(!mask.contains(StatxMask::INO));
+    let metadata = file.metadata_with(mask).await.unwrap();
+    assert_eq!(metadata.len(), 10);
+    let full = file.metadata().await.unwrap();
+    assert_eq!(metadata.modified().unwrap(), full.modified().unwrap());
+    assert!(full.is_file());
+}
+
+#[osiris::test]
+async fn test_borrow_fd() {
+    use std::os::fd::AsFd;
+
+    let file = File::open("tests/fs_test_files/bar.txt").await.unwrap();
+    let fd = file.as_fd().try_clone_to_owned().unwrap();
+    let len = std::fs::File::from(fd).metadata().unwrap().len();
+    assert_eq!(len, file.metadata().await.unwrap().len() as u64);
+}
+
+#[osiris::test]
+async fn test_direct_io() {
+    let path = "tests/fs_test_files/test_direct_io";
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .custom_flags(libc::O_DIRECT)
+        .open(path)
+        .await;
+    let mut file = match file {
+        Ok(file) => file,
+        // the filesystem doesn't support direct I/O
+        Err(err) if err.raw_os_error() == Some(libc::EINVAL) => return,
+        Err(err) => panic!("{err}"),
+    };
+    let mut buf = AlignedVec::for_file(&file, 4096).await.unwrap();
+    buf.resize(4096, b'x');
+    let (res, buf) = file.write_at(buf, 0).await;
+    assert_eq!(res.unwrap(), 4096);
+    let (res, read) = file.read_at(AlignedVec::with_capacity(4096), 0).await;
+    assert_eq!(res.unwrap(), 4096);
+    assert_eq!(&read[..], &buf[..]);
+    file.close().await.unwrap();
+    remove_file(path).await.unwrap();
+}
+
+#[osiris::test]
+async fn test_dir_at() {
+    let path = "tests/fs_test_files/test_dir_at";
+    let _ = std::fs::remove_dir_all(path);
+    create_dir(path).await.unwrap();
+    let dir = Dir::open(path).await.unwrap();
+    assert!(dir.metadata().await.unwrap().is_dir());
+
+    dir.create_dir_at("nested").await.unwrap();
+    let nested = dir.open_dir_at("nested").await.unwrap();
+    let mut file = nested
+        .open_at("file.txt", OpenOptions::new().write(true).create(true))
+        .await
+        .unwrap();
+    file.write_at("hello", 0).await.0.unwrap();
+    file.close().await.unwrap();
+
+    // paths are resolved relative to the directory, even after it is moved
+    std::fs::rename(path, "tests/fs_test_files/test_dir_at_moved").unwrap();
+    let metadata = dir.metadata_at("nested/file.txt").await.unwrap();
+    assert_eq!(metadata.len(), 5);
+    assert!(nested
+        .symlink_metadata_at("file.txt")
+        .await
+        .unwrap()
+        .is_file());
+    let err = dir.open_dir_at("nested/file.txt").await.unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ENOTDIR));
+
+    nested.remove_file_at("file.txt").await.unwrap();
+    nested.close().await.unwrap();
+    dir.remove_dir_at("nested").await.unwrap();
+    dir.close().await.unwrap();
+    remove_dir("tests/fs_test_files/test_dir_at_moved")
+        .await
+        .unwrap();
+}
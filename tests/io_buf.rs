@@ -1,4 +1,4 @@
-use osiris::buf::IoBuf;
+use osiris::buf::{AlignedVec, IoBuf, IoBufMut, Uninit};
 use std::rc::Rc;
 
 const ARRAY: [u8; 10] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
@@ -96,3 +96,131 @@ async fn io_buf_slice() {
     assert_eq!(slice.bytes_total(), ARRAY.len() - 1);
     assert_eq!(slice.into_inner(), ARRAY);
 }
+
+#[osiris::test]
+async fn io_buf_split_owned() {
+    let mut b = Vec::from(ARRAY);
+    b.reserve_exact(2);
+    let (head, tail) = b.split_owned(4);
+    assert_eq!(head.bytes_init(), 4);
+    assert_eq!(head.bytes_total(), 4);
+    assert_eq!(tail.bytes_init(), ARRAY.len() - 4);
+    assert_eq!(tail.bytes_total(), ARRAY.len() - 2);
+    assert_eq!(&head[..], &ARRAY[..4]);
+    assert_eq!(&tail[..], &ARRAY[4..]);
+    assert!(std::ptr::eq(
+        head.stable_ptr().wrapping_add(4),
+        tail.stable_ptr()
+    ));
+
+    drop(head);
+    let b = Rc::try_unwrap(tail.into_inner()).unwrap();
+    assert_eq!(b, ARRAY);
+}
+
+#[test]
+#[should_panic]
+fn io_buf_split_owned_out_of_bounds() {
+    let b = Vec::from(ARRAY);
+    b.split_owned(ARRAY.len() + 1);
+}
+
+#[osiris::test]
+async fn io_buf_mut_limit() {
+    let b: Vec<u8> = Vec::with_capacity(64);
+    let limited = b.limit(4);
+    assert_eq!(limited.bytes_init(), 0);
+    assert_eq!(limited.bytes_total(), 4);
+
+    let mut file = osiris::fs::File::open("tests/fs_test_files/bar.txt")
+        .await
+        .unwrap();
+    let (n, limited) = file.read_at(limited, 0).await;
+    assert_eq!(n.unwrap(), 4);
+    assert_eq!(limited.bytes_init(), 4);
+
+    let b = limited.into_inner();
+    assert_eq!(b.len(), 4);
+    assert_eq!(b.capacity(), 64);
+    assert_eq!(b.limit(128).bytes_total(), 64);
+}
+
+#[osiris::test]
+async fn io_buf_uninit() {
+    let mut b = Uninit::new(16);
+    assert_eq!(b.bytes_init(), 0);
+    assert_eq!(b.bytes_total(), 16);
+    assert!(b.is_empty());
+
+    let (mut reader, mut writer) = osiris::net::pipe::pipe().await.unwrap();
+    writer.write_all(TEXT).await.0.unwrap();
+    let (n, buf) = reader.read(b.slice(..)).await;
+    b = buf.into_inner();
+    assert_eq!(n.unwrap(), TEXT.len());
+    assert_eq!(&*b, TEXT.as_bytes());
+
+    let v = b.into_vec();
+    assert_eq!(v, TEXT.as_bytes());
+    assert_eq!(v.capacity(), 16);
+}
+
+#[osiris::test]
+async fn read_into_slice() {
+    use osiris::net::{TcpListener, TcpStream, UdpSocket};
+
+    let listener = TcpListener::bind("127.0.0.1:7009").await.unwrap();
+    let mut client = TcpStream::connect("127.0.0.1:7009").await.unwrap();
+    let (mut server, _) = listener.accept().await.unwrap();
+    client.write_all(b"abcde").await.0.unwrap();
+
+    let mut buf = Vec::with_capacity(32);
+    buf.extend_from_slice(&ARRAY);
+    let (n, slice) = server.read(buf.slice(10..)).await;
+    let n = n.unwrap();
+    assert_eq!(n, 5);
+    assert_eq!(slice.bytes_init(), 5);
+    assert_eq!(&slice[..], b"abcde");
+    let buf = slice.into_inner();
+    assert_eq!(&buf[..10], &ARRAY);
+    assert_eq!(&buf[10..], b"abcde");
+
+    // receiving into a bounded range of a datagram socket
+    let mut alice = UdpSocket::bind("127.0.0.1:2405").await.unwrap();
+    let mut bob = UdpSocket::bind("127.0.0.1:2406").await.unwrap();
+    alice.connect("127.0.0.1:2406").await.unwrap();
+    bob.connect("127.0.0.1:2405").await.unwrap();
+    alice.write(b"0123456789").await.0.unwrap();
+    let (n, slice) = bob.recv(Vec::from(TEXT).slice(6..10)).await;
+    assert_eq!(n.unwrap(), 4);
+    assert_eq!(slice.into_inner(), b"hello 0123d");
+
+    alice.write(b"ping").await.0.unwrap();
+    let (n, buf) = bob.recv(Vec::with_capacity(16)).await;
+    assert_eq!(n.unwrap(), 4);
+    assert_eq!(buf, b"ping");
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic = "initialized past the end of the buffer"]
+fn set_init_past_capacity() {
+    let mut buf = Vec::<u8>::with_capacity(4);
+    unsafe { buf.set_init(buf.capacity() + 1) };
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic = "initialized past the end of the buffer"]
+fn aligned_set_init_past_capacity() {
+    let mut buf = AlignedVec::with_capacity_aligned(512, 512);
+    unsafe { buf.set_init(buf.bytes_total() + 1) };
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic = "initialized past the end of the slice"]
+fn set_init_past_slice_end() {
+    let mut slice = Vec::from(ARRAY).slice(2..6);
+    // within the underlying buffer, but past the end of the slice
+    unsafe { slice.set_init(5) };
+}
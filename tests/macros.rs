@@ -1,3 +1,4 @@
+use std::process::ExitCode;
 use std::sync::Mutex;
 
 #[test]
@@ -64,3 +65,46 @@ fn test_main_that_panics_once() {
 
     assert!(*COUNT.lock().unwrap() == 2)
 }
+
+#[test]
+fn test_main_with_restart_limit() {
+    static COUNT: Mutex<i32> = Mutex::new(0);
+
+    #[osiris::main(restart = 2)]
+    async fn main_that_always_panics() {
+        *COUNT.lock().unwrap() += 1;
+        panic!()
+    }
+
+    assert_eq!(main_that_always_panics(), ExitCode::FAILURE);
+    assert_eq!(*COUNT.lock().unwrap(), 3);
+}
+
+#[test]
+fn test_scaled_main_with_panicked_replica() {
+    static PANICKED: Mutex<bool> = Mutex::new(false);
+
+    #[osiris::main(scale = 2)]
+    async fn scaled_main_that_panics_once() {
+        let mut panicked = PANICKED.lock().unwrap();
+        if !*panicked {
+            *panicked = true;
+            drop(panicked);
+            panic!()
+        }
+    }
+
+    assert_eq!(scaled_main_that_panics_once(), ExitCode::FAILURE);
+}
+
+#[test]
+fn test_main_with_config_args() {
+    #[osiris::main(queue_depth = 512, event_interval = 16)]
+    async fn main_with_config_args() {
+        let handles: Vec<_> = (0..64).map(|i| osiris::spawn(async move { i })).collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.await, i);
+        }
+    }
+    main_with_config_args();
+}
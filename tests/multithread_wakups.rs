@@ -19,3 +19,38 @@ async fn thread_safe_send() {
         }
     });
 }
+
+#[test]
+fn wakes_blocked_reactor() {
+    use std::time::{Duration, Instant};
+    // nothing else is pending, so the reactor blocks until
+    // the wakeup pipe is written to by the other thread.
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        tx.send(()).unwrap();
+    });
+    let start = Instant::now();
+    block_on(rx).unwrap().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    thread.join().unwrap();
+}
+
+#[test]
+fn wakes_after_panicking_block_on() {
+    use osiris::runtime::Runtime;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::time::Duration;
+
+    let rt = Runtime::new().unwrap();
+    let res = catch_unwind(AssertUnwindSafe(|| rt.block_on(async { panic!() })));
+    assert!(res.is_err());
+    // the wakeups must still be forwarded after the previous call unwound.
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        tx.send(()).unwrap();
+    });
+    rt.block_on(rx).unwrap().unwrap();
+    thread.join().unwrap();
+}
@@ -0,0 +1,133 @@
+use osiris::buf::{IoBuf, IoBufMut};
+use osiris::net::pipe::pipe;
+use osiris::spawn;
+use std::cell::Cell;
+use std::io::Result;
+use std::rc::Rc;
+
+#[osiris::test]
+async fn pipe_read_write() -> Result<()> {
+    let (mut reader, mut writer) = pipe().await?;
+    // the read is pending until the writer writes
+    let read = spawn(async move {
+        let (n, buf) = reader.read(vec![0; 64]).await;
+        let n = n.unwrap();
+        (reader, buf[..n].to_vec())
+    });
+    writer.write_all("hello world").await.0?;
+    let (mut reader, buf) = read.await;
+    assert_eq!(buf, b"hello world");
+
+    // the reader sees EOF once the writer is closed
+    writer.close().await?;
+    let (n, _) = reader.read(vec![0; 64]).await;
+    assert_eq!(n?, 0);
+    Ok(())
+}
+
+/// A buffer that records when it is dropped.
+struct FlaggedBuf(Vec<u8>, Rc<Cell<bool>>);
+
+impl Drop for FlaggedBuf {
+    fn drop(&mut self) {
+        self.1.set(true);
+    }
+}
+
+// Safety: the pointers are those of the vector, which is never reallocated.
+unsafe impl IoBuf for FlaggedBuf {
+    fn stable_ptr(&self) -> *const u8 {
+        self.0.stable_ptr()
+    }
+    fn bytes_init(&self) -> usize {
+        self.0.bytes_init()
+    }
+    fn bytes_total(&self) -> usize {
+        self.0.bytes_total()
+    }
+}
+
+// Safety: the pointers are those of the vector, which is never reallocated.
+unsafe impl IoBufMut for FlaggedBuf {
+    fn stable_mut_ptr(&mut self) -> *mut u8 {
+        self.0.stable_mut_ptr()
+    }
+    unsafe fn set_init(&mut self, pos: usize) {
+        self.0.set_init(pos)
+    }
+}
+
+#[osiris::test]
+async fn dropped_read_is_cancelled() -> Result<()> {
+    use osiris::task::yield_now;
+    use osiris::time::{timeout, Duration};
+
+    let (mut reader, mut writer) = pipe().await?;
+    let dropped = Rc::new(Cell::new(false));
+    let buf = FlaggedBuf(vec![0; 64], dropped.clone());
+    let read = timeout(Duration::from_millis(10), reader.read(buf)).await;
+    assert!(read.is_err());
+    // the buffer of the cancelled read is released once the kernel is done with it
+    while !dropped.get() {
+        yield_now().await;
+    }
+    // the cancelled read does not consume the data
+    writer.write_all("hello world").await.0?;
+    let (n, buf) = reader.read(vec![0; 64]).await;
+    assert_eq!(&buf[..n?], b"hello world");
+    Ok(())
+}
+
+#[osiris::test]
+async fn splice_and_tee() -> Result<()> {
+    use osiris::fs::{read, remove_file, File};
+    use osiris::io::{splice, tee};
+
+    let path = "splice_and_tee.txt";
+    let (mut reader, mut writer) = pipe().await?;
+    let (copy_reader, copy_writer) = pipe().await?;
+    writer.write_all("hello world").await.0?;
+
+    // the tee leaves the data in the first pipe
+    assert_eq!(tee(&reader, &copy_writer, 64).await?, 11);
+    let file = File::create(path).await?;
+    assert_eq!(splice(&copy_reader, &file, 64).await?, 11);
+    file.close().await?;
+    assert_eq!(read(path).await?, b"hello world");
+    remove_file(path).await?;
+
+    let (n, buf) = reader.read(vec![0; 64]).await;
+    assert_eq!(&buf[..n?], b"hello world");
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[osiris::test]
+async fn read_interrupted_by_signal() -> Result<()> {
+    use osiris::time::{sleep, Duration};
+
+    extern "C" fn handler(_: libc::c_int) {}
+
+    // without SA_RESTART, blocking system calls fail with EINTR when the handler runs
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler as usize;
+        libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut());
+    }
+    let (mut reader, mut writer) = pipe().await?;
+    let thread = unsafe { libc::pthread_self() };
+    let signaler = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        unsafe { libc::pthread_kill(thread, libc::SIGUSR1) };
+    });
+    let write = spawn(async move {
+        sleep(Duration::from_millis(50)).await;
+        writer.write_all("hello").await.0.unwrap();
+        writer
+    });
+    // the runtime is blocked waiting for the read when the signal arrives
+    let (n, buf) = reader.read(vec![0; 64]).await;
+    assert_eq!(&buf[..n?], b"hello");
+    signaler.join().unwrap();
+    write.await.close().await
+}
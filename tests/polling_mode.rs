@@ -48,3 +48,29 @@ async fn server() {
     client.write(SERVER_MSG).await.0.unwrap();
     client.close().await.unwrap();
 }
+
+/// Returns the CPU time consumed by the current thread.
+#[cfg(target_os = "linux")]
+fn thread_cpu_time() -> Duration {
+    // Safety: zeroed is a valid value for rusage
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    // Safety: the pointer is valid for writes
+    unsafe { libc::getrusage(libc::RUSAGE_THREAD, &mut usage) };
+    let micros = |time: libc::timeval| time.tv_sec as u64 * 1_000_000 + time.tv_usec as u64;
+    Duration::from_micros(micros(usage.ru_utime) + micros(usage.ru_stime))
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn polling_mode_goes_idle() {
+    // the sleep outlasts the idle timeout, so the runtime must stop
+    // spinning and block the thread until the timer fires.
+    let rt = Config::default()
+        .mode(Mode::Polling { idle_timeout: 5 })
+        .build()
+        .unwrap();
+    let start = thread_cpu_time();
+    rt.block_on(sleep(Duration::from_millis(200))).unwrap();
+    let spent = thread_cpu_time() - start;
+    assert!(spent < Duration::from_millis(100), "{spent:?}");
+}
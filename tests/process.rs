@@ -0,0 +1,48 @@
+use osiris::process::{Command, Stdio};
+use std::io::Result;
+
+#[osiris::test]
+async fn process_stdio() -> Result<()> {
+    let mut child = Command::new("cat")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all("hello world").await.0?;
+    stdin.close().await?;
+
+    let mut stdout = child.stdout.take().unwrap();
+    let mut out = vec![];
+    loop {
+        let (n, buf) = stdout.read(vec![0; 64]).await;
+        let n = n?;
+        if n == 0 {
+            break;
+        }
+        out.extend_from_slice(&buf[..n]);
+    }
+    assert_eq!(out, b"hello world");
+    assert!(child.wait().await?.success());
+    Ok(())
+}
+
+#[osiris::test]
+async fn process_wait_exit_code() -> Result<()> {
+    let status = Command::new("sh")
+        .args(["-c", "exit 3"])
+        .spawn()?
+        .wait()
+        .await?;
+    assert_eq!(status.code(), Some(3));
+
+    // other tasks can make progress while the child is running
+    let mut child = Command::new("sleep").arg("0.2").spawn()?;
+    let ticker = osiris::spawn(async {
+        osiris::time::sleep(std::time::Duration::from_millis(10)).await;
+    });
+    ticker.await;
+    assert!(child.try_wait()?.is_none());
+    assert!(child.wait().await?.success());
+    Ok(())
+}
@@ -10,3 +10,65 @@ async fn foo() -> Result<()> {
 fn bar() {
     foo();
 }
+
+fn config() -> Result<osiris::runtime::Config> {
+    Ok(osiris::runtime::Config {
+        init_capacity: 16,
+        ..Default::default()
+    })
+}
+
+#[osiris::test(config = config, queue_depth = 16)]
+async fn test_with_config() {
+    osiris::task::yield_now().await;
+}
+
+#[osiris::test]
+async fn test_returning_io_result() -> Result<()> {
+    osiris::task::yield_now().await;
+    Ok(())
+}
+
+#[osiris::test]
+async fn test_returning_boxed_error() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    osiris::task::yield_now().await;
+    Ok(())
+}
+
+// these tests are ignored because they fail, they are
+// called from `returned_errors_are_propagated` instead.
+#[osiris::test]
+#[ignore]
+async fn failing_io_result() -> Result<()> {
+    Err(std::io::Error::other("failed"))
+}
+
+#[osiris::test]
+#[ignore]
+async fn failing_boxed_error() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    Err("failed".into())
+}
+
+#[test]
+fn returned_errors_are_propagated() {
+    assert_eq!(failing_io_result().unwrap_err().to_string(), "failed");
+    assert_eq!(failing_boxed_error().unwrap_err().to_string(), "failed");
+}
+
+#[osiris::test]
+#[should_panic(expected = "child task panicked")]
+async fn child_task_panics_are_propagated() {
+    osiris::spawn(async { panic!("child task panicked") }).await;
+}
+
+#[osiris::test(scale = 4)]
+async fn scaled_test() {
+    static BARRIER: std::sync::Barrier = std::sync::Barrier::new(4);
+    // every replica binds the same port through `SO_REUSEPORT`.
+    let listener = osiris::net::TcpListener::bind("127.0.0.1:7100")
+        .await
+        .unwrap();
+    // blocks until the four replicas are running.
+    BARRIER.wait();
+    listener.close().await.unwrap();
+}
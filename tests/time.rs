@@ -1,7 +1,7 @@
-use osiris::block_on;
-use osiris::time::sleep;
+use osiris::time::{interval, sleep, timeout};
+use osiris::{block_on, detach, spawn};
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[test]
 fn timer_smoke_test() {
@@ -12,3 +12,65 @@ fn timer_smoke_test() {
     })
     .unwrap();
 }
+
+#[test]
+fn many_short_sleeps() {
+    block_on(async {
+        let start = Instant::now();
+        let mut handles = vec![];
+        for i in 0..10_000 {
+            let dur = Duration::from_millis(i % 100);
+            handles.push(spawn(async move {
+                sleep(dur).await;
+                assert!(start.elapsed() >= dur);
+            }));
+        }
+        // drop the timeouts that lose the race
+        for i in 0..10_000u64 {
+            detach(timeout(
+                Duration::from_millis(i % 20),
+                sleep(Duration::from_millis(50)),
+            ));
+        }
+        for handle in handles {
+            handle.await;
+        }
+        assert!(start.elapsed() >= Duration::from_millis(99));
+    })
+    .unwrap();
+}
+
+#[test]
+fn interval_ticks() {
+    block_on(async {
+        let period = Duration::from_millis(20);
+        let mut interval = interval(period);
+        let mut last = interval.tick().await;
+        for _ in 1..5 {
+            // ticks are only delayed past their schedule when missed.
+            let tick = interval.tick().await;
+            assert!(tick >= last + period);
+            assert!(Instant::now() >= tick);
+            last = tick;
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn block_on_timeout_gives_up() {
+    use osiris::runtime::block_on_timeout;
+
+    let start = Instant::now();
+    let out = block_on_timeout(
+        async {
+            detach(sleep(Duration::from_secs(60)));
+            sleep(Duration::from_secs(60)).await;
+        },
+        Duration::from_millis(50),
+    );
+    assert_eq!(out.unwrap(), None);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    let out = block_on_timeout(async { 5 }, Duration::from_millis(50));
+    assert_eq!(out.unwrap(), Some(5));
+}
@@ -0,0 +1,75 @@
+use osiris::net::tls::rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+use osiris::net::tls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+use osiris::net::tls::{TlsAcceptor, TlsConnector};
+use osiris::net::{TcpListener, TcpStream};
+use osiris::spawn;
+use std::io::ErrorKind;
+use std::sync::Arc;
+
+fn configs() -> (TlsAcceptor, TlsConnector) {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+    let key = PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der());
+    let server = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert.cert.der().clone()], PrivateKeyDer::Pkcs8(key))
+        .unwrap();
+
+    let mut roots = RootCertStore::empty();
+    roots.add(cert.cert.der().clone()).unwrap();
+    let client = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    (
+        TlsAcceptor::from(Arc::new(server)),
+        TlsConnector::from(Arc::new(client)),
+    )
+}
+
+#[osiris::test]
+async fn tls_echo() {
+    let (acceptor, connector) = configs();
+    let listener = TcpListener::bind("127.0.0.1:7012").await.unwrap();
+    let server = spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = acceptor.accept(stream).await.unwrap();
+        loop {
+            let (n, buf) = stream.read(Vec::with_capacity(1024)).await;
+            if n.unwrap() == 0 {
+                break;
+            }
+            stream.write_all(buf).await.0.unwrap();
+        }
+        stream.shutdown().await.unwrap();
+    });
+
+    let stream = TcpStream::connect("127.0.0.1:7012").await.unwrap();
+    let mut stream = connector.connect("localhost", stream).await.unwrap();
+    // larger than a single TLS record
+    let msg = vec![7; 64 * 1024];
+    let write = stream.write_all(msg.clone()).await.0;
+    write.unwrap();
+    let mut echoed = Vec::new();
+    while echoed.len() < msg.len() {
+        let (n, buf) = stream.read(vec![0; 4096]).await;
+        echoed.extend_from_slice(&buf[..n.unwrap()]);
+    }
+    assert_eq!(echoed, msg);
+    stream.shutdown().await.unwrap();
+    let (n, _) = stream.read(vec![0; 16]).await;
+    assert_eq!(n.unwrap(), 0);
+    server.await;
+}
+
+#[osiris::test]
+async fn tls_invalid_domain() {
+    let (acceptor, connector) = configs();
+    let listener = TcpListener::bind("127.0.0.1:7013").await.unwrap();
+    let server = spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        assert!(acceptor.accept(stream).await.is_err());
+    });
+    let stream = TcpStream::connect("127.0.0.1:7013").await.unwrap();
+    let err = connector.connect("example.com", stream).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData, "{err}");
+    server.await;
+}
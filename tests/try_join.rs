@@ -1,6 +1,9 @@
+use osiris::buf::{IoBuf, IoBufMut};
 use osiris::task::yield_now;
 use osiris::time::sleep;
 use osiris::try_join;
+use std::cell::Cell;
+use std::rc::Rc;
 use std::time::{Duration, Instant};
 
 #[osiris::test]
@@ -71,3 +74,108 @@ async fn test_waker_err() {
 
     assert!(approx_one < 0.01, "{approx_one}");
 }
+
+#[osiris::test]
+async fn branches_dropped_in_order() {
+    use std::cell::RefCell;
+
+    struct Guard(usize, Rc<RefCell<Vec<usize>>>);
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            self.1.borrow_mut().push(self.0);
+        }
+    }
+
+    let log = Rc::new(RefCell::new(vec![]));
+    let branch = |i| {
+        let guard = Guard(i, log.clone());
+        async move {
+            let _guard = guard;
+            ok_fn(Duration::from_secs(1)).await
+        }
+    };
+    let failing = {
+        let guard = Guard(2, log.clone());
+        async move {
+            let _guard = guard;
+            err_fn().await
+        }
+    };
+    let done = {
+        let guard = Guard(4, log.clone());
+        async move {
+            let _guard = guard;
+            Ok(())
+        }
+    };
+
+    let time = Instant::now();
+    try_join!(branch(0), branch(1), failing, branch(3), done).unwrap_err();
+    assert!(time.elapsed() < Duration::from_millis(500));
+    // the branches that completed drop their state first, and the
+    // rest are dropped in order as soon as the error is returned.
+    assert_eq!(*log.borrow(), [4, 2, 0, 1, 3]);
+}
+
+/// A buffer that records when it is dropped.
+struct FlaggedBuf(Vec<u8>, Rc<Cell<bool>>);
+
+impl Drop for FlaggedBuf {
+    fn drop(&mut self) {
+        self.1.set(true);
+    }
+}
+
+// Safety: the pointers are those of the vector, which is never reallocated.
+unsafe impl IoBuf for FlaggedBuf {
+    fn stable_ptr(&self) -> *const u8 {
+        self.0.stable_ptr()
+    }
+    fn bytes_init(&self) -> usize {
+        self.0.bytes_init()
+    }
+    fn bytes_total(&self) -> usize {
+        self.0.bytes_total()
+    }
+}
+
+// Safety: the pointers are those of the vector, which is never reallocated.
+unsafe impl IoBufMut for FlaggedBuf {
+    fn stable_mut_ptr(&mut self) -> *mut u8 {
+        self.0.stable_mut_ptr()
+    }
+    unsafe fn set_init(&mut self, pos: usize) {
+        self.0.set_init(pos)
+    }
+}
+
+#[osiris::test]
+async fn losing_branches_cancel_io() {
+    use osiris::net::UdpSocket;
+
+    let mut socket = UdpSocket::bind("127.0.0.1:2412").await.unwrap();
+    let mut sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let dropped = Rc::new(Cell::new(false));
+    let buf = FlaggedBuf(vec![0; 16], dropped.clone());
+
+    try_join!(
+        async {
+            let (res, _) = socket.recv(buf).await;
+            res.map_err(|_| "recv failed")
+        },
+        err_fn()
+    )
+    .unwrap_err();
+
+    // no datagram was sent, so the receive only completes, releasing
+    // its buffer, once it has been cancelled.
+    while !dropped.get() {
+        yield_now().await;
+    }
+    // the cancelled receive must not swallow the datagram
+    let addr = "127.0.0.1:2412".parse().unwrap();
+    sender.send_to(b"ping".to_vec(), addr).await.0.unwrap();
+    let (n, buf) = socket.recv(vec![0; 16]).await;
+    assert_eq!(&buf[..n.unwrap()], b"ping");
+}